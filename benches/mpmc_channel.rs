@@ -6,9 +6,9 @@ use futures::{
     stream::StreamExt, FutureExt,
 };
 use futures_intrusive::channel::{
-    shared::channel, shared::unbuffered_channel, LocalChannel,
+    shared::channel, shared::unbuffered_channel, Channel, LocalChannel,
 };
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 /// Elements to transfer per producer
 const ELEMS_TO_SEND: usize = 1000;
@@ -285,6 +285,97 @@ fn intrusive_local_chan_bounded_variable_tx_single_thread(producers: usize) {
     });
 }
 
+/// variable producers, single consumer, values are sent and received in batches
+fn intrusive_local_chan_batched_variable_tx_single_thread(producers: usize) {
+    let elems_per_producer = ELEMS_TO_SEND / producers;
+
+    block_on(async {
+        let rx = LocalChannel::<i32, [i32; CHANNEL_BUFFER_SIZE]>::new();
+        let produce_done = join_all((0..producers).into_iter().map(|_| {
+            Box::pin(async {
+                let mut values = vec![Some(4); elems_per_producer];
+                let r = rx.send_all(&mut values).await;
+                assert!(r.is_ok());
+            })
+        }));
+
+        let consume_done = async {
+            let mut count = 0;
+            let needed = elems_per_producer * producers;
+            let mut values = [None; CHANNEL_BUFFER_SIZE];
+            loop {
+                count += rx.receive_many(&mut values).await;
+                if count == needed {
+                    break;
+                }
+            }
+        };
+
+        join!(produce_done, consume_done);
+    });
+}
+
+macro_rules! intrusive_std_channel_variable_tx {
+    ($producers: expr, |$tx:ident, $elems_per_producer:ident| $produce: expr, |$rx:ident| $consume: expr) => {
+        let $elems_per_producer = ELEMS_TO_SEND / $producers;
+        let channel =
+            Arc::new(Channel::<i32, [i32; CHANNEL_BUFFER_SIZE]>::new());
+
+        let threads: Vec<_> = (0..$producers)
+            .map(|_| {
+                let $tx = channel.clone();
+                std::thread::spawn(move || block_on($produce))
+            })
+            .collect();
+
+        block_on(async {
+            let $rx = &channel;
+            let mut count = 0;
+            let needed = $elems_per_producer * $producers;
+            // The channel doesn't automatically get closed when producers are
+            // gone. Therefore we need to count receives.
+            while count < needed {
+                count += $consume;
+            }
+        });
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    };
+}
+
+/// variable producers, single consumer
+fn intrusive_std_chan_variable_tx(producers: usize) {
+    intrusive_std_channel_variable_tx!(
+        producers,
+        |tx, elems_per_producer| async move {
+            for _i in 0..elems_per_producer {
+                let r = tx.send(4).await;
+                assert!(r.is_ok());
+            }
+        },
+        |rx| {
+            let _ = rx.receive().await.unwrap();
+            1
+        }
+    );
+}
+
+/// variable producers, single consumer, values are sent and received in batches
+fn intrusive_std_chan_batched_variable_tx(producers: usize) {
+    let mut values = [None; CHANNEL_BUFFER_SIZE];
+    intrusive_std_channel_variable_tx!(
+        producers,
+        |tx, elems_per_producer| async move {
+            let mut values = vec![Some(4); elems_per_producer];
+            let r = tx.send_all(&mut values).await;
+            assert!(r.is_ok());
+        },
+        |rx| rx.receive_many(&mut values).await
+    );
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     // Producer and consumer are running on the same thread
     c.bench(
@@ -363,9 +454,50 @@ fn criterion_benchmark(c: &mut Criterion) {
     );
 }
 
+fn batched_benchmark(c: &mut Criterion) {
+    // Compares sending and receiving values one at a time with batched
+    // transfers, which acquire the channel lock only once per batch
+    c.bench(
+        "Channels (Batched)",
+        ParameterizedBenchmark::new(
+            "intrusive local channel with producers",
+            |b, &&producers| {
+                b.iter(|| {
+                    intrusive_local_chan_bounded_variable_tx_single_thread(
+                        producers,
+                    )
+                })
+            },
+            &[1, 5, 20, 100],
+        )
+        .with_function(
+            "batched intrusive local channel with producers",
+            |b, &&producers| {
+                b.iter(|| {
+                    intrusive_local_chan_batched_variable_tx_single_thread(
+                        producers,
+                    )
+                })
+            },
+        )
+        .with_function(
+            "intrusive channel with thread per producer",
+            |b, &&producers| {
+                b.iter(|| intrusive_std_chan_variable_tx(producers))
+            },
+        )
+        .with_function(
+            "batched intrusive channel with thread per producer",
+            |b, &&producers| {
+                b.iter(|| intrusive_std_chan_batched_variable_tx(producers))
+            },
+        ),
+    );
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().measurement_time(Duration::from_secs(10)).nresamples(50);
-    targets = criterion_benchmark
+    targets = criterion_benchmark, batched_benchmark
 }
 criterion_main!(benches);
@@ -66,7 +66,7 @@ macro_rules! run_with_mutex {
             }));
         }
 
-        sem.acquire($nr_tasks).await;
+        sem.acquire($nr_tasks).await.unwrap();
     };
 }
 
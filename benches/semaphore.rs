@@ -52,7 +52,7 @@ fn create_tokio_semaphore(permits: usize) -> TokioSemaphore {
 async fn acquire_intrusive_semaphore(
     sem: &IntrusiveSemaphore,
 ) -> IntrusiveSemaphoreReleaser<'_> {
-    sem.acquire(1).await
+    sem.acquire(1).await.unwrap()
 }
 
 async fn acquire_tokio_semaphore(
@@ -86,7 +86,7 @@ macro_rules! run_with_semaphore {
             }));
         }
 
-        sem.acquire($nr_tasks).await;
+        sem.acquire($nr_tasks).await.unwrap();
     };
 }
 
@@ -3,9 +3,14 @@
 mod real_array;
 pub use real_array::RealArray;
 
+mod priority_buffer;
+pub use priority_buffer::{ArrayPriorityBuf, MaxFirst, PriorityOrder};
+
 mod ring_buffer;
-pub use ring_buffer::{ArrayBuf, RingBuf};
+pub use ring_buffer::{ArrayBuf, ConstRingBuf, IndexedRingBuf, RingBuf};
 
+#[cfg(feature = "alloc")]
+pub use priority_buffer::HeapPriorityBuf;
 #[cfg(feature = "alloc")]
 pub use ring_buffer::FixedHeapBuf;
 #[cfg(feature = "alloc")]
@@ -0,0 +1,415 @@
+use super::{ConstRingBuf, RealArray, RingBuf};
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+/// Determines the order in which a priority buffer yields its items.
+///
+/// Implementations can e.g. compare the items by a key in order to order
+/// items which don't implement `Ord`, or which should be ordered differently.
+pub trait PriorityOrder<T> {
+    /// Compares the priority of two items. An item which is `Greater` than
+    /// another one is yielded first.
+    fn compare(a: &T, b: &T) -> Ordering;
+}
+
+/// A [`PriorityOrder`] which yields the biggest item according to its `Ord`
+/// implementation first.
+///
+/// In order to yield the smallest item first, items can be wrapped in
+/// [`core::cmp::Reverse`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxFirst;
+
+impl<T: Ord> PriorityOrder<T> for MaxFirst {
+    #[inline]
+    fn compare(a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// Moves the last item of the binary heap in `heap` upwards until the heap
+/// property is restored.
+fn sift_up<T, O: PriorityOrder<T>>(heap: &mut [T]) {
+    let mut idx = heap.len() - 1;
+    while idx > 0 {
+        let parent = (idx - 1) / 2;
+        if O::compare(&heap[idx], &heap[parent]) != Ordering::Greater {
+            break;
+        }
+        heap.swap(idx, parent);
+        idx = parent;
+    }
+}
+
+/// Moves the first item of the binary heap in `heap` downwards until the heap
+/// property is restored.
+fn sift_down<T, O: PriorityOrder<T>>(heap: &mut [T]) {
+    let mut idx = 0;
+    loop {
+        let left = 2 * idx + 1;
+        if left >= heap.len() {
+            break;
+        }
+        let right = left + 1;
+        let child = if right < heap.len()
+            && O::compare(&heap[right], &heap[left]) == Ordering::Greater
+        {
+            right
+        } else {
+            left
+        };
+        if O::compare(&heap[child], &heap[idx]) != Ordering::Greater {
+            break;
+        }
+        heap.swap(idx, child);
+        idx = child;
+    }
+}
+
+/// An array-backed priority buffer.
+///
+/// In contrast to a regular Ring Buffer, `pop` does not return the oldest
+/// item but the one with the highest priority according to the
+/// [`PriorityOrder`] `O`. The order of items with the same priority is
+/// unspecified.
+///
+/// Utilizing the buffer in a [`GenericChannel`] yields a priority channel.
+///
+/// `A` is the type of the backing array, which must satisfy the
+/// [`RealArray`] constraint. In order to create a priority buffer backed by
+/// an array of 5 integer elements, the following code can be utilized:
+///
+/// ```
+/// use futures_intrusive::buffer::{ArrayPriorityBuf, RingBuf};
+///
+/// type Buffer5 = ArrayPriorityBuf<i32, [i32; 5]>;
+/// let mut buffer = Buffer5::new();
+/// buffer.push(1);
+/// buffer.push(3);
+/// buffer.push(2);
+/// assert_eq!(3, buffer.pop());
+/// ```
+///
+/// [`GenericChannel`]: ../channel/struct.GenericChannel.html
+pub struct ArrayPriorityBuf<T, A, O = MaxFirst>
+where
+    A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
+    O: PriorityOrder<T>,
+{
+    buffer: MaybeUninit<A>,
+    size: usize,
+    _phantom: PhantomData<(T, O)>,
+}
+
+impl<T, A, O> core::fmt::Debug for ArrayPriorityBuf<T, A, O>
+where
+    A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
+    O: PriorityOrder<T>,
+{
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter,
+    ) -> Result<(), core::fmt::Error> {
+        f.debug_struct("ArrayPriorityBuf")
+            .field("size", &self.size)
+            .field("cap", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T, A, O> ArrayPriorityBuf<T, A, O>
+where
+    A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
+    O: PriorityOrder<T>,
+{
+    /// Returns the stored items in heap order.
+    fn heap_mut(&mut self) -> &mut [T] {
+        // Safety: The first `size` elements of the array have been written
+        // and haven't been read afterwards.
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self.buffer.as_mut_ptr() as *mut T,
+                self.size,
+            )
+        }
+    }
+}
+
+impl<T, A, O> RingBuf for ArrayPriorityBuf<T, A, O>
+where
+    A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
+    O: PriorityOrder<T>,
+{
+    type Item = T;
+
+    fn new() -> Self {
+        Self::INIT
+    }
+
+    fn with_capacity(_cap: usize) -> Self {
+        // The fixed size array backed priority buffer doesn't support an
+        // adjustable capacity. Therefore only the default capacity is utilized.
+        Self::new()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        A::LEN
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    fn can_push(&self) -> bool {
+        self.len() != self.capacity()
+    }
+
+    fn push(&mut self, value: Self::Item) {
+        assert!(self.can_push());
+        // Safety: We asserted that there is available space for an item.
+        // Therefore the memory address is valid.
+        unsafe {
+            let arr_ptr = self.buffer.as_mut_ptr() as *mut T;
+            arr_ptr.add(self.size).write(value);
+        }
+        self.size += 1;
+        sift_up::<T, O>(self.heap_mut());
+    }
+
+    fn pop(&mut self) -> Self::Item {
+        assert!(self.size > 0);
+        self.size -= 1;
+        // Safety: We asserted that there is an element available, so the
+        // first and the last element must have been written before. The last
+        // element is moved into the place of the first one, which is removed.
+        let val = unsafe {
+            let arr_ptr = self.buffer.as_mut_ptr() as *mut T;
+            let val = arr_ptr.read();
+            if self.size > 0 {
+                arr_ptr.write(arr_ptr.add(self.size).read());
+            }
+            val
+        };
+        sift_down::<T, O>(self.heap_mut());
+        val
+    }
+}
+
+impl<T, A, O> ConstRingBuf for ArrayPriorityBuf<T, A, O>
+where
+    A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
+    O: PriorityOrder<T>,
+{
+    const INIT: Self = ArrayPriorityBuf {
+        buffer: MaybeUninit::uninit(),
+        size: 0,
+        _phantom: PhantomData,
+    };
+}
+
+impl<T, A, O> Drop for ArrayPriorityBuf<T, A, O>
+where
+    A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
+    O: PriorityOrder<T>,
+{
+    fn drop(&mut self) {
+        // Drop all elements which are still stored inside the buffer.
+        // Safety: This drops only as many elements as have been written via
+        // ptr::write and haven't read via ptr::read before
+        unsafe {
+            core::ptr::drop_in_place(self.heap_mut());
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod if_alloc {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// A priority buffer which stores all items on the heap.
+    ///
+    /// In contrast to a regular Ring Buffer, `pop` does not return the oldest
+    /// item but the one with the highest priority according to the
+    /// [`PriorityOrder`] `O`. The order of items with the same priority is
+    /// unspecified.
+    ///
+    /// Like the `GrowingHeapBuf`, the buffer does not allocate its capacity
+    /// ahead of time, but grows dynamically up to the given limit.
+    pub struct HeapPriorityBuf<T, O = MaxFirst>
+    where
+        O: PriorityOrder<T>,
+    {
+        buffer: Vec<T>,
+        /// The maximum number of elements in the buffer.
+        limit: usize,
+        _phantom: PhantomData<O>,
+    }
+
+    impl<T, O> core::fmt::Debug for HeapPriorityBuf<T, O>
+    where
+        O: PriorityOrder<T>,
+    {
+        fn fmt(
+            &self,
+            f: &mut core::fmt::Formatter,
+        ) -> Result<(), core::fmt::Error> {
+            f.debug_struct("HeapPriorityBuf")
+                .field("size", &self.buffer.len())
+                .field("limit", &self.limit)
+                .finish()
+        }
+    }
+
+    impl<T, O> RingBuf for HeapPriorityBuf<T, O>
+    where
+        O: PriorityOrder<T>,
+    {
+        type Item = T;
+
+        fn new() -> Self {
+            Self::INIT
+        }
+
+        fn with_capacity(limit: usize) -> Self {
+            HeapPriorityBuf {
+                buffer: Vec::new(),
+                limit,
+                _phantom: PhantomData,
+            }
+        }
+
+        #[inline]
+        fn capacity(&self) -> usize {
+            self.limit
+        }
+
+        #[inline]
+        fn len(&self) -> usize {
+            self.buffer.len()
+        }
+
+        #[inline]
+        fn can_push(&self) -> bool {
+            self.buffer.len() != self.limit
+        }
+
+        fn push(&mut self, value: Self::Item) {
+            assert!(self.can_push());
+            self.buffer.push(value);
+            sift_up::<T, O>(&mut self.buffer);
+        }
+
+        fn pop(&mut self) -> Self::Item {
+            let val = self.buffer.swap_remove(0);
+            sift_down::<T, O>(&mut self.buffer);
+            val
+        }
+    }
+
+    impl<T, O> ConstRingBuf for HeapPriorityBuf<T, O>
+    where
+        O: PriorityOrder<T>,
+    {
+        const INIT: Self = HeapPriorityBuf {
+            buffer: Vec::new(),
+            limit: 0,
+            _phantom: PhantomData,
+        };
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use if_alloc::*;
+
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests {
+    use super::*;
+    use core::cmp::Reverse;
+
+    /// Orders tuples by their second element
+    struct BySecond;
+
+    impl PriorityOrder<(&'static str, u32)> for BySecond {
+        fn compare(
+            a: &(&'static str, u32),
+            b: &(&'static str, u32),
+        ) -> Ordering {
+            a.1.cmp(&b.1)
+        }
+    }
+
+    fn test_priority_buf<Buf: RingBuf<Item = u32>>(mut buf: Buf) {
+        assert_eq!(5, buf.capacity());
+        assert_eq!(0, buf.len());
+        assert_eq!(true, buf.is_empty());
+        assert_eq!(true, buf.can_push());
+
+        for val in [3, 1, 4, 1, 5].iter() {
+            buf.push(*val);
+        }
+        assert_eq!(5, buf.len());
+        assert_eq!(false, buf.can_push());
+
+        assert_eq!(5, buf.pop());
+        assert_eq!(4, buf.pop());
+        buf.push(9);
+        buf.push(2);
+        assert_eq!(5, buf.len());
+
+        for val in [9, 3, 2, 1, 1].iter() {
+            assert_eq!(*val, buf.pop());
+        }
+        assert_eq!(true, buf.is_empty());
+    }
+
+    #[test]
+    fn test_array_priority_buf() {
+        let buf = ArrayPriorityBuf::<u32, [u32; 5]>::new();
+        test_priority_buf(buf);
+    }
+
+    #[test]
+    fn test_heap_priority_buf() {
+        let buf = HeapPriorityBuf::<u32>::with_capacity(5);
+        test_priority_buf(buf);
+    }
+
+    #[test]
+    fn test_custom_order() {
+        let mut buf = ArrayPriorityBuf::<_, [(&str, u32); 3], BySecond>::new();
+        buf.push(("b", 2));
+        buf.push(("c", 3));
+        buf.push(("a", 1));
+        assert_eq!(("c", 3), buf.pop());
+        assert_eq!(("b", 2), buf.pop());
+        assert_eq!(("a", 1), buf.pop());
+
+        let mut buf = HeapPriorityBuf::<Reverse<u32>>::with_capacity(3);
+        buf.push(Reverse(2));
+        buf.push(Reverse(1));
+        buf.push(Reverse(3));
+        assert_eq!(Reverse(1), buf.pop());
+        assert_eq!(Reverse(2), buf.pop());
+        assert_eq!(Reverse(3), buf.pop());
+    }
+
+    #[test]
+    fn test_drops_remaining_items() {
+        let item = alloc::sync::Arc::new(());
+        {
+            let mut buf = ArrayPriorityBuf::<_, [_; 4]>::new();
+            buf.push(item.clone());
+            buf.push(item.clone());
+            buf.push(item.clone());
+            drop(buf.pop());
+            assert_eq!(3, alloc::sync::Arc::strong_count(&item));
+        }
+        assert_eq!(1, alloc::sync::Arc::strong_count(&item));
+    }
+}
@@ -30,10 +30,31 @@ pub trait RingBuf {
     /// Panics if there is not enough free space.
     fn push(&mut self, item: Self::Item);
     /// Returns the oldest item inside the buffer.
+    /// Buffers which order their items differently, like the
+    /// [`ArrayPriorityBuf`](super::ArrayPriorityBuf), return the item that
+    /// comes first according to their ordering instead.
     /// Panics if there is no available item.
     fn pop(&mut self) -> Self::Item;
 }
 
+/// A Ring Buffer which can be created in `const` contexts.
+pub trait ConstRingBuf: RingBuf {
+    /// An empty instance of the Ring Buffer with the default capacity.
+    ///
+    /// In contrast to [`RingBuf::new`] this can be utilized in `const`
+    /// contexts, e.g. in order to create a `static` channel.
+    const INIT: Self;
+}
+
+/// A Ring Buffer which allows to access the stored items without removing
+/// them.
+pub trait IndexedRingBuf: RingBuf {
+    /// Returns a reference to the item at position `index`, where the oldest
+    /// item is stored at position 0.
+    /// Panics if there is no item at this position.
+    fn get(&self, index: usize) -> &Self::Item;
+}
+
 /// An array-backed Ring Buffer
 ///
 /// `A` is the type of the backing array. The backing array must be a real
@@ -42,10 +63,13 @@ pub trait RingBuf {
 /// the following code can be utilized:
 ///
 /// ```
-/// use futures_intrusive::buffer::{ArrayBuf, RingBuf};
+/// use futures_intrusive::buffer::{ArrayBuf, ConstRingBuf, RingBuf};
 ///
 /// type Buffer5 = ArrayBuf<i32, [i32; 5]>;
 /// let buffer = Buffer5::new();
+///
+/// // In const contexts the `INIT` constant can be utilized instead
+/// static BUFFER: Buffer5 = Buffer5::INIT;
 /// ```
 pub struct ArrayBuf<T, A>
 where
@@ -92,13 +116,7 @@ where
     type Item = T;
 
     fn new() -> Self {
-        ArrayBuf {
-            buffer: MaybeUninit::uninit(),
-            send_idx: 0,
-            recv_idx: 0,
-            size: 0,
-            _phantom: PhantomData,
-        }
+        Self::INIT
     }
 
     fn with_capacity(_cap: usize) -> Self {
@@ -150,6 +168,36 @@ where
     }
 }
 
+impl<T, A> ConstRingBuf for ArrayBuf<T, A>
+where
+    A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
+{
+    const INIT: Self = ArrayBuf {
+        buffer: MaybeUninit::uninit(),
+        send_idx: 0,
+        recv_idx: 0,
+        size: 0,
+        _phantom: PhantomData,
+    };
+}
+
+impl<T, A> IndexedRingBuf for ArrayBuf<T, A>
+where
+    A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
+{
+    #[inline]
+    fn get(&self, index: usize) -> &Self::Item {
+        assert!(index < self.size);
+        let idx = (self.recv_idx + index) % self.capacity();
+        // Safety: We asserted that the element is within the stored range, so
+        // it must have been written before.
+        unsafe {
+            let arr_ptr = self.buffer.as_ptr() as *const T;
+            &*arr_ptr.add(idx)
+        }
+    }
+}
+
 impl<T, A> Drop for ArrayBuf<T, A>
 where
     A: core::convert::AsMut<[T]> + core::convert::AsRef<[T]> + RealArray<T>,
@@ -201,10 +249,7 @@ mod if_alloc {
         type Item = T;
 
         fn new() -> Self {
-            FixedHeapBuf {
-                buffer: VecDeque::new(),
-                cap: 0,
-            }
+            Self::INIT
         }
 
         fn with_capacity(cap: usize) -> Self {
@@ -242,6 +287,20 @@ mod if_alloc {
         }
     }
 
+    impl<T> ConstRingBuf for FixedHeapBuf<T> {
+        const INIT: Self = FixedHeapBuf {
+            buffer: VecDeque::new(),
+            cap: 0,
+        };
+    }
+
+    impl<T> IndexedRingBuf for FixedHeapBuf<T> {
+        #[inline]
+        fn get(&self, index: usize) -> &Self::Item {
+            &self.buffer[index]
+        }
+    }
+
     /// A Ring Buffer which stores all items on the heap but grows dynamically.
     ///
     /// A `GrowingHeapBuf` does not allocate the capacity ahead of time, as
@@ -271,10 +330,7 @@ mod if_alloc {
         type Item = T;
 
         fn new() -> Self {
-            GrowingHeapBuf {
-                buffer: VecDeque::new(),
-                limit: 0,
-            }
+            Self::INIT
         }
 
         fn with_capacity(limit: usize) -> Self {
@@ -311,6 +367,20 @@ mod if_alloc {
             self.buffer.pop_front().unwrap()
         }
     }
+
+    impl<T> ConstRingBuf for GrowingHeapBuf<T> {
+        const INIT: Self = GrowingHeapBuf {
+            buffer: VecDeque::new(),
+            limit: 0,
+        };
+    }
+
+    impl<T> IndexedRingBuf for GrowingHeapBuf<T> {
+        #[inline]
+        fn get(&self, index: usize) -> &Self::Item {
+            &self.buffer[index]
+        }
+    }
 }
 
 #[cfg(feature = "alloc")]
@@ -359,6 +429,33 @@ mod tests {
         }
     }
 
+    fn test_indexed_ring_buf<Buf: IndexedRingBuf<Item = u32>>(mut buf: Buf) {
+        buf.push(1);
+        buf.push(2);
+        buf.push(3);
+        assert_eq!(1, *buf.get(0));
+        assert_eq!(2, *buf.get(1));
+        assert_eq!(3, *buf.get(2));
+
+        assert_eq!(1, buf.pop());
+        assert_eq!(2, *buf.get(0));
+        assert_eq!(3, *buf.get(1));
+
+        // Wrap around the end of the storage
+        buf.push(4);
+        buf.push(5);
+        buf.push(6);
+        for (i, val) in [2, 3, 4, 5, 6].iter().enumerate() {
+            assert_eq!(*val, *buf.get(i));
+        }
+
+        assert_eq!(2, buf.pop());
+        assert_eq!(3, buf.pop());
+        for (i, val) in [4, 5, 6].iter().enumerate() {
+            assert_eq!(*val, *buf.get(i));
+        }
+    }
+
     #[test]
     fn test_array_ring_buf() {
         let buf = ArrayBuf::<u32, [u32; 5]>::new();
@@ -376,4 +473,22 @@ mod tests {
         let buf = GrowingHeapBuf::<u32>::with_capacity(5);
         test_ring_buf(buf);
     }
+
+    #[test]
+    fn test_indexed_array_ring_buf() {
+        let buf = ArrayBuf::<u32, [u32; 5]>::new();
+        test_indexed_ring_buf(buf);
+    }
+
+    #[test]
+    fn test_indexed_heap_ring_buf() {
+        let buf = FixedHeapBuf::<u32>::with_capacity(5);
+        test_indexed_ring_buf(buf);
+    }
+
+    #[test]
+    fn test_indexed_growing_ring_buf() {
+        let buf = GrowingHeapBuf::<u32>::with_capacity(5);
+        test_indexed_ring_buf(buf);
+    }
 }
@@ -0,0 +1,857 @@
+//! An asynchronously awaitable multi-value broadcast channel
+
+use super::{
+    BroadcastReceiveError, ChannelSendError, CloseStatus,
+    TryBroadcastReceiveError,
+};
+use crate::{
+    buffer::{ArrayBuf, ConstRingBuf, IndexedRingBuf},
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use core::marker::PhantomData;
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Tracks how the future had interacted with the channel
+#[derive(PartialEq, Debug)]
+enum RecvPollState {
+    /// The task is not registered at the wait queue at the channel
+    Unregistered,
+    /// The task was added to the wait queue at the channel.
+    Registered,
+}
+
+/// Tracks the channel futures waiting state.
+/// Access to this struct is synchronized through the channel.
+#[derive(Debug)]
+struct RecvWaitQueueEntry {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: RecvPollState,
+}
+
+impl RecvWaitQueueEntry {
+    /// Creates a new RecvWaitQueueEntry
+    fn new() -> RecvWaitQueueEntry {
+        RecvWaitQueueEntry {
+            task: None,
+            state: RecvPollState::Unregistered,
+        }
+    }
+}
+
+/// Adapter trait that allows Futures to generically interact with Channel
+/// implementations via dynamic dispatch.
+trait BroadcastReceiveAccess<T> {
+    unsafe fn receive_or_register(
+        &self,
+        wait_node: &mut ListNode<RecvWaitQueueEntry>,
+        cursor: &mut u64,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, BroadcastReceiveError>>;
+
+    fn remove_receive_waiter(
+        &self,
+        wait_node: &mut ListNode<RecvWaitQueueEntry>,
+    );
+}
+
+/// A Future that is returned by the `receive` function of a broadcast channel
+/// receiver.
+///
+/// The future gets resolved with `Ok(value)` when the next value which had
+/// not yet been observed by the receiver is available.
+///
+/// If the receiver fell behind and values had been evicted from the channel
+/// before it could observe them, the future resolves with
+/// [`BroadcastReceiveError::Lagged`]. If the channel gets closed and the
+/// receiver had observed all values, the future resolves with
+/// [`BroadcastReceiveError::Closed`].
+#[must_use = "futures do nothing unless polled"]
+pub struct BroadcastReceiveFuture<'a, MutexType, T> {
+    /// The channel that is associated with this BroadcastReceiveFuture
+    channel: Option<&'a dyn BroadcastReceiveAccess<T>>,
+    /// The position of the receiver which is advanced on reception
+    cursor: &'a mut u64,
+    /// Node for waiting on the channel
+    wait_node: ListNode<RecvWaitQueueEntry>,
+    /// Marker for mutex type
+    _phantom: PhantomData<MutexType>,
+}
+
+// Safety: Channel futures can be sent between threads as long as the underlying
+// channel is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: Sync, T: Send> Send
+    for BroadcastReceiveFuture<'a, MutexType, T>
+{
+}
+
+impl<'a, MutexType, T> core::fmt::Debug
+    for BroadcastReceiveFuture<'a, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("BroadcastReceiveFuture").finish()
+    }
+}
+
+impl<'a, MutexType, T> Future for BroadcastReceiveFuture<'a, MutexType, T> {
+    type Output = Result<T, BroadcastReceiveError>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, BroadcastReceiveError>> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside BroadcastReceiveFuture is
+        // stable, and we don't move any fields inside the future until it gets
+        // dropped.
+        let mut_self: &mut BroadcastReceiveFuture<MutexType, T> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let channel = mut_self
+            .channel
+            .expect("polled BroadcastReceiveFuture after completion");
+
+        let poll_res = unsafe {
+            channel.receive_or_register(
+                &mut mut_self.wait_node,
+                mut_self.cursor,
+                cx,
+            )
+        };
+
+        if poll_res.is_ready() {
+            // A value or an error was available
+            mut_self.channel = None;
+        }
+
+        poll_res
+    }
+}
+
+impl<'a, MutexType, T> FusedFuture
+    for BroadcastReceiveFuture<'a, MutexType, T>
+{
+    fn is_terminated(&self) -> bool {
+        self.channel.is_none()
+    }
+}
+
+impl<'a, MutexType, T> Drop for BroadcastReceiveFuture<'a, MutexType, T> {
+    fn drop(&mut self) {
+        // If this BroadcastReceiveFuture has been polled and it was added to the
+        // wait queue at the channel, it must be removed before dropping.
+        // Otherwise the channel would access invalid memory.
+        if let Some(channel) = self.channel {
+            channel.remove_receive_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+fn wake_waiters(waiters: &mut LinkedList<RecvWaitQueueEntry>) {
+    // Remove all waiters from the waiting list in reverse order and wake them.
+    // Since every receiver observes every value, the order only determines
+    // which task gets scheduled first.
+    waiters.reverse_drain(|waiter| {
+        if let Some(handle) = waiter.task.take() {
+            handle.wake();
+        }
+        waiter.state = RecvPollState::Unregistered;
+    });
+}
+
+/// Internal state of the broadcast channel
+struct ChannelState<T, A>
+where
+    A: IndexedRingBuf<Item = T>,
+{
+    /// Whether the channel was actively closed
+    is_closed: bool,
+    /// The sequence number of the oldest value inside the buffer.
+    /// Each value which is sent into the channel gets assigned the next
+    /// sequence number.
+    head: u64,
+    /// The buffer which holds the most recently sent values
+    buffer: A,
+    /// The list of waiters, which are waiting for the next value
+    waiters: LinkedList<RecvWaitQueueEntry>,
+}
+
+impl<T, A> ChannelState<T, A>
+where
+    T: Clone,
+    A: IndexedRingBuf<Item = T>,
+{
+    fn new(buffer: A) -> ChannelState<T, A> {
+        ChannelState::<T, A> {
+            is_closed: false,
+            head: 0,
+            buffer,
+            waiters: LinkedList::new(),
+        }
+    }
+
+    /// Returns the sequence number which the next value will get assigned.
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+
+    /// Writes a single value to the channel.
+    /// If the buffer is full, the oldest value gets evicted.
+    fn send(&mut self, value: T) -> Result<(), ChannelSendError<T>> {
+        if self.is_closed {
+            return Err(ChannelSendError(value));
+        }
+
+        if self.buffer.can_push() {
+            self.buffer.push(value);
+        } else if self.buffer.is_empty() {
+            // A buffer without capacity can't retain the value. Receivers
+            // will observe it as lost.
+            self.head += 1;
+        } else {
+            drop(self.buffer.pop());
+            self.buffer.push(value);
+            self.head += 1;
+        }
+
+        // Wakeup all waiters
+        wake_waiters(&mut self.waiters);
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> CloseStatus {
+        if self.is_closed {
+            return CloseStatus::AlreadyClosed;
+        }
+        self.is_closed = true;
+
+        // Wakeup all waiters
+        wake_waiters(&mut self.waiters);
+
+        CloseStatus::NewlyClosed
+    }
+
+    /// Tries to read the value with the sequence number `cursor` from the
+    /// channel, and advances the cursor on success.
+    fn try_receive(
+        &mut self,
+        cursor: &mut u64,
+    ) -> Result<T, TryBroadcastReceiveError> {
+        if *cursor < self.head {
+            // The values the receiver was interested in are gone. It
+            // continues with the oldest value that is still available.
+            let lagged = self.head - *cursor;
+            *cursor = self.head;
+            return Err(TryBroadcastReceiveError::Lagged(lagged));
+        }
+
+        let index = (*cursor - self.head) as usize;
+        if index < self.buffer.len() {
+            *cursor += 1;
+            Ok(self.buffer.get(index).clone())
+        } else if self.is_closed {
+            Err(TryBroadcastReceiveError::Closed)
+        } else {
+            Err(TryBroadcastReceiveError::Empty)
+        }
+    }
+
+    /// Tries to read the value with the sequence number `cursor` from the
+    /// channel.
+    /// If the value isn't available yet, the BroadcastReceiveFuture gets added
+    /// to the wait queue at the channel, and will be signalled once ready.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn receive_or_register(
+        &mut self,
+        wait_node: &mut ListNode<RecvWaitQueueEntry>,
+        cursor: &mut u64,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, BroadcastReceiveError>> {
+        match wait_node.state {
+            RecvPollState::Unregistered => match self.try_receive(cursor) {
+                Ok(value) => Poll::Ready(Ok(value)),
+                Err(TryBroadcastReceiveError::Lagged(lagged)) => {
+                    Poll::Ready(Err(BroadcastReceiveError::Lagged(lagged)))
+                }
+                Err(TryBroadcastReceiveError::Closed) => {
+                    Poll::Ready(Err(BroadcastReceiveError::Closed))
+                }
+                Err(TryBroadcastReceiveError::Empty) => {
+                    // Added the task to the wait queue
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = RecvPollState::Registered;
+                    self.waiters.add_front(wait_node);
+                    Poll::Pending
+                }
+            },
+            RecvPollState::Registered => {
+                // Since the channel wakes up all waiters and moves their states
+                // to unregistered there can't be any new value in the channel in
+                // this state. However the caller might have passed a different
+                // `Waker`. In this case we need to update it.
+                update_waker_ref(&mut wait_node.task, cx);
+                Poll::Pending
+            }
+        }
+    }
+
+    fn remove_waiter(&mut self, wait_node: &mut ListNode<RecvWaitQueueEntry>) {
+        // BroadcastReceiveFuture only needs to get removed if it had been added
+        // to the wait queue of the channel. This has happened in the
+        // RecvPollState::Registered case.
+        if let RecvPollState::Registered = wait_node.state {
+            // Safety: Due to the state, we know that the node must be part
+            // of the waiter list
+            if !unsafe { self.waiters.remove(wait_node) } {
+                // Panic if the address isn't found. This can only happen if the contract was
+                // violated, e.g. the RecvWaitQueueEntry got moved after the initial poll.
+                panic!("Future could not be removed from wait queue");
+            }
+            wait_node.state = RecvPollState::Unregistered;
+        }
+    }
+}
+
+/// A channel which broadcasts every value that is sent into it to an arbitrary
+/// number of receivers.
+///
+/// The channel retains the most recently sent values in a buffer of type `A`.
+/// Each receiver tracks its own position inside the stream of values, and
+/// will observe every value that is sent after it subscribed.
+///
+/// Sending never waits for receivers. If the buffer is full, the oldest value
+/// is evicted. Receivers which hadn't observed the evicted values yet will
+/// get notified about the amount of lost values via
+/// [`BroadcastReceiveError::Lagged`] on their next reception attempt.
+///
+/// Receivers are created through [`GenericBroadcastChannel::subscribe`].
+pub struct GenericBroadcastChannel<MutexType: RawMutex, T, A>
+where
+    A: IndexedRingBuf<Item = T>,
+{
+    inner: Mutex<MutexType, ChannelState<T, A>>,
+}
+
+// The channel can be sent to other threads as long as it's not borrowed and the
+// value in it can be sent to other threads.
+unsafe impl<MutexType: RawMutex + Send, T: Send, A> Send
+    for GenericBroadcastChannel<MutexType, T, A>
+where
+    A: IndexedRingBuf<Item = T> + Send,
+{
+}
+// The channel is thread-safe as long as a thread-safe mutex is used
+unsafe impl<MutexType: RawMutex + Sync, T: Send, A> Sync
+    for GenericBroadcastChannel<MutexType, T, A>
+where
+    A: IndexedRingBuf<Item = T>,
+{
+}
+
+impl<MutexType: RawMutex, T, A> core::fmt::Debug
+    for GenericBroadcastChannel<MutexType, T, A>
+where
+    A: IndexedRingBuf<Item = T>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericBroadcastChannel").finish()
+    }
+}
+
+impl<MutexType: RawMutex, T, A> Default
+    for GenericBroadcastChannel<MutexType, T, A>
+where
+    T: Clone,
+    A: IndexedRingBuf<Item = T>,
+{
+    fn default() -> Self {
+        GenericBroadcastChannel {
+            inner: Mutex::new(ChannelState::new(A::new())),
+        }
+    }
+}
+
+impl<MutexType: RawMutex, T, A> GenericBroadcastChannel<MutexType, T, A>
+where
+    T: Clone,
+    A: IndexedRingBuf<Item = T> + ConstRingBuf,
+{
+    /// Creates a new broadcast channel which retains as many values as
+    /// the RingBuffer in `A` provides.
+    pub const fn new() -> Self {
+        GenericBroadcastChannel {
+            inner: Mutex::const_new(
+                MutexType::INIT,
+                ChannelState {
+                    is_closed: false,
+                    head: 0,
+                    buffer: A::INIT,
+                    waiters: LinkedList::new(),
+                },
+            ),
+        }
+    }
+}
+
+impl<MutexType: RawMutex, T, A> GenericBroadcastChannel<MutexType, T, A>
+where
+    T: Clone,
+    A: IndexedRingBuf<Item = T>,
+{
+    /// Creates a new broadcast channel which retains up to `capacity` values.
+    ///
+    /// Depending on the utilized `RingBuf` type, the capacity argument might
+    /// be ignored and the default capacity might be utilized.
+    pub fn with_capacity(capacity: usize) -> Self {
+        GenericBroadcastChannel {
+            inner: Mutex::new(ChannelState::new(A::with_capacity(capacity))),
+        }
+    }
+
+    /// Writes a single value to the channel.
+    ///
+    /// This will notify waiting receivers about the availability of the value.
+    /// If the buffer of the channel is full, the oldest value gets evicted.
+    /// If the channel is closed, the value will be rejected and returned
+    /// inside the error variant.
+    pub fn send(&self, value: T) -> Result<(), ChannelSendError<T>> {
+        self.inner.lock().send(value)
+    }
+
+    /// Closes the channel.
+    ///
+    /// Receivers will still be able to receive the values which are retained
+    /// inside the channel. Afterwards their `receive` operations will resolve
+    /// with [`BroadcastReceiveError::Closed`].
+    /// `send(value)` attempts which follow this call will fail with a
+    /// [`ChannelSendError`].
+    pub fn close(&self) -> CloseStatus {
+        self.inner.lock().close()
+    }
+
+    /// Creates a new receiver for the channel.
+    ///
+    /// The receiver starts at the tail of the channel: It will only observe
+    /// values which are sent after this call.
+    pub fn subscribe(&self) -> BroadcastSubscriber<'_, MutexType, T, A> {
+        BroadcastSubscriber {
+            channel: self,
+            cursor: self.inner.lock().tail(),
+        }
+    }
+}
+
+impl<MutexType: RawMutex, T, A> BroadcastReceiveAccess<T>
+    for GenericBroadcastChannel<MutexType, T, A>
+where
+    T: Clone,
+    A: IndexedRingBuf<Item = T>,
+{
+    unsafe fn receive_or_register(
+        &self,
+        wait_node: &mut ListNode<RecvWaitQueueEntry>,
+        cursor: &mut u64,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, BroadcastReceiveError>> {
+        self.inner.lock().receive_or_register(wait_node, cursor, cx)
+    }
+
+    fn remove_receive_waiter(
+        &self,
+        wait_node: &mut ListNode<RecvWaitQueueEntry>,
+    ) {
+        self.inner.lock().remove_waiter(wait_node)
+    }
+}
+
+/// A receiver of a [`GenericBroadcastChannel`], which is obtained through
+/// [`GenericBroadcastChannel::subscribe`].
+///
+/// The receiver tracks which values it had already observed. Cloning the
+/// receiver creates a receiver at the same position.
+pub struct BroadcastSubscriber<'a, MutexType: RawMutex, T, A>
+where
+    A: IndexedRingBuf<Item = T>,
+{
+    /// The channel the receiver is subscribed to
+    channel: &'a GenericBroadcastChannel<MutexType, T, A>,
+    /// The sequence number of the next value the receiver will observe
+    cursor: u64,
+}
+
+impl<'a, MutexType: RawMutex, T, A> core::fmt::Debug
+    for BroadcastSubscriber<'a, MutexType, T, A>
+where
+    A: IndexedRingBuf<Item = T>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("BroadcastSubscriber").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, A> Clone
+    for BroadcastSubscriber<'a, MutexType, T, A>
+where
+    A: IndexedRingBuf<Item = T>,
+{
+    fn clone(&self) -> Self {
+        BroadcastSubscriber {
+            channel: self.channel,
+            cursor: self.cursor,
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, A> BroadcastSubscriber<'a, MutexType, T, A>
+where
+    T: Clone,
+    A: IndexedRingBuf<Item = T>,
+{
+    /// Returns a future that gets fulfilled with the next value the receiver
+    /// hadn't observed yet, or with an error if the receiver lagged behind or
+    /// the channel is closed.
+    ///
+    /// Refer to [`BroadcastReceiveFuture`] for details.
+    pub fn receive(&mut self) -> BroadcastReceiveFuture<'_, MutexType, T> {
+        BroadcastReceiveFuture {
+            channel: Some(self.channel),
+            cursor: &mut self.cursor,
+            wait_node: ListNode::new(RecvWaitQueueEntry::new()),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Attempt to retrieve the next value the receiver hadn't observed yet
+    /// without waiting.
+    pub fn try_receive(&mut self) -> Result<T, TryBroadcastReceiveError> {
+        self.channel.inner.lock().try_receive(&mut self.cursor)
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericBroadcastChannel`] implementation which is not thread-safe.
+pub type LocalBroadcastChannel<T, A> =
+    GenericBroadcastChannel<NoopLock, T, ArrayBuf<T, A>>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericBroadcastChannel`] implementation backed by [`parking_lot`].
+    pub type BroadcastChannel<T, A> =
+        GenericBroadcastChannel<parking_lot::RawMutex, T, ArrayBuf<T, A>>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
+
+#[cfg(feature = "alloc")]
+mod if_alloc {
+    use super::*;
+
+    pub mod shared {
+        use super::*;
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        struct GenericBroadcastChannelSharedState<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            T: Clone + 'static,
+            A: IndexedRingBuf<Item = T>,
+        {
+            /// The amount of [`GenericBroadcastSender`] instances which
+            /// reference this state.
+            senders: AtomicUsize,
+            /// The channel on which is acted.
+            channel: GenericBroadcastChannel<MutexType, T, A>,
+        }
+
+        /// The sending side of a broadcast channel.
+        ///
+        /// Values can be sent into the channel through `send`. New receivers
+        /// can be created through `subscribe`.
+        ///
+        /// The channel gets closed once the last sender is dropped.
+        pub struct GenericBroadcastSender<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            T: Clone + 'static,
+            A: IndexedRingBuf<Item = T>,
+        {
+            inner: alloc::sync::Arc<
+                GenericBroadcastChannelSharedState<MutexType, T, A>,
+            >,
+        }
+
+        /// The receiving side of a broadcast channel.
+        ///
+        /// The receiver tracks which values it had already observed. Cloning
+        /// the receiver creates a receiver at the same position.
+        pub struct GenericBroadcastReceiver<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            T: Clone + 'static,
+            A: IndexedRingBuf<Item = T>,
+        {
+            inner: alloc::sync::Arc<
+                GenericBroadcastChannelSharedState<MutexType, T, A>,
+            >,
+            /// The sequence number of the next value the receiver will observe
+            cursor: u64,
+        }
+
+        impl<MutexType, T, A> core::fmt::Debug
+            for GenericBroadcastSender<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            T: Clone,
+            A: IndexedRingBuf<Item = T>,
+        {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.debug_struct("BroadcastSender").finish()
+            }
+        }
+
+        impl<MutexType, T, A> core::fmt::Debug
+            for GenericBroadcastReceiver<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            T: Clone,
+            A: IndexedRingBuf<Item = T>,
+        {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.debug_struct("BroadcastReceiver").finish()
+            }
+        }
+
+        impl<MutexType, T, A> Clone for GenericBroadcastSender<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            T: Clone,
+            A: IndexedRingBuf<Item = T>,
+        {
+            fn clone(&self) -> Self {
+                let old_size =
+                    self.inner.senders.fetch_add(1, Ordering::Relaxed);
+                if old_size > isize::MAX as usize {
+                    panic!("Reached maximum refcount");
+                }
+                GenericBroadcastSender {
+                    inner: self.inner.clone(),
+                }
+            }
+        }
+
+        impl<MutexType, T, A> Drop for GenericBroadcastSender<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            T: Clone,
+            A: IndexedRingBuf<Item = T>,
+        {
+            fn drop(&mut self) {
+                if self.inner.senders.fetch_sub(1, Ordering::Release) != 1 {
+                    return;
+                }
+                core::sync::atomic::fence(Ordering::Acquire);
+                // Close the channel, before last sender gets destroyed.
+                // Receivers can still observe the retained values.
+                self.inner.channel.close();
+            }
+        }
+
+        impl<MutexType, T, A> Clone for GenericBroadcastReceiver<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            T: Clone,
+            A: IndexedRingBuf<Item = T>,
+        {
+            fn clone(&self) -> Self {
+                GenericBroadcastReceiver {
+                    inner: self.inner.clone(),
+                    cursor: self.cursor,
+                }
+            }
+        }
+
+        /// Creates a new broadcast channel which can be used to distribute
+        /// values of type `T` to an arbitrary number of concurrent tasks.
+        /// The channel retains up to `capacity` values, depending on the
+        /// utilized `RingBuf` type.
+        ///
+        /// The ends of the channel are represented through the returned
+        /// Sender and Receiver. Additional receivers can be created through
+        /// [`GenericBroadcastSender::subscribe`].
+        ///
+        /// The channel is closed once all senders are dropped.
+        pub fn generic_broadcast_channel<MutexType, T, A>(
+            capacity: usize,
+        ) -> (
+            GenericBroadcastSender<MutexType, T, A>,
+            GenericBroadcastReceiver<MutexType, T, A>,
+        )
+        where
+            MutexType: RawMutex,
+            T: Clone + Send,
+            A: IndexedRingBuf<Item = T>,
+        {
+            let inner =
+                alloc::sync::Arc::new(GenericBroadcastChannelSharedState {
+                    channel: GenericBroadcastChannel::with_capacity(capacity),
+                    senders: AtomicUsize::new(1),
+                });
+
+            let sender = GenericBroadcastSender {
+                inner: inner.clone(),
+            };
+            let receiver = GenericBroadcastReceiver { inner, cursor: 0 };
+
+            (sender, receiver)
+        }
+
+        impl<MutexType, T, A> GenericBroadcastSender<MutexType, T, A>
+        where
+            MutexType: RawMutex + 'static,
+            T: Clone,
+            A: 'static + IndexedRingBuf<Item = T>,
+        {
+            /// Writes a single value to the channel.
+            ///
+            /// This will notify waiting receivers about the availability of
+            /// the value. If the buffer of the channel is full, the oldest
+            /// value gets evicted. If the channel is closed, the value will be
+            /// rejected and returned inside the error variant.
+            pub fn send(&self, value: T) -> Result<(), ChannelSendError<T>> {
+                self.inner.channel.send(value)
+            }
+
+            /// Closes the channel.
+            /// All pending and future send attempts will fail.
+            /// Receivers will still be able to receive the values which are
+            /// retained inside the channel.
+            pub fn close(&self) -> CloseStatus {
+                self.inner.channel.close()
+            }
+
+            /// Creates a new receiver for the channel.
+            ///
+            /// The receiver starts at the tail of the channel: It will only
+            /// observe values which are sent after this call.
+            pub fn subscribe(
+                &self,
+            ) -> GenericBroadcastReceiver<MutexType, T, A> {
+                GenericBroadcastReceiver {
+                    inner: self.inner.clone(),
+                    cursor: self.inner.channel.inner.lock().tail(),
+                }
+            }
+        }
+
+        impl<MutexType, T, A> GenericBroadcastReceiver<MutexType, T, A>
+        where
+            MutexType: RawMutex + 'static,
+            T: Clone,
+            A: 'static + IndexedRingBuf<Item = T>,
+        {
+            /// Returns a future that gets fulfilled with the next value the
+            /// receiver hadn't observed yet, or with an error if the receiver
+            /// lagged behind or the channel is closed.
+            ///
+            /// Refer to [`BroadcastReceiveFuture`] for details.
+            pub fn receive(
+                &mut self,
+            ) -> BroadcastReceiveFuture<'_, MutexType, T> {
+                BroadcastReceiveFuture {
+                    channel: Some(&self.inner.channel),
+                    cursor: &mut self.cursor,
+                    wait_node: ListNode::new(RecvWaitQueueEntry::new()),
+                    _phantom: PhantomData,
+                }
+            }
+
+            /// Attempt to retrieve the next value the receiver hadn't observed
+            /// yet without waiting.
+            pub fn try_receive(
+                &mut self,
+            ) -> Result<T, TryBroadcastReceiveError> {
+                self.inner
+                    .channel
+                    .inner
+                    .lock()
+                    .try_receive(&mut self.cursor)
+            }
+        }
+
+        // Export parking_lot based shared channels in std mode
+        #[cfg(feature = "std")]
+        mod if_std {
+            use super::*;
+
+            use crate::buffer::GrowingHeapBuf;
+
+            /// A [`GenericBroadcastSender`] implementation backed by [`parking_lot`].
+            ///
+            /// Uses a `GrowingHeapBuf` whose capacity grows dynamically up to
+            /// the given limit. Refer to [`GrowingHeapBuf`] for more information.
+            ///
+            /// [`GrowingHeapBuf`]: ../../buffer/struct.GrowingHeapBuf.html
+            pub type BroadcastSender<T> = GenericBroadcastSender<
+                parking_lot::RawMutex,
+                T,
+                GrowingHeapBuf<T>,
+            >;
+            /// A [`GenericBroadcastReceiver`] implementation backed by [`parking_lot`].
+            ///
+            /// Uses a `GrowingHeapBuf` whose capacity grows dynamically up to
+            /// the given limit. Refer to [`GrowingHeapBuf`] for more information.
+            ///
+            /// [`GrowingHeapBuf`]: ../../buffer/struct.GrowingHeapBuf.html
+            pub type BroadcastReceiver<T> = GenericBroadcastReceiver<
+                parking_lot::RawMutex,
+                T,
+                GrowingHeapBuf<T>,
+            >;
+
+            /// Creates a new broadcast channel which retains up to `capacity`
+            /// values.
+            ///
+            /// Refer to [`generic_broadcast_channel`] for details.
+            ///
+            /// ```
+            /// # use futures_intrusive::channel::shared::broadcast_channel;
+            /// let (sender, receiver) = broadcast_channel::<i32>(16);
+            /// ```
+            pub fn broadcast_channel<T>(
+                capacity: usize,
+            ) -> (BroadcastSender<T>, BroadcastReceiver<T>)
+            where
+                T: Clone + Send,
+            {
+                generic_broadcast_channel::<
+                    parking_lot::RawMutex,
+                    T,
+                    GrowingHeapBuf<T>,
+                >(capacity)
+            }
+        }
+
+        #[cfg(feature = "std")]
+        pub use self::if_std::*;
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use self::if_alloc::*;
@@ -7,6 +7,14 @@
 #[derive(PartialEq, Debug)]
 pub struct ChannelSendError<T>(pub T);
 
+/// The error which is returned by the sinks of a channel once the channel
+/// had been closed.
+///
+/// In contrast to [`ChannelSendError`], the error doesn't carry a value, since
+/// a sink can already fail before a value had been passed to it.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct ChannelClosedError;
+
 /// The error which is returned when trying to receive from a channel
 /// without waiting fails.
 #[derive(PartialEq, Debug, Copy, Clone)]
@@ -70,3 +78,57 @@ impl<T> TrySendError<T> {
         }
     }
 }
+
+/// The error which is returned when receiving from a broadcast channel fails.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum BroadcastReceiveError {
+    /// The receiver fell behind the sender, and the given amount of values
+    /// had been evicted from the channel before they could be received.
+    /// The receiver continues with the oldest value which is still available.
+    Lagged(u64),
+    /// The channel had been closed and no more value is available for reception.
+    Closed,
+}
+
+impl BroadcastReceiveError {
+    /// Returns whether the error is the `Lagged` variant.
+    pub fn is_lagged(self) -> bool {
+        matches!(self, Self::Lagged(_))
+    }
+
+    /// Returns whether the error is the `Closed` variant.
+    pub fn is_closed(self) -> bool {
+        matches!(self, Self::Closed)
+    }
+}
+
+/// The error which is returned when trying to receive from a broadcast channel
+/// without waiting fails.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TryBroadcastReceiveError {
+    /// No new value is available for reception.
+    Empty,
+    /// The receiver fell behind the sender, and the given amount of values
+    /// had been evicted from the channel before they could be received.
+    /// The receiver continues with the oldest value which is still available.
+    Lagged(u64),
+    /// The channel had been closed and no more value is available for reception.
+    Closed,
+}
+
+impl TryBroadcastReceiveError {
+    /// Returns whether the error is the `Empty` variant.
+    pub fn is_empty(self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    /// Returns whether the error is the `Lagged` variant.
+    pub fn is_lagged(self) -> bool {
+        matches!(self, Self::Lagged(_))
+    }
+
+    /// Returns whether the error is the `Closed` variant.
+    pub fn is_closed(self) -> bool {
+        matches!(self, Self::Closed)
+    }
+}
@@ -4,7 +4,10 @@
 //! asynchronous tasks.
 
 mod error;
-pub use self::error::{ChannelSendError, TryReceiveError, TrySendError};
+pub use self::error::{
+    BroadcastReceiveError, ChannelClosedError, ChannelSendError,
+    TryBroadcastReceiveError, TryReceiveError, TrySendError,
+};
 
 mod channel_future;
 use channel_future::{
@@ -40,14 +43,27 @@ pub use state_broadcast::{
 #[cfg(feature = "std")]
 pub use self::state_broadcast::StateBroadcastChannel;
 
+mod broadcast;
+pub use broadcast::{
+    BroadcastReceiveFuture, BroadcastSubscriber, GenericBroadcastChannel,
+    LocalBroadcastChannel,
+};
+
+#[cfg(feature = "std")]
+pub use self::broadcast::BroadcastChannel;
+
 mod mpmc;
 
 pub use self::mpmc::{
-    ChannelStream, GenericChannel, LocalChannel, LocalUnbufferedChannel,
+    ChannelReceiveManyFuture, ChannelSendAllFuture, ChannelStream,
+    GenericChannel, LocalChannel, LocalPriorityChannel, LocalUnbufferedChannel,
 };
 
 #[cfg(feature = "std")]
-pub use self::mpmc::{Channel, UnbufferedChannel};
+pub use self::mpmc::{Channel, PriorityChannel, UnbufferedChannel};
+
+#[cfg(feature = "sink")]
+pub use self::mpmc::ChannelSink;
 
 #[cfg(feature = "alloc")]
 mod if_alloc {
@@ -57,6 +73,7 @@ mod if_alloc {
     /// The Futures produced by channels in this module don't require a lifetime
     /// parameter.
     pub mod shared {
+        pub use super::super::broadcast::shared::*;
         pub use super::super::channel_future::shared::*;
         pub use super::super::mpmc::shared::*;
         pub use super::super::oneshot::shared::*;
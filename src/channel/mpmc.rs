@@ -2,13 +2,14 @@
 
 use crate::intrusive_double_linked_list::{LinkedList, ListNode};
 use crate::{
-    buffer::{ArrayBuf, RingBuf},
+    buffer::{ArrayBuf, ArrayPriorityBuf, ConstRingBuf, RingBuf},
+    select::SelectSource,
     utils::update_waker_ref,
     NoopLock,
 };
 use core::{marker::PhantomData, pin::Pin};
 use futures_core::{
-    future::Future,
+    future::{FusedFuture, Future},
     stream::{FusedStream, Stream},
     task::{Context, Poll, Waker},
 };
@@ -20,6 +21,10 @@ use super::{
     SendPollState, SendWaitQueueEntry, TryReceiveError, TrySendError,
 };
 
+use super::ChannelClosedError;
+#[cfg(feature = "sink")]
+use futures_sink::Sink;
+
 fn wake_recv_waiters(waiters: &mut LinkedList<RecvWaitQueueEntry>) {
     // Remove all waiters from the waiting list in reverse order and wake them.
     // We reverse the waiter list, so that the oldest waker (which is
@@ -64,6 +69,15 @@ fn return_oldest_receive_waiter(
     }
 }
 
+/// Drops the values which are stored inside `values`.
+/// This needs to happen before the channel gets locked, since dropping a value
+/// might run arbitrary code.
+fn clear_slots<T>(values: &mut [Option<T>]) {
+    for slot in values.iter_mut() {
+        *slot = None;
+    }
+}
+
 /// Internal state of the channel
 struct ChannelState<T, A>
 where
@@ -77,21 +91,31 @@ where
     receive_waiters: LinkedList<RecvWaitQueueEntry>,
     /// Futures which are waiting on send
     send_waiters: LinkedList<SendWaitQueueEntry<T>>,
+    /// The amount of slots inside the buffer which had been reserved by sinks
+    reserved: usize,
 }
 
 impl<T, A> ChannelState<T, A>
 where
     A: RingBuf<Item = T>,
 {
-    fn new(buffer: A) -> ChannelState<T, A> {
+    const fn new(buffer: A) -> ChannelState<T, A> {
         ChannelState::<T, A> {
             is_closed: false,
             buffer,
             receive_waiters: LinkedList::new(),
             send_waiters: LinkedList::new(),
+            reserved: 0,
         }
     }
 
+    /// Returns true if there is enough space in the buffer to store another
+    /// item, which is not reserved by a sink.
+    fn can_push(&self) -> bool {
+        self.buffer.can_push()
+            && self.buffer.len() + self.reserved < self.buffer.capacity()
+    }
+
     fn clear(&mut self) {
         while !self.buffer.is_empty() {
             self.buffer.pop();
@@ -122,7 +146,7 @@ where
 
         if self.is_closed {
             Err(TrySendError::Closed(value))
-        } else if self.buffer.can_push() {
+        } else if self.can_push() {
             self.buffer.push(value);
 
             // Return the oldest receive waiter
@@ -132,6 +156,164 @@ where
         }
     }
 
+    /// Returns the `Waker` of the oldest receive waiter if values are still
+    /// available inside the channel.
+    /// Operations which make multiple values available only notify a single
+    /// receive waiter. Every receiver which leaves values inside the channel
+    /// passes the notification on to the next receive waiter.
+    #[must_use]
+    fn return_next_receive_waiter(&mut self) -> Option<Waker> {
+        if self.buffer.is_empty() {
+            None
+        } else {
+            return_oldest_receive_waiter(&mut self.receive_waiters)
+        }
+    }
+
+    /// Copies the value of the oldest send waiter into the channel if space is
+    /// still available, and returns its `Waker`.
+    /// Operations which free multiple slots only complete a single send
+    /// waiter. Every completed sender which leaves space inside the channel
+    /// passes the space on to the next send waiter.
+    #[must_use]
+    fn return_next_send_waiter(&mut self) -> Option<Waker> {
+        if self.can_push() {
+            self.try_copy_value_from_oldest_waiter()
+        } else {
+            None
+        }
+    }
+
+    /// Moves values out of `values` into the buffer, until either the buffer
+    /// is full or all values had been moved. Empty slots are skipped.
+    /// Returns the amount of moved values.
+    fn push_many(&mut self, values: &mut [Option<T>]) -> usize {
+        let mut sent = 0;
+        for slot in values.iter_mut() {
+            if !self.can_push() {
+                break;
+            }
+            if let Some(value) = slot.take() {
+                self.buffer.push(value);
+                sent += 1;
+            }
+        }
+        sent
+    }
+
+    /// Attempt to send as many values as possible without waiting.
+    /// Returns the amount of values which had been sent, and the `Waker` of a
+    /// task which can receive them.
+    fn try_send_many(
+        &mut self,
+        values: &mut [Option<T>],
+    ) -> Result<(usize, Option<Waker>), TrySendError<()>> {
+        debug_assert!(
+            self.buffer.capacity() > 0,
+            "try_send_many is not supported for unbuffered channels"
+        );
+
+        if self.is_closed {
+            return Err(TrySendError::Closed(()));
+        }
+
+        let sent = self.push_many(values);
+        if sent == 0 {
+            if values.iter().any(Option::is_some) {
+                return Err(TrySendError::Full(()));
+            }
+            return Ok((0, None));
+        }
+
+        // Return the oldest receive waiter
+        Ok((
+            sent,
+            return_oldest_receive_waiter(&mut self.receive_waiters),
+        ))
+    }
+
+    /// Tries to send all values of `values` to the channel.
+    /// If not all values fit into the channel, the next value gets moved into
+    /// the `wait_node`, which is added to the wait queue at the channel. It
+    /// will be signalled once the value has been copied into the channel.
+    /// `values` gets advanced to the slot of the waiting value. If the channel
+    /// is closed, the waiting value is moved back into this slot.
+    /// Returns the `Waker`s of a receive and a send waiter which can make
+    /// progress.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn send_many_or_register(
+        &mut self,
+        wait_node: &mut ListNode<SendWaitQueueEntry<T>>,
+        values: &mut &mut [Option<T>],
+        cx: &mut Context<'_>,
+    ) -> (
+        Poll<Result<(), ChannelClosedError>>,
+        Option<Waker>,
+        Option<Waker>,
+    ) {
+        match wait_node.state {
+            SendPollState::Unregistered | SendPollState::SendComplete => {
+                wait_node.state = SendPollState::Unregistered;
+
+                if self.is_closed {
+                    // A value might still be stored in the wait node if the
+                    // channel got closed while the future was registered.
+                    if let Some(value) = wait_node.value.take() {
+                        values[0] = Some(value);
+                    }
+                    let result = if values.iter().any(Option::is_some) {
+                        Err(ChannelClosedError)
+                    } else {
+                        Ok(())
+                    };
+                    return (Poll::Ready(result), None, None);
+                }
+
+                let sent = self.push_many(values);
+                let remaining = core::mem::take(values);
+                match remaining.iter().position(Option::is_some) {
+                    Some(index) => {
+                        // The capacity is exhausted. Register a waiter, whose
+                        // value gets copied into the channel by the receiver.
+                        let remaining = &mut remaining[index..];
+                        wait_node.value = remaining[0].take();
+                        *values = remaining;
+                        wait_node.task = Some(cx.waker().clone());
+                        wait_node.state = SendPollState::Registered;
+                        self.send_waiters.add_front(wait_node);
+
+                        // Return the oldest receive waiter
+                        let waker = return_oldest_receive_waiter(
+                            &mut self.receive_waiters,
+                        );
+                        (Poll::Pending, waker, None)
+                    }
+                    None => {
+                        let receive_waker = if sent > 0 {
+                            return_oldest_receive_waiter(
+                                &mut self.receive_waiters,
+                            )
+                        } else {
+                            None
+                        };
+                        // Pass remaining space on to the next send waiter
+                        let send_waker = self.return_next_send_waiter();
+                        (Poll::Ready(Ok(())), receive_waker, send_waker)
+                    }
+                }
+            }
+            SendPollState::Registered => {
+                // Since the channel wakes up all waiters and moves their states
+                // to unregistered there can't be space available in the channel.
+                // However the caller might have passed a different `Waker`.
+                // In this case we need to update it.
+                update_waker_ref(&mut wait_node.task, cx);
+                (Poll::Pending, None, None)
+            }
+        }
+    }
+
     /// Tries to send a value to the channel.
     /// If the value isn't available yet, the ChannelSendFuture gets added to the
     /// wait queue at the channel, and will be signalled once ready.
@@ -152,7 +334,7 @@ where
                     return (Poll::Ready(()), value, None);
                 }
 
-                if !self.buffer.can_push() {
+                if !self.can_push() {
                     // If the capacity is exhausted, register a waiter
                     wait_node.task = Some(cx.waker().clone());
                     wait_node.state = SendPollState::Registered;
@@ -187,24 +369,26 @@ where
             }
             SendPollState::SendComplete => {
                 // The transfer is complete, and the sender has already been removed from the
-                // list of pending senders
-                (Poll::Ready(()), None, None)
+                // list of pending senders. Pass remaining space on to the next
+                // send waiter.
+                (Poll::Ready(()), None, self.return_next_send_waiter())
             }
         }
     }
 
     /// If there is a send waiter, copy it's value into the channel buffer and complete it.
+    /// If the waiter belongs to a sink which waits for capacity, the free
+    /// space is reserved for the sink instead.
     /// The method may only be called if there is space in the receive buffer.
     #[must_use]
     fn try_copy_value_from_oldest_waiter(&mut self) -> Option<Waker> {
         let last_waiter = self.send_waiters.remove_last();
 
         if let Some(last_waiter) = last_waiter {
-            let value = last_waiter
-                .value
-                .take()
-                .expect("wait_node must contain value");
-            self.buffer.push(value);
+            match last_waiter.value.take() {
+                Some(value) => self.buffer.push(value),
+                None => self.reserved += 1,
+            }
 
             last_waiter.state = SendPollState::SendComplete;
 
@@ -217,14 +401,19 @@ where
     /// Tries to extract a value from the sending waiter which has been waiting
     /// longest on the send operation to complete.
     fn try_take_value_from_sender(&mut self) -> Option<(T, Option<Waker>)> {
+        // Senders of buffered channels only wait for space inside the
+        // buffer. Their values get copied into the buffer once space is
+        // available.
+        if self.buffer.capacity() != 0 {
+            return None;
+        }
+
         // Safety: The method is only called inside the lock on a consistent
         // list.
         match self.send_waiters.remove_last() {
             Some(last_sender) => {
-                // This path should be only used for 0 capacity queues.
                 // Since the list is not empty, a value is available.
                 // Extract it from the sender in order to return it
-                debug_assert_eq!(0, self.buffer.capacity());
 
                 // Safety: The sender can't be invalid, since we only add valid
                 // senders to the queue
@@ -258,29 +447,117 @@ where
         }
     }
 
+    /// Tries to receive values from the channel without waiting, and stores
+    /// them in the leading slots of `values`.
+    /// Returns the amount of values which had been received, and the `Waker`
+    /// of a task which can send further values.
+    fn try_receive_many(
+        &mut self,
+        values: &mut [Option<T>],
+    ) -> Result<(usize, Option<Waker>), TryReceiveError> {
+        if values.is_empty() {
+            return Ok((0, None));
+        }
+
+        let mut received = 0;
+        for slot in values.iter_mut() {
+            if self.buffer.is_empty() {
+                break;
+            }
+            *slot = Some(self.buffer.pop());
+            received += 1;
+        }
+
+        if received == 0 {
+            // Unbuffered channels transfer the value of a single sender
+            let (value, waker) = self.try_receive()?;
+            values[0] = Some(value);
+            return Ok((1, waker));
+        }
+
+        // Only the oldest send waiter is completed. It passes the remaining
+        // space on to the next send waiter.
+        Ok((received, self.try_copy_value_from_oldest_waiter()))
+    }
+
+    /// Tries to read values from the channel and stores them in the leading
+    /// slots of `values`.
+    /// If no value is available yet, the `wait_node` gets added to the wait
+    /// queue at the channel, and will be signalled once ready.
+    /// Returns `0` if the channel is closed and no value is left, as well as
+    /// the `Waker`s of a send and a receive waiter which can make progress.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn receive_many_or_register(
+        &mut self,
+        wait_node: &mut ListNode<RecvWaitQueueEntry>,
+        values: &mut [Option<T>],
+        cx: &mut Context<'_>,
+    ) -> (Poll<usize>, Option<Waker>, Option<Waker>) {
+        match wait_node.state {
+            RecvPollState::Unregistered | RecvPollState::Notified => {
+                wait_node.state = RecvPollState::Unregistered;
+
+                match self.try_receive_many(values) {
+                    Ok((received, waker)) => (
+                        Poll::Ready(received),
+                        waker,
+                        self.return_next_receive_waiter(),
+                    ),
+                    Err(TryReceiveError::Closed) => {
+                        (Poll::Ready(0), None, None)
+                    }
+                    Err(TryReceiveError::Empty) => {
+                        // Added the task to the wait queue
+                        wait_node.task = Some(cx.waker().clone());
+                        wait_node.state = RecvPollState::Registered;
+                        self.receive_waiters.add_front(wait_node);
+                        (Poll::Pending, None, None)
+                    }
+                }
+            }
+            RecvPollState::Registered => {
+                // Since the channel wakes up all waiters and moves their states
+                // to unregistered there can't be any value in the channel in
+                // this state. However the caller might have passed a different `Waker`.
+                // In this case we need to update it.
+                update_waker_ref(&mut wait_node.task, cx);
+                (Poll::Pending, None, None)
+            }
+        }
+    }
+
     /// Tries to read the value from the channel.
     /// If the value isn't available yet, the ChannelReceiveFuture gets added to the
     /// wait queue at the channel, and will be signalled once ready.
+    /// Returns the `Waker`s of a send and a receive waiter which can make
+    /// progress.
     /// This function is only safe as long as the `wait_node`s address is guaranteed
     /// to be stable until it gets removed from the queue.
     unsafe fn receive_or_register(
         &mut self,
         wait_node: &mut ListNode<RecvWaitQueueEntry>,
         cx: &mut Context<'_>,
-    ) -> Poll<Option<(T, Option<Waker>)>> {
+    ) -> (Poll<Option<T>>, Option<Waker>, Option<Waker>) {
         match wait_node.state {
             RecvPollState::Unregistered | RecvPollState::Notified => {
                 wait_node.state = RecvPollState::Unregistered;
 
                 match self.try_receive() {
-                    Ok(val) => Poll::Ready(Some(val)),
-                    Err(TryReceiveError::Closed) => Poll::Ready(None),
+                    Ok((val, waker)) => (
+                        Poll::Ready(Some(val)),
+                        waker,
+                        self.return_next_receive_waiter(),
+                    ),
+                    Err(TryReceiveError::Closed) => {
+                        (Poll::Ready(None), None, None)
+                    }
                     Err(TryReceiveError::Empty) => {
                         // Added the task to the wait queue
                         wait_node.task = Some(cx.waker().clone());
                         wait_node.state = RecvPollState::Registered;
                         self.receive_waiters.add_front(wait_node);
-                        Poll::Pending
+                        (Poll::Pending, None, None)
                     }
                 }
             }
@@ -290,15 +567,16 @@ where
                 // this state. However the caller might have passed a different `Waker`.
                 // In this case we need to update it.
                 update_waker_ref(&mut wait_node.task, cx);
-                Poll::Pending
+                (Poll::Pending, None, None)
             }
         }
     }
 
+    #[must_use]
     fn remove_send_waiter(
         &mut self,
         wait_node: &mut ListNode<SendWaitQueueEntry<T>>,
-    ) {
+    ) -> Option<Waker> {
         // ChannelSendFuture only needs to get removed if it had been added to
         // the wait queue of the channel.
         // This has happened in the SendPollState::Registered case.
@@ -312,10 +590,14 @@ where
                     panic!("Future could not be removed from wait queue");
                 }
                 wait_node.state = SendPollState::Unregistered;
+                None
             }
-            SendPollState::Unregistered => {}
+            SendPollState::Unregistered => None,
             SendPollState::SendComplete => {
-                // Send was complete. In that case the queue item is not in the list
+                // Send was complete. In that case the queue item is not in the list.
+                // Pass remaining space on to the next send waiter instead.
+                wait_node.state = SendPollState::Unregistered;
+                self.return_next_send_waiter()
             }
         }
     }
@@ -349,6 +631,194 @@ where
     }
 }
 
+/// The state of a sink which sends values into a channel
+#[cfg(feature = "sink")]
+struct SinkState<T> {
+    /// Node for waiting at the channel. A sink of a buffered channel waits
+    /// here until space for a value is available. A sink of an unbuffered
+    /// channel stores the value here until it had been received.
+    wait_node: ListNode<SendWaitQueueEntry<T>>,
+    /// Whether a slot inside the buffer of the channel is reserved for the
+    /// next value
+    is_reserved: bool,
+    /// Whether a value is stored inside `wait_node`
+    is_sending: bool,
+}
+
+#[cfg(feature = "sink")]
+impl<T> SinkState<T> {
+    fn new() -> Self {
+        SinkState {
+            wait_node: ListNode::new(SendWaitQueueEntry {
+                task: None,
+                state: SendPollState::Unregistered,
+                value: None,
+            }),
+            is_reserved: false,
+            is_sending: false,
+        }
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<T, A> ChannelState<T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    /// Drives the delivery of a value which is stored inside the wait node of
+    /// a sink. This is only the case for unbuffered channels.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn poll_sink_flush(
+        &mut self,
+        sink: &mut SinkState<T>,
+        cx: &mut Context<'_>,
+    ) -> (Poll<Result<(), ChannelClosedError>>, Option<Waker>) {
+        if !sink.is_sending {
+            return (Poll::Ready(Ok(())), None);
+        }
+
+        match self.send_or_register(&mut sink.wait_node, cx) {
+            (Poll::Pending, _, waker) => (Poll::Pending, waker),
+            (Poll::Ready(()), value, waker) => {
+                sink.is_sending = false;
+                sink.wait_node.state = SendPollState::Unregistered;
+                match value {
+                    // The channel had been closed before the value was received
+                    Some(_) => (Poll::Ready(Err(ChannelClosedError)), waker),
+                    None => (Poll::Ready(Ok(())), waker),
+                }
+            }
+        }
+    }
+
+    /// Checks whether a sink can accept the next value. In a buffered channel
+    /// this reserves a slot for the value. If no slot is available, the
+    /// `wait_node` of the sink gets added to the wait queue at the channel,
+    /// and will be signalled once a slot had been reserved for it.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn poll_sink_ready(
+        &mut self,
+        sink: &mut SinkState<T>,
+        cx: &mut Context<'_>,
+    ) -> (Poll<Result<(), ChannelClosedError>>, Option<Waker>) {
+        let (poll, mut waker) = self.poll_sink_flush(sink, cx);
+        if sink.is_reserved || !matches!(poll, Poll::Ready(Ok(()))) {
+            return (poll, waker);
+        }
+
+        let poll = match sink.wait_node.state {
+            SendPollState::Unregistered => {
+                if self.is_closed {
+                    Poll::Ready(Err(ChannelClosedError))
+                } else if self.buffer.capacity() == 0 {
+                    // The value waits inside the sink until it gets received
+                    Poll::Ready(Ok(()))
+                } else if self.can_push() {
+                    self.reserved += 1;
+                    sink.is_reserved = true;
+                    Poll::Ready(Ok(()))
+                } else {
+                    // Wait until a receiver reserves a slot for the sink
+                    sink.wait_node.task = Some(cx.waker().clone());
+                    sink.wait_node.state = SendPollState::Registered;
+                    self.send_waiters.add_front(&mut sink.wait_node);
+                    Poll::Pending
+                }
+            }
+            SendPollState::Registered => {
+                update_waker_ref(&mut sink.wait_node.task, cx);
+                Poll::Pending
+            }
+            SendPollState::SendComplete => {
+                // A receiver reserved a slot for the sink. Pass remaining
+                // space on to the next send waiter.
+                sink.wait_node.state = SendPollState::Unregistered;
+                sink.is_reserved = true;
+                waker = self.return_next_send_waiter();
+                Poll::Ready(Ok(()))
+            }
+        };
+
+        (poll, waker)
+    }
+
+    /// Passes a value to a sink which had been polled ready before.
+    /// In a buffered channel the value is stored inside the reserved slot.
+    /// Returns the `Waker` of a task which can receive the value.
+    fn start_sink_send(
+        &mut self,
+        sink: &mut SinkState<T>,
+        value: T,
+    ) -> Result<Option<Waker>, ChannelClosedError> {
+        if !sink.is_reserved {
+            assert!(
+                self.buffer.capacity() == 0 && !sink.is_sending,
+                "start_send called on a sink which is not ready"
+            );
+            // The value is delivered once the sink gets flushed
+            sink.wait_node.value = Some(value);
+            sink.is_sending = true;
+            return Ok(None);
+        }
+
+        sink.is_reserved = false;
+        self.reserved -= 1;
+        if self.is_closed {
+            return Err(ChannelClosedError);
+        }
+        self.buffer.push(value);
+        Ok(return_oldest_receive_waiter(&mut self.receive_waiters))
+    }
+
+    /// Removes the sink from the channel, and releases its reserved slot.
+    /// Returns the `Waker`s of a send and a receive waiter which might be able
+    /// to make progress due to the released slot.
+    fn remove_sink(
+        &mut self,
+        sink: &mut SinkState<T>,
+    ) -> (Option<Waker>, Option<Waker>) {
+        if !sink.is_sending
+            && sink.wait_node.state == SendPollState::SendComplete
+        {
+            // A slot had been reserved for the sink, but the sink didn't
+            // observe it yet
+            sink.wait_node.state = SendPollState::Unregistered;
+            sink.is_reserved = true;
+        }
+        // A sink which had been completed was moved back to the unregistered
+        // state above. Its reserved slot is passed on below.
+        let waker = self.remove_send_waiter(&mut sink.wait_node);
+        debug_assert!(waker.is_none());
+
+        if !sink.is_reserved {
+            return (None, None);
+        }
+        sink.is_reserved = false;
+        self.reserved -= 1;
+
+        // Pass the slot on to the oldest send waiter
+        match self.send_waiters.remove_last() {
+            Some(last_waiter) => {
+                last_waiter.state = SendPollState::SendComplete;
+                let receive_waker = match last_waiter.value.take() {
+                    Some(value) => {
+                        self.buffer.push(value);
+                        return_oldest_receive_waiter(&mut self.receive_waiters)
+                    }
+                    None => {
+                        self.reserved += 1;
+                        None
+                    }
+                };
+                (last_waiter.task.take(), receive_waker)
+            }
+            None => (None, None),
+        }
+    }
+}
+
 /// A channel which can be used to exchange values of type `T` between
 /// concurrent tasks.
 ///
@@ -365,6 +835,13 @@ where
 /// Values can be sent into the channel through `send`.
 /// The returned Future will get resolved when the value has been stored
 /// inside the channel.
+///
+/// If `A` is a priority buffer like [`ArrayPriorityBuf`], values are received
+/// in the order of their priority instead of the order in which they had been
+/// sent. [`LocalPriorityChannel`] provides a channel which receives the
+/// biggest value first.
+///
+/// [`ArrayPriorityBuf`]: crate::buffer::ArrayPriorityBuf
 pub struct GenericChannel<MutexType: RawMutex, T, A>
 where
     A: RingBuf<Item = T>,
@@ -400,16 +877,24 @@ where
 
 impl<MutexType: RawMutex, T, A> GenericChannel<MutexType, T, A>
 where
-    A: RingBuf<Item = T>,
+    A: ConstRingBuf<Item = T>,
 {
     /// Creates a new Channel, utilizing the default capacity that
     /// the RingBuffer in `A` provides.
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         GenericChannel {
-            inner: Mutex::new(ChannelState::new(A::new())),
+            inner: Mutex::const_new(
+                MutexType::INIT,
+                ChannelState::new(A::INIT),
+            ),
         }
     }
+}
 
+impl<MutexType: RawMutex, T, A> GenericChannel<MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
     /// Creates a new Channel, which has storage for a `capacity` items.
     /// Depending on the utilized `RingBuf` type, the capacity argument might
     /// be ignored and the default capacity might be utilized.
@@ -454,6 +939,54 @@ where
         }
     }
 
+    /// Returns a future that gets fulfilled when all values of `values` have
+    /// been written to the channel.
+    ///
+    /// Whenever the future is polled, it moves as many values out of `values`
+    /// as the channel can store under a single acquisition of the channel's
+    /// lock. Empty slots are skipped.
+    /// If the channel gets closed while the send is in progress, the future
+    /// will resolve to a `ChannelClosedError`. The values which had not been
+    /// sent remain inside `values`. This is also the case if the future gets
+    /// dropped before it completes.
+    pub fn send_all<'a>(
+        &'a self,
+        values: &'a mut [Option<T>],
+    ) -> ChannelSendAllFuture<'a, MutexType, T, A> {
+        ChannelSendAllFuture {
+            channel: Some(self),
+            values,
+            wait_node: ListNode::new(SendWaitQueueEntry {
+                task: None,
+                state: SendPollState::Unregistered,
+                value: None,
+            }),
+        }
+    }
+
+    /// Attempt to send as many values of `values` as possible without waiting.
+    ///
+    /// All values are sent under a single acquisition of the channel's lock.
+    /// Values which had been sent are taken out of `values`, and empty slots
+    /// are skipped. Returns the amount of values which had been sent. If the
+    /// channel is full and no value could be sent, `TrySendError::Full` is
+    /// returned.
+    ///
+    /// Like `try_send`, this operation is not supported for unbuffered
+    /// channels and will panic if the capacity of the `RingBuf` is zero.
+    pub fn try_send_many(
+        &self,
+        values: &mut [Option<T>],
+    ) -> Result<usize, TrySendError<()>> {
+        let (sent, waker) = { self.inner.lock().try_send_many(values)? };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        Ok(sent)
+    }
+
     /// Returns a future that gets fulfilled when a value is written to the channel.
     /// If the channels gets closed, the future will resolve to `None`.
     pub fn receive(&self) -> ChannelReceiveFuture<MutexType, T> {
@@ -479,86 +1012,426 @@ where
         }
     }
 
-    /// Returns a stream that will receive values from this channel.
-    ///
-    /// This stream does not yield `None` when the channel is empty,
-    /// instead it yields `None` when it is terminated.
-    pub fn stream(&self) -> ChannelStream<MutexType, T, A> {
-        ChannelStream {
-            channel: Some(self),
-            future: None,
+    /// Returns a future that gets fulfilled when values are available in the
+    /// channel.
+    ///
+    /// Up to `values.len()` values are received under a single acquisition of
+    /// the channel's lock and get stored in the leading slots of `values`.
+    /// Values which are still stored inside `values` get dropped beforehand.
+    /// The future resolves with the amount of received values, which is at
+    /// least one unless `values` is empty. If the channels gets closed and no
+    /// values are left, the future will resolve to `0`.
+    pub fn receive_many<'a>(
+        &'a self,
+        values: &'a mut [Option<T>],
+    ) -> ChannelReceiveManyFuture<'a, MutexType, T, A> {
+        clear_slots(values);
+
+        ChannelReceiveManyFuture {
+            channel: Some(self),
+            values,
+            wait_node: ListNode::new(RecvWaitQueueEntry::new()),
+        }
+    }
+
+    /// Attempt to receive up to `values.len()` values of the channel without
+    /// waiting.
+    ///
+    /// All values are received under a single acquisition of the channel's
+    /// lock and get stored in the leading slots of `values`. Values which are
+    /// still stored inside `values` get dropped beforehand. Returns the amount
+    /// of received values.
+    pub fn try_receive_many(
+        &self,
+        values: &mut [Option<T>],
+    ) -> Result<usize, TryReceiveError> {
+        clear_slots(values);
+
+        let (received, waker) = { self.inner.lock().try_receive_many(values)? };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        Ok(received)
+    }
+
+    /// Returns a stream that will receive values from this channel.
+    ///
+    /// This stream does not yield `None` when the channel is empty,
+    /// instead it yields `None` when it is terminated.
+    pub fn stream(&self) -> ChannelStream<MutexType, T, A> {
+        ChannelStream {
+            channel: Some(self),
+            future: None,
+        }
+    }
+
+    /// Returns a sink that will send values into this channel.
+    ///
+    /// The sink becomes ready once space for a value is available inside the
+    /// channel. Closing the sink closes the channel.
+    #[cfg(feature = "sink")]
+    pub fn sink(&self) -> ChannelSink<'_, MutexType, T, A> {
+        ChannelSink {
+            channel: self,
+            state: SinkState::new(),
+        }
+    }
+
+    /// Closes the channel.
+    /// All pending and future send attempts will fail.
+    /// Receive attempts will continue to succeed as long as there are items
+    /// stored inside the channel. Further attempts will fail.
+    pub fn close(&self) -> CloseStatus {
+        self.inner.lock().close()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<MutexType: RawMutex, T, A> GenericChannel<MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    unsafe fn poll_sink_flush(
+        &self,
+        sink: &mut SinkState<T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ChannelClosedError>> {
+        let (poll_result, waker) =
+            { self.inner.lock().poll_sink_flush(sink, cx) };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        poll_result
+    }
+
+    unsafe fn poll_sink_ready(
+        &self,
+        sink: &mut SinkState<T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ChannelClosedError>> {
+        let (poll_result, waker) =
+            { self.inner.lock().poll_sink_ready(sink, cx) };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        poll_result
+    }
+
+    fn start_sink_send(
+        &self,
+        sink: &mut SinkState<T>,
+        value: T,
+    ) -> Result<(), ChannelClosedError> {
+        let waker = { self.inner.lock().start_sink_send(sink, value)? };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        Ok(())
+    }
+
+    fn remove_sink(&self, sink: &mut SinkState<T>) {
+        let (send_waker, receive_waker) =
+            { self.inner.lock().remove_sink(sink) };
+
+        if let Some(waker) = send_waker {
+            waker.wake();
+        }
+        if let Some(waker) = receive_waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<MutexType: RawMutex, T, A> ChannelSendAccess<T>
+    for GenericChannel<MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    unsafe fn send_or_register(
+        &self,
+        wait_node: &mut ListNode<SendWaitQueueEntry<T>>,
+        cx: &mut Context<'_>,
+    ) -> (Poll<()>, Option<T>) {
+        let (poll_result, value, waker) =
+            { self.inner.lock().send_or_register(wait_node, cx) };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        (poll_result, value)
+    }
+
+    fn remove_send_waiter(
+        &self,
+        wait_node: &mut ListNode<SendWaitQueueEntry<T>>,
+    ) {
+        let waker = { self.inner.lock().remove_send_waiter(wait_node) };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<MutexType: RawMutex, T, A> ChannelReceiveAccess<T>
+    for GenericChannel<MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    unsafe fn receive_or_register(
+        &self,
+        wait_node: &mut ListNode<RecvWaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<T>> {
+        let (poll_result, send_waker, receive_waker) =
+            { self.inner.lock().receive_or_register(wait_node, cx) };
+
+        if let Some(waker) = send_waker {
+            waker.wake();
+        }
+        if let Some(waker) = receive_waker {
+            waker.wake();
+        }
+
+        poll_result
+    }
+
+    fn remove_receive_waiter(
+        &self,
+        wait_node: &mut ListNode<RecvWaitQueueEntry>,
+    ) {
+        let waker = { self.inner.lock().remove_receive_waiter(wait_node) };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A Future that is returned by the `send_all` function on a channel.
+/// The future gets resolved with `Ok(())` when all values of the slice have
+/// been written to the channel.
+/// If the channel gets closed before all values could be sent, the future
+/// will resolve to `ChannelClosedError`. The values which could not be sent
+/// remain inside the slice.
+#[must_use = "futures do nothing unless polled"]
+pub struct ChannelSendAllFuture<'a, MutexType: RawMutex, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    /// The Channel that is associated with this ChannelSendAllFuture
+    channel: Option<&'a GenericChannel<MutexType, T, A>>,
+    /// The values which still need to be sent. The first slot belongs to the
+    /// value which is stored inside `wait_node` while the future waits.
+    values: &'a mut [Option<T>],
+    /// Node for waiting on the channel
+    wait_node: ListNode<SendWaitQueueEntry<T>>,
+}
+
+// Safety: Channel futures can be sent between threads as long as the underlying
+// channel is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: Send, A> Send
+    for ChannelSendAllFuture<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+}
+
+impl<'a, MutexType: RawMutex, T, A> core::fmt::Debug
+    for ChannelSendAllFuture<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("ChannelSendAllFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, A> Future
+    for ChannelSendAllFuture<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    type Output = Result<(), ChannelClosedError>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), ChannelClosedError>> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside ChannelSendAllFuture is
+        // stable, and we don't move any fields inside the future until it
+        // gets dropped.
+        let mut_self: &mut Self = unsafe { Pin::get_unchecked_mut(self) };
+
+        let channel = mut_self
+            .channel
+            .expect("polled ChannelSendAllFuture after completion");
+
+        let (poll_res, receive_waker, send_waker) = unsafe {
+            channel.inner.lock().send_many_or_register(
+                &mut mut_self.wait_node,
+                &mut mut_self.values,
+                cx,
+            )
+        };
+
+        if let Some(waker) = receive_waker {
+            waker.wake();
+        }
+        if let Some(waker) = send_waker {
+            waker.wake();
+        }
+
+        if poll_res.is_ready() {
+            // All values have been transmitted or the channel was closed
+            mut_self.channel = None;
+        }
+
+        poll_res
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, A> FusedFuture
+    for ChannelSendAllFuture<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    fn is_terminated(&self) -> bool {
+        self.channel.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, A> Drop
+    for ChannelSendAllFuture<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    fn drop(&mut self) {
+        // If this ChannelSendAllFuture has been polled and it was added to the
+        // wait queue at the channel, it must be removed before dropping.
+        // Otherwise the channel would access invalid memory.
+        if let Some(channel) = self.channel {
+            channel.remove_send_waiter(&mut self.wait_node);
+        }
+        // A value which had not been sent is moved back into its slot
+        if let Some(value) = self.wait_node.value.take() {
+            self.values[0] = Some(value);
         }
     }
+}
 
-    /// Closes the channel.
-    /// All pending and future send attempts will fail.
-    /// Receive attempts will continue to succeed as long as there are items
-    /// stored inside the channel. Further attempts will fail.
-    pub fn close(&self) -> CloseStatus {
-        self.inner.lock().close()
+/// A Future that is returned by the `receive_many` function on a channel.
+/// The future gets resolved with the amount of values which had been
+/// received from the channel.
+/// If the channels gets closed and no items are still enqueued inside the
+/// channel, the future will resolve to `0`.
+#[must_use = "futures do nothing unless polled"]
+pub struct ChannelReceiveManyFuture<'a, MutexType: RawMutex, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    /// The Channel that is associated with this ChannelReceiveManyFuture
+    channel: Option<&'a GenericChannel<MutexType, T, A>>,
+    /// The slots in which received values are stored
+    values: &'a mut [Option<T>],
+    /// Node for waiting on the channel
+    wait_node: ListNode<RecvWaitQueueEntry>,
+}
+
+// Safety: Channel futures can be sent between threads as long as the underlying
+// channel is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: Send, A> Send
+    for ChannelReceiveManyFuture<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+}
+
+impl<'a, MutexType: RawMutex, T, A> core::fmt::Debug
+    for ChannelReceiveManyFuture<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("ChannelReceiveManyFuture").finish()
     }
 }
 
-impl<MutexType: RawMutex, T, A> ChannelSendAccess<T>
-    for GenericChannel<MutexType, T, A>
+impl<'a, MutexType: RawMutex, T, A> Future
+    for ChannelReceiveManyFuture<'a, MutexType, T, A>
 where
     A: RingBuf<Item = T>,
 {
-    unsafe fn send_or_register(
-        &self,
-        wait_node: &mut ListNode<SendWaitQueueEntry<T>>,
-        cx: &mut Context<'_>,
-    ) -> (Poll<()>, Option<T>) {
-        let (poll_result, value, waker) =
-            { self.inner.lock().send_or_register(wait_node, cx) };
+    type Output = usize;
 
-        if let Some(waker) = waker {
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<usize> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside ChannelReceiveManyFuture
+        // is stable, and we don't move any fields inside the future until it
+        // gets dropped.
+        let mut_self: &mut Self = unsafe { Pin::get_unchecked_mut(self) };
+
+        let channel = mut_self
+            .channel
+            .expect("polled ChannelReceiveManyFuture after completion");
+
+        let (poll_res, send_waker, receive_waker) = unsafe {
+            channel.inner.lock().receive_many_or_register(
+                &mut mut_self.wait_node,
+                mut_self.values,
+                cx,
+            )
+        };
+
+        if let Some(waker) = send_waker {
+            waker.wake();
+        }
+        if let Some(waker) = receive_waker {
             waker.wake();
         }
 
-        (poll_result, value)
-    }
+        if poll_res.is_ready() {
+            // Values were available or the channel was closed
+            mut_self.channel = None;
+        }
 
-    fn remove_send_waiter(
-        &self,
-        wait_node: &mut ListNode<SendWaitQueueEntry<T>>,
-    ) {
-        self.inner.lock().remove_send_waiter(wait_node)
+        poll_res
     }
 }
 
-impl<MutexType: RawMutex, T, A> ChannelReceiveAccess<T>
-    for GenericChannel<MutexType, T, A>
+impl<'a, MutexType: RawMutex, T, A> FusedFuture
+    for ChannelReceiveManyFuture<'a, MutexType, T, A>
 where
     A: RingBuf<Item = T>,
 {
-    unsafe fn receive_or_register(
-        &self,
-        wait_node: &mut ListNode<RecvWaitQueueEntry>,
-        cx: &mut Context<'_>,
-    ) -> Poll<Option<T>> {
-        let result = { self.inner.lock().receive_or_register(wait_node, cx) };
-
-        match result {
-            Poll::Ready(Some((val, waker))) => {
-                if let Some(waker) = waker {
-                    waker.wake();
-                }
-                Poll::Ready(Some(val))
-            }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
-        }
+    fn is_terminated(&self) -> bool {
+        self.channel.is_none()
     }
+}
 
-    fn remove_receive_waiter(
-        &self,
-        wait_node: &mut ListNode<RecvWaitQueueEntry>,
-    ) {
-        let waker = { self.inner.lock().remove_receive_waiter(wait_node) };
-
-        if let Some(waker) = waker {
-            waker.wake();
+impl<'a, MutexType: RawMutex, T, A> Drop
+    for ChannelReceiveManyFuture<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    fn drop(&mut self) {
+        // If this ChannelReceiveManyFuture has been polled and it was added to
+        // the wait queue at the channel, it must be removed before dropping.
+        // Otherwise the channel would access invalid memory.
+        if let Some(channel) = self.channel {
+            channel.remove_receive_waiter(&mut self.wait_node);
         }
     }
 }
@@ -630,6 +1503,21 @@ where
     }
 }
 
+impl<'a, MutexType, T, A> SelectSource for ChannelStream<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+    MutexType: RawMutex,
+{
+    type Output = Option<T>;
+
+    fn poll_select(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<T>> {
+        self.poll_next(cx)
+    }
+}
+
 impl<'a, MutexType, T, A> FusedStream for ChannelStream<'a, MutexType, T, A>
 where
     A: RingBuf<Item = T>,
@@ -640,6 +1528,111 @@ where
     }
 }
 
+/// A sink that sends values into a `GenericChannel`.
+///
+/// The sink only becomes ready to accept a value once space for the value is
+/// available inside the channel. This space is reserved for the sink, and the
+/// value which is passed to the sink afterwards is directly stored in it.
+/// If the channel is unbuffered, the sink accepts a single value, which waits
+/// in the send queue of the channel until it gets received. The sink becomes
+/// ready again once this had happened.
+///
+/// If the channel gets closed, the sink fails with a [`ChannelClosedError`].
+///
+/// Not driving the `ChannelSink` to completion after a value had been passed
+/// to an unbuffered channel might lead to lost values.
+#[cfg(feature = "sink")]
+pub struct ChannelSink<'a, MutexType: RawMutex, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+    channel: &'a GenericChannel<MutexType, T, A>,
+    state: SinkState<T>,
+}
+
+// Safety: Sinks can be sent between threads as long as the underlying
+// channel is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+#[cfg(feature = "sink")]
+unsafe impl<'a, MutexType: RawMutex + Sync, T: Send, A> Send
+    for ChannelSink<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+{
+}
+
+#[cfg(feature = "sink")]
+impl<'a, MutexType, T, A> core::fmt::Debug for ChannelSink<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+    MutexType: RawMutex,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("ChannelSink").finish()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<'a, MutexType, T, A> Sink<T> for ChannelSink<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+    MutexType: RawMutex,
+{
+    type Error = ChannelClosedError;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside the sink is stable,
+        // and we don't move any fields inside the sink until it gets dropped.
+        let mut_self: &mut Self = unsafe { Pin::get_unchecked_mut(self) };
+        unsafe { mut_self.channel.poll_sink_ready(&mut mut_self.state, cx) }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        // Safety: The wait queue entry is not moved
+        let mut_self: &mut Self = unsafe { Pin::get_unchecked_mut(self) };
+        mut_self.channel.start_sink_send(&mut mut_self.state, item)
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        // Safety: The wait queue entry is not moved
+        let mut_self: &mut Self = unsafe { Pin::get_unchecked_mut(self) };
+        unsafe { mut_self.channel.poll_sink_flush(&mut mut_self.state, cx) }
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        match self.as_mut().poll_flush(cx) {
+            Poll::Ready(result) => {
+                self.channel.close();
+                Poll::Ready(result)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<'a, MutexType, T, A> Drop for ChannelSink<'a, MutexType, T, A>
+where
+    A: RingBuf<Item = T>,
+    MutexType: RawMutex,
+{
+    fn drop(&mut self) {
+        // The sink must be removed from the wait queue, and the slot which
+        // had been reserved for it must be released.
+        self.channel.remove_sink(&mut self.state)
+    }
+}
+
 // Export a non thread-safe version using NoopLock
 
 /// A [`GenericChannel`] implementation which is not thread-safe.
@@ -648,6 +1641,11 @@ pub type LocalChannel<T, A> = GenericChannel<NoopLock, T, ArrayBuf<T, A>>;
 /// An unbuffered [`GenericChannel`] implementation which is not thread-safe.
 pub type LocalUnbufferedChannel<T> = LocalChannel<T, [T; 0]>;
 
+/// A [`GenericChannel`] implementation which is not thread-safe, and which
+/// delivers the biggest buffered value first.
+pub type LocalPriorityChannel<T, A> =
+    GenericChannel<NoopLock, T, ArrayPriorityBuf<T, A>>;
+
 #[cfg(feature = "std")]
 mod if_std {
     use super::*;
@@ -669,6 +1667,11 @@ mod if_std {
 
     /// An unbuffered [`GenericChannel`] implementation backed by [`parking_lot`].
     pub type UnbufferedChannel<T> = Channel<T, [T; 0]>;
+
+    /// A [`GenericChannel`] implementation backed by [`parking_lot`], which
+    /// delivers the biggest buffered value first.
+    pub type PriorityChannel<T, A> =
+        GenericChannel<parking_lot::RawMutex, T, ArrayPriorityBuf<T, A>>;
 }
 
 #[cfg(feature = "std")]
@@ -984,6 +1987,24 @@ mod if_alloc {
             }
         }
 
+        impl<MutexType, T, A> GenericSender<MutexType, T, A>
+        where
+            MutexType: 'static + RawMutex,
+            A: 'static + RingBuf<Item = T>,
+        {
+            /// Returns a sink that will send values into this channel.
+            ///
+            /// The sink becomes ready once space for a value is available
+            /// inside the channel. Closing the sink closes the channel.
+            #[cfg(feature = "sink")]
+            pub fn into_sink(self) -> SharedSink<MutexType, T, A> {
+                SharedSink {
+                    sender: self,
+                    state: SinkState::new(),
+                }
+            }
+        }
+
         /// A stream that receives from channel using a `GenericReceiver`.
         ///
         /// Not driving the `SharedStream` to completion after it has been polled
@@ -1060,6 +2081,21 @@ mod if_alloc {
             }
         }
 
+        impl<MutexType, T, A> SelectSource for SharedStream<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            A: 'static + RingBuf<Item = T>,
+        {
+            type Output = Option<T>;
+
+            fn poll_select(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Option<T>> {
+                self.poll_next(cx)
+            }
+        }
+
         impl<MutexType, T, A> FusedStream for SharedStream<MutexType, T, A>
         where
             MutexType: RawMutex,
@@ -1070,12 +2106,140 @@ mod if_alloc {
             }
         }
 
+        /// A sink that sends values into a channel using a `GenericSender`.
+        ///
+        /// The sink only becomes ready to accept a value once space for the
+        /// value is available inside the channel. See [`ChannelSink`] for
+        /// more information.
+        ///
+        /// Not driving the `SharedSink` to completion after a value had been
+        /// passed to an unbuffered channel might lead to lost values.
+        #[cfg(feature = "sink")]
+        pub struct SharedSink<MutexType, T, A>
+        where
+            MutexType: 'static + RawMutex,
+            T: 'static,
+            A: 'static + RingBuf<Item = T>,
+        {
+            sender: GenericSender<MutexType, T, A>,
+            state: SinkState<T>,
+        }
+
+        // Safety: Sinks can be sent between threads as long as the underlying
+        // channel is thread-safe (Sync), which allows to poll/register/unregister
+        // from a different thread.
+        #[cfg(feature = "sink")]
+        unsafe impl<MutexType: RawMutex + Sync, T: Send, A> Send
+            for SharedSink<MutexType, T, A>
+        where
+            A: 'static + RingBuf<Item = T> + Send,
+        {
+        }
+
+        #[cfg(feature = "sink")]
+        impl<MutexType, T, A> core::fmt::Debug for SharedSink<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            A: 'static + RingBuf<Item = T>,
+        {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.debug_struct("SharedSink").finish()
+            }
+        }
+
+        #[cfg(feature = "sink")]
+        impl<MutexType, T, A> SharedSink<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            A: 'static + RingBuf<Item = T>,
+        {
+            /// Closes the channel.
+            /// All pending and future send attempts will fail.
+            /// Receive attempts will continue to succeed as long as there are items
+            /// stored inside the channel. Further attempts will fail.
+            pub fn close(&self) -> CloseStatus {
+                self.sender.close()
+            }
+        }
+
+        #[cfg(feature = "sink")]
+        impl<MutexType, T, A> Sink<T> for SharedSink<MutexType, T, A>
+        where
+            MutexType: RawMutex,
+            A: 'static + RingBuf<Item = T>,
+        {
+            type Error = ChannelClosedError;
+
+            fn poll_ready(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                // Safety: The next operations are safe, because Pin promises us
+                // that the address of the wait queue entry inside the sink is
+                // stable, and we don't move any fields inside the sink until it
+                // gets dropped.
+                let mut_self: &mut Self =
+                    unsafe { Pin::get_unchecked_mut(self) };
+                let channel = &mut_self.sender.inner.channel;
+                unsafe { channel.poll_sink_ready(&mut mut_self.state, cx) }
+            }
+
+            fn start_send(
+                self: Pin<&mut Self>,
+                item: T,
+            ) -> Result<(), Self::Error> {
+                // Safety: The wait queue entry is not moved
+                let mut_self: &mut Self =
+                    unsafe { Pin::get_unchecked_mut(self) };
+                let channel = &mut_self.sender.inner.channel;
+                channel.start_sink_send(&mut mut_self.state, item)
+            }
+
+            fn poll_flush(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                // Safety: The wait queue entry is not moved
+                let mut_self: &mut Self =
+                    unsafe { Pin::get_unchecked_mut(self) };
+                let channel = &mut_self.sender.inner.channel;
+                unsafe { channel.poll_sink_flush(&mut mut_self.state, cx) }
+            }
+
+            fn poll_close(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                match self.as_mut().poll_flush(cx) {
+                    Poll::Ready(result) => {
+                        self.close();
+                        Poll::Ready(result)
+                    }
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+        }
+
+        #[cfg(feature = "sink")]
+        impl<MutexType, T, A> Drop for SharedSink<MutexType, T, A>
+        where
+            MutexType: 'static + RawMutex,
+            T: 'static,
+            A: 'static + RingBuf<Item = T>,
+        {
+            fn drop(&mut self) {
+                // The sink must be removed from the wait queue, and the slot
+                // which had been reserved for it must be released.
+                self.sender.inner.channel.remove_sink(&mut self.state)
+            }
+        }
+
         // Export parking_lot based shared channels in std mode
         #[cfg(feature = "std")]
         mod if_std {
             use super::*;
 
-            use crate::buffer::GrowingHeapBuf;
+            use crate::buffer::{GrowingHeapBuf, HeapPriorityBuf};
 
             /// A [`GenericSender`] implementation backed by [`parking_lot`].
             ///
@@ -1131,6 +2295,38 @@ mod if_alloc {
                     0,
                 )
             }
+
+            /// A [`GenericSender`] implementation backed by [`parking_lot`],
+            /// whose channel delivers the biggest buffered value first.
+            pub type PrioritySender<T> =
+                GenericSender<parking_lot::RawMutex, T, HeapPriorityBuf<T>>;
+            /// A [`GenericReceiver`] implementation backed by [`parking_lot`],
+            /// which receives the biggest buffered value first.
+            pub type PriorityReceiver<T> =
+                GenericReceiver<parking_lot::RawMutex, T, HeapPriorityBuf<T>>;
+
+            /// Creates a new channel with the given buffering capacity, which
+            /// delivers the biggest buffered value first.
+            ///
+            /// Uses a `HeapPriorityBuf` whose capacity grows dynamically up to
+            /// the given limit. Refer to [`generic_channel`] and [`HeapPriorityBuf`] for more information.
+            ///
+            /// [`HeapPriorityBuf`]: ../../buffer/struct.HeapPriorityBuf.html
+            ///
+            /// ```
+            /// # use futures_intrusive::channel::shared::priority_channel;
+            /// let (sender, receiver) = priority_channel::<i32>(4);
+            /// ```
+            pub fn priority_channel<T>(
+                capacity: usize,
+            ) -> (PrioritySender<T>, PriorityReceiver<T>)
+            where
+                T: Ord + Send,
+            {
+                generic_channel::<parking_lot::RawMutex, T, HeapPriorityBuf<T>>(
+                    capacity,
+                )
+            }
         }
 
         #[cfg(feature = "std")]
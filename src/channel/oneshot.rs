@@ -37,7 +37,7 @@ struct ChannelState<T> {
 }
 
 impl<T> ChannelState<T> {
-    fn new() -> ChannelState<T> {
+    const fn new() -> ChannelState<T> {
         ChannelState::<T> {
             is_fulfilled: false,
             value: None,
@@ -171,9 +171,9 @@ impl<MutexType: RawMutex, T> core::fmt::Debug
 
 impl<MutexType: RawMutex, T> GenericOneshotChannel<MutexType, T> {
     /// Creates a new OneshotChannel in the given state
-    pub fn new() -> GenericOneshotChannel<MutexType, T> {
+    pub const fn new() -> GenericOneshotChannel<MutexType, T> {
         GenericOneshotChannel {
-            inner: Mutex::new(ChannelState::new()),
+            inner: Mutex::const_new(MutexType::INIT, ChannelState::new()),
         }
     }
 
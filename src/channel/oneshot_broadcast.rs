@@ -41,7 +41,7 @@ impl<T> ChannelState<T>
 where
     T: Clone,
 {
-    fn new() -> ChannelState<T> {
+    const fn new() -> ChannelState<T> {
         ChannelState::<T> {
             is_fulfilled: false,
             value: None,
@@ -178,9 +178,9 @@ where
     T: Clone,
 {
     /// Creates a new OneshotBroadcastChannel in the given state
-    pub fn new() -> GenericOneshotBroadcastChannel<MutexType, T> {
+    pub const fn new() -> GenericOneshotBroadcastChannel<MutexType, T> {
         GenericOneshotBroadcastChannel {
-            inner: Mutex::new(ChannelState::new()),
+            inner: Mutex::const_new(MutexType::INIT, ChannelState::new()),
         }
     }
 
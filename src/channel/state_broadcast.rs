@@ -23,7 +23,7 @@ pub struct StateId(u64);
 impl StateId {
     /// Returns the initial StateId, which is guaranteed to return the
     /// oldest buffered value available.
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         StateId(0)
     }
 }
@@ -195,7 +195,7 @@ impl<T> ChannelState<T>
 where
     T: Clone,
 {
-    fn new() -> ChannelState<T> {
+    const fn new() -> ChannelState<T> {
         ChannelState::<T> {
             is_closed: false,
             state_id: StateId(0),
@@ -346,12 +346,12 @@ where
     T: Clone,
 {
     /// Creates a new State Broadcast Channel in the given state
-    pub fn new() -> GenericStateBroadcastChannel<MutexType, T>
+    pub const fn new() -> GenericStateBroadcastChannel<MutexType, T>
     where
         T: Clone,
     {
         GenericStateBroadcastChannel {
-            inner: Mutex::new(ChannelState::new()),
+            inner: Mutex::const_new(MutexType::INIT, ChannelState::new()),
         }
     }
 
@@ -1,7 +1,7 @@
 //! An intrusive double linked list of data
 
 use core::{
-    marker::PhantomPinned,
+    marker::{PhantomData, PhantomPinned},
     ops::{Deref, DerefMut},
     ptr::NonNull,
 };
@@ -22,7 +22,7 @@ pub struct ListNode<T> {
 
 impl<T> ListNode<T> {
     /// Creates a new node with the associated data
-    pub fn new(data: T) -> ListNode<T> {
+    pub const fn new(data: T) -> ListNode<T> {
         ListNode::<T> {
             prev: None,
             next: None,
@@ -56,7 +56,7 @@ pub struct LinkedList<T> {
 
 impl<T> LinkedList<T> {
     /// Creates an empty linked list
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         LinkedList::<T> {
             head: None,
             tail: None,
@@ -254,6 +254,19 @@ impl<T> LinkedList<T> {
         }
     }
 
+    /// Returns an iterator over the nodes of the list, which starts at the last
+    /// node and ends at the first one.
+    ///
+    /// The iterator mutably borrows the list. The list can therefore not be
+    /// modified during the iteration, but the data which is associated with
+    /// the nodes can be.
+    pub fn reverse_iter_mut(&mut self) -> ReverseIterMut<'_, T> {
+        ReverseIterMut {
+            current: self.tail,
+            _list: PhantomData,
+        }
+    }
+
     /// Drains the list in reverse order by calling a callback on each list node
     ///
     /// The method does not return an iterator since stopping or deferring
@@ -289,6 +302,31 @@ impl<T> LinkedList<T> {
     }
 }
 
+/// An iterator over the nodes of a [`LinkedList`] in reverse order.
+///
+/// It is returned by [`LinkedList::reverse_iter_mut`].
+pub struct ReverseIterMut<'a, T> {
+    current: Option<NonNull<ListNode<T>>>,
+    _list: PhantomData<&'a mut LinkedList<T>>,
+}
+
+impl<'a, T> Iterator for ReverseIterMut<'a, T> {
+    type Item = &'a mut ListNode<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Safety: The nodes are alive as long as they are part of the list.
+        // Since the list is mutably borrowed for the lifetime of the iterator,
+        // no node can be removed from it in the meantime. Each node is only
+        // returned once, and the link pointers are not accessible through the
+        // returned references.
+        self.current.map(|mut node| unsafe {
+            let node_ref = &mut *(node.as_mut() as *mut ListNode<T>);
+            self.current = node_ref.prev;
+            node_ref
+        })
+    }
+}
+
 #[cfg(all(test, feature = "alloc"))] // Tests make use of Vec at the moment
 mod tests {
     use super::*;
@@ -369,6 +407,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reverse_iter_mut() {
+        unsafe {
+            let mut a = ListNode::new(5);
+            let mut b = ListNode::new(7);
+            let mut c = ListNode::new(31);
+
+            let mut list = LinkedList::new();
+            assert!(list.reverse_iter_mut().next().is_none());
+            add_nodes(&mut list, &mut [&mut c, &mut b, &mut a]);
+
+            let items: Vec<i32> =
+                list.reverse_iter_mut().map(|n| **n).collect();
+            assert_eq!([31, 7, 5].to_vec(), items);
+
+            // The data of the nodes can be modified during iteration
+            for node in list.reverse_iter_mut().take(2) {
+                **node += 1;
+            }
+            let items: Vec<i32> = collect_list(list);
+            assert_eq!([5, 8, 32].to_vec(), items);
+        }
+    }
+
     #[test]
     fn peek_last() {
         unsafe {
@@ -63,7 +63,7 @@ pub struct HeapNode<T> {
 
 impl<T> HeapNode<T> {
     /// Creates a new node with the associated data
-    pub fn new(data: T) -> HeapNode<T> {
+    pub const fn new(data: T) -> HeapNode<T> {
         HeapNode::<T> {
             parent: None,
             prev: None,
@@ -232,7 +232,7 @@ pub struct PairingHeap<T> {
 
 impl<T: Ord> PairingHeap<T> {
     /// Creates an empty heap
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         PairingHeap::<T> { root: None }
     }
 
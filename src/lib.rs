@@ -5,11 +5,23 @@
 //! - Channels in a variety of flavors:
 //!   - Oneshot
 //!   - Multi-Producer Multi-Consumer (MPMC)
+//!   - Priority
 //!   - State Broadcast
+//!   - Broadcast
+//! - Waiting on the first of multiple channels or events via `select`
 //! - Synchronization Primitives:
 //!   - Manual Reset Event
+//!   - Auto Reset Event
+//!   - Barrier
+//!   - Cancellation Token
+//!   - Condition Variable
 //!   - Mutex
+//!   - Lock Map
+//!   - Once Cell
+//!   - Object Pool
+//!   - RwLock
 //!   - Semaphore
+//!   - Wait Group
 //! - A timer
 //!
 //! ## Intrusive collections?
@@ -229,6 +241,7 @@ mod intrusive_double_linked_list;
 mod intrusive_pairing_heap;
 
 pub mod channel;
+pub mod select;
 pub mod sync;
 pub mod timer;
 
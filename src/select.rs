@@ -0,0 +1,239 @@
+//! Waiting for the first of multiple sources to produce a value
+
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll},
+};
+
+/// A source of values which can be awaited through [`select`].
+///
+/// In contrast to a `Future`, a source can be polled again after it produced
+/// a value. Sources stay registered at the underlying primitive between
+/// polls, and only take a value out of the primitive when they return it.
+/// Therefore no value is lost if another source is chosen by [`select`].
+///
+/// The source is implemented by [`ChannelStream`] and the shared
+/// `SharedStream`, which produce `Some(value)` for each received value and
+/// `None` once the channel is closed. It is also implemented by the wait
+/// futures of manual reset events, which produce `()` once and stay pending
+/// afterwards.
+///
+/// [`ChannelStream`]: crate::channel::ChannelStream
+pub trait SelectSource {
+    /// The type of the values which are produced by the source.
+    type Output;
+
+    /// Attempts to take the next value out of the source.
+    ///
+    /// If no value is available, the current task is registered for wakeup
+    /// once a value is available.
+    fn poll_select(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output>;
+
+    /// Maps the values which are produced by the source with `f`.
+    ///
+    /// This allows to wait on sources with different output types through
+    /// the same [`select`] call.
+    fn map_output<F, U>(self, f: F) -> MapOutput<Self, F>
+    where
+        F: FnMut(Self::Output) -> U,
+        Self: Sized,
+    {
+        MapOutput { source: self, f }
+    }
+}
+
+/// A source which maps the values of another source.
+///
+/// It is created by [`SelectSource::map_output`].
+#[derive(Debug)]
+pub struct MapOutput<S, F> {
+    source: S,
+    f: F,
+}
+
+impl<S, F, U> SelectSource for MapOutput<S, F>
+where
+    S: SelectSource,
+    F: FnMut(S::Output) -> U,
+{
+    type Output = U;
+
+    fn poll_select(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<U> {
+        // Safety: The source is structurally pinned and never moved. The
+        // mapping function is not pinned.
+        let mut_self = unsafe { Pin::get_unchecked_mut(self) };
+        let source = unsafe { Pin::new_unchecked(&mut mut_self.source) };
+        match source.poll_select(cx) {
+            Poll::Ready(output) => Poll::Ready((mut_self.f)(output)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// A set of sources which can be awaited through [`select`].
+///
+/// The trait is implemented for slices of sources, as well as for tuples of
+/// up to 8 sources which produce the same output type. A pinned array of
+/// sources can be passed to [`select`] by coercing it into a pinned slice,
+/// e.g. via `let sources: Pin<&mut [_]> = sources;`.
+pub trait SelectSources {
+    /// The type of the values which are produced by the sources.
+    type Output;
+
+    /// Polls the sources in order until one of them produces a value.
+    /// Returns the index of this source as well as the value.
+    fn poll_any(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<(usize, Self::Output)>;
+}
+
+impl<S: SelectSource> SelectSources for [S] {
+    type Output = S::Output;
+
+    fn poll_any(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<(usize, S::Output)> {
+        // Safety: The elements of the slice are structurally pinned and
+        // never moved.
+        let sources = unsafe { Pin::get_unchecked_mut(self) };
+        for (index, source) in sources.iter_mut().enumerate() {
+            let source = unsafe { Pin::new_unchecked(source) };
+            if let Poll::Ready(output) = source.poll_select(cx) {
+                return Poll::Ready((index, output));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+macro_rules! impl_select_sources_for_tuple {
+    ($($index:tt $source:ident),+) => {
+        impl<Output, $($source),+> SelectSources for ($($source,)+)
+        where
+            $($source: SelectSource<Output = Output>),+
+        {
+            type Output = Output;
+
+            fn poll_any(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<(usize, Output)> {
+                // Safety: The elements of the tuple are structurally pinned
+                // and never moved.
+                let sources = unsafe { Pin::get_unchecked_mut(self) };
+                $(
+                    let source = unsafe { Pin::new_unchecked(&mut sources.$index) };
+                    if let Poll::Ready(output) = source.poll_select(cx) {
+                        return Poll::Ready(($index, output));
+                    }
+                )+
+                Poll::Pending
+            }
+        }
+    };
+}
+
+impl_select_sources_for_tuple!(0 S0);
+impl_select_sources_for_tuple!(0 S0, 1 S1);
+impl_select_sources_for_tuple!(0 S0, 1 S1, 2 S2);
+impl_select_sources_for_tuple!(0 S0, 1 S1, 2 S2, 3 S3);
+impl_select_sources_for_tuple!(0 S0, 1 S1, 2 S2, 3 S3, 4 S4);
+impl_select_sources_for_tuple!(0 S0, 1 S1, 2 S2, 3 S3, 4 S4, 5 S5);
+impl_select_sources_for_tuple!(0 S0, 1 S1, 2 S2, 3 S3, 4 S4, 5 S5, 6 S6);
+impl_select_sources_for_tuple!(0 S0, 1 S1, 2 S2, 3 S3, 4 S4, 5 S5, 6 S6, 7 S7);
+
+/// A Future that is returned by [`select`]. It resolves with the index and the
+/// value of the first source which produced a value.
+#[must_use = "futures do nothing unless polled"]
+pub struct Select<'a, S: SelectSources + ?Sized> {
+    /// The sources which are awaited
+    sources: Option<Pin<&'a mut S>>,
+}
+
+impl<'a, S: SelectSources + ?Sized> core::fmt::Debug for Select<'a, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Select").finish()
+    }
+}
+
+impl<'a, S: SelectSources + ?Sized> Future for Select<'a, S> {
+    type Output = (usize, S::Output);
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<(usize, S::Output)> {
+        let sources = self
+            .sources
+            .as_mut()
+            .expect("polled Select after completion");
+
+        let poll_res = sources.as_mut().poll_any(cx);
+        if poll_res.is_ready() {
+            self.sources = None;
+        }
+
+        poll_res
+    }
+}
+
+impl<'a, S: SelectSources + ?Sized> FusedFuture for Select<'a, S> {
+    fn is_terminated(&self) -> bool {
+        self.sources.is_none()
+    }
+}
+
+/// Waits until one of the given sources produces a value, and resolves with
+/// the index of this source as well as the produced value.
+///
+/// The sources are polled in order, and only the first source which produces
+/// a value is consumed. All other sources stay registered at their underlying
+/// channels or events, and retain any values which are available. Since the
+/// sources are borrowed, they can be awaited again by another `select` call
+/// without losing their registration. `select` doesn't allocate.
+///
+/// If multiple sources are ready, the source with the lowest index is chosen.
+///
+/// Example for receiving values from two channels as long as an event is not
+/// set:
+///
+/// ```
+/// # use futures::executor::block_on;
+/// # use futures_intrusive::channel::LocalChannel;
+/// # use futures_intrusive::select::{select, SelectSource};
+/// # use futures_intrusive::sync::LocalManualResetEvent;
+/// # use pin_utils::pin_mut;
+/// # block_on(async {
+/// let jobs = LocalChannel::<u32, [u32; 4]>::new();
+/// let urgent_jobs = LocalChannel::<u32, [u32; 4]>::new();
+/// let shutdown = LocalManualResetEvent::new(false);
+///
+/// jobs.try_send(1).unwrap();
+/// urgent_jobs.try_send(2).unwrap();
+///
+/// let sources = (
+///     shutdown.wait().map_output(|()| None),
+///     urgent_jobs.stream(),
+///     jobs.stream(),
+/// );
+/// pin_mut!(sources);
+///
+/// assert_eq!((1, Some(2)), select(sources.as_mut()).await);
+/// assert_eq!((2, Some(1)), select(sources.as_mut()).await);
+/// shutdown.set();
+/// assert_eq!((0, None), select(sources.as_mut()).await);
+/// # });
+/// ```
+pub fn select<S: SelectSources + ?Sized>(
+    sources: Pin<&mut S>,
+) -> Select<'_, S> {
+    Select {
+        sources: Some(sources),
+    }
+}
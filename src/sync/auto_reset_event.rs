@@ -0,0 +1,547 @@
+//! An asynchronously awaitable event which wakes up a single waiter per
+//! notification.
+
+use crate::{
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Tracks how the future had interacted with the event
+#[derive(PartialEq)]
+enum PollState {
+    /// The task has never interacted with the event.
+    New,
+    /// The task was added to the wait queue at the event.
+    Waiting,
+    /// The task was selected by `notify_one` but has not yet observed the
+    /// notification. If the task aborts its wait, the notification must be
+    /// passed on.
+    Notified,
+    /// The task has been woken up by `notify_all` or has been polled to
+    /// completion.
+    Done,
+}
+
+/// Tracks the WaitForAutoResetEventFuture waiting state.
+/// Access to this struct is synchronized through the mutex in the Event.
+struct WaitQueueEntry {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: PollState,
+}
+
+impl WaitQueueEntry {
+    /// Creates a new WaitQueueEntry
+    fn new() -> WaitQueueEntry {
+        WaitQueueEntry {
+            task: None,
+            state: PollState::New,
+        }
+    }
+}
+
+/// Internal state of the `AutoResetEvent`
+struct EventState {
+    /// Whether a notification is stored, since no task was waiting when
+    /// `notify_one` was called.
+    is_set: bool,
+    waiters: LinkedList<WaitQueueEntry>,
+}
+
+impl EventState {
+    const fn new(is_set: bool) -> EventState {
+        EventState {
+            is_set,
+            waiters: LinkedList::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.is_set = false;
+    }
+
+    fn is_set(&self) -> bool {
+        self.is_set
+    }
+
+    fn notify_one(&mut self) {
+        // Wakeup the oldest waiter. If there is no waiter, the notification
+        // gets stored until the next task waits on the event.
+        match self.waiters.remove_last() {
+            Some(waiter) => {
+                waiter.state = PollState::Notified;
+                if let Some(handle) = waiter.task.take() {
+                    handle.wake();
+                }
+            }
+            None => {
+                self.is_set = true;
+            }
+        }
+    }
+
+    fn notify_all(&mut self) {
+        // Wakeup all waiters. This does not store a notification for tasks
+        // which start waiting afterwards.
+        // Use a reverse iterator, so that the oldest waiter gets
+        // scheduled first
+        self.waiters.reverse_drain(|waiter| {
+            if let Some(handle) = waiter.task.take() {
+                handle.wake();
+            }
+            waiter.state = PollState::Done;
+        });
+    }
+
+    /// Checks if the event is set. If it is this consumes the notification
+    /// and returns immediately.
+    /// If the event isn't set, the WaitForAutoResetEventFuture gets added to
+    /// the wait queue at the event, and will be signalled once ready.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn try_wait(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        match wait_node.state {
+            PollState::New => {
+                if self.is_set {
+                    // A notification is stored. It gets consumed by this task.
+                    self.is_set = false;
+                    wait_node.state = PollState::Done;
+                    Poll::Ready(())
+                } else {
+                    // Added the task to the wait queue
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = PollState::Waiting;
+                    self.waiters.add_front(wait_node);
+                    Poll::Pending
+                }
+            }
+            PollState::Waiting => {
+                // The WaitForAutoResetEventFuture is already in the queue.
+                // The caller might have passed a different `Waker`.
+                // In this case we need to update it.
+                update_waker_ref(&mut wait_node.task, cx);
+                Poll::Pending
+            }
+            PollState::Notified | PollState::Done => {
+                // We have been woken up by the event.
+                wait_node.state = PollState::Done;
+                Poll::Ready(())
+            }
+        }
+    }
+
+    fn remove_waiter(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        match wait_node.state {
+            PollState::Waiting => {
+                // Safety: Due to the state, we know that the node must be part
+                // of the waiter list
+                if !unsafe { self.waiters.remove(wait_node) } {
+                    // Panic if the address isn't found. This can only happen if the contract was
+                    // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                    panic!("Future could not be removed from wait queue");
+                }
+                wait_node.state = PollState::Done;
+            }
+            PollState::Notified => {
+                // The task was selected by `notify_one`, but never observed the
+                // notification. Pass it on to the next waiter, in order to
+                // not lose it.
+                wait_node.state = PollState::Done;
+                self.notify_one();
+            }
+            PollState::New | PollState::Done => {}
+        }
+    }
+}
+
+/// A synchronization primitive which wakes up a single waiting task per
+/// notification.
+///
+/// Tasks can wait for the event by obtaining a Future via `wait`.
+/// Calling `notify_one` will complete the oldest of those futures. If no task
+/// is waiting at that point of time, the notification gets stored inside the
+/// event, and the next task which waits on the event will consume it.
+/// At most a single notification is stored.
+///
+/// `notify_all` wakes up all tasks which are currently waiting, without storing
+/// a notification.
+pub struct GenericAutoResetEvent<MutexType: RawMutex> {
+    inner: Mutex<MutexType, EventState>,
+}
+
+// The Event is can be sent to other threads as long as it's not borrowed
+unsafe impl<MutexType: RawMutex + Send> Send
+    for GenericAutoResetEvent<MutexType>
+{
+}
+// The Event is thread-safe as long as the utilized Mutex is thread-safe
+unsafe impl<MutexType: RawMutex + Sync> Sync
+    for GenericAutoResetEvent<MutexType>
+{
+}
+
+impl<MutexType: RawMutex> core::fmt::Debug
+    for GenericAutoResetEvent<MutexType>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("AutoResetEvent").finish()
+    }
+}
+
+impl<MutexType: RawMutex> GenericAutoResetEvent<MutexType> {
+    /// Creates a new AutoResetEvent.
+    ///
+    /// If `is_set` is true, the event starts with a stored notification.
+    pub const fn new(is_set: bool) -> GenericAutoResetEvent<MutexType> {
+        GenericAutoResetEvent {
+            inner: Mutex::<MutexType, EventState>::const_new(
+                MutexType::INIT,
+                EventState::new(is_set),
+            ),
+        }
+    }
+
+    /// Wakes up a single waiting task.
+    ///
+    /// If no task is waiting, the notification is stored and will be consumed
+    /// by the next task that waits on the event.
+    pub fn notify_one(&self) {
+        self.inner.lock().notify_one()
+    }
+
+    /// Wakes up all tasks which are currently waiting on the event.
+    ///
+    /// In contrast to `notify_one`, no notification is stored if no task is
+    /// waiting.
+    pub fn notify_all(&self) {
+        self.inner.lock().notify_all()
+    }
+
+    /// Removes a stored notification.
+    pub fn reset(&self) {
+        self.inner.lock().reset()
+    }
+
+    /// Returns whether a notification is stored in the event.
+    pub fn is_set(&self) -> bool {
+        self.inner.lock().is_set()
+    }
+
+    /// Returns a future that gets fulfilled when the event is notified.
+    pub fn wait(&self) -> GenericWaitForAutoResetEventFuture<'_, MutexType> {
+        GenericWaitForAutoResetEventFuture {
+            event: Some(self),
+            wait_node: ListNode::new(WaitQueueEntry::new()),
+        }
+    }
+
+    unsafe fn try_wait(
+        &self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        self.inner.lock().try_wait(wait_node, cx)
+    }
+
+    fn remove_waiter(&self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        self.inner.lock().remove_waiter(wait_node)
+    }
+}
+
+/// A Future that is resolved once the corresponding AutoResetEvent has been
+/// notified
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericWaitForAutoResetEventFuture<'a, MutexType: RawMutex> {
+    /// The AutoResetEvent that is associated with this future
+    event: Option<&'a GenericAutoResetEvent<MutexType>>,
+    /// Node for waiting at the event
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// event is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync> Send
+    for GenericWaitForAutoResetEventFuture<'a, MutexType>
+{
+}
+
+impl<'a, MutexType: RawMutex> core::fmt::Debug
+    for GenericWaitForAutoResetEventFuture<'a, MutexType>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericWaitForAutoResetEventFuture")
+            .finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Future
+    for GenericWaitForAutoResetEventFuture<'a, MutexType>
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside the future is stable,
+        // and we don't move any fields inside the future until it gets dropped.
+        let mut_self: &mut GenericWaitForAutoResetEventFuture<MutexType> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let event = mut_self
+            .event
+            .expect("polled WaitForAutoResetEventFuture after completion");
+
+        let poll_res = unsafe { event.try_wait(&mut mut_self.wait_node, cx) };
+
+        if let Poll::Ready(()) = poll_res {
+            // The event was set
+            mut_self.event = None;
+        }
+
+        poll_res
+    }
+}
+
+impl<'a, MutexType: RawMutex> FusedFuture
+    for GenericWaitForAutoResetEventFuture<'a, MutexType>
+{
+    fn is_terminated(&self) -> bool {
+        self.event.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Drop
+    for GenericWaitForAutoResetEventFuture<'a, MutexType>
+{
+    fn drop(&mut self) {
+        // If this future has been polled and it was added to the
+        // wait queue at the event, it must be removed before dropping.
+        // Otherwise the event would access invalid memory.
+        if let Some(ev) = self.event {
+            ev.remove_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericAutoResetEvent`] which is not thread-safe.
+pub type LocalAutoResetEvent = GenericAutoResetEvent<NoopLock>;
+/// A [`GenericWaitForAutoResetEventFuture`] for [`LocalAutoResetEvent`].
+pub type LocalWaitForAutoResetEventFuture<'a> =
+    GenericWaitForAutoResetEventFuture<'a, NoopLock>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericAutoResetEvent`] implementation backed by [`parking_lot`].
+    pub type AutoResetEvent = GenericAutoResetEvent<parking_lot::RawMutex>;
+    /// A [`GenericWaitForAutoResetEventFuture`] for [`AutoResetEvent`].
+    pub type WaitForAutoResetEventFuture<'a> =
+        GenericWaitForAutoResetEventFuture<'a, parking_lot::RawMutex>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
+
+#[cfg(feature = "alloc")]
+mod if_alloc {
+    use super::*;
+
+    use alloc::sync::Arc;
+
+    /// A Future that is resolved once the corresponding
+    /// [`GenericSharedAutoResetEvent`] has been notified.
+    #[must_use = "futures do nothing unless polled"]
+    pub struct GenericSharedWaitForAutoResetEventFuture<MutexType: RawMutex> {
+        /// The event that is associated with this future
+        event: Option<GenericSharedAutoResetEvent<MutexType>>,
+        /// Node for waiting at the event
+        wait_node: ListNode<WaitQueueEntry>,
+    }
+
+    // Safety: Futures can be sent between threads as long as the underlying
+    // event is thread-safe (Sync), which allows to poll/register/unregister from
+    // a different thread.
+    unsafe impl<MutexType: RawMutex + Sync> Send
+        for GenericSharedWaitForAutoResetEventFuture<MutexType>
+    {
+    }
+
+    impl<MutexType: RawMutex> core::fmt::Debug
+        for GenericSharedWaitForAutoResetEventFuture<MutexType>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("GenericSharedWaitForAutoResetEventFuture")
+                .finish()
+        }
+    }
+
+    impl<MutexType: RawMutex> Future
+        for GenericSharedWaitForAutoResetEventFuture<MutexType>
+    {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            // Safety: The next operations are safe, because Pin promises us that
+            // the address of the wait queue entry inside the future is stable,
+            // and we don't move any fields inside the future until it gets dropped.
+            let mut_self: &mut GenericSharedWaitForAutoResetEventFuture<
+                MutexType,
+            > = unsafe { Pin::get_unchecked_mut(self) };
+
+            let event = mut_self.event.take().expect(
+                "polled SharedWaitForAutoResetEventFuture after completion",
+            );
+
+            let poll_res = unsafe {
+                event.inner.lock().try_wait(&mut mut_self.wait_node, cx)
+            };
+
+            if poll_res.is_pending() {
+                mut_self.event.replace(event);
+            }
+
+            poll_res
+        }
+    }
+
+    impl<MutexType: RawMutex> FusedFuture
+        for GenericSharedWaitForAutoResetEventFuture<MutexType>
+    {
+        fn is_terminated(&self) -> bool {
+            self.event.is_none()
+        }
+    }
+
+    impl<MutexType: RawMutex> Drop
+        for GenericSharedWaitForAutoResetEventFuture<MutexType>
+    {
+        fn drop(&mut self) {
+            // If this future has been polled and it was added to the
+            // wait queue at the event, it must be removed before dropping.
+            // Otherwise the event would access invalid memory.
+            if let Some(ev) = self.event.take() {
+                ev.inner.lock().remove_waiter(&mut self.wait_node);
+            }
+        }
+    }
+
+    /// A shared version of [`GenericAutoResetEvent`].
+    ///
+    /// The event can be cloned, which creates another handle to the same
+    /// event. The futures which are produced by the event do not have an
+    /// associated lifetime.
+    pub struct GenericSharedAutoResetEvent<MutexType: RawMutex> {
+        inner: Arc<Mutex<MutexType, EventState>>,
+    }
+
+    impl<MutexType: RawMutex> Clone for GenericSharedAutoResetEvent<MutexType> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+
+    // The Event is can be sent to other threads as long as it's not borrowed
+    unsafe impl<MutexType: RawMutex + Send + Sync> Send
+        for GenericSharedAutoResetEvent<MutexType>
+    {
+    }
+    // The Event is thread-safe as long as the utilized Mutex is thread-safe
+    unsafe impl<MutexType: RawMutex + Sync> Sync
+        for GenericSharedAutoResetEvent<MutexType>
+    {
+    }
+
+    impl<MutexType: RawMutex> core::fmt::Debug
+        for GenericSharedAutoResetEvent<MutexType>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("SharedAutoResetEvent").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex> GenericSharedAutoResetEvent<MutexType> {
+        /// Creates a new shared AutoResetEvent.
+        ///
+        /// See [`GenericAutoResetEvent::new`] for more information.
+        pub fn new(is_set: bool) -> GenericSharedAutoResetEvent<MutexType> {
+            GenericSharedAutoResetEvent {
+                inner: Arc::new(Mutex::new(EventState::new(is_set))),
+            }
+        }
+
+        /// Wakes up a single waiting task.
+        ///
+        /// If no task is waiting, the notification is stored and will be
+        /// consumed by the next task that waits on the event.
+        pub fn notify_one(&self) {
+            self.inner.lock().notify_one()
+        }
+
+        /// Wakes up all tasks which are currently waiting on the event.
+        ///
+        /// In contrast to `notify_one`, no notification is stored if no task
+        /// is waiting.
+        pub fn notify_all(&self) {
+            self.inner.lock().notify_all()
+        }
+
+        /// Removes a stored notification.
+        pub fn reset(&self) {
+            self.inner.lock().reset()
+        }
+
+        /// Returns whether a notification is stored in the event.
+        pub fn is_set(&self) -> bool {
+            self.inner.lock().is_set()
+        }
+
+        /// Returns a future that gets fulfilled when the event is notified.
+        pub fn wait(
+            &self,
+        ) -> GenericSharedWaitForAutoResetEventFuture<MutexType> {
+            GenericSharedWaitForAutoResetEventFuture {
+                event: Some(self.clone()),
+                wait_node: ListNode::new(WaitQueueEntry::new()),
+            }
+        }
+    }
+
+    // Export parking_lot based shared events in std mode
+    #[cfg(feature = "std")]
+    mod if_std {
+        use super::*;
+
+        /// A [`GenericSharedAutoResetEvent`] backed by [`parking_lot`].
+        pub type SharedAutoResetEvent =
+            GenericSharedAutoResetEvent<parking_lot::RawMutex>;
+        /// A [`GenericSharedWaitForAutoResetEventFuture`] for
+        /// [`SharedAutoResetEvent`].
+        pub type SharedWaitForAutoResetEventFuture =
+            GenericSharedWaitForAutoResetEventFuture<parking_lot::RawMutex>;
+    }
+
+    #[cfg(feature = "std")]
+    pub use self::if_std::*;
+}
+
+#[cfg(feature = "alloc")]
+pub use self::if_alloc::*;
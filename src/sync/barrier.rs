@@ -0,0 +1,337 @@
+//! An asynchronously awaitable barrier which allows a group of tasks to
+//! rendezvous.
+
+use crate::{
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Tracks how the future had interacted with the barrier
+#[derive(PartialEq)]
+enum PollState {
+    /// The task has never interacted with the barrier.
+    New,
+    /// The task arrived at the barrier and was added to the wait queue.
+    Waiting,
+    /// The generation of the task was completed, or the task has been
+    /// polled to completion.
+    Done,
+}
+
+/// Tracks the BarrierWaitFuture waiting state.
+/// Access to this struct is synchronized through the mutex in the Barrier.
+struct WaitQueueEntry {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: PollState,
+}
+
+impl WaitQueueEntry {
+    /// Creates a new WaitQueueEntry
+    fn new() -> WaitQueueEntry {
+        WaitQueueEntry {
+            task: None,
+            state: PollState::New,
+        }
+    }
+}
+
+/// Internal state of the `Barrier`
+struct BarrierState {
+    /// The amount of tasks which need to arrive in each generation
+    num_tasks: usize,
+    /// The amount of tasks which arrived in the current generation
+    arrived: usize,
+    /// The amount of generations which have been completed
+    generation: usize,
+    waiters: LinkedList<WaitQueueEntry>,
+}
+
+impl BarrierState {
+    const fn new(num_tasks: usize) -> BarrierState {
+        BarrierState {
+            // A barrier for 0 tasks behaves like a barrier for a single task
+            num_tasks: if num_tasks == 0 { 1 } else { num_tasks },
+            arrived: 0,
+            generation: 0,
+            waiters: LinkedList::new(),
+        }
+    }
+
+    /// Registers the arrival of a task at the barrier.
+    ///
+    /// If the task is the last one which is required to complete the current
+    /// generation, all waiting tasks get woken up and the barrier is reset
+    /// for the next generation. In this case the task is the leader of the
+    /// generation.
+    /// Otherwise the WaitQueueEntry gets added to the wait queue.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn try_wait(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<BarrierWaitResult> {
+        match wait_node.state {
+            PollState::New => {
+                self.arrived += 1;
+                if self.arrived == self.num_tasks {
+                    // This task completes the generation
+                    self.arrived = 0;
+                    self.generation = self.generation.wrapping_add(1);
+
+                    // Use a reverse iterator, so that the oldest waiter gets
+                    // scheduled first
+                    self.waiters.reverse_drain(|waiter| {
+                        if let Some(handle) = waiter.task.take() {
+                            handle.wake();
+                        }
+                        waiter.state = PollState::Done;
+                    });
+
+                    wait_node.state = PollState::Done;
+                    Poll::Ready(BarrierWaitResult { is_leader: true })
+                } else {
+                    // Added the task to the wait queue
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = PollState::Waiting;
+                    self.waiters.add_front(wait_node);
+                    Poll::Pending
+                }
+            }
+            PollState::Waiting => {
+                // The generation isn't complete yet. However the caller might
+                // have passed a different `Waker`. In this case we need to
+                // update it.
+                update_waker_ref(&mut wait_node.task, cx);
+                Poll::Pending
+            }
+            PollState::Done => {
+                // The generation has been completed by another task
+                Poll::Ready(BarrierWaitResult { is_leader: false })
+            }
+        }
+    }
+
+    fn remove_waiter(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        // The future only needs to get removed if it has been added to
+        // the wait queue of the Barrier. This has happened in the
+        // PollState::Waiting case. Since the generation of the task has not
+        // been completed yet, the task is no longer counted as arrived.
+        if let PollState::Waiting = wait_node.state {
+            // Safety: Due to the state, we know that the node must be part
+            // of the waiter list
+            if !unsafe { self.waiters.remove(wait_node) } {
+                // Panic if the address isn't found. This can only happen if the contract was
+                // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                panic!("Future could not be removed from wait queue");
+            }
+            wait_node.state = PollState::Done;
+            self.arrived -= 1;
+        }
+    }
+}
+
+/// The result of waiting on a barrier.
+///
+/// Exactly one task of each generation is the leader of the generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult {
+    is_leader: bool,
+}
+
+impl BarrierWaitResult {
+    /// Returns whether the task is the leader of its generation.
+    ///
+    /// The leader is the task whose arrival completed the generation.
+    pub fn is_leader(&self) -> bool {
+        self.is_leader
+    }
+}
+
+/// A barrier which allows a fixed amount of tasks to wait until all of them
+/// reached a certain point in their execution.
+///
+/// Tasks obtain a Future via `wait`. All of those futures complete once the
+/// configured amount of tasks has arrived at the barrier. The barrier then
+/// automatically resets itself, and can be used for the next generation.
+///
+/// If a future which has been polled gets dropped before the generation was
+/// completed, the task is no longer counted as arrived.
+pub struct GenericBarrier<MutexType: RawMutex> {
+    inner: Mutex<MutexType, BarrierState>,
+}
+
+// The Barrier is can be sent to other threads as long as it's not borrowed
+unsafe impl<MutexType: RawMutex + Send> Send for GenericBarrier<MutexType> {}
+// The Barrier is thread-safe as long as the utilized Mutex is thread-safe
+unsafe impl<MutexType: RawMutex + Sync> Sync for GenericBarrier<MutexType> {}
+
+impl<MutexType: RawMutex> core::fmt::Debug for GenericBarrier<MutexType> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let state = self.inner.lock();
+        f.debug_struct("Barrier")
+            .field("num_tasks", &state.num_tasks)
+            .field("arrived", &state.arrived)
+            .finish()
+    }
+}
+
+impl<MutexType: RawMutex> GenericBarrier<MutexType> {
+    /// Creates a new Barrier which completes each generation once `num_tasks`
+    /// tasks are waiting on it.
+    ///
+    /// A barrier for 0 tasks behaves like a barrier for a single task.
+    pub const fn new(num_tasks: usize) -> GenericBarrier<MutexType> {
+        GenericBarrier {
+            inner: Mutex::<MutexType, BarrierState>::const_new(
+                MutexType::INIT,
+                BarrierState::new(num_tasks),
+            ),
+        }
+    }
+
+    /// Returns a future that gets fulfilled when the required amount of tasks
+    /// is waiting on the barrier.
+    ///
+    /// The task is counted as arrived once the future has been polled for the
+    /// first time.
+    pub fn wait(&self) -> GenericBarrierWaitFuture<'_, MutexType> {
+        GenericBarrierWaitFuture {
+            barrier: Some(self),
+            wait_node: ListNode::new(WaitQueueEntry::new()),
+        }
+    }
+
+    /// Returns the amount of tasks which have arrived at the barrier in the
+    /// current generation.
+    pub fn arrived(&self) -> usize {
+        self.inner.lock().arrived
+    }
+
+    /// Returns the amount of generations which have been completed.
+    ///
+    /// The counter wraps around on overflow.
+    pub fn generation(&self) -> usize {
+        self.inner.lock().generation
+    }
+
+    unsafe fn try_wait(
+        &self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<BarrierWaitResult> {
+        self.inner.lock().try_wait(wait_node, cx)
+    }
+
+    fn remove_waiter(&self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        self.inner.lock().remove_waiter(wait_node)
+    }
+}
+
+/// A Future that is resolved once the required amount of tasks is waiting on
+/// the corresponding Barrier
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericBarrierWaitFuture<'a, MutexType: RawMutex> {
+    /// The Barrier that is associated with this BarrierWaitFuture
+    barrier: Option<&'a GenericBarrier<MutexType>>,
+    /// Node for waiting at the barrier
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// barrier is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync> Send
+    for GenericBarrierWaitFuture<'a, MutexType>
+{
+}
+
+impl<'a, MutexType: RawMutex> core::fmt::Debug
+    for GenericBarrierWaitFuture<'a, MutexType>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericBarrierWaitFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Future
+    for GenericBarrierWaitFuture<'a, MutexType>
+{
+    type Output = BarrierWaitResult;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<BarrierWaitResult> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside GenericBarrierWaitFuture
+        // is stable, and we don't move any fields inside the future until it
+        // gets dropped.
+        let mut_self: &mut GenericBarrierWaitFuture<MutexType> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let barrier = mut_self
+            .barrier
+            .expect("polled BarrierWaitFuture after completion");
+
+        let poll_res = unsafe { barrier.try_wait(&mut mut_self.wait_node, cx) };
+
+        if poll_res.is_ready() {
+            // The generation was completed
+            mut_self.barrier = None;
+        }
+
+        poll_res
+    }
+}
+
+impl<'a, MutexType: RawMutex> FusedFuture
+    for GenericBarrierWaitFuture<'a, MutexType>
+{
+    fn is_terminated(&self) -> bool {
+        self.barrier.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Drop for GenericBarrierWaitFuture<'a, MutexType> {
+    fn drop(&mut self) {
+        // If this BarrierWaitFuture has been polled and it was added to the
+        // wait queue at the barrier, it must be removed before dropping.
+        // Otherwise the barrier would access invalid memory.
+        if let Some(barrier) = self.barrier {
+            barrier.remove_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericBarrier`] which is not thread-safe.
+pub type LocalBarrier = GenericBarrier<NoopLock>;
+/// A [`GenericBarrierWaitFuture`] for [`LocalBarrier`].
+pub type LocalBarrierWaitFuture<'a> = GenericBarrierWaitFuture<'a, NoopLock>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericBarrier`] implementation backed by [`parking_lot`].
+    pub type Barrier = GenericBarrier<parking_lot::RawMutex>;
+    /// A [`GenericBarrierWaitFuture`] for [`Barrier`].
+    pub type BarrierWaitFuture<'a> =
+        GenericBarrierWaitFuture<'a, parking_lot::RawMutex>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
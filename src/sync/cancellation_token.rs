@@ -0,0 +1,661 @@
+//! An asynchronously awaitable token for cooperative and hierarchical
+//! cancellation of tasks.
+
+use crate::{
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Tracks how the future had interacted with the token
+#[derive(PartialEq)]
+enum PollState {
+    /// The task is not registered at the token. This is the case for new
+    /// tasks, as well as for tasks which got woken up due to the cancellation
+    /// of the token or one of its parent tokens.
+    New,
+    /// The task was added to the wait queue at the token.
+    Waiting,
+    /// The task has been polled to completion.
+    Done,
+}
+
+/// Tracks the WaitForCancellationFuture waiting state.
+/// Access to this struct is synchronized through the mutex in the root token.
+struct WaitQueueEntry {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: PollState,
+}
+
+impl WaitQueueEntry {
+    /// Creates a new WaitQueueEntry
+    fn new() -> WaitQueueEntry {
+        WaitQueueEntry {
+            task: None,
+            state: PollState::New,
+        }
+    }
+}
+
+/// Points to the state of a child token which is linked into the state of
+/// its parent token.
+type ChildLink<MutexType> = *const Mutex<MutexType, TokenState<MutexType>>;
+
+/// Internal state of a `CancellationToken`
+///
+/// Tokens don't know about all of their child tokens, since child tokens only
+/// borrow their parent and can be moved freely. However a child token can't
+/// be moved as long as tasks are waiting on it or on one of its own child
+/// tokens, since those borrow the token. During this time the child token is
+/// linked into the `active_children` of its parent, which allows to wake up
+/// only the waiters of the cancelled part of the hierarchy.
+///
+/// A waiting future which gets leaked via `mem::forget` doesn't borrow the
+/// token anymore. Therefore tokens unlink themselves from their parent when
+/// they get dropped.
+///
+/// The hierarchy is only modified while the lock of the root token is held.
+struct TokenState<MutexType: RawMutex> {
+    is_cancelled: bool,
+    /// The tasks which are waiting on this token.
+    waiters: LinkedList<WaitQueueEntry>,
+    /// The child tokens which have waiting tasks in their part of the
+    /// hierarchy.
+    active_children: LinkedList<ChildLink<MutexType>>,
+    /// The node which links this token into the `active_children` of its
+    /// parent token.
+    link: ListNode<ChildLink<MutexType>>,
+    /// Whether `link` is part of the `active_children` of the parent token.
+    is_linked: bool,
+}
+
+impl<MutexType: RawMutex> TokenState<MutexType> {
+    const fn new(is_cancelled: bool) -> TokenState<MutexType> {
+        TokenState {
+            is_cancelled,
+            waiters: LinkedList::new(),
+            active_children: LinkedList::new(),
+            link: ListNode::new(core::ptr::null()),
+            is_linked: false,
+        }
+    }
+
+    /// Returns whether tasks are waiting on this token or on one of its child
+    /// tokens.
+    fn is_active(&self) -> bool {
+        !self.waiters.is_empty() || !self.active_children.is_empty()
+    }
+
+    /// Wakes up all tasks which are waiting on this token or on one of its
+    /// child tokens, and unlinks the child tokens.
+    /// The tasks will recheck whether their token got cancelled once they get
+    /// polled again.
+    fn wakeup_waiters(&mut self) {
+        // Use a reverse iterator, so that the oldest waiter gets
+        // scheduled first
+        self.waiters.reverse_drain(|waiter| {
+            if let Some(handle) = waiter.task.take() {
+                handle.wake();
+            }
+            waiter.state = PollState::New;
+        });
+        self.active_children.reverse_drain(|child| {
+            // Safety: Linked child tokens are borrowed by waiting futures or
+            // by their own linked child tokens. Tokens which are dropped
+            // while being linked unlink themselves.
+            let mut child_state = unsafe { (***child).lock() };
+            child_state.is_linked = false;
+            child_state.wakeup_waiters();
+        });
+    }
+
+    fn remove_waiter(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        // WaitForCancellationFuture only needs to get removed if it has been
+        // added to the wait queue of the token. This has happened in the
+        // PollState::Waiting case.
+        if let PollState::Waiting = wait_node.state {
+            // Safety: Due to the state, we know that the node must be part
+            // of the waiter list
+            if !unsafe { self.waiters.remove(wait_node) } {
+                // Panic if the address isn't found. This can only happen if the contract was
+                // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                panic!("Future could not be removed from wait queue");
+            }
+        }
+        wait_node.state = PollState::Done;
+    }
+}
+
+/// A token which allows to signal cancellation to a set of tasks.
+///
+/// Tasks can check whether the token got cancelled via `is_cancelled`, or
+/// obtain a Future via `cancelled`, which gets fulfilled once the token got
+/// cancelled.
+///
+/// Tokens form a hierarchy: Child tokens which are created via
+/// `child_token` get cancelled whenever their parent token gets cancelled.
+/// Cancelling a child token does not cancel its parent token.
+///
+/// Child tokens borrow their parent token, and therefore can't outlive it.
+/// Cancellation tokens which can be shared between independent tasks are
+/// available as [`GenericSharedCancellationToken`] if the `alloc` feature is
+/// enabled.
+pub struct GenericCancellationToken<'a, MutexType: RawMutex> {
+    parent: Option<&'a GenericCancellationToken<'a, MutexType>>,
+    inner: Mutex<MutexType, TokenState<MutexType>>,
+}
+
+// The token is can be sent to other threads as long as it's not borrowed
+unsafe impl<'a, MutexType: RawMutex + Send + Sync> Send
+    for GenericCancellationToken<'a, MutexType>
+{
+}
+// The token is thread-safe as long as the utilized Mutex is thread-safe
+unsafe impl<'a, MutexType: RawMutex + Sync> Sync
+    for GenericCancellationToken<'a, MutexType>
+{
+}
+
+impl<'a, MutexType: RawMutex> core::fmt::Debug
+    for GenericCancellationToken<'a, MutexType>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Default
+    for GenericCancellationToken<'a, MutexType>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, MutexType: RawMutex> GenericCancellationToken<'a, MutexType> {
+    /// Creates a new CancellationToken, which is not cancelled.
+    pub const fn new() -> GenericCancellationToken<'a, MutexType> {
+        GenericCancellationToken {
+            parent: None,
+            inner: Mutex::<MutexType, TokenState<MutexType>>::const_new(
+                MutexType::INIT,
+                TokenState::new(false),
+            ),
+        }
+    }
+
+    /// Creates a child token of this token.
+    ///
+    /// The child token gets cancelled when this token gets cancelled.
+    /// Cancelling the child token does not cancel this token.
+    pub fn child_token(&self) -> GenericCancellationToken<'_, MutexType> {
+        GenericCancellationToken {
+            parent: Some(self),
+            inner: Mutex::new(TokenState::new(false)),
+        }
+    }
+
+    /// Cancels the token and all of its child tokens.
+    ///
+    /// All tasks which are waiting for the cancellation of any of those tokens
+    /// get woken up. Tasks which are waiting on other tokens of the hierarchy
+    /// are not affected.
+    pub fn cancel(&self) {
+        let mut root_state = self.root().inner.lock();
+        let was_cancelled = self.with_state(&mut root_state, |state| {
+            let was_cancelled = state.is_cancelled;
+            if !was_cancelled {
+                state.is_cancelled = true;
+                state.wakeup_waiters();
+            }
+            was_cancelled
+        });
+        if !was_cancelled {
+            self.deactivate(&mut root_state);
+        }
+    }
+
+    /// Returns whether the token or any of its parent tokens has been
+    /// cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        let mut token = Some(self);
+        while let Some(t) = token {
+            if t.inner.lock().is_cancelled {
+                return true;
+            }
+            token = t.parent;
+        }
+        false
+    }
+
+    /// Returns a future that gets fulfilled when the token or any of its
+    /// parent tokens gets cancelled.
+    pub fn cancelled(&self) -> GenericWaitForCancellationFuture<'_, MutexType> {
+        GenericWaitForCancellationFuture {
+            token: Some(self),
+            wait_node: ListNode::new(WaitQueueEntry::new()),
+        }
+    }
+
+    /// Returns the root token of the hierarchy.
+    fn root(&self) -> &Self {
+        let mut token = self;
+        while let Some(parent) = token.parent {
+            token = parent;
+        }
+        token
+    }
+
+    /// Calls `func` with the state of this token, while the lock of the root
+    /// token is already held.
+    fn with_state<R>(
+        &self,
+        root_state: &mut TokenState<MutexType>,
+        func: impl FnOnce(&mut TokenState<MutexType>) -> R,
+    ) -> R {
+        if self.parent.is_none() {
+            func(root_state)
+        } else {
+            func(&mut self.inner.lock())
+        }
+    }
+
+    /// Returns whether the token or any of its parent tokens has been
+    /// cancelled, while the lock of the root token is already held.
+    fn is_cancelled_locked(&self, root_state: &TokenState<MutexType>) -> bool {
+        let mut token = self;
+        while let Some(parent) = token.parent {
+            if token.inner.lock().is_cancelled {
+                return true;
+            }
+            token = parent;
+        }
+        root_state.is_cancelled
+    }
+
+    /// Links the token and all of its inactive parent tokens into the
+    /// `active_children` of their respective parent, after a task started
+    /// to wait on the token.
+    ///
+    /// Safety: The token may not be moved as long as it is linked, which is
+    /// guaranteed by the waiting futures which borrow it.
+    unsafe fn activate(&self, root_state: &mut TokenState<MutexType>) {
+        let mut token = self;
+        while let Some(parent) = token.parent {
+            let link = {
+                let mut state = token.inner.lock();
+                if state.is_linked {
+                    return;
+                }
+                state.is_linked = true;
+                *state.link = &token.inner;
+                &mut state.link as *mut ListNode<ChildLink<MutexType>>
+            };
+            parent.with_state(root_state, |parent_state| {
+                parent_state.active_children.add_front(&mut *link);
+            });
+            token = parent;
+        }
+    }
+
+    /// Unlinks the token and all of its parent tokens which don't have
+    /// waiting tasks anymore from the `active_children` of their respective
+    /// parent.
+    fn deactivate(&self, root_state: &mut TokenState<MutexType>) {
+        let mut token = self;
+        while let Some(parent) = token.parent {
+            let link = {
+                let mut state = token.inner.lock();
+                if !state.is_linked || state.is_active() {
+                    return;
+                }
+                state.is_linked = false;
+                &mut state.link as *mut ListNode<ChildLink<MutexType>>
+            };
+            parent.with_state(root_state, |parent_state| {
+                // Safety: The token was linked, and therefore must be part of
+                // the list of its parent.
+                if !unsafe { parent_state.active_children.remove(&mut *link) } {
+                    panic!("Token could not be removed from parent token");
+                }
+            });
+            token = parent;
+        }
+    }
+
+    unsafe fn try_wait(
+        &self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        let mut root_state = self.root().inner.lock();
+        if self.is_cancelled_locked(&root_state) {
+            self.remove_waiter_locked(&mut root_state, wait_node);
+            return Poll::Ready(());
+        }
+
+        match wait_node.state {
+            PollState::New => {
+                // Added the task to the wait queue
+                wait_node.task = Some(cx.waker().clone());
+                wait_node.state = PollState::Waiting;
+                self.with_state(&mut root_state, |state| {
+                    state.waiters.add_front(wait_node)
+                });
+                self.activate(&mut root_state);
+                Poll::Pending
+            }
+            PollState::Waiting => {
+                // The WaitForCancellationFuture is already in the queue.
+                // However the caller might have passed a different `Waker`.
+                // In this case we need to update it.
+                update_waker_ref(&mut wait_node.task, cx);
+                Poll::Pending
+            }
+            PollState::Done => Poll::Ready(()),
+        }
+    }
+
+    fn remove_waiter_locked(
+        &self,
+        root_state: &mut TokenState<MutexType>,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+    ) {
+        self.with_state(root_state, |state| state.remove_waiter(wait_node));
+        self.deactivate(root_state);
+    }
+
+    fn remove_waiter(&self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        let mut root_state = self.root().inner.lock();
+        self.remove_waiter_locked(&mut root_state, wait_node);
+    }
+}
+
+impl<'a, MutexType: RawMutex> Drop for GenericCancellationToken<'a, MutexType> {
+    fn drop(&mut self) {
+        // The token might still be linked into its parent if a future which
+        // waited on it had been leaked. It must be unlinked before dropping.
+        // Otherwise cancelling the parent would access invalid memory.
+        let parent = match self.parent {
+            Some(parent) => parent,
+            None => return,
+        };
+
+        let mut root_state = parent.root().inner.lock();
+        let link = {
+            let mut state = self.inner.lock();
+            if !state.is_linked {
+                return;
+            }
+            state.is_linked = false;
+            &mut state.link as *mut ListNode<ChildLink<MutexType>>
+        };
+        parent.with_state(&mut root_state, |parent_state| {
+            // Safety: The token was linked, and therefore must be part of
+            // the list of its parent.
+            if !unsafe { parent_state.active_children.remove(&mut *link) } {
+                panic!("Token could not be removed from parent token");
+            }
+        });
+        parent.deactivate(&mut root_state);
+    }
+}
+
+/// A Future that is resolved once the corresponding CancellationToken has
+/// been cancelled
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericWaitForCancellationFuture<'a, MutexType: RawMutex> {
+    /// The CancellationToken that is associated with this future
+    token: Option<&'a GenericCancellationToken<'a, MutexType>>,
+    /// Node for waiting at the token
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// token is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync> Send
+    for GenericWaitForCancellationFuture<'a, MutexType>
+{
+}
+
+impl<'a, MutexType: RawMutex> core::fmt::Debug
+    for GenericWaitForCancellationFuture<'a, MutexType>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericWaitForCancellationFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Future
+    for GenericWaitForCancellationFuture<'a, MutexType>
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside the future is stable,
+        // and we don't move any fields inside the future until it gets dropped.
+        let mut_self: &mut GenericWaitForCancellationFuture<MutexType> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let token = mut_self
+            .token
+            .expect("polled WaitForCancellationFuture after completion");
+
+        let poll_res = unsafe { token.try_wait(&mut mut_self.wait_node, cx) };
+
+        if poll_res.is_ready() {
+            // The token was cancelled
+            mut_self.token = None;
+        }
+
+        poll_res
+    }
+}
+
+impl<'a, MutexType: RawMutex> FusedFuture
+    for GenericWaitForCancellationFuture<'a, MutexType>
+{
+    fn is_terminated(&self) -> bool {
+        self.token.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Drop
+    for GenericWaitForCancellationFuture<'a, MutexType>
+{
+    fn drop(&mut self) {
+        // If this WaitForCancellationFuture has been polled and it was added
+        // to the wait queue at the root token, it must be removed before
+        // dropping. Otherwise the token would access invalid memory.
+        if let Some(token) = self.token {
+            token.remove_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericCancellationToken`] which is not thread-safe.
+pub type LocalCancellationToken<'a> = GenericCancellationToken<'a, NoopLock>;
+/// A [`GenericWaitForCancellationFuture`] for [`LocalCancellationToken`].
+pub type LocalWaitForCancellationFuture<'a> =
+    GenericWaitForCancellationFuture<'a, NoopLock>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericCancellationToken`] implementation backed by [`parking_lot`].
+    pub type CancellationToken<'a> =
+        GenericCancellationToken<'a, parking_lot::RawMutex>;
+    /// A [`GenericWaitForCancellationFuture`] for [`CancellationToken`].
+    pub type WaitForCancellationFuture<'a> =
+        GenericWaitForCancellationFuture<'a, parking_lot::RawMutex>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
+
+#[cfg(feature = "alloc")]
+mod if_alloc {
+    use super::*;
+
+    use crate::sync::{
+        GenericSharedManualResetEvent, GenericSharedWaitForEventFuture,
+    };
+    use alloc::{
+        sync::{Arc, Weak},
+        vec::Vec,
+    };
+
+    /// A Future that is resolved once the corresponding
+    /// [`GenericSharedCancellationToken`] has been cancelled.
+    pub type GenericSharedWaitForCancellationFuture<MutexType> =
+        GenericSharedWaitForEventFuture<MutexType>;
+
+    struct SharedTokenNode<MutexType: RawMutex> {
+        /// The event which gets set once the token is cancelled
+        event: GenericSharedManualResetEvent<MutexType>,
+        /// The child tokens which need to get cancelled together with this
+        /// token
+        children: Mutex<MutexType, Vec<Weak<SharedTokenNode<MutexType>>>>,
+    }
+
+    impl<MutexType: RawMutex> SharedTokenNode<MutexType> {
+        fn new(is_cancelled: bool) -> SharedTokenNode<MutexType> {
+            SharedTokenNode {
+                event: GenericSharedManualResetEvent::new(is_cancelled),
+                children: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn cancel(&self) {
+            if self.event.is_set() {
+                return;
+            }
+            self.event.set();
+
+            // Child tokens which get created after the children had been
+            // taken will observe the set event, and start in cancelled state.
+            let children = core::mem::take(&mut *self.children.lock());
+            for child in children.iter().filter_map(Weak::upgrade) {
+                child.cancel();
+            }
+        }
+    }
+
+    /// A shared version of [`GenericCancellationToken`].
+    ///
+    /// The token can be cloned, which creates another handle to the same
+    /// token. Child tokens and the futures which are produced by the token do
+    /// not have an associated lifetime. Therefore they can be moved into
+    /// independent tasks.
+    pub struct GenericSharedCancellationToken<MutexType: RawMutex> {
+        inner: Arc<SharedTokenNode<MutexType>>,
+    }
+
+    impl<MutexType: RawMutex> Clone for GenericSharedCancellationToken<MutexType> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+
+    impl<MutexType: RawMutex> core::fmt::Debug
+        for GenericSharedCancellationToken<MutexType>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("SharedCancellationToken")
+                .field("is_cancelled", &self.is_cancelled())
+                .finish()
+        }
+    }
+
+    impl<MutexType: RawMutex> Default
+        for GenericSharedCancellationToken<MutexType>
+    {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<MutexType: RawMutex> GenericSharedCancellationToken<MutexType> {
+        /// Creates a new shared CancellationToken, which is not cancelled.
+        pub fn new() -> GenericSharedCancellationToken<MutexType> {
+            GenericSharedCancellationToken {
+                inner: Arc::new(SharedTokenNode::new(false)),
+            }
+        }
+
+        /// Creates a child token of this token.
+        ///
+        /// See [`GenericCancellationToken::child_token`] for more information.
+        pub fn child_token(&self) -> GenericSharedCancellationToken<MutexType> {
+            let mut children = self.inner.children.lock();
+            // The event is checked while the children are locked, in order to
+            // avoid a race with a concurrent cancellation.
+            let is_cancelled = self.inner.event.is_set();
+            let child = Arc::new(SharedTokenNode::new(is_cancelled));
+            if !is_cancelled {
+                // Remove the child tokens which had already been dropped
+                children.retain(|child| child.strong_count() != 0);
+                children.push(Arc::downgrade(&child));
+            }
+            GenericSharedCancellationToken { inner: child }
+        }
+
+        /// Cancels the token and all of its child tokens.
+        ///
+        /// See [`GenericCancellationToken::cancel`] for more information.
+        pub fn cancel(&self) {
+            self.inner.cancel()
+        }
+
+        /// Returns whether the token has been cancelled.
+        pub fn is_cancelled(&self) -> bool {
+            self.inner.event.is_set()
+        }
+
+        /// Returns a future that gets fulfilled when the token gets cancelled.
+        pub fn cancelled(
+            &self,
+        ) -> GenericSharedWaitForCancellationFuture<MutexType> {
+            self.inner.event.wait()
+        }
+    }
+
+    // Export parking_lot based shared tokens in std mode
+    #[cfg(feature = "std")]
+    mod if_std {
+        use super::*;
+
+        /// A [`GenericSharedCancellationToken`] backed by [`parking_lot`].
+        pub type SharedCancellationToken =
+            GenericSharedCancellationToken<parking_lot::RawMutex>;
+        /// A [`GenericSharedWaitForCancellationFuture`] for
+        /// [`SharedCancellationToken`].
+        pub type SharedWaitForCancellationFuture =
+            GenericSharedWaitForCancellationFuture<parking_lot::RawMutex>;
+    }
+
+    #[cfg(feature = "std")]
+    pub use self::if_std::*;
+}
+
+#[cfg(feature = "alloc")]
+pub use self::if_alloc::*;
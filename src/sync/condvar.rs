@@ -0,0 +1,467 @@
+//! An asynchronously awaitable condition variable, which can be used together
+//! with the asynchronous [`GenericMutex`].
+
+use super::mutex::{GenericMutex, GenericMutexGuard, GenericMutexLockFuture};
+use crate::{
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Tracks how the future had interacted with the condition variable
+#[derive(PartialEq)]
+enum PollState {
+    /// The task has never interacted with the condition variable.
+    New,
+    /// The task was added to the wait queue at the condition variable.
+    Waiting,
+    /// The task had been notified, and is trying to reacquire the mutex.
+    Notified,
+    /// The task has been polled to completion.
+    Done,
+}
+
+/// Tracks the CondvarWaitFuture waiting state.
+/// Access to this struct is synchronized through the mutex in the Condvar.
+struct WaitQueueEntry {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: PollState,
+}
+
+impl WaitQueueEntry {
+    /// Creates a new WaitQueueEntry
+    fn new() -> WaitQueueEntry {
+        WaitQueueEntry {
+            task: None,
+            state: PollState::New,
+        }
+    }
+}
+
+/// Internal state of the `Condvar`
+struct CondvarState {
+    waiters: LinkedList<WaitQueueEntry>,
+}
+
+impl CondvarState {
+    const fn new() -> CondvarState {
+        CondvarState {
+            waiters: LinkedList::new(),
+        }
+    }
+
+    /// Wakes up the oldest waiter
+    fn notify_one(&mut self) {
+        if let Some(waiter) = self.waiters.remove_last() {
+            if let Some(handle) = waiter.task.take() {
+                handle.wake();
+            }
+            waiter.state = PollState::Notified;
+        }
+    }
+
+    /// Wakes up all waiters
+    fn notify_all(&mut self) {
+        // Use a reverse iterator, so that the oldest waiter gets
+        // scheduled first
+        self.waiters.reverse_drain(|waiter| {
+            if let Some(handle) = waiter.task.take() {
+                handle.wake();
+            }
+            waiter.state = PollState::Notified;
+        });
+    }
+
+    /// Adds the WaitQueueEntry to the wait queue at the condition variable.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn register(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) {
+        wait_node.task = Some(cx.waker().clone());
+        wait_node.state = PollState::Waiting;
+        self.waiters.add_front(wait_node);
+    }
+
+    /// Checks whether the WaitQueueEntry had been notified in the meantime.
+    fn try_wait(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        match wait_node.state {
+            PollState::Waiting => {
+                // The condition variable had not been notified yet. However
+                // the caller might have passed a different `Waker`. In this
+                // case we need to update it.
+                update_waker_ref(&mut wait_node.task, cx);
+                Poll::Pending
+            }
+            PollState::Notified => Poll::Ready(()),
+            PollState::New | PollState::Done => {
+                unreachable!("Condvar wait node is not registered");
+            }
+        }
+    }
+
+    fn remove_waiter(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        match wait_node.state {
+            PollState::Waiting => {
+                // Safety: Due to the state, we know that the node must be part
+                // of the waiter list
+                if !unsafe { self.waiters.remove(wait_node) } {
+                    // Panic if the address isn't found. This can only happen if the contract was
+                    // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                    panic!("Future could not be removed from wait queue");
+                }
+            }
+            PollState::Notified => {
+                // The task was notified, but had not reacquired the mutex.
+                // Since it won't observe the condition anymore, the
+                // notification gets passed on to the next waiter.
+                self.notify_one();
+            }
+            PollState::New | PollState::Done => {}
+        }
+        wait_node.state = PollState::Done;
+    }
+}
+
+/// A futures-aware condition variable.
+///
+/// Condition variables allow tasks to wait until a condition which is
+/// protected by a [`GenericMutex`] is met. Tasks obtain a Future via `wait`,
+/// which releases the mutex while waiting, and reacquires it before it
+/// resolves.
+///
+/// Like their synchronous counterparts, tasks which are waiting on the
+/// condition variable might be woken up spuriously. The condition should
+/// therefore always be checked in a loop, e.g. via `wait_while`.
+pub struct GenericCondvar<MutexType: RawMutex> {
+    inner: Mutex<MutexType, CondvarState>,
+}
+
+// The Condvar is can be sent to other threads as long as it's not borrowed
+unsafe impl<MutexType: RawMutex + Send> Send for GenericCondvar<MutexType> {}
+// The Condvar is thread-safe as long as the utilized Mutex is thread-safe
+unsafe impl<MutexType: RawMutex + Sync> Sync for GenericCondvar<MutexType> {}
+
+impl<MutexType: RawMutex> core::fmt::Debug for GenericCondvar<MutexType> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Condvar").finish()
+    }
+}
+
+impl<MutexType: RawMutex> Default for GenericCondvar<MutexType> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<MutexType: RawMutex> GenericCondvar<MutexType> {
+    /// Creates a new condition variable.
+    pub const fn new() -> GenericCondvar<MutexType> {
+        GenericCondvar {
+            inner: Mutex::<MutexType, CondvarState>::const_new(
+                MutexType::INIT,
+                CondvarState::new(),
+            ),
+        }
+    }
+
+    /// Wakes up the task which has been waiting the longest on the condition
+    /// variable.
+    ///
+    /// If a woken up task gets cancelled before it reacquired the mutex, the
+    /// notification is passed on to the next waiting task.
+    pub fn notify_one(&self) {
+        self.inner.lock().notify_one()
+    }
+
+    /// Wakes up all tasks which are currently waiting on the condition
+    /// variable.
+    pub fn notify_all(&self) {
+        self.inner.lock().notify_all()
+    }
+
+    /// Returns a future which releases the given mutex guard, waits until the
+    /// condition variable is notified, and then reacquires the mutex.
+    ///
+    /// The mutex is only released once the future gets polled for the first
+    /// time. If the future is dropped before it resolves, the mutex is not
+    /// held anymore.
+    pub fn wait<'a, T>(
+        &'a self,
+        guard: GenericMutexGuard<'a, MutexType, T>,
+    ) -> GenericCondvarWaitFuture<'a, MutexType, T> {
+        GenericCondvarWaitFuture {
+            condvar: self,
+            mutex: Some(guard.mutex),
+            guard: Some(guard),
+            lock_future: None,
+            wait_node: ListNode::new(WaitQueueEntry::new()),
+        }
+    }
+
+    /// Returns a future which waits on the condition variable as long as
+    /// `condition` returns `true`.
+    ///
+    /// The condition is checked while the mutex is held, before the first
+    /// wait and after each wakeup. The future resolves with the reacquired
+    /// mutex guard once the condition returns `false`.
+    pub fn wait_while<'a, T, F>(
+        &'a self,
+        guard: GenericMutexGuard<'a, MutexType, T>,
+        condition: F,
+    ) -> GenericCondvarWaitWhileFuture<'a, MutexType, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        GenericCondvarWaitWhileFuture {
+            condvar: self,
+            guard: Some(guard),
+            wait_future: None,
+            condition,
+        }
+    }
+
+    fn remove_waiter(&self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        self.inner.lock().remove_waiter(wait_node)
+    }
+}
+
+/// A Future that is resolved once the corresponding Condvar has been notified
+/// and the associated mutex had been reacquired.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericCondvarWaitFuture<'a, MutexType: RawMutex, T: 'a> {
+    /// The Condvar that is associated with this CondvarWaitFuture
+    condvar: &'a GenericCondvar<MutexType>,
+    /// The Mutex which needs to get reacquired
+    mutex: Option<&'a GenericMutex<MutexType, T>>,
+    /// The guard which gets released on the first poll
+    guard: Option<GenericMutexGuard<'a, MutexType, T>>,
+    /// The future which reacquires the mutex after a notification
+    lock_future: Option<GenericMutexLockFuture<'a, MutexType, T>>,
+    /// Node for waiting at the condition variable
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// condition variable and mutex are thread-safe (Sync), which allows to
+// poll/register/unregister from a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: Send> Send
+    for GenericCondvarWaitFuture<'a, MutexType, T>
+{
+}
+
+impl<'a, MutexType: RawMutex, T> core::fmt::Debug
+    for GenericCondvarWaitFuture<'a, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericCondvarWaitFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Future
+    for GenericCondvarWaitFuture<'a, MutexType, T>
+{
+    type Output = GenericMutexGuard<'a, MutexType, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry and of the lock future inside
+        // GenericCondvarWaitFuture is stable, and we don't move any fields
+        // inside the future until it gets dropped.
+        let mut_self: &mut GenericCondvarWaitFuture<MutexType, T> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let mutex = mut_self
+            .mutex
+            .expect("polled CondvarWaitFuture after completion");
+
+        if let Some(guard) = mut_self.guard.take() {
+            // The task needs to be registered at the condition variable before
+            // the mutex is released. Otherwise notifications which are emitted
+            // right after the mutex had been released would get lost.
+            unsafe {
+                mut_self
+                    .condvar
+                    .inner
+                    .lock()
+                    .register(&mut mut_self.wait_node, cx);
+            }
+            drop(guard);
+            return Poll::Pending;
+        }
+
+        if mut_self.lock_future.is_none() {
+            let poll_res = mut_self
+                .condvar
+                .inner
+                .lock()
+                .try_wait(&mut mut_self.wait_node, cx);
+            if poll_res.is_pending() {
+                return Poll::Pending;
+            }
+            mut_self.lock_future = Some(mutex.lock());
+        }
+
+        let lock_future = unsafe {
+            Pin::new_unchecked(mut_self.lock_future.as_mut().unwrap())
+        };
+        match lock_future.poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(guard) => {
+                // The mutex was reacquired
+                mut_self.wait_node.state = PollState::Done;
+                mut_self.mutex = None;
+                Poll::Ready(guard)
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> FusedFuture
+    for GenericCondvarWaitFuture<'a, MutexType, T>
+{
+    fn is_terminated(&self) -> bool {
+        self.mutex.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Drop
+    for GenericCondvarWaitFuture<'a, MutexType, T>
+{
+    fn drop(&mut self) {
+        // If this CondvarWaitFuture has been polled and it was added to the
+        // wait queue at the condition variable, it must be removed before
+        // dropping. Otherwise the condition variable would access invalid
+        // memory. If the future had already been notified, the notification
+        // gets passed on to another waiter.
+        // The lock future and the guard release their resources on their own.
+        if self.mutex.is_some() {
+            self.condvar.remove_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+/// A Future that waits on a Condvar until a condition is no longer met, and
+/// resolves with the guard of the associated mutex.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericCondvarWaitWhileFuture<'a, MutexType: RawMutex, T: 'a, F> {
+    /// The Condvar that is associated with this CondvarWaitWhileFuture
+    condvar: &'a GenericCondvar<MutexType>,
+    /// The guard on which the condition needs to be checked
+    guard: Option<GenericMutexGuard<'a, MutexType, T>>,
+    /// The future which waits on the condition variable
+    wait_future: Option<GenericCondvarWaitFuture<'a, MutexType, T>>,
+    /// The condition which is checked after each wakeup
+    condition: F,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// condition variable and mutex are thread-safe (Sync), which allows to
+// poll/register/unregister from a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: Send, F: Send> Send
+    for GenericCondvarWaitWhileFuture<'a, MutexType, T, F>
+{
+}
+
+impl<'a, MutexType: RawMutex, T, F> core::fmt::Debug
+    for GenericCondvarWaitWhileFuture<'a, MutexType, T, F>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericCondvarWaitWhileFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F> Future
+    for GenericCondvarWaitWhileFuture<'a, MutexType, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Output = GenericMutexGuard<'a, MutexType, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait future inside GenericCondvarWaitWhileFuture
+        // is stable. The wait future is only replaced in place after it had
+        // completed.
+        let mut_self: &mut GenericCondvarWaitWhileFuture<MutexType, T, F> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        loop {
+            if let Some(mut guard) = mut_self.guard.take() {
+                if !(mut_self.condition)(&mut *guard) {
+                    mut_self.wait_future = None;
+                    return Poll::Ready(guard);
+                }
+                mut_self.wait_future = Some(mut_self.condvar.wait(guard));
+            }
+
+            let wait_future =
+                unsafe {
+                    Pin::new_unchecked(mut_self.wait_future.as_mut().expect(
+                        "polled CondvarWaitWhileFuture after completion",
+                    ))
+                };
+            match wait_future.poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(guard) => {
+                    // Recheck the condition
+                    mut_self.guard = Some(guard);
+                }
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F> FusedFuture
+    for GenericCondvarWaitWhileFuture<'a, MutexType, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    fn is_terminated(&self) -> bool {
+        self.guard.is_none() && self.wait_future.is_none()
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericCondvar`] which is not thread-safe.
+pub type LocalCondvar = GenericCondvar<NoopLock>;
+/// A [`GenericCondvarWaitFuture`] for [`LocalCondvar`].
+pub type LocalCondvarWaitFuture<'a, T> =
+    GenericCondvarWaitFuture<'a, NoopLock, T>;
+/// A [`GenericCondvarWaitWhileFuture`] for [`LocalCondvar`].
+pub type LocalCondvarWaitWhileFuture<'a, T, F> =
+    GenericCondvarWaitWhileFuture<'a, NoopLock, T, F>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericCondvar`] implementation backed by [`parking_lot`].
+    pub type Condvar = GenericCondvar<parking_lot::RawMutex>;
+    /// A [`GenericCondvarWaitFuture`] for [`Condvar`].
+    pub type CondvarWaitFuture<'a, T> =
+        GenericCondvarWaitFuture<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericCondvarWaitWhileFuture`] for [`Condvar`].
+    pub type CondvarWaitWhileFuture<'a, T, F> =
+        GenericCondvarWaitWhileFuture<'a, parking_lot::RawMutex, T, F>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
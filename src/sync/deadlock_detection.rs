@@ -0,0 +1,352 @@
+//! Detection of deadlocks between asynchronous mutexes.
+//!
+//! If the `deadlock_detection` feature is enabled, all [`GenericMutex`]
+//! instances report which task holds them and which tasks are waiting for
+//! them to a global registry.
+//!
+//! Each waiting lock future and each acquisition of a mutex is tracked under
+//! its own identity: the address of the wait node of the lock future which
+//! acquired the mutex, or the address of the mutex for an acquisition through
+//! `try_lock`. The guard releases the mutex under the same identity. In order
+//! to determine which waits and acquisitions belong to the same task, the
+//! [`Waker`] which is used for polling the lock futures is recorded. Futures
+//! which are polled with the same `Waker`, e.g. as part of `join!`, are
+//! therefore considered to be part of the same task.
+//!
+//! Whenever a task starts to wait for a mutex, the registry checks whether
+//! this closes a cycle of tasks which wait for each other. In addition to
+//! this it tracks the order in which mutexes are acquired while other mutexes
+//! are held, and detects lock-order inversions which might lead to deadlocks.
+//! The order is remembered until one of the mutexes gets dropped. Inversions
+//! are therefore also detected if the mutexes had been acquired in opposite
+//! order at different times. Mutexes are identified by a [`LockId`] which is
+//! assigned when the mutex is first tracked and which is never reused. Their
+//! address can't be used for this purpose, since it changes if a mutex gets
+//! moved while it is unlocked.
+//!
+//! Detected problems are reported through a handler which can be installed
+//! via [`set_deadlock_handler`]. The default handler panics.
+//!
+//! The handler is called from within the `poll` method of the lock future
+//! which detected the problem, after the state of the mutex had been updated.
+//! It is therefore allowed to interact with the mutexes which are part of the
+//! report.
+//!
+//! A mutex which is acquired through `try_lock` is recorded as held. Since no
+//! `Waker` is available in this case, the acquisition can't be associated with
+//! a task and doesn't take part in the detection of cycles and lock-order
+//! inversions.
+//!
+//! Only mutexes are instrumented. Semaphores are not tracked, since their
+//! permits are not owned by a single task, and a waiting task can get
+//! unblocked by any of the permit holders. Waits on semaphores are therefore
+//! never part of a reported cycle.
+//!
+//! [`GenericMutex`]: super::GenericMutex
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::Waker;
+use lock_api::{Mutex, RawMutex};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+/// Identifies a mutex inside a [`Deadlock`] report.
+///
+/// The identity of a mutex can be queried through
+/// [`GenericMutex::lock_id`](super::GenericMutex::lock_id). It doesn't change
+/// if the mutex gets moved, and is not reused for other mutexes.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct LockId(usize);
+
+impl LockId {
+    /// Allocates a new identity
+    pub(crate) fn next() -> LockId {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+        LockId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl core::fmt::Display for LockId {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A problem which had been detected by the deadlock detector.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Deadlock {
+    /// Tasks are waiting for each other in a cycle.
+    ///
+    /// The first lock is the one that the task which closed the cycle waits
+    /// for. Each following lock is waited for by the holder of the previous
+    /// lock. The holder of the last lock waits for the first one.
+    Cycle(Vec<LockId>),
+    /// Two locks had been acquired by different tasks in opposite order.
+    ///
+    /// A task acquired `second` while holding `first`, while the locks had
+    /// previously been acquired the other way around, either directly or
+    /// through other locks. This is reported even if the tasks never held the
+    /// locks at the same time, as long as none of the locks had been dropped
+    /// in between.
+    LockOrderInversion {
+        /// The lock which is held while `second` gets acquired
+        first: LockId,
+        /// The lock which gets acquired while `first` is held
+        second: LockId,
+    },
+}
+
+impl core::fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Deadlock::Cycle(locks) => {
+                write!(
+                    f,
+                    "deadlock detected: tasks wait in a cycle on locks "
+                )?;
+                for (i, lock) in locks.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, " -> ")?;
+                    }
+                    write!(f, "{}", lock)?;
+                }
+                Ok(())
+            }
+            Deadlock::LockOrderInversion { first, second } => write!(
+                f,
+                "lock-order inversion detected: lock {} acquired while \
+                 holding lock {}, but they had been acquired in the \
+                 opposite order before",
+                second, first
+            ),
+        }
+    }
+}
+
+type Handler = Arc<dyn Fn(&Deadlock) + Send + Sync>;
+
+/// A lock which is held by a task
+struct Holder {
+    /// The identity of the acquisition
+    id: usize,
+    lock: LockId,
+    /// The task which acquired the lock, if it is known
+    task: Option<Waker>,
+}
+
+/// A lock future which waits for a lock
+struct Waiter {
+    /// The identity of the waiting future
+    id: usize,
+    lock: LockId,
+    task: Waker,
+}
+
+/// Tracks holders and waiters of all locks
+struct Registry {
+    holders: Vec<Holder>,
+    waiters: Vec<Waiter>,
+    /// For each lock, the locks which had been acquired while it was held.
+    /// Entries are kept until one of the locks gets destroyed.
+    order: BTreeMap<LockId, BTreeSet<LockId>>,
+    handler: Option<Handler>,
+}
+
+impl Registry {
+    const fn new() -> Registry {
+        Registry {
+            holders: Vec::new(),
+            waiters: Vec::new(),
+            order: BTreeMap::new(),
+            handler: None,
+        }
+    }
+
+    fn holder_of(&self, lock: LockId) -> Option<&Waker> {
+        self.holders
+            .iter()
+            .find(|holder| holder.lock == lock)
+            .and_then(|holder| holder.task.as_ref())
+    }
+
+    /// Records that `task` acquires or waits for `lock`, and checks whether
+    /// this inverts the order in which locks had been acquired before.
+    fn record_order(&mut self, lock: LockId, task: &Waker) -> Option<Deadlock> {
+        let held: Vec<LockId> = self
+            .holders
+            .iter()
+            .filter(|holder| {
+                holder.lock != lock
+                    && holder.task.as_ref().is_some_and(|t| t.will_wake(task))
+            })
+            .map(|holder| holder.lock)
+            .collect();
+
+        let mut inversion = None;
+        for first in held {
+            if self
+                .order
+                .get(&first)
+                .is_some_and(|after| after.contains(&lock))
+            {
+                continue;
+            }
+            if inversion.is_none() && self.is_ordered_before(lock, first) {
+                inversion = Some(Deadlock::LockOrderInversion {
+                    first,
+                    second: lock,
+                });
+            }
+            self.order.entry(first).or_default().insert(lock);
+        }
+        inversion
+    }
+
+    /// Returns whether `first` had been acquired before `second`, either
+    /// directly or through other locks.
+    fn is_ordered_before(&self, first: LockId, second: LockId) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut pending = vec![first];
+        while let Some(lock) = pending.pop() {
+            if lock == second {
+                return true;
+            }
+            if !visited.insert(lock) {
+                continue;
+            }
+            if let Some(after) = self.order.get(&lock) {
+                pending.extend(after.iter().copied());
+            }
+        }
+        false
+    }
+
+    /// Searches for a cycle of tasks which wait for each other, which starts
+    /// with `task` waiting for the last lock in `path`.
+    fn find_cycle(&self, task: &Waker, path: &mut Vec<LockId>) -> bool {
+        let lock = *path.last().unwrap();
+        let holder = match self.holder_of(lock) {
+            Some(holder) => holder,
+            None => return false,
+        };
+        if holder.will_wake(task) {
+            // A task which waits for a lock that it holds itself is not
+            // reported. This is typically a different future which is
+            // executed as part of the same task.
+            return path.len() > 1;
+        }
+
+        for waiter in self.waiters.iter() {
+            if waiter.task.will_wake(holder) && !path.contains(&waiter.lock) {
+                path.push(waiter.lock);
+                if self.find_cycle(task, path) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        false
+    }
+}
+
+static REGISTRY: Mutex<parking_lot::RawMutex, Registry> =
+    Mutex::const_new(parking_lot::RawMutex::INIT, Registry::new());
+
+/// Installs a handler which gets called whenever a deadlock is detected.
+///
+/// This replaces the default handler, which panics.
+pub fn set_deadlock_handler<F>(handler: F)
+where
+    F: Fn(&Deadlock) + Send + Sync + 'static,
+{
+    REGISTRY.lock().handler = Some(Arc::new(handler));
+}
+
+/// Restores the default deadlock handler, which panics.
+pub fn reset_deadlock_handler() {
+    REGISTRY.lock().handler = None;
+}
+
+/// Passes a detected deadlock to the installed handler
+pub(crate) fn report(deadlock: Deadlock) {
+    let handler = REGISTRY.lock().handler.clone();
+    match handler {
+        Some(handler) => handler(&deadlock),
+        None => panic!("{}", deadlock),
+    }
+}
+
+/// Records that the future with identity `waiter` started to wait for `lock`.
+///
+/// Returns the problem which needs to get reported, if any.
+pub(crate) fn wait_started(
+    lock: LockId,
+    waiter: usize,
+    task: &Waker,
+) -> Option<Deadlock> {
+    let mut registry = REGISTRY.lock();
+    if let Some(existing) = registry.waiters.iter_mut().find(|w| w.id == waiter)
+    {
+        // Only the task might have changed since the last poll
+        existing.task = task.clone();
+        return None;
+    }
+    registry.waiters.push(Waiter {
+        id: waiter,
+        lock,
+        task: task.clone(),
+    });
+
+    let mut path = vec![lock];
+    if registry.find_cycle(task, &mut path) {
+        Some(Deadlock::Cycle(path))
+    } else {
+        registry.record_order(lock, task)
+    }
+}
+
+/// Records that the future with identity `waiter` stopped waiting.
+pub(crate) fn wait_finished(waiter: usize) {
+    REGISTRY.lock().waiters.retain(|w| w.id != waiter);
+}
+
+/// Records that `lock` had been acquired under the identity `holder`.
+///
+/// If the lock had been acquired by a lock future, `holder` is the identity
+/// of the future and `task` the task which polled it.
+///
+/// Returns the problem which needs to get reported, if any.
+pub(crate) fn lock_acquired(
+    lock: LockId,
+    holder: usize,
+    task: Option<&Waker>,
+) -> Option<Deadlock> {
+    let mut registry = REGISTRY.lock();
+    registry.waiters.retain(|w| w.id != holder);
+    registry.holders.push(Holder {
+        id: holder,
+        lock,
+        task: task.cloned(),
+    });
+    task.and_then(|task| registry.record_order(lock, task))
+}
+
+/// Records that `lock` had been released by the acquisition with identity
+/// `holder`.
+pub(crate) fn lock_released(lock: LockId, holder: usize) {
+    REGISTRY
+        .lock()
+        .holders
+        .retain(|h| h.id != holder || h.lock != lock);
+}
+
+/// Removes all information about `lock` after it had been destroyed.
+pub(crate) fn lock_destroyed(lock: LockId) {
+    let mut registry = REGISTRY.lock();
+    registry.holders.retain(|holder| holder.lock != lock);
+    registry.waiters.retain(|waiter| waiter.lock != lock);
+    registry.order.remove(&lock);
+    for after in registry.order.values_mut() {
+        after.remove(&lock);
+    }
+}
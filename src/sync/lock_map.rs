@@ -0,0 +1,334 @@
+//! A map of asynchronously awaitable locks, which provides mutual exclusion
+//! for operations on the same key.
+
+#[cfg(feature = "deadlock_detection")]
+use super::deadlock_detection;
+use super::mutex::{MutexState, WaitQueueEntry};
+use super::priority_wait_queue::WaitNode;
+use crate::NoopLock;
+use alloc::{boxed::Box, collections::BTreeMap};
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll},
+};
+use lock_api::{Mutex as LockApiMutex, RawMutex};
+
+/// The locks of all keys which are currently locked or waited for.
+///
+/// The state of each lock is boxed, since its address must stay stable while
+/// entries of other keys are inserted or removed.
+type Entries<K> = BTreeMap<K, Box<MutexState>>;
+
+/// Applies `f` to the lock of `key`, and removes the lock from the map if
+/// it is neither locked nor waited for afterwards.
+fn update_entry<K: Ord, R>(
+    entries: &mut Entries<K>,
+    key: &K,
+    f: impl FnOnce(&mut MutexState) -> R,
+) -> Option<R> {
+    let state = entries.get_mut(key)?;
+    let result = f(state);
+    if !state.is_locked() && !state.has_waiters() {
+        entries.remove(key);
+    }
+    Some(result)
+}
+
+/// An RAII guard returned by the `lock` and `try_lock` methods of a lock map.
+/// When this structure is dropped (falls out of scope), the lock for the
+/// associated key will be unlocked.
+pub struct GenericLockMapGuard<'a, MutexType: RawMutex, K: Ord> {
+    /// The LockMap which is associated with this Guard
+    map: &'a GenericLockMap<MutexType, K>,
+    /// The key which is locked
+    key: K,
+}
+
+impl<MutexType: RawMutex, K: Ord + core::fmt::Debug> core::fmt::Debug
+    for GenericLockMapGuard<'_, MutexType, K>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericLockMapGuard")
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+impl<MutexType: RawMutex, K: Ord> Drop
+    for GenericLockMapGuard<'_, MutexType, K>
+{
+    fn drop(&mut self) {
+        // Release the lock
+        let waker = {
+            let mut entries = self.map.entries.lock();
+            update_entry(&mut entries, &self.key, |state| state.unlock())
+                .flatten()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<MutexType: RawMutex, K: Ord> GenericLockMapGuard<'_, MutexType, K> {
+    /// Returns the key which is locked by this guard.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+/// A future which resolves when the lock for a key has been successfully
+/// acquired.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericLockMapLockFuture<'a, MutexType: RawMutex, K: Ord + Clone> {
+    /// The LockMap which should get locked trough this Future
+    map: Option<&'a GenericLockMap<MutexType, K>>,
+    /// The key which should get locked. This is moved into the guard once
+    /// the lock is acquired.
+    key: Option<K>,
+    /// Node for waiting at the lock of the key
+    wait_node: WaitNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// lock map is thread-safe (Sync), which allows to poll/register/unregister
+// from a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, K: Ord + Clone + Send> Send
+    for GenericLockMapLockFuture<'a, MutexType, K>
+{
+}
+
+impl<'a, MutexType: RawMutex, K: Ord + Clone> core::fmt::Debug
+    for GenericLockMapLockFuture<'a, MutexType, K>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericLockMapLockFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, K: Ord + Clone> Future
+    for GenericLockMapLockFuture<'a, MutexType, K>
+{
+    type Output = GenericLockMapGuard<'a, MutexType, K>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside GenericLockMapLockFuture
+        // is stable, and we don't move any fields inside the future until it
+        // gets dropped.
+        let mut_self: &mut GenericLockMapLockFuture<MutexType, K> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let map = mut_self
+            .map
+            .expect("polled GenericLockMapLockFuture after completion");
+        let key = mut_self.key.as_ref().unwrap();
+
+        let mut entries = map.entries.lock();
+        // The lock of the key only exists as long as it is locked or waited
+        // for. Otherwise it needs to get created.
+        if !entries.contains_key(key) {
+            entries.insert(key.clone(), Box::new(MutexState::new(true)));
+        }
+        let state = entries.get_mut(key).unwrap();
+
+        let poll_res = unsafe { state.try_lock(&mut mut_self.wait_node, cx) };
+        #[cfg(feature = "deadlock_detection")]
+        let deadlock = state.take_deadlock();
+        drop(entries);
+
+        let res = match poll_res {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(()) => {
+                // The lock was acquired
+                mut_self.map = None;
+                Poll::Ready(GenericLockMapGuard::<'a, MutexType, K> {
+                    map,
+                    key: mut_self.key.take().unwrap(),
+                })
+            }
+        };
+
+        // The handler is invoked after the future is in a consistent state,
+        // since it might panic.
+        #[cfg(feature = "deadlock_detection")]
+        {
+            if let Some(deadlock) = deadlock {
+                deadlock_detection::report(deadlock);
+            }
+        }
+
+        res
+    }
+}
+
+impl<'a, MutexType: RawMutex, K: Ord + Clone> FusedFuture
+    for GenericLockMapLockFuture<'a, MutexType, K>
+{
+    fn is_terminated(&self) -> bool {
+        self.map.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex, K: Ord + Clone> Drop
+    for GenericLockMapLockFuture<'a, MutexType, K>
+{
+    fn drop(&mut self) {
+        // If this GenericLockMapLockFuture has been polled and it was added to
+        // the wait queue of the key, it must be removed before dropping.
+        // Otherwise the lock would access invalid memory.
+        let waker = match (self.map, self.key.as_ref()) {
+            (Some(map), Some(key)) => {
+                let mut entries = map.entries.lock();
+                let wait_node = &mut self.wait_node;
+                update_entry(&mut entries, key, |state| {
+                    state.remove_waiter(wait_node)
+                })
+                .flatten()
+            }
+            _ => None,
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A futures-aware map of locks, which allows to exclusively lock individual
+/// keys.
+///
+/// Operations which lock different keys can run concurrently, while
+/// operations on the same key are serialized. Tasks which wait for the same
+/// key are served in FIFO order.
+///
+/// The lock for a key only occupies memory while it is locked or while
+/// tasks are waiting for it.
+pub struct GenericLockMap<MutexType: RawMutex, K: Ord> {
+    entries: LockApiMutex<MutexType, Entries<K>>,
+}
+
+// It is safe to send lock maps between threads, as long as they are not used
+// and thereby borrowed
+unsafe impl<K: Ord + Send, MutexType: RawMutex + Send> Send
+    for GenericLockMap<MutexType, K>
+{
+}
+// The lock map is thread-safe as long as the utilized mutex is thread-safe
+unsafe impl<K: Ord + Send, MutexType: RawMutex + Sync> Sync
+    for GenericLockMap<MutexType, K>
+{
+}
+
+impl<MutexType: RawMutex, K: Ord> core::fmt::Debug
+    for GenericLockMap<MutexType, K>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("LockMap")
+            .field("locked_keys", &self.len())
+            .finish()
+    }
+}
+
+impl<MutexType: RawMutex, K: Ord> Default for GenericLockMap<MutexType, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<MutexType: RawMutex, K: Ord> GenericLockMap<MutexType, K> {
+    /// Creates a new lock map in which no key is locked.
+    pub fn new() -> GenericLockMap<MutexType, K> {
+        GenericLockMap::<MutexType, K> {
+            entries: LockApiMutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Acquire the lock for `key` asynchronously.
+    ///
+    /// This method returns a future that will resolve once the lock has been
+    /// successfully acquired.
+    pub fn lock(&self, key: K) -> GenericLockMapLockFuture<'_, MutexType, K>
+    where
+        K: Clone,
+    {
+        GenericLockMapLockFuture::<MutexType, K> {
+            map: Some(self),
+            key: Some(key),
+            wait_node: WaitNode::fifo(WaitQueueEntry::new()),
+        }
+    }
+
+    /// Tries to acquire the lock for `key`.
+    ///
+    /// If acquiring the lock is successful, a [`GenericLockMapGuard`]
+    /// will be returned, which releases the lock once dropped.
+    ///
+    /// Otherwise `None` will be returned.
+    pub fn try_lock(
+        &self,
+        key: K,
+    ) -> Option<GenericLockMapGuard<'_, MutexType, K>>
+    where
+        K: Clone,
+    {
+        let mut entries = self.entries.lock();
+        if entries.contains_key(&key) {
+            // Another task holds the lock or waits for it
+            return None;
+        }
+
+        let mut state = Box::new(MutexState::new(true));
+        state.try_lock_sync();
+        entries.insert(key.clone(), state);
+        Some(GenericLockMapGuard { map: self, key })
+    }
+
+    /// Returns whether the lock for `key` is held.
+    pub fn is_locked(&self, key: &K) -> bool {
+        match self.entries.lock().get(key) {
+            Some(state) => state.is_locked(),
+            None => false,
+        }
+    }
+
+    /// Returns the amount of keys which are currently locked or waited for.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Returns whether no key is currently locked or waited for.
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericLockMap`] which is not thread-safe.
+pub type LocalLockMap<K> = GenericLockMap<NoopLock, K>;
+/// A [`GenericLockMapGuard`] for [`LocalLockMap`].
+pub type LocalLockMapGuard<'a, K> = GenericLockMapGuard<'a, NoopLock, K>;
+/// A [`GenericLockMapLockFuture`] for [`LocalLockMap`].
+pub type LocalLockMapLockFuture<'a, K> =
+    GenericLockMapLockFuture<'a, NoopLock, K>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericLockMap`] backed by [`parking_lot`].
+    pub type LockMap<K> = GenericLockMap<parking_lot::RawMutex, K>;
+    /// A [`GenericLockMapGuard`] for [`LockMap`].
+    pub type LockMapGuard<'a, K> =
+        GenericLockMapGuard<'a, parking_lot::RawMutex, K>;
+    /// A [`GenericLockMapLockFuture`] for [`LockMap`].
+    pub type LockMapLockFuture<'a, K> =
+        GenericLockMapLockFuture<'a, parking_lot::RawMutex, K>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
@@ -2,6 +2,7 @@
 
 use crate::{
     intrusive_double_linked_list::{LinkedList, ListNode},
+    select::SelectSource,
     utils::update_waker_ref,
     NoopLock,
 };
@@ -49,7 +50,7 @@ struct EventState {
 }
 
 impl EventState {
-    fn new(is_set: bool) -> EventState {
+    const fn new(is_set: bool) -> EventState {
         EventState {
             is_set,
             waiters: LinkedList::new(),
@@ -173,9 +174,12 @@ impl<MutexType: RawMutex> core::fmt::Debug
 
 impl<MutexType: RawMutex> GenericManualResetEvent<MutexType> {
     /// Creates a new ManualResetEvent in the given state
-    pub fn new(is_set: bool) -> GenericManualResetEvent<MutexType> {
+    pub const fn new(is_set: bool) -> GenericManualResetEvent<MutexType> {
         GenericManualResetEvent {
-            inner: Mutex::<MutexType, EventState>::new(EventState::new(is_set)),
+            inner: Mutex::<MutexType, EventState>::const_new(
+                MutexType::INIT,
+                EventState::new(is_set),
+            ),
         }
     }
 
@@ -280,6 +284,21 @@ impl<'a, MutexType: RawMutex> FusedFuture
     }
 }
 
+impl<'a, MutexType: RawMutex> SelectSource
+    for GenericWaitForEventFuture<'a, MutexType>
+{
+    type Output = ();
+
+    fn poll_select(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // The future only produces a single value. Afterwards it can't be
+        // chosen anymore.
+        if self.is_terminated() {
+            return Poll::Pending;
+        }
+        self.poll(cx)
+    }
+}
+
 impl<'a, MutexType: RawMutex> Drop
     for GenericWaitForEventFuture<'a, MutexType>
 {
@@ -315,3 +334,189 @@ mod if_std {
 
 #[cfg(feature = "std")]
 pub use self::if_std::*;
+
+#[cfg(feature = "alloc")]
+mod if_alloc {
+    use super::*;
+
+    use alloc::sync::Arc;
+
+    /// A Future that is resolved once the corresponding
+    /// [`GenericSharedManualResetEvent`] has been set.
+    #[must_use = "futures do nothing unless polled"]
+    pub struct GenericSharedWaitForEventFuture<MutexType: RawMutex> {
+        /// The event that is associated with this future
+        event: Option<GenericSharedManualResetEvent<MutexType>>,
+        /// Node for waiting at the event
+        wait_node: ListNode<WaitQueueEntry>,
+    }
+
+    // Safety: Futures can be sent between threads as long as the underlying
+    // event is thread-safe (Sync), which allows to poll/register/unregister from
+    // a different thread.
+    unsafe impl<MutexType: RawMutex + Sync> Send
+        for GenericSharedWaitForEventFuture<MutexType>
+    {
+    }
+
+    impl<MutexType: RawMutex> core::fmt::Debug
+        for GenericSharedWaitForEventFuture<MutexType>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("GenericSharedWaitForEventFuture").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex> Future
+        for GenericSharedWaitForEventFuture<MutexType>
+    {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            // Safety: The next operations are safe, because Pin promises us that
+            // the address of the wait queue entry inside the future is stable,
+            // and we don't move any fields inside the future until it gets dropped.
+            let mut_self: &mut GenericSharedWaitForEventFuture<MutexType> =
+                unsafe { Pin::get_unchecked_mut(self) };
+
+            let event = mut_self
+                .event
+                .take()
+                .expect("polled SharedWaitForEventFuture after completion");
+
+            let poll_res = unsafe {
+                event.inner.lock().try_wait(&mut mut_self.wait_node, cx)
+            };
+
+            if poll_res.is_pending() {
+                mut_self.event.replace(event);
+            }
+
+            poll_res
+        }
+    }
+
+    impl<MutexType: RawMutex> FusedFuture
+        for GenericSharedWaitForEventFuture<MutexType>
+    {
+        fn is_terminated(&self) -> bool {
+            self.event.is_none()
+        }
+    }
+
+    impl<MutexType: RawMutex> SelectSource
+        for GenericSharedWaitForEventFuture<MutexType>
+    {
+        type Output = ();
+
+        fn poll_select(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            // The future only produces a single value. Afterwards it can't be
+            // chosen anymore.
+            if self.is_terminated() {
+                return Poll::Pending;
+            }
+            self.poll(cx)
+        }
+    }
+
+    impl<MutexType: RawMutex> Drop for GenericSharedWaitForEventFuture<MutexType> {
+        fn drop(&mut self) {
+            // If this future has been polled and it was added to the
+            // wait queue at the event, it must be removed before dropping.
+            // Otherwise the event would access invalid memory.
+            if let Some(ev) = self.event.take() {
+                ev.inner.lock().remove_waiter(&mut self.wait_node);
+            }
+        }
+    }
+
+    /// A shared version of [`GenericManualResetEvent`].
+    ///
+    /// The event can be cloned, which creates another handle to the same
+    /// event. The futures which are produced by the event do not have an
+    /// associated lifetime.
+    pub struct GenericSharedManualResetEvent<MutexType: RawMutex> {
+        inner: Arc<Mutex<MutexType, EventState>>,
+    }
+
+    impl<MutexType: RawMutex> Clone for GenericSharedManualResetEvent<MutexType> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+
+    // The Event is can be sent to other threads as long as it's not borrowed
+    unsafe impl<MutexType: RawMutex + Send + Sync> Send
+        for GenericSharedManualResetEvent<MutexType>
+    {
+    }
+    // The Event is thread-safe as long as the utilized Mutex is thread-safe
+    unsafe impl<MutexType: RawMutex + Sync> Sync
+        for GenericSharedManualResetEvent<MutexType>
+    {
+    }
+
+    impl<MutexType: RawMutex> core::fmt::Debug
+        for GenericSharedManualResetEvent<MutexType>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("SharedManualResetEvent").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex> GenericSharedManualResetEvent<MutexType> {
+        /// Creates a new shared ManualResetEvent in the given state
+        pub fn new(is_set: bool) -> GenericSharedManualResetEvent<MutexType> {
+            GenericSharedManualResetEvent {
+                inner: Arc::new(Mutex::new(EventState::new(is_set))),
+            }
+        }
+
+        /// Sets the event.
+        ///
+        /// Setting the event will notify all pending waiters.
+        pub fn set(&self) {
+            self.inner.lock().set()
+        }
+
+        /// Resets the event.
+        pub fn reset(&self) {
+            self.inner.lock().reset()
+        }
+
+        /// Returns whether the event is set
+        pub fn is_set(&self) -> bool {
+            self.inner.lock().is_set()
+        }
+
+        /// Returns a future that gets fulfilled when the event is set.
+        pub fn wait(&self) -> GenericSharedWaitForEventFuture<MutexType> {
+            GenericSharedWaitForEventFuture {
+                event: Some(self.clone()),
+                wait_node: ListNode::new(WaitQueueEntry::new()),
+            }
+        }
+    }
+
+    // Export parking_lot based shared events in std mode
+    #[cfg(feature = "std")]
+    mod if_std {
+        use super::*;
+
+        /// A [`GenericSharedManualResetEvent`] backed by [`parking_lot`].
+        pub type SharedManualResetEvent =
+            GenericSharedManualResetEvent<parking_lot::RawMutex>;
+        /// A [`GenericSharedWaitForEventFuture`] for
+        /// [`SharedManualResetEvent`].
+        pub type SharedWaitForEventFuture =
+            GenericSharedWaitForEventFuture<parking_lot::RawMutex>;
+    }
+
+    #[cfg(feature = "std")]
+    pub use self::if_std::*;
+}
+
+#[cfg(feature = "alloc")]
+pub use self::if_alloc::*;
@@ -3,6 +3,77 @@
 //! This module provides various primitives for synchronizing concurrently
 //! executing futures.
 
+mod auto_reset_event;
+
+pub use self::auto_reset_event::{
+    GenericAutoResetEvent, GenericWaitForAutoResetEventFuture,
+    LocalAutoResetEvent, LocalWaitForAutoResetEventFuture,
+};
+
+#[cfg(feature = "alloc")]
+pub use self::auto_reset_event::{
+    GenericSharedAutoResetEvent, GenericSharedWaitForAutoResetEventFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::auto_reset_event::{
+    AutoResetEvent, SharedAutoResetEvent, SharedWaitForAutoResetEventFuture,
+    WaitForAutoResetEventFuture,
+};
+
+mod barrier;
+
+pub use self::barrier::{
+    BarrierWaitResult, GenericBarrier, GenericBarrierWaitFuture, LocalBarrier,
+    LocalBarrierWaitFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::barrier::{Barrier, BarrierWaitFuture};
+
+mod cancellation_token;
+
+pub use self::cancellation_token::{
+    GenericCancellationToken, GenericWaitForCancellationFuture,
+    LocalCancellationToken, LocalWaitForCancellationFuture,
+};
+
+#[cfg(feature = "alloc")]
+pub use self::cancellation_token::{
+    GenericSharedCancellationToken, GenericSharedWaitForCancellationFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::cancellation_token::{
+    CancellationToken, SharedCancellationToken,
+    SharedWaitForCancellationFuture, WaitForCancellationFuture,
+};
+
+mod condvar;
+
+pub use self::condvar::{
+    GenericCondvar, GenericCondvarWaitFuture, GenericCondvarWaitWhileFuture,
+    LocalCondvar, LocalCondvarWaitFuture, LocalCondvarWaitWhileFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::condvar::{Condvar, CondvarWaitFuture, CondvarWaitWhileFuture};
+
+#[cfg(feature = "deadlock_detection")]
+pub mod deadlock_detection;
+
+#[cfg(feature = "alloc")]
+mod lock_map;
+
+#[cfg(feature = "alloc")]
+pub use self::lock_map::{
+    GenericLockMap, GenericLockMapGuard, GenericLockMapLockFuture,
+    LocalLockMap, LocalLockMapGuard, LocalLockMapLockFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::lock_map::{LockMap, LockMapGuard, LockMapLockFuture};
+
 mod manual_reset_event;
 
 pub use self::manual_reset_event::{
@@ -10,24 +81,91 @@ pub use self::manual_reset_event::{
     LocalWaitForEventFuture,
 };
 
+#[cfg(feature = "alloc")]
+pub use self::manual_reset_event::{
+    GenericSharedManualResetEvent, GenericSharedWaitForEventFuture,
+};
+
 #[cfg(feature = "std")]
-pub use self::manual_reset_event::{ManualResetEvent, WaitForEventFuture};
+pub use self::manual_reset_event::{
+    ManualResetEvent, SharedManualResetEvent, SharedWaitForEventFuture,
+    WaitForEventFuture,
+};
 
 mod mutex;
 
 pub use self::mutex::{
-    GenericMutex, GenericMutexGuard, GenericMutexLockFuture, LocalMutex,
-    LocalMutexGuard, LocalMutexLockFuture,
+    GenericMappedMutexGuard, GenericMutex, GenericMutexGuard,
+    GenericMutexLockFuture, GenericMutexUnlockedFuture, LocalMappedMutexGuard,
+    LocalMutex, LocalMutexGuard, LocalMutexLockFuture,
+    LocalMutexUnlockedFuture,
+};
+
+#[cfg(feature = "alloc")]
+pub use self::mutex::{
+    GenericSharedMappedMutexGuard, GenericSharedMutex, GenericSharedMutexGuard,
+    GenericSharedMutexLockFuture, GenericSharedMutexUnlockedFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::mutex::{
+    MappedMutexGuard, Mutex, MutexGuard, MutexLockFuture, MutexUnlockedFuture,
+    SharedMappedMutexGuard, SharedMutex, SharedMutexGuard,
+    SharedMutexLockFuture, SharedMutexUnlockedFuture,
+};
+
+mod once_cell;
+
+pub use self::once_cell::{
+    GenericOnceCell, GenericOnceCellGetOrInitFuture,
+    GenericOnceCellGetOrTryInitFuture, LocalOnceCell,
+    LocalOnceCellGetOrInitFuture, LocalOnceCellGetOrTryInitFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::once_cell::{
+    OnceCell, OnceCellGetOrInitFuture, OnceCellGetOrTryInitFuture,
+};
+
+#[cfg(feature = "alloc")]
+mod pool;
+
+#[cfg(feature = "alloc")]
+pub use self::pool::{
+    GenericPool, GenericPoolGetFuture, GenericPoolGuard, LocalPool,
+    LocalPoolGetFuture, LocalPoolGuard,
+};
+
+#[cfg(feature = "std")]
+pub use self::pool::{Pool, PoolGetFuture, PoolGuard};
+
+mod priority_wait_queue;
+
+mod rwlock;
+
+pub use self::rwlock::{
+    GenericRwLock, GenericRwLockReadFuture, GenericRwLockReadGuard,
+    GenericRwLockUpgradableReadFuture, GenericRwLockUpgradableReadGuard,
+    GenericRwLockUpgradeFuture, GenericRwLockWriteFuture,
+    GenericRwLockWriteGuard, LocalRwLock, LocalRwLockReadFuture,
+    LocalRwLockReadGuard, LocalRwLockUpgradableReadFuture,
+    LocalRwLockUpgradableReadGuard, LocalRwLockUpgradeFuture,
+    LocalRwLockWriteFuture, LocalRwLockWriteGuard,
 };
 
 #[cfg(feature = "std")]
-pub use self::mutex::{Mutex, MutexGuard, MutexLockFuture};
+pub use self::rwlock::{
+    RwLock, RwLockReadFuture, RwLockReadGuard, RwLockUpgradableReadFuture,
+    RwLockUpgradableReadGuard, RwLockUpgradeFuture, RwLockWriteFuture,
+    RwLockWriteGuard,
+};
 
 mod semaphore;
 
 pub use self::semaphore::{
-    GenericSemaphore, GenericSemaphoreAcquireFuture, GenericSemaphoreReleaser,
-    LocalSemaphore, LocalSemaphoreAcquireFuture, LocalSemaphoreReleaser,
+    AcquireError, GenericSemaphore, GenericSemaphoreAcquireFuture,
+    GenericSemaphoreReleaser, LocalSemaphore, LocalSemaphoreAcquireFuture,
+    LocalSemaphoreReleaser,
 };
 
 #[cfg(feature = "alloc")]
@@ -41,3 +179,22 @@ pub use self::semaphore::{
     Semaphore, SemaphoreAcquireFuture, SemaphoreReleaser, SharedSemaphore,
     SharedSemaphoreAcquireFuture, SharedSemaphoreReleaser,
 };
+
+mod wait_group;
+
+pub use self::wait_group::{
+    GenericWaitGroup, GenericWaitGroupWaitFuture, LocalWaitGroup,
+    LocalWaitGroupWaitFuture,
+};
+
+#[cfg(feature = "alloc")]
+pub use self::wait_group::{
+    GenericSharedWaitGroup, GenericSharedWaitGroupGuard,
+    GenericSharedWaitGroupWaitFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::wait_group::{
+    SharedWaitGroup, SharedWaitGroupGuard, SharedWaitGroupWaitFuture,
+    WaitGroup, WaitGroupWaitFuture,
+};
@@ -1,13 +1,15 @@
 //! An asynchronously awaitable mutex for synchronization between concurrently
 //! executing futures.
 
+#[cfg(feature = "deadlock_detection")]
+use super::deadlock_detection::{self, Deadlock, LockId};
+use super::priority_wait_queue::{PriorityWaitQueue, WaitNode};
 use crate::{
-    intrusive_double_linked_list::{LinkedList, ListNode},
-    utils::update_waker_ref,
-    NoopLock,
+    intrusive_double_linked_list::LinkedList, utils::update_waker_ref, NoopLock,
 };
 use core::{
     cell::UnsafeCell,
+    marker::PhantomData,
     ops::{Deref, DerefMut},
     pin::Pin,
 };
@@ -27,13 +29,17 @@ enum PollState {
     /// The task had previously waited on the mutex, but was notified
     /// that the mutex was released in the meantime.
     Notified,
+    /// The mutex was handed over to the task by its previous owner without
+    /// getting unlocked in between. The task is no longer part of the wait
+    /// queue.
+    HandedOver,
     /// The task had been polled to completion.
     Done,
 }
 
 /// Tracks the MutexLockFuture waiting state.
 /// Access to this struct is synchronized through the mutex in the Event.
-struct WaitQueueEntry {
+pub(super) struct WaitQueueEntry {
     /// The task handle of the waiting task
     task: Option<Waker>,
     /// Current polling state
@@ -42,7 +48,7 @@ struct WaitQueueEntry {
 
 impl WaitQueueEntry {
     /// Creates a new WaitQueueEntry
-    fn new() -> WaitQueueEntry {
+    pub(super) fn new() -> WaitQueueEntry {
         WaitQueueEntry {
             task: None,
             state: PollState::New,
@@ -51,26 +57,73 @@ impl WaitQueueEntry {
 }
 
 /// Internal state of the `Mutex`
-struct MutexState {
+pub(super) struct MutexState {
     is_fair: bool,
     is_locked: bool,
     waiters: LinkedList<WaitQueueEntry>,
+    /// Waiters which had been registered with a priority. Those are served
+    /// before all waiters in `waiters`.
+    priority_waiters: PriorityWaitQueue<WaitQueueEntry>,
+    /// The identity of the mutex inside the deadlock detection, which is
+    /// assigned once it is required.
+    #[cfg(feature = "deadlock_detection")]
+    id: Option<LockId>,
+    /// A problem which had been detected by the last call to `try_lock`, and
+    /// which must get reported once the state is no longer locked.
+    #[cfg(feature = "deadlock_detection")]
+    deadlock: Option<Deadlock>,
+    /// The identity under which the mutex had been acquired by its current
+    /// holder.
+    #[cfg(feature = "deadlock_detection")]
+    holder: usize,
 }
 
 impl MutexState {
-    fn new(is_fair: bool) -> Self {
+    pub(super) const fn new(is_fair: bool) -> Self {
         MutexState {
             is_fair,
             is_locked: false,
             waiters: LinkedList::new(),
+            priority_waiters: PriorityWaitQueue::new(),
+            #[cfg(feature = "deadlock_detection")]
+            id: None,
+            #[cfg(feature = "deadlock_detection")]
+            deadlock: None,
+            #[cfg(feature = "deadlock_detection")]
+            holder: 0,
         }
     }
 
     /// Returns the `Waker` associated with the up the last waiter
     ///
     /// If the Mutex is not fair, removes the associated wait node also from
-    /// the wait queue
-    fn return_last_waiter(&mut self) -> Option<Waker> {
+    /// the wait queue.
+    ///
+    /// If a waiter with a priority exists and the mutex is not locked, the
+    /// mutex is instead handed over to the waiter with the highest priority.
+    ///
+    /// If `force_handover` is set, the mutex gets locked again and is handed
+    /// over to the last waiter, independent of whether the mutex is fair.
+    fn return_last_waiter(&mut self, force_handover: bool) -> Option<Waker> {
+        if !self.is_locked {
+            if let Some(waiter) = self.priority_waiters.remove_first() {
+                // The mutex stays locked, and is owned by the waiter from now on
+                self.is_locked = true;
+                waiter.state = PollState::HandedOver;
+                return waiter.task.take();
+            }
+        }
+
+        if force_handover {
+            if let Some(last_waiter) = self.waiters.remove_last() {
+                // The mutex stays locked, and is owned by the waiter from now on
+                self.is_locked = true;
+                last_waiter.state = PollState::HandedOver;
+                return last_waiter.task.take();
+            }
+            return None;
+        }
+
         let last_waiter = if self.is_fair {
             self.waiters.peek_last()
         } else {
@@ -91,7 +144,7 @@ impl MutexState {
         None
     }
 
-    fn is_locked(&self) -> bool {
+    pub(super) fn is_locked(&self) -> bool {
         self.is_locked
     }
 
@@ -100,26 +153,100 @@ impl MutexState {
     /// This is expected to be only called from the current holder of the mutex.
     /// The method returns the `Waker` which is associated with the task that
     /// needs to get woken due to the unlock.
-    fn unlock(&mut self) -> Option<Waker> {
+    pub(super) fn unlock(&mut self) -> Option<Waker> {
+        self.release(false)
+    }
+
+    /// Unlocks the mutex and hands it over to the oldest waiter, or to the
+    /// waiter with the highest priority if such a waiter exists.
+    ///
+    /// In contrast to `unlock` the mutex never becomes available to other
+    /// tasks in between, even if it isn't fair. If no task is waiting, the
+    /// mutex simply gets unlocked.
+    ///
+    /// This is expected to be only called from the current holder of the mutex.
+    /// The method returns the `Waker` which is associated with the task that
+    /// needs to get woken due to the unlock.
+    fn unlock_fair(&mut self) -> Option<Waker> {
+        self.release(true)
+    }
+
+    /// Hands the mutex over to the waiter which has been waiting the longest,
+    /// as in `unlock_fair`. If no task is waiting, the mutex is kept and
+    /// handed over to `wait_node` instead.
+    ///
+    /// This is expected to be only called from the current holder of the mutex.
+    fn bump(
+        &mut self,
+        wait_node: &mut WaitNode<WaitQueueEntry>,
+    ) -> Option<Waker> {
+        if self.has_waiters() {
+            return self.unlock_fair();
+        }
+        // The acquisition gets recorded for `wait_node` once it gets polled
+        #[cfg(feature = "deadlock_detection")]
+        deadlock_detection::lock_released(self.lock_id(), self.holder);
+        wait_node.state = PollState::HandedOver;
+        None
+    }
+
+    /// Unlocks the mutex and returns the `Waker` of the waiter which needs to
+    /// get woken. See `return_last_waiter` for `force_handover`.
+    fn release(&mut self, force_handover: bool) -> Option<Waker> {
         if self.is_locked {
             self.is_locked = false;
+            #[cfg(feature = "deadlock_detection")]
+            deadlock_detection::lock_released(self.lock_id(), self.holder);
             // TODO: Does this require a memory barrier for the actual data,
             // or is this covered by unlocking the mutex which protects the data?
             // Wakeup the last waiter
-            self.return_last_waiter()
+            self.return_last_waiter(force_handover)
         } else {
             None
         }
     }
 
+    /// Returns the identity of the mutex inside the deadlock detection
+    #[cfg(feature = "deadlock_detection")]
+    pub(super) fn lock_id(&mut self) -> LockId {
+        *self.id.get_or_insert_with(LockId::next)
+    }
+
+    /// Returns whether any task is waiting for the mutex
+    pub(super) fn has_waiters(&self) -> bool {
+        !self.waiters.is_empty() || !self.priority_waiters.is_empty()
+    }
+
     /// Tries to lock the mutex synchronously.
     ///
     /// Returns true if the lock obtained and false otherwise.
-    fn try_lock_sync(&mut self) -> bool {
+    pub(super) fn try_lock_sync(&mut self) -> bool {
+        let is_locked = self.acquire_sync();
+
+        #[cfg(feature = "deadlock_detection")]
+        {
+            if is_locked {
+                // No lock future is involved. The acquisition is therefore
+                // identified by the address of the mutex.
+                self.holder = self as *const Self as usize;
+                deadlock_detection::lock_acquired(
+                    self.lock_id(),
+                    self.holder,
+                    None,
+                );
+            }
+        }
+
+        is_locked
+    }
+
+    /// Implements the state transitions for `try_lock_sync`
+    fn acquire_sync(&mut self) -> bool {
         // The lock can only be obtained synchronously if
         // - it is not locked
-        // - the Semaphore is either not fair, or there are no waiters
-        // - required_permits == 0
+        // - the Mutex is either not fair, or there are no waiters
+        // Waiters with a priority never wait on an unlocked Mutex, since the
+        // mutex gets handed over to them directly.
         if !self.is_locked && (!self.is_fair || self.waiters.is_empty()) {
             self.is_locked = true;
             true
@@ -134,23 +261,60 @@ impl MutexState {
     /// queue at the Mutex, and will be signalled once ready.
     /// This function is only safe as long as the `wait_node`s address is guaranteed
     /// to be stable until it gets removed from the queue.
-    unsafe fn try_lock(
+    pub(super) unsafe fn try_lock(
+        &mut self,
+        wait_node: &mut WaitNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        let poll_res = self.try_lock_inner(wait_node, cx);
+
+        #[cfg(feature = "deadlock_detection")]
+        {
+            let lock = self.lock_id();
+            let waiter = wait_node as *const _ as usize;
+            self.deadlock = match poll_res {
+                Poll::Ready(()) => {
+                    self.holder = waiter;
+                    deadlock_detection::lock_acquired(
+                        lock,
+                        waiter,
+                        Some(cx.waker()),
+                    )
+                }
+                Poll::Pending => {
+                    deadlock_detection::wait_started(lock, waiter, cx.waker())
+                }
+            };
+        }
+
+        poll_res
+    }
+
+    /// Returns the problem which had been detected by the last call to
+    /// `try_lock`.
+    #[cfg(feature = "deadlock_detection")]
+    pub(super) fn take_deadlock(&mut self) -> Option<Deadlock> {
+        self.deadlock.take()
+    }
+
+    /// Implements the state transitions for `try_lock`
+    unsafe fn try_lock_inner(
         &mut self,
-        wait_node: &mut ListNode<WaitQueueEntry>,
+        wait_node: &mut WaitNode<WaitQueueEntry>,
         cx: &mut Context<'_>,
     ) -> Poll<()> {
         match wait_node.state {
             PollState::New => {
                 // The fast path - the Mutex isn't locked by anyone else.
                 // If the mutex is fair, noone must be in the wait list before us.
-                if self.try_lock_sync() {
+                if self.acquire_sync() {
                     wait_node.state = PollState::Done;
                     Poll::Ready(())
                 } else {
                     // Add the task to the wait queue
                     wait_node.task = Some(cx.waker().clone());
                     wait_node.state = PollState::Waiting;
-                    self.waiters.add_front(wait_node);
+                    self.add_waiter(wait_node);
                     Poll::Pending
                 }
             }
@@ -205,10 +369,16 @@ impl MutexState {
                     // Add to queue
                     wait_node.task = Some(cx.waker().clone());
                     wait_node.state = PollState::Waiting;
-                    self.waiters.add_front(wait_node);
+                    self.add_waiter(wait_node);
                     Poll::Pending
                 }
             }
+            PollState::HandedOver => {
+                // The previous owner passed the mutex to us
+                debug_assert!(self.is_locked);
+                wait_node.state = PollState::Done;
+                Poll::Ready(())
+            }
             PollState::Done => {
                 // The future had been polled to completion before
                 panic!("polled Mutex after completion");
@@ -216,16 +386,32 @@ impl MutexState {
         }
     }
 
+    /// Adds a waiter to the wait queue which is determined by its node type.
+    ///
+    /// This function is only safe as long as the `wait_node`s address is
+    /// guaranteed to be stable until it gets removed from the queue.
+    unsafe fn add_waiter(&mut self, wait_node: &mut WaitNode<WaitQueueEntry>) {
+        match wait_node {
+            WaitNode::Fifo(node) => self.waiters.add_front(node),
+            WaitNode::Priority(node) => self.priority_waiters.insert(node),
+        }
+    }
+
     /// Tries to remove a waiter from the wait queue, and panics if the
     /// waiter is no longer valid.
     unsafe fn force_remove_waiter(
         &mut self,
-        wait_node: &mut ListNode<WaitQueueEntry>,
+        wait_node: &mut WaitNode<WaitQueueEntry>,
     ) {
-        if !self.waiters.remove(wait_node) {
-            // Panic if the address isn't found. This can only happen if the contract was
-            // violated, e.g. the WaitQueueEntry got moved after the initial poll.
-            panic!("Future could not be removed from wait queue");
+        match wait_node {
+            WaitNode::Fifo(node) => {
+                if !self.waiters.remove(node) {
+                    // Panic if the address isn't found. This can only happen if the contract was
+                    // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                    panic!("Future could not be removed from wait queue");
+                }
+            }
+            WaitNode::Priority(node) => self.priority_waiters.remove(node),
         }
     }
 
@@ -237,13 +423,24 @@ impl MutexState {
     ///
     /// Returns the `Waker` of another task which might get ready to run due to
     /// this.
-    fn remove_waiter(
+    pub(super) fn remove_waiter(
         &mut self,
-        wait_node: &mut ListNode<WaitQueueEntry>,
+        wait_node: &mut WaitNode<WaitQueueEntry>,
     ) -> Option<Waker> {
         // MutexLockFuture only needs to get removed if it had been added to
         // the wait queue of the Mutex. This has happened in the PollState::Waiting case.
         // If the current waiter was notified, another waiter must get notified now.
+        #[cfg(feature = "deadlock_detection")]
+        {
+            if wait_node.state != PollState::New
+                && wait_node.state != PollState::Done
+            {
+                deadlock_detection::wait_finished(
+                    wait_node as *const _ as usize,
+                );
+            }
+        }
+
         match wait_node.state {
             PollState::Notified => {
                 if self.is_fair {
@@ -256,7 +453,7 @@ impl MutexState {
                 wait_node.state = PollState::Done;
                 // Since the task was notified but did not lock the Mutex,
                 // another task gets the chance to run.
-                self.return_last_waiter()
+                self.return_last_waiter(false)
             }
             PollState::Waiting => {
                 // Remove the WaitQueueEntry from the linked list
@@ -266,17 +463,31 @@ impl MutexState {
                 wait_node.state = PollState::Done;
                 None
             }
+            PollState::HandedOver => {
+                // The task owns the mutex, but is no longer interested in it.
+                wait_node.state = PollState::Done;
+                self.unlock()
+            }
             PollState::New | PollState::Done => None,
         }
     }
 }
 
+#[cfg(feature = "deadlock_detection")]
+impl Drop for MutexState {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            deadlock_detection::lock_destroyed(id);
+        }
+    }
+}
+
 /// An RAII guard returned by the `lock` and `try_lock` methods.
 /// When this structure is dropped (falls out of scope), the lock will be
 /// unlocked.
 pub struct GenericMutexGuard<'a, MutexType: RawMutex, T: 'a> {
     /// The Mutex which is associated with this Guard
-    mutex: &'a GenericMutex<MutexType, T>,
+    pub(crate) mutex: &'a GenericMutex<MutexType, T>,
 }
 
 impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
@@ -317,13 +528,341 @@ unsafe impl<MutexType: RawMutex, T: Sync> Sync
 {
 }
 
+impl<'a, MutexType: RawMutex, T> GenericMutexGuard<'a, MutexType, T> {
+    /// Makes a new [`GenericMappedMutexGuard`] for a component of the locked
+    /// data.
+    ///
+    /// The mutex stays locked until the returned guard gets dropped.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `GenericMutexGuard::map(...)`. A method would interfere with methods of
+    /// the same name on the contents of the locked data.
+    pub fn map<U: ?Sized, F>(
+        this: Self,
+        f: F,
+    ) -> GenericMappedMutexGuard<'a, MutexType, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let mutex = this.mutex;
+        let value = f(unsafe { &mut *mutex.value.get() }) as *mut U;
+        // The mutex is now unlocked by the mapped guard
+        core::mem::forget(this);
+        GenericMappedMutexGuard {
+            state: &mutex.state,
+            value,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Attempts to make a new [`GenericMappedMutexGuard`] for a component of
+    /// the locked data.
+    ///
+    /// If the closure returns `None`, the original guard is returned in the
+    /// error variant and the mutex stays locked.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `GenericMutexGuard::try_map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the locked data.
+    pub fn try_map<U: ?Sized, F>(
+        this: Self,
+        f: F,
+    ) -> Result<GenericMappedMutexGuard<'a, MutexType, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        let mutex = this.mutex;
+        let value = match f(unsafe { &mut *mutex.value.get() }) {
+            Some(value) => value as *mut U,
+            None => return Err(this),
+        };
+        // The mutex is now unlocked by the mapped guard
+        core::mem::forget(this);
+        Ok(GenericMappedMutexGuard {
+            state: &mutex.state,
+            value,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Unlocks the mutex and hands it over to the task which has been waiting
+    /// the longest on it.
+    ///
+    /// Dropping a guard allows any task to acquire an unfair mutex. Unlocking
+    /// it via this method guarantees that the oldest waiter gets the mutex
+    /// instead, independent of the fairness setting.
+    pub fn unlock_fair(this: Self) {
+        let mutex = this.mutex;
+        // The mutex is released below
+        core::mem::forget(this);
+        let waker = { mutex.state.lock().unlock_fair() };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Temporarily yields the mutex to a waiting task.
+    ///
+    /// If another task is waiting on the mutex, the mutex is handed over to
+    /// the task which has been waiting the longest, as in
+    /// [`GenericMutexGuard::unlock_fair`]. The returned future resolves once
+    /// the mutex has been reacquired. If no task is waiting, the mutex is not
+    /// released and the returned future resolves immediately.
+    pub fn bump(this: Self) -> GenericMutexLockFuture<'a, MutexType, T> {
+        let mutex = this.mutex;
+        // The mutex is either released or kept by the returned future
+        core::mem::forget(this);
+
+        let mut wait_node = WaitNode::fifo(WaitQueueEntry::new());
+        let waker = {
+            let mut mutex_state = mutex.state.lock();
+            // The returned future takes over the ownership if noone waits
+            mutex_state.bump(&mut wait_node)
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        GenericMutexLockFuture::<MutexType, T> {
+            mutex: Some(mutex),
+            wait_node,
+        }
+    }
+
+    /// Unlocks the mutex while the future which is returned by `f` is
+    /// running, and reacquires it afterwards.
+    ///
+    /// The mutex is released immediately, before `f` is called. The returned
+    /// future resolves to the reacquired guard and the output of the future
+    /// which was returned by `f`.
+    ///
+    /// The mutex is only requested again after the future which was returned
+    /// by `f` has completed. The task therefore does not keep its place in
+    /// the wait queue, and waits behind all tasks which started to wait for
+    /// the mutex in the meantime. This avoids blocking other tasks from
+    /// acquiring the mutex while `f` is running, but means that reacquiring
+    /// the mutex is not prioritized.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `GenericMutexGuard::unlocked(...)`. A method would interfere with
+    /// methods of the same name on the contents of the locked data.
+    pub fn unlocked<F, Fut>(
+        this: Self,
+        f: F,
+    ) -> GenericMutexUnlockedFuture<'a, MutexType, T, Fut>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future,
+    {
+        let mutex = this.mutex;
+        drop(this);
+
+        GenericMutexUnlockedFuture {
+            future: Some(f()),
+            output: None,
+            lock_future: mutex.lock(),
+        }
+    }
+}
+
+/// A future which runs another future while the mutex is unlocked, and
+/// reacquires the mutex afterwards.
+///
+/// It is returned by [`GenericMutexGuard::unlocked`].
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericMutexUnlockedFuture<'a, MutexType: RawMutex, T: 'a, Fut>
+where
+    Fut: Future,
+{
+    /// The future which runs while the mutex is unlocked
+    future: Option<Fut>,
+    /// The output of `future` once it has completed
+    output: Option<Fut::Output>,
+    /// The future which reacquires the mutex
+    lock_future: GenericMutexLockFuture<'a, MutexType, T>,
+}
+
+impl<'a, MutexType: RawMutex, T, Fut> core::fmt::Debug
+    for GenericMutexUnlockedFuture<'a, MutexType, T, Fut>
+where
+    Fut: Future,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericMutexUnlockedFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, Fut> Future
+    for GenericMutexUnlockedFuture<'a, MutexType, T, Fut>
+where
+    Fut: Future,
+{
+    type Output = (GenericMutexGuard<'a, MutexType, T>, Fut::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The futures inside GenericMutexUnlockedFuture are never
+        // moved until they get dropped.
+        let mut_self: &mut GenericMutexUnlockedFuture<MutexType, T, Fut> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        if let Some(future) = mut_self.future.as_mut() {
+            let future = unsafe { Pin::new_unchecked(future) };
+            match future.poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(output) => {
+                    // Dropping the future in place is allowed
+                    mut_self.future = None;
+                    mut_self.output = Some(output);
+                }
+            }
+        }
+
+        let lock_future =
+            unsafe { Pin::new_unchecked(&mut mut_self.lock_future) };
+        match lock_future.poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(guard) => {
+                let output = mut_self.output.take().expect(
+                    "polled GenericMutexUnlockedFuture after completion",
+                );
+                Poll::Ready((guard, output))
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, Fut> FusedFuture
+    for GenericMutexUnlockedFuture<'a, MutexType, T, Fut>
+where
+    Fut: Future,
+{
+    fn is_terminated(&self) -> bool {
+        self.lock_future.is_terminated()
+    }
+}
+
+/// An RAII guard returned by [`GenericMutexGuard::map`] and
+/// [`GenericMutexGuard::try_map`], which only provides access to a component
+/// of the data which is protected by the mutex.
+/// When this structure is dropped (falls out of scope), the lock will be
+/// unlocked.
+pub struct GenericMappedMutexGuard<'a, MutexType: RawMutex, T: ?Sized + 'a> {
+    /// The state of the Mutex which is associated with this Guard
+    state: &'a LockApiMutex<MutexType, MutexState>,
+    /// The component of the locked data which is accessible through the Guard
+    value: *mut T,
+    _phantom: PhantomData<&'a mut T>,
+}
+
+impl<MutexType: RawMutex, T: ?Sized + core::fmt::Debug> core::fmt::Debug
+    for GenericMappedMutexGuard<'_, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericMappedMutexGuard").finish()
+    }
+}
+
+impl<MutexType: RawMutex, T: ?Sized> Drop
+    for GenericMappedMutexGuard<'_, MutexType, T>
+{
+    fn drop(&mut self) {
+        // Release the mutex
+        let waker = { self.state.lock().unlock() };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<MutexType: RawMutex, T: ?Sized> Deref
+    for GenericMappedMutexGuard<'_, MutexType, T>
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.value }
+    }
+}
+
+impl<MutexType: RawMutex, T: ?Sized> DerefMut
+    for GenericMappedMutexGuard<'_, MutexType, T>
+{
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.value }
+    }
+}
+
+// Safety: GenericMappedMutexGuard can be sent between threads under the same
+// conditions as the GenericMutexGuard it was created from.
+unsafe impl<MutexType: RawMutex + Sync, T: ?Sized + Send> Send
+    for GenericMappedMutexGuard<'_, MutexType, T>
+{
+}
+
+// Safety: GenericMappedMutexGuard may only be used across threads if the
+// underlying type is Sync.
+unsafe impl<MutexType: RawMutex, T: ?Sized + Sync> Sync
+    for GenericMappedMutexGuard<'_, MutexType, T>
+{
+}
+
+impl<'a, MutexType: RawMutex, T: ?Sized>
+    GenericMappedMutexGuard<'a, MutexType, T>
+{
+    /// Makes a new [`GenericMappedMutexGuard`] for a component of the locked
+    /// data.
+    ///
+    /// See [`GenericMutexGuard::map`] for more information.
+    pub fn map<U: ?Sized, F>(
+        this: Self,
+        f: F,
+    ) -> GenericMappedMutexGuard<'a, MutexType, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let state = this.state;
+        let value = f(unsafe { &mut *this.value }) as *mut U;
+        // The mutex is now unlocked by the new mapped guard
+        core::mem::forget(this);
+        GenericMappedMutexGuard {
+            state,
+            value,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Attempts to make a new [`GenericMappedMutexGuard`] for a component of
+    /// the locked data.
+    ///
+    /// See [`GenericMutexGuard::try_map`] for more information.
+    pub fn try_map<U: ?Sized, F>(
+        this: Self,
+        f: F,
+    ) -> Result<GenericMappedMutexGuard<'a, MutexType, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        let state = this.state;
+        let value = match f(unsafe { &mut *this.value }) {
+            Some(value) => value as *mut U,
+            None => return Err(this),
+        };
+        // The mutex is now unlocked by the new mapped guard
+        core::mem::forget(this);
+        Ok(GenericMappedMutexGuard {
+            state,
+            value,
+            _phantom: PhantomData,
+        })
+    }
+}
+
 /// A future which resolves when the target mutex has been successfully acquired.
 #[must_use = "futures do nothing unless polled"]
 pub struct GenericMutexLockFuture<'a, MutexType: RawMutex, T: 'a> {
     /// The Mutex which should get locked trough this Future
     mutex: Option<&'a GenericMutex<MutexType, T>>,
     /// Node for waiting at the mutex
-    wait_node: ListNode<WaitQueueEntry>,
+    wait_node: WaitNode<WaitQueueEntry>,
 }
 
 // Safety: Futures can be sent between threads as long as the underlying
@@ -361,15 +900,29 @@ impl<'a, MutexType: RawMutex, T> Future
 
         let poll_res =
             unsafe { mutex_state.try_lock(&mut mut_self.wait_node, cx) };
+        #[cfg(feature = "deadlock_detection")]
+        let deadlock = mutex_state.take_deadlock();
+        drop(mutex_state);
 
-        match poll_res {
+        let res = match poll_res {
             Poll::Pending => Poll::Pending,
             Poll::Ready(()) => {
                 // The mutex was acquired
                 mut_self.mutex = None;
                 Poll::Ready(GenericMutexGuard::<'a, MutexType, T> { mutex })
             }
+        };
+
+        // The handler is invoked after the future is in a consistent state,
+        // since it might panic.
+        #[cfg(feature = "deadlock_detection")]
+        {
+            if let Some(deadlock) = deadlock {
+                deadlock_detection::report(deadlock);
+            }
         }
+
+        res
     }
 }
 
@@ -438,10 +991,13 @@ impl<MutexType: RawMutex, T> GenericMutex<MutexType, T> {
     /// Other waiters must wait until either this locking attempt completes, and
     /// the `Mutex` gets unlocked again, or until the `MutexLockFuture` which
     /// tried to gain the lock is dropped.
-    pub fn new(value: T, is_fair: bool) -> GenericMutex<MutexType, T> {
+    pub const fn new(value: T, is_fair: bool) -> GenericMutex<MutexType, T> {
         GenericMutex::<MutexType, T> {
             value: UnsafeCell::new(value),
-            state: LockApiMutex::new(MutexState::new(is_fair)),
+            state: LockApiMutex::const_new(
+                MutexType::INIT,
+                MutexState::new(is_fair),
+            ),
         }
     }
 
@@ -452,7 +1008,29 @@ impl<MutexType: RawMutex, T> GenericMutex<MutexType, T> {
     pub fn lock(&self) -> GenericMutexLockFuture<'_, MutexType, T> {
         GenericMutexLockFuture::<MutexType, T> {
             mutex: Some(&self),
-            wait_node: ListNode::new(WaitQueueEntry::new()),
+            wait_node: WaitNode::fifo(WaitQueueEntry::new()),
+        }
+    }
+
+    /// Acquire the mutex asynchronously with the given priority.
+    ///
+    /// This method returns a future that will resolve once the mutex has been
+    /// successfully acquired.
+    ///
+    /// Waiters with a priority are served before all waiters which had been
+    /// created through [`GenericMutex::lock`], independent of the fairness
+    /// setting of the mutex. Among each other they are served in order of
+    /// their `priority`, where higher values take precedence. Waiters with
+    /// equal priorities are served in FIFO order.
+    /// Once the mutex gets unlocked it is handed over directly to the
+    /// waiter with the highest priority.
+    pub fn lock_with_priority(
+        &self,
+        priority: usize,
+    ) -> GenericMutexLockFuture<'_, MutexType, T> {
+        GenericMutexLockFuture::<MutexType, T> {
+            mutex: Some(self),
+            wait_node: WaitNode::priority(priority, WaitQueueEntry::new()),
         }
     }
 
@@ -474,6 +1052,13 @@ impl<MutexType: RawMutex, T> GenericMutex<MutexType, T> {
     pub fn is_locked(&self) -> bool {
         self.state.lock().is_locked()
     }
+
+    /// Returns the identity of the mutex inside
+    /// [`Deadlock`](super::deadlock_detection::Deadlock) reports.
+    #[cfg(feature = "deadlock_detection")]
+    pub fn lock_id(&self) -> LockId {
+        self.state.lock().lock_id()
+    }
 }
 
 // Export a non thread-safe version using NoopLock
@@ -482,8 +1067,14 @@ impl<MutexType: RawMutex, T> GenericMutex<MutexType, T> {
 pub type LocalMutex<T> = GenericMutex<NoopLock, T>;
 /// A [`GenericMutexGuard`] for [`LocalMutex`].
 pub type LocalMutexGuard<'a, T> = GenericMutexGuard<'a, NoopLock, T>;
+/// A [`GenericMappedMutexGuard`] for [`LocalMutex`].
+pub type LocalMappedMutexGuard<'a, T> =
+    GenericMappedMutexGuard<'a, NoopLock, T>;
 /// A [`GenericMutexLockFuture`] for [`LocalMutex`].
 pub type LocalMutexLockFuture<'a, T> = GenericMutexLockFuture<'a, NoopLock, T>;
+/// A [`GenericMutexUnlockedFuture`] for [`LocalMutex`].
+pub type LocalMutexUnlockedFuture<'a, T, Fut> =
+    GenericMutexUnlockedFuture<'a, NoopLock, T, Fut>;
 
 #[cfg(feature = "std")]
 mod if_std {
@@ -496,10 +1087,615 @@ mod if_std {
     /// A [`GenericMutexGuard`] for [`Mutex`].
     pub type MutexGuard<'a, T> =
         GenericMutexGuard<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericMappedMutexGuard`] for [`Mutex`].
+    pub type MappedMutexGuard<'a, T> =
+        GenericMappedMutexGuard<'a, parking_lot::RawMutex, T>;
     /// A [`GenericMutexLockFuture`] for [`Mutex`].
     pub type MutexLockFuture<'a, T> =
         GenericMutexLockFuture<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericMutexUnlockedFuture`] for [`Mutex`].
+    pub type MutexUnlockedFuture<'a, T, Fut> =
+        GenericMutexUnlockedFuture<'a, parking_lot::RawMutex, T, Fut>;
 }
 
 #[cfg(feature = "std")]
 pub use self::if_std::*;
+
+#[cfg(feature = "alloc")]
+mod if_alloc {
+    use super::*;
+
+    use alloc::sync::Arc;
+
+    /// An RAII guard returned by the `lock` and `try_lock` methods of
+    /// [`GenericSharedMutex`].
+    ///
+    /// The guard owns a reference to the mutex and thereby has no associated
+    /// lifetime. When this structure is dropped (falls out of scope), the lock
+    /// will be unlocked.
+    pub struct GenericSharedMutexGuard<MutexType: RawMutex, T> {
+        /// The Mutex which is associated with this Guard
+        mutex: GenericSharedMutex<MutexType, T>,
+    }
+
+    impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+        for GenericSharedMutexGuard<MutexType, T>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("GenericSharedMutexGuard").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex, T> Drop for GenericSharedMutexGuard<MutexType, T> {
+        fn drop(&mut self) {
+            // Release the mutex
+            let waker = { self.mutex.inner.state.lock().unlock() };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    impl<MutexType: RawMutex, T> Deref for GenericSharedMutexGuard<MutexType, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            unsafe { &*self.mutex.inner.value.get() }
+        }
+    }
+
+    impl<MutexType: RawMutex, T> DerefMut
+        for GenericSharedMutexGuard<MutexType, T>
+    {
+        fn deref_mut(&mut self) -> &mut T {
+            unsafe { &mut *self.mutex.inner.value.get() }
+        }
+    }
+
+    // Safety: GenericSharedMutexGuard may only be used across threads if the
+    // underlying type is Sync.
+    unsafe impl<MutexType: RawMutex, T: Sync> Sync
+        for GenericSharedMutexGuard<MutexType, T>
+    {
+    }
+
+    impl<MutexType: RawMutex, T> GenericSharedMutexGuard<MutexType, T> {
+        /// Makes a new [`GenericSharedMappedMutexGuard`] for a component of
+        /// the locked data.
+        ///
+        /// See [`GenericMutexGuard::map`] for more information.
+        pub fn map<U: ?Sized, F>(
+            this: Self,
+            f: F,
+        ) -> GenericSharedMappedMutexGuard<MutexType, T, U>
+        where
+            F: FnOnce(&mut T) -> &mut U,
+        {
+            let value =
+                f(unsafe { &mut *this.mutex.inner.value.get() }) as *mut U;
+            GenericSharedMappedMutexGuard {
+                mutex: Self::into_mutex(this),
+                value,
+            }
+        }
+
+        /// Attempts to make a new [`GenericSharedMappedMutexGuard`] for a
+        /// component of the locked data.
+        ///
+        /// See [`GenericMutexGuard::try_map`] for more information.
+        pub fn try_map<U: ?Sized, F>(
+            this: Self,
+            f: F,
+        ) -> Result<GenericSharedMappedMutexGuard<MutexType, T, U>, Self>
+        where
+            F: FnOnce(&mut T) -> Option<&mut U>,
+        {
+            let value = match f(unsafe { &mut *this.mutex.inner.value.get() }) {
+                Some(value) => value as *mut U,
+                None => return Err(this),
+            };
+            Ok(GenericSharedMappedMutexGuard {
+                mutex: Self::into_mutex(this),
+                value,
+            })
+        }
+
+        /// Unlocks the mutex and hands it over to the task which has been
+        /// waiting the longest on it.
+        ///
+        /// See [`GenericMutexGuard::unlock_fair`] for more information.
+        pub fn unlock_fair(this: Self) {
+            let mutex = Self::into_mutex(this);
+            let waker = { mutex.inner.state.lock().unlock_fair() };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+
+        /// Temporarily yields the mutex to a waiting task.
+        ///
+        /// See [`GenericMutexGuard::bump`] for more information.
+        pub fn bump(this: Self) -> GenericSharedMutexLockFuture<MutexType, T> {
+            let mutex = Self::into_mutex(this);
+
+            let mut wait_node = WaitNode::fifo(WaitQueueEntry::new());
+            let waker = {
+                let mut mutex_state = mutex.inner.state.lock();
+                // The returned future takes over the ownership if noone waits
+                mutex_state.bump(&mut wait_node)
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+
+            GenericSharedMutexLockFuture::<MutexType, T> {
+                mutex: Some(mutex),
+                wait_node,
+            }
+        }
+
+        /// Unlocks the mutex while the future which is returned by `f` is
+        /// running, and reacquires it afterwards.
+        ///
+        /// See [`GenericMutexGuard::unlocked`] for more information.
+        pub fn unlocked<F, Fut>(
+            this: Self,
+            f: F,
+        ) -> GenericSharedMutexUnlockedFuture<MutexType, T, Fut>
+        where
+            F: FnOnce() -> Fut,
+            Fut: Future,
+        {
+            let lock_future = this.mutex.lock();
+            drop(this);
+
+            GenericSharedMutexUnlockedFuture {
+                future: Some(f()),
+                output: None,
+                lock_future,
+            }
+        }
+
+        /// Extracts the mutex from the guard without unlocking it.
+        fn into_mutex(this: Self) -> GenericSharedMutex<MutexType, T> {
+            let this = core::mem::ManuallyDrop::new(this);
+            // Safety: The guard is never accessed or dropped again
+            unsafe { core::ptr::read(&this.mutex) }
+        }
+    }
+
+    /// A future which runs another future while the mutex is unlocked, and
+    /// reacquires the mutex afterwards.
+    ///
+    /// It is returned by [`GenericSharedMutexGuard::unlocked`].
+    #[must_use = "futures do nothing unless polled"]
+    pub struct GenericSharedMutexUnlockedFuture<MutexType: RawMutex, T, Fut>
+    where
+        Fut: Future,
+    {
+        /// The future which runs while the mutex is unlocked
+        future: Option<Fut>,
+        /// The output of `future` once it has completed
+        output: Option<Fut::Output>,
+        /// The future which reacquires the mutex
+        lock_future: GenericSharedMutexLockFuture<MutexType, T>,
+    }
+
+    impl<MutexType: RawMutex, T, Fut> core::fmt::Debug
+        for GenericSharedMutexUnlockedFuture<MutexType, T, Fut>
+    where
+        Fut: Future,
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("GenericSharedMutexUnlockedFuture").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex, T, Fut> Future
+        for GenericSharedMutexUnlockedFuture<MutexType, T, Fut>
+    where
+        Fut: Future,
+    {
+        type Output = (GenericSharedMutexGuard<MutexType, T>, Fut::Output);
+
+        fn poll(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Self::Output> {
+            // Safety: The futures inside GenericSharedMutexUnlockedFuture are
+            // never moved until they get dropped.
+            let mut_self: &mut GenericSharedMutexUnlockedFuture<
+                MutexType,
+                T,
+                Fut,
+            > = unsafe { Pin::get_unchecked_mut(self) };
+
+            if let Some(future) = mut_self.future.as_mut() {
+                let future = unsafe { Pin::new_unchecked(future) };
+                match future.poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(output) => {
+                        // Dropping the future in place is allowed
+                        mut_self.future = None;
+                        mut_self.output = Some(output);
+                    }
+                }
+            }
+
+            let lock_future =
+                unsafe { Pin::new_unchecked(&mut mut_self.lock_future) };
+            match lock_future.poll(cx) {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(guard) => {
+                    let output = mut_self.output.take().expect(
+                        "polled GenericSharedMutexUnlockedFuture after completion",
+                    );
+                    Poll::Ready((guard, output))
+                }
+            }
+        }
+    }
+
+    impl<MutexType: RawMutex, T, Fut> FusedFuture
+        for GenericSharedMutexUnlockedFuture<MutexType, T, Fut>
+    where
+        Fut: Future,
+    {
+        fn is_terminated(&self) -> bool {
+            self.lock_future.is_terminated()
+        }
+    }
+
+    /// An RAII guard returned by [`GenericSharedMutexGuard::map`] and
+    /// [`GenericSharedMutexGuard::try_map`], which only provides access to a
+    /// component of the data which is protected by the mutex.
+    ///
+    /// The guard owns a reference to the mutex and thereby has no associated
+    /// lifetime. When this structure is dropped (falls out of scope), the lock
+    /// will be unlocked.
+    pub struct GenericSharedMappedMutexGuard<MutexType: RawMutex, T, U: ?Sized> {
+        /// The Mutex which is associated with this Guard
+        mutex: GenericSharedMutex<MutexType, T>,
+        /// The component of the locked data which is accessible through the
+        /// Guard
+        value: *mut U,
+    }
+
+    impl<MutexType: RawMutex, T, U: ?Sized + core::fmt::Debug> core::fmt::Debug
+        for GenericSharedMappedMutexGuard<MutexType, T, U>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("GenericSharedMappedMutexGuard").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex, T, U: ?Sized> Drop
+        for GenericSharedMappedMutexGuard<MutexType, T, U>
+    {
+        fn drop(&mut self) {
+            // Release the mutex
+            let waker = { self.mutex.inner.state.lock().unlock() };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    impl<MutexType: RawMutex, T, U: ?Sized> Deref
+        for GenericSharedMappedMutexGuard<MutexType, T, U>
+    {
+        type Target = U;
+        fn deref(&self) -> &U {
+            unsafe { &*self.value }
+        }
+    }
+
+    impl<MutexType: RawMutex, T, U: ?Sized> DerefMut
+        for GenericSharedMappedMutexGuard<MutexType, T, U>
+    {
+        fn deref_mut(&mut self) -> &mut U {
+            unsafe { &mut *self.value }
+        }
+    }
+
+    // Safety: GenericSharedMappedMutexGuard can be sent between threads under
+    // the same conditions as the GenericSharedMutexGuard it was created from.
+    unsafe impl<MutexType: RawMutex + Send + Sync, T: Send, U: ?Sized + Send>
+        Send for GenericSharedMappedMutexGuard<MutexType, T, U>
+    {
+    }
+
+    // Safety: GenericSharedMappedMutexGuard may only be used across threads if
+    // the underlying type is Sync.
+    unsafe impl<MutexType: RawMutex, T, U: ?Sized + Sync> Sync
+        for GenericSharedMappedMutexGuard<MutexType, T, U>
+    {
+    }
+
+    impl<MutexType: RawMutex, T, U: ?Sized>
+        GenericSharedMappedMutexGuard<MutexType, T, U>
+    {
+        /// Makes a new [`GenericSharedMappedMutexGuard`] for a component of
+        /// the locked data.
+        ///
+        /// See [`GenericMutexGuard::map`] for more information.
+        pub fn map<V: ?Sized, F>(
+            this: Self,
+            f: F,
+        ) -> GenericSharedMappedMutexGuard<MutexType, T, V>
+        where
+            F: FnOnce(&mut U) -> &mut V,
+        {
+            let value = f(unsafe { &mut *this.value }) as *mut V;
+            GenericSharedMappedMutexGuard {
+                mutex: Self::into_mutex(this),
+                value,
+            }
+        }
+
+        /// Attempts to make a new [`GenericSharedMappedMutexGuard`] for a
+        /// component of the locked data.
+        ///
+        /// See [`GenericMutexGuard::try_map`] for more information.
+        pub fn try_map<V: ?Sized, F>(
+            this: Self,
+            f: F,
+        ) -> Result<GenericSharedMappedMutexGuard<MutexType, T, V>, Self>
+        where
+            F: FnOnce(&mut U) -> Option<&mut V>,
+        {
+            let value = match f(unsafe { &mut *this.value }) {
+                Some(value) => value as *mut V,
+                None => return Err(this),
+            };
+            Ok(GenericSharedMappedMutexGuard {
+                mutex: Self::into_mutex(this),
+                value,
+            })
+        }
+
+        /// Extracts the mutex from the guard without unlocking it.
+        fn into_mutex(this: Self) -> GenericSharedMutex<MutexType, T> {
+            let this = core::mem::ManuallyDrop::new(this);
+            // Safety: The guard is never accessed or dropped again
+            unsafe { core::ptr::read(&this.mutex) }
+        }
+    }
+
+    /// A future which resolves when the target mutex has been successfully
+    /// acquired.
+    #[must_use = "futures do nothing unless polled"]
+    pub struct GenericSharedMutexLockFuture<MutexType: RawMutex, T> {
+        /// The Mutex which should get locked trough this Future
+        mutex: Option<GenericSharedMutex<MutexType, T>>,
+        /// Node for waiting at the mutex
+        wait_node: WaitNode<WaitQueueEntry>,
+    }
+
+    // Safety: Futures can be sent between threads as long as the underlying
+    // mutex is thread-safe (Sync), which allows to poll/register/unregister from
+    // a different thread.
+    unsafe impl<MutexType: RawMutex + Sync, T: Send> Send
+        for GenericSharedMutexLockFuture<MutexType, T>
+    {
+    }
+
+    impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+        for GenericSharedMutexLockFuture<MutexType, T>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("GenericSharedMutexLockFuture").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex, T> Future
+        for GenericSharedMutexLockFuture<MutexType, T>
+    {
+        type Output = GenericSharedMutexGuard<MutexType, T>;
+
+        fn poll(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Self::Output> {
+            // Safety: The next operations are safe, because Pin promises us that
+            // the address of the wait queue entry inside
+            // GenericSharedMutexLockFuture is stable,
+            // and we don't move any fields inside the future until it gets dropped.
+            let mut_self: &mut GenericSharedMutexLockFuture<MutexType, T> =
+                unsafe { Pin::get_unchecked_mut(self) };
+
+            let mutex = mut_self
+                .mutex
+                .take()
+                .expect("polled GenericSharedMutexLockFuture after completion");
+
+            let mut mutex_state = mutex.inner.state.lock();
+            let poll_res =
+                unsafe { mutex_state.try_lock(&mut mut_self.wait_node, cx) };
+            #[cfg(feature = "deadlock_detection")]
+            let deadlock = mutex_state.take_deadlock();
+            drop(mutex_state);
+
+            let res = match poll_res {
+                Poll::Pending => {
+                    mut_self.mutex.replace(mutex);
+                    Poll::Pending
+                }
+                Poll::Ready(()) => {
+                    // The mutex was acquired
+                    Poll::Ready(GenericSharedMutexGuard::<MutexType, T> {
+                        mutex,
+                    })
+                }
+            };
+
+            // The handler is invoked after the future is in a consistent
+            // state, since it might panic.
+            #[cfg(feature = "deadlock_detection")]
+            {
+                if let Some(deadlock) = deadlock {
+                    deadlock_detection::report(deadlock);
+                }
+            }
+
+            res
+        }
+    }
+
+    impl<MutexType: RawMutex, T> FusedFuture
+        for GenericSharedMutexLockFuture<MutexType, T>
+    {
+        fn is_terminated(&self) -> bool {
+            self.mutex.is_none()
+        }
+    }
+
+    impl<MutexType: RawMutex, T> Drop
+        for GenericSharedMutexLockFuture<MutexType, T>
+    {
+        fn drop(&mut self) {
+            // If this GenericSharedMutexLockFuture has been polled and it was
+            // added to the wait queue at the mutex, it must be removed before
+            // dropping. Otherwise the mutex would access invalid memory.
+            let waker = if let Some(mutex) = self.mutex.take() {
+                let mut mutex_state = mutex.inner.state.lock();
+                mutex_state.remove_waiter(&mut self.wait_node)
+            } else {
+                None
+            };
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    /// A futures-aware shared mutex.
+    ///
+    /// The mutex can be cloned, which creates another handle to the same
+    /// mutex. The futures and guards which are produced by the mutex do not
+    /// have an associated lifetime.
+    pub struct GenericSharedMutex<MutexType: RawMutex, T> {
+        inner: Arc<GenericMutex<MutexType, T>>,
+    }
+
+    impl<MutexType: RawMutex, T> Clone for GenericSharedMutex<MutexType, T> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+
+    impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+        for GenericSharedMutex<MutexType, T>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("SharedMutex")
+                .field("is_locked", &self.is_locked())
+                .finish()
+        }
+    }
+
+    impl<MutexType: RawMutex, T> GenericSharedMutex<MutexType, T> {
+        /// Creates a new futures-aware shared mutex.
+        ///
+        /// See [`GenericMutex::new`] for the meaning of `is_fair`.
+        pub fn new(
+            value: T,
+            is_fair: bool,
+        ) -> GenericSharedMutex<MutexType, T> {
+            GenericSharedMutex::<MutexType, T> {
+                inner: Arc::new(GenericMutex::new(value, is_fair)),
+            }
+        }
+
+        /// Acquire the mutex asynchronously.
+        ///
+        /// This method returns a future that will resolve once the mutex has been
+        /// successfully acquired.
+        /// The Future will resolve to a [`GenericSharedMutexGuard`], which
+        /// will unlock the mutex when dropped.
+        pub fn lock(&self) -> GenericSharedMutexLockFuture<MutexType, T> {
+            GenericSharedMutexLockFuture::<MutexType, T> {
+                mutex: Some(self.clone()),
+                wait_node: WaitNode::fifo(WaitQueueEntry::new()),
+            }
+        }
+
+        /// Acquire the mutex asynchronously with the given priority.
+        ///
+        /// This method returns a future that will resolve once the mutex has been
+        /// successfully acquired.
+        /// The Future will resolve to a [`GenericSharedMutexGuard`], which
+        /// will unlock the mutex when dropped.
+        ///
+        /// The ordering of waiters follows the same rules as for
+        /// [`GenericMutex::lock_with_priority`].
+        pub fn lock_with_priority(
+            &self,
+            priority: usize,
+        ) -> GenericSharedMutexLockFuture<MutexType, T> {
+            GenericSharedMutexLockFuture::<MutexType, T> {
+                mutex: Some(self.clone()),
+                wait_node: WaitNode::priority(priority, WaitQueueEntry::new()),
+            }
+        }
+
+        /// Tries to acquire the mutex
+        ///
+        /// If acquiring the mutex is successful, a [`GenericSharedMutexGuard`]
+        /// will be returned, which allows to access the contained data.
+        ///
+        /// Otherwise `None` will be returned.
+        pub fn try_lock(
+            &self,
+        ) -> Option<GenericSharedMutexGuard<MutexType, T>> {
+            if self.inner.state.lock().try_lock_sync() {
+                Some(GenericSharedMutexGuard {
+                    mutex: self.clone(),
+                })
+            } else {
+                None
+            }
+        }
+
+        /// Returns whether the mutex is locked.
+        pub fn is_locked(&self) -> bool {
+            self.inner.is_locked()
+        }
+
+        /// Returns the identity of the mutex inside
+        /// [`Deadlock`](super::super::deadlock_detection::Deadlock) reports.
+        #[cfg(feature = "deadlock_detection")]
+        pub fn lock_id(&self) -> LockId {
+            self.inner.lock_id()
+        }
+    }
+
+    // Export parking_lot based shared mutexes in std mode
+    #[cfg(feature = "std")]
+    mod if_std {
+        use super::*;
+
+        /// A [`GenericSharedMutex`] backed by [`parking_lot`].
+        pub type SharedMutex<T> = GenericSharedMutex<parking_lot::RawMutex, T>;
+        /// A [`GenericSharedMutexGuard`] for [`SharedMutex`].
+        pub type SharedMutexGuard<T> =
+            GenericSharedMutexGuard<parking_lot::RawMutex, T>;
+        /// A [`GenericSharedMappedMutexGuard`] for [`SharedMutex`].
+        pub type SharedMappedMutexGuard<T, U> =
+            GenericSharedMappedMutexGuard<parking_lot::RawMutex, T, U>;
+        /// A [`GenericSharedMutexLockFuture`] for [`SharedMutex`].
+        pub type SharedMutexLockFuture<T> =
+            GenericSharedMutexLockFuture<parking_lot::RawMutex, T>;
+        /// A [`GenericSharedMutexUnlockedFuture`] for [`SharedMutex`].
+        pub type SharedMutexUnlockedFuture<T, Fut> =
+            GenericSharedMutexUnlockedFuture<parking_lot::RawMutex, T, Fut>;
+    }
+
+    #[cfg(feature = "std")]
+    pub use self::if_std::*;
+}
+
+#[cfg(feature = "alloc")]
+pub use self::if_alloc::*;
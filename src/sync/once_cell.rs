@@ -0,0 +1,547 @@
+//! An asynchronously initializable cell, which can be written to only once.
+
+use crate::{
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use core::{cell::UnsafeCell, convert::Infallible, pin::Pin};
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Tracks how the future had interacted with the cell
+#[derive(PartialEq)]
+enum PollState {
+    /// The task is not registered at the cell. This is the case for new tasks,
+    /// as well as for tasks which got woken up because the initializer of
+    /// another task completed or was aborted.
+    New,
+    /// The task was added to the wait queue at the cell.
+    Waiting,
+}
+
+/// Tracks the waiting state of the futures which initialize the cell.
+/// Access to this struct is synchronized through the mutex in the OnceCell.
+struct WaitQueueEntry {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: PollState,
+}
+
+impl WaitQueueEntry {
+    /// Creates a new WaitQueueEntry
+    fn new() -> WaitQueueEntry {
+        WaitQueueEntry {
+            task: None,
+            state: PollState::New,
+        }
+    }
+}
+
+/// The outcome of trying to start the initialization of the cell
+enum AcquireResult {
+    /// The cell had already been initialized
+    Initialized,
+    /// The task is responsible for initializing the cell
+    Initializer,
+}
+
+/// Internal state of the `OnceCell`
+struct OnceState {
+    is_initialized: bool,
+    is_initializing: bool,
+    waiters: LinkedList<WaitQueueEntry>,
+}
+
+impl OnceState {
+    const fn new() -> OnceState {
+        OnceState {
+            is_initialized: false,
+            is_initializing: false,
+            waiters: LinkedList::new(),
+        }
+    }
+
+    /// Wakes up all tasks which are waiting for the initialization.
+    /// The tasks will recheck the state of the cell once they get polled
+    /// again.
+    fn wakeup_waiters(&mut self) {
+        // Use a reverse iterator, so that the oldest waiter gets
+        // scheduled first
+        self.waiters.reverse_drain(|waiter| {
+            if let Some(handle) = waiter.task.take() {
+                handle.wake();
+            }
+            waiter.state = PollState::New;
+        });
+    }
+
+    /// Checks if the cell has been initialized, or if the task can start
+    /// initializing it. If neither is the case, the WaitQueueEntry gets added
+    /// to the wait queue at the cell.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn try_acquire(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<AcquireResult> {
+        if self.is_initialized {
+            self.remove_waiter(wait_node);
+            return Poll::Ready(AcquireResult::Initialized);
+        }
+        if !self.is_initializing {
+            self.remove_waiter(wait_node);
+            self.is_initializing = true;
+            return Poll::Ready(AcquireResult::Initializer);
+        }
+
+        match wait_node.state {
+            PollState::New => {
+                // Added the task to the wait queue
+                wait_node.task = Some(cx.waker().clone());
+                wait_node.state = PollState::Waiting;
+                self.waiters.add_front(wait_node);
+            }
+            PollState::Waiting => {
+                // Another task is still initializing the cell. However the
+                // caller might have passed a different `Waker`. In this case
+                // we need to update it.
+                update_waker_ref(&mut wait_node.task, cx);
+            }
+        }
+        Poll::Pending
+    }
+
+    fn remove_waiter(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        // The future only needs to get removed if it has been added to the
+        // wait queue of the cell. This has happened in the PollState::Waiting
+        // case.
+        if let PollState::Waiting = wait_node.state {
+            // Safety: Due to the state, we know that the node must be part
+            // of the waiter list
+            if !unsafe { self.waiters.remove(wait_node) } {
+                // Panic if the address isn't found. This can only happen if the contract was
+                // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                panic!("Future could not be removed from wait queue");
+            }
+            wait_node.state = PollState::New;
+        }
+    }
+}
+
+/// A cell which can be written to only once, and which supports asynchronous
+/// initialization.
+///
+/// If multiple tasks try to initialize the cell concurrently via
+/// `get_or_init` or `get_or_try_init`, only one of them runs its initializer.
+/// The other tasks wait until the initialization completes. If the
+/// initializer fails or gets cancelled, one of the waiting tasks takes over
+/// and runs its own initializer.
+pub struct GenericOnceCell<MutexType: RawMutex, T> {
+    value: UnsafeCell<Option<T>>,
+    state: Mutex<MutexType, OnceState>,
+}
+
+// It is safe to send cells between threads, as long as they are not used and
+// thereby borrowed
+unsafe impl<MutexType: RawMutex + Send, T: Send> Send
+    for GenericOnceCell<MutexType, T>
+{
+}
+// The cell is thread-safe as long as the utilized mutex is thread-safe.
+// The value can be initialized and accessed from different threads, which
+// requires it to be Send and Sync.
+unsafe impl<MutexType: RawMutex + Sync, T: Send + Sync> Sync
+    for GenericOnceCell<MutexType, T>
+{
+}
+
+impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericOnceCell<MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("OnceCell")
+            .field("value", &self.get())
+            .finish()
+    }
+}
+
+impl<MutexType: RawMutex, T> Default for GenericOnceCell<MutexType, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<MutexType: RawMutex, T> GenericOnceCell<MutexType, T> {
+    /// Creates a new OnceCell, which is not initialized.
+    pub const fn new() -> GenericOnceCell<MutexType, T> {
+        GenericOnceCell {
+            value: UnsafeCell::new(None),
+            state: Mutex::const_new(MutexType::INIT, OnceState::new()),
+        }
+    }
+
+    /// Returns a reference to the value of the cell, or `None` if the cell
+    /// has not been initialized yet.
+    pub fn get(&self) -> Option<&T> {
+        if self.state.lock().is_initialized {
+            Some(unsafe { self.get_unchecked() })
+        } else {
+            None
+        }
+    }
+
+    /// Sets the value of the cell.
+    ///
+    /// Returns the passed value as an error if the cell had already been
+    /// initialized, or if another task is currently running its initializer.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut state = self.state.lock();
+        if state.is_initialized || state.is_initializing {
+            return Err(value);
+        }
+        // Safety: The value is only written while the lock is held and the
+        // cell is not initialized. References to the value are only handed
+        // out after the initialization.
+        unsafe {
+            *self.value.get() = Some(value);
+        }
+        state.is_initialized = true;
+        Ok(())
+    }
+
+    /// Returns a future which resolves to the value of the cell.
+    ///
+    /// If the cell has not been initialized yet, the future either runs the
+    /// asynchronous initializer `init`, or waits until the initializer of
+    /// another task has completed. `init` is only invoked if the future
+    /// becomes responsible for the initialization.
+    pub fn get_or_init<F, Fut>(
+        &self,
+        init: F,
+    ) -> GenericOnceCellGetOrInitFuture<'_, MutexType, T, F, Fut>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        GenericOnceCellGetOrInitFuture {
+            inner: InitFuture::new(self, init),
+        }
+    }
+
+    /// Returns a future which resolves to the value of the cell, or to the
+    /// error of a failed initialization.
+    ///
+    /// This behaves like `get_or_init`. However the initializer is allowed to
+    /// fail. In this case the cell is not initialized, the error is returned,
+    /// and one of the tasks which are waiting for the initialization takes
+    /// over.
+    pub fn get_or_try_init<F, Fut, E>(
+        &self,
+        init: F,
+    ) -> GenericOnceCellGetOrTryInitFuture<'_, MutexType, T, F, Fut>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        GenericOnceCellGetOrTryInitFuture {
+            inner: InitFuture::new(self, init),
+        }
+    }
+
+    /// Returns a reference to the value.
+    /// This is only safe if the cell has been initialized.
+    unsafe fn get_unchecked(&self) -> &T {
+        match &*self.value.get() {
+            Some(value) => value,
+            None => unreachable!("OnceCell is not initialized"),
+        }
+    }
+
+    /// Stores the value which had been produced by the initializer, and wakes
+    /// up all tasks which are waiting for the initialization.
+    fn complete_init(&self, value: T) -> &T {
+        let mut state = self.state.lock();
+        // Safety: Only the initializer is allowed to write the value, and
+        // references are only handed out after the initialization.
+        unsafe {
+            *self.value.get() = Some(value);
+        }
+        state.is_initialized = true;
+        state.is_initializing = false;
+        state.wakeup_waiters();
+        unsafe { self.get_unchecked() }
+    }
+
+    /// Aborts the initialization after the initializer failed or got
+    /// cancelled. The tasks which are waiting for the initialization get woken
+    /// up, so that one of them can take over.
+    fn abort_init(&self) {
+        let mut state = self.state.lock();
+        state.is_initializing = false;
+        state.wakeup_waiters();
+    }
+}
+
+/// The shared implementation of the initialization futures.
+struct InitFuture<'a, MutexType: RawMutex, T, F, Fut> {
+    /// The cell which should be initialized
+    cell: Option<&'a GenericOnceCell<MutexType, T>>,
+    /// The initializer, which is only invoked if the task is responsible for
+    /// the initialization
+    init: Option<F>,
+    /// The future which had been returned by the initializer
+    init_future: Option<Fut>,
+    /// Whether this task is responsible for the initialization
+    is_initializer: bool,
+    /// Node for waiting at the cell
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+impl<'a, MutexType: RawMutex, T, F, Fut> InitFuture<'a, MutexType, T, F, Fut>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future,
+{
+    fn new(cell: &'a GenericOnceCell<MutexType, T>, init: F) -> Self {
+        InitFuture {
+            cell: Some(cell),
+            init: Some(init),
+            init_future: None,
+            is_initializer: false,
+            wait_node: ListNode::new(WaitQueueEntry::new()),
+        }
+    }
+
+    /// Drives the initialization of the cell.
+    /// `convert` converts the output of the initializer into either the value
+    /// or an error.
+    /// This function is only safe as long as the future is pinned.
+    unsafe fn poll_init<E>(
+        &mut self,
+        cx: &mut Context<'_>,
+        convert: fn(Fut::Output) -> Result<T, E>,
+    ) -> Poll<Result<&'a T, E>> {
+        let cell = self
+            .cell
+            .expect("polled OnceCell initialization future after completion");
+
+        loop {
+            if let Some(init_future) = self.init_future.as_mut() {
+                // This task is responsible for the initialization
+                let output = match Pin::new_unchecked(init_future).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(output) => output,
+                };
+                self.init_future = None;
+                self.cell = None;
+                return match convert(output) {
+                    Ok(value) => Poll::Ready(Ok(cell.complete_init(value))),
+                    Err(e) => {
+                        cell.abort_init();
+                        Poll::Ready(Err(e))
+                    }
+                };
+            }
+
+            let acquire_res =
+                cell.state.lock().try_acquire(&mut self.wait_node, cx);
+            match acquire_res {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(AcquireResult::Initialized) => {
+                    self.cell = None;
+                    return Poll::Ready(Ok(cell.get_unchecked()));
+                }
+                Poll::Ready(AcquireResult::Initializer) => {
+                    // The initializer is invoked outside of the lock, since
+                    // it might access the cell.
+                    self.is_initializer = true;
+                    let init =
+                        self.init.take().expect("initializer is missing");
+                    self.init_future = Some(init());
+                }
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F, Fut> Drop
+    for InitFuture<'a, MutexType, T, F, Fut>
+{
+    fn drop(&mut self) {
+        if let Some(cell) = self.cell {
+            if self.is_initializer {
+                // The initialization was cancelled. Another task needs to
+                // take over.
+                cell.abort_init();
+            } else {
+                // If this future has been polled and it was added to the
+                // wait queue at the cell, it must be removed before dropping.
+                // Otherwise the cell would access invalid memory.
+                cell.state.lock().remove_waiter(&mut self.wait_node);
+            }
+        }
+    }
+}
+
+/// A Future that resolves to the value of a OnceCell, and initializes it if
+/// necessary.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericOnceCellGetOrInitFuture<'a, MutexType: RawMutex, T, F, Fut> {
+    inner: InitFuture<'a, MutexType, T, F, Fut>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// cell is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: Send + Sync, F: Send, Fut: Send>
+    Send for GenericOnceCellGetOrInitFuture<'a, MutexType, T, F, Fut>
+{
+}
+
+impl<'a, MutexType: RawMutex, T, F, Fut> core::fmt::Debug
+    for GenericOnceCellGetOrInitFuture<'a, MutexType, T, F, Fut>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericOnceCellGetOrInitFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F, Fut> Future
+    for GenericOnceCellGetOrInitFuture<'a, MutexType, T, F, Fut>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = T>,
+{
+    type Output = &'a T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<&'a T> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry and of the initializer future
+        // inside the future is stable, and we don't move any fields inside
+        // the future until it gets dropped.
+        let mut_self: &mut GenericOnceCellGetOrInitFuture<
+            MutexType,
+            T,
+            F,
+            Fut,
+        > = unsafe { Pin::get_unchecked_mut(self) };
+
+        let poll_res = unsafe {
+            mut_self
+                .inner
+                .poll_init(cx, Ok::<T, Infallible> as fn(T) -> _)
+        };
+
+        match poll_res {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(value)) => Poll::Ready(value),
+            Poll::Ready(Err(e)) => match e {},
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F, Fut> FusedFuture
+    for GenericOnceCellGetOrInitFuture<'a, MutexType, T, F, Fut>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = T>,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.cell.is_none()
+    }
+}
+
+/// A Future that resolves to the value of a OnceCell, and tries to initialize
+/// it if necessary.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericOnceCellGetOrTryInitFuture<'a, MutexType: RawMutex, T, F, Fut>
+{
+    inner: InitFuture<'a, MutexType, T, F, Fut>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// cell is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: Send + Sync, F: Send, Fut: Send>
+    Send for GenericOnceCellGetOrTryInitFuture<'a, MutexType, T, F, Fut>
+{
+}
+
+impl<'a, MutexType: RawMutex, T, F, Fut> core::fmt::Debug
+    for GenericOnceCellGetOrTryInitFuture<'a, MutexType, T, F, Fut>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericOnceCellGetOrTryInitFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F, Fut, E> Future
+    for GenericOnceCellGetOrTryInitFuture<'a, MutexType, T, F, Fut>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    type Output = Result<&'a T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry and of the initializer future
+        // inside the future is stable, and we don't move any fields inside
+        // the future until it gets dropped.
+        let mut_self: &mut GenericOnceCellGetOrTryInitFuture<
+            MutexType,
+            T,
+            F,
+            Fut,
+        > = unsafe { Pin::get_unchecked_mut(self) };
+
+        unsafe { mut_self.inner.poll_init(cx, |res| res) }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F, Fut, E> FusedFuture
+    for GenericOnceCellGetOrTryInitFuture<'a, MutexType, T, F, Fut>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.cell.is_none()
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericOnceCell`] which is not thread-safe.
+pub type LocalOnceCell<T> = GenericOnceCell<NoopLock, T>;
+/// A [`GenericOnceCellGetOrInitFuture`] for [`LocalOnceCell`].
+pub type LocalOnceCellGetOrInitFuture<'a, T, F, Fut> =
+    GenericOnceCellGetOrInitFuture<'a, NoopLock, T, F, Fut>;
+/// A [`GenericOnceCellGetOrTryInitFuture`] for [`LocalOnceCell`].
+pub type LocalOnceCellGetOrTryInitFuture<'a, T, F, Fut> =
+    GenericOnceCellGetOrTryInitFuture<'a, NoopLock, T, F, Fut>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericOnceCell`] implementation backed by [`parking_lot`].
+    pub type OnceCell<T> = GenericOnceCell<parking_lot::RawMutex, T>;
+    /// A [`GenericOnceCellGetOrInitFuture`] for [`OnceCell`].
+    pub type OnceCellGetOrInitFuture<'a, T, F, Fut> =
+        GenericOnceCellGetOrInitFuture<'a, parking_lot::RawMutex, T, F, Fut>;
+    /// A [`GenericOnceCellGetOrTryInitFuture`] for [`OnceCell`].
+    pub type OnceCellGetOrTryInitFuture<'a, T, F, Fut> =
+        GenericOnceCellGetOrTryInitFuture<'a, parking_lot::RawMutex, T, F, Fut>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
@@ -0,0 +1,544 @@
+//! An asynchronously awaitable pool of reusable objects.
+
+use crate::{
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use alloc::vec::Vec;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex as LockApiMutex, RawMutex};
+
+/// Tracks how the future had interacted with the pool
+#[derive(PartialEq)]
+enum PollState {
+    /// The task has never interacted with the pool.
+    New,
+    /// The task was added to the wait queue at the pool.
+    Waiting,
+    /// The pool handed over either an object or the permission to create a
+    /// new object to the task, and the task was removed from the wait queue.
+    Granted,
+    /// The task had been polled to completion.
+    Done,
+}
+
+/// Tracks the GenericPoolGetFuture waiting state.
+struct WaitQueueEntry<T> {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: PollState,
+    /// The object which had been handed over to the task. If the state is
+    /// `Granted` and no object is stored, the task must create a new one.
+    object: Option<T>,
+}
+
+impl<T> WaitQueueEntry<T> {
+    /// Creates a new WaitQueueEntry
+    fn new() -> WaitQueueEntry<T> {
+        WaitQueueEntry {
+            task: None,
+            state: PollState::New,
+            object: None,
+        }
+    }
+}
+
+/// The result of checking out an object from the pool
+enum Checkout<T> {
+    /// An existing object had been checked out
+    Object(T),
+    /// A slot for a new object had been reserved, and the object must get
+    /// created through the factory.
+    Create,
+}
+
+/// Internal state of the `Pool`
+struct PoolState<T> {
+    max_size: usize,
+    /// The amount of objects which belong to the pool. This includes idle
+    /// objects, checked out objects, and objects which are getting created.
+    size: usize,
+    idle: Vec<T>,
+    waiters: LinkedList<WaitQueueEntry<T>>,
+}
+
+impl<T> PoolState<T> {
+    fn new(max_size: usize) -> Self {
+        PoolState {
+            max_size,
+            size: 0,
+            idle: Vec::new(),
+            waiters: LinkedList::new(),
+        }
+    }
+
+    /// Tries to check out an object synchronously.
+    ///
+    /// Objects are only handed out if no task is waiting for them, which
+    /// guarantees that waiters are served in FIFO order.
+    fn try_checkout(&mut self) -> Option<Checkout<T>> {
+        if !self.waiters.is_empty() {
+            return None;
+        }
+
+        if let Some(object) = self.idle.pop() {
+            Some(Checkout::Object(object))
+        } else if self.size < self.max_size {
+            self.size += 1;
+            Some(Checkout::Create)
+        } else {
+            None
+        }
+    }
+
+    /// Returns an object to the pool.
+    ///
+    /// If a task is waiting for an object, the object is handed over to the
+    /// oldest waiter, and the `Waker` of that task is returned.
+    fn put_back(&mut self, object: T) -> Option<Waker> {
+        match self.waiters.remove_last() {
+            Some(last_waiter) => {
+                last_waiter.object = Some(object);
+                last_waiter.state = PollState::Granted;
+                last_waiter.task.take()
+            }
+            None => {
+                self.idle.push(object);
+                None
+            }
+        }
+    }
+
+    /// Releases the slot of an object which no longer belongs to the pool.
+    ///
+    /// If a task is waiting for an object, the slot is handed over to the
+    /// oldest waiter, which will then create a new object.
+    fn release_slot(&mut self) -> Option<Waker> {
+        match self.waiters.remove_last() {
+            Some(last_waiter) => {
+                last_waiter.state = PollState::Granted;
+                last_waiter.task.take()
+            }
+            None => {
+                self.size -= 1;
+                None
+            }
+        }
+    }
+
+    /// Tries to check out an object from a WaitQueueEntry.
+    ///
+    /// If no object is available, the WaitQueueEntry gets added to the wait
+    /// queue at the pool, and will be signalled once ready.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn try_get(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry<T>>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Checkout<T>> {
+        match wait_node.state {
+            PollState::New => match self.try_checkout() {
+                Some(checkout) => {
+                    wait_node.state = PollState::Done;
+                    Poll::Ready(checkout)
+                }
+                None => {
+                    // Add the task to the wait queue
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = PollState::Waiting;
+                    self.waiters.add_front(wait_node);
+                    Poll::Pending
+                }
+            },
+            PollState::Waiting => {
+                // The task is still waiting. Update the waker if necessary.
+                update_waker_ref(&mut wait_node.task, cx);
+                Poll::Pending
+            }
+            PollState::Granted => {
+                wait_node.state = PollState::Done;
+                match wait_node.object.take() {
+                    Some(object) => Poll::Ready(Checkout::Object(object)),
+                    None => Poll::Ready(Checkout::Create),
+                }
+            }
+            PollState::Done => {
+                // The future had been polled to completion before
+                panic!("polled GenericPoolGetFuture after completion");
+            }
+        }
+    }
+
+    /// Removes the waiter from the wait list.
+    ///
+    /// If the waiter had already been granted an object or a slot, those are
+    /// passed on to the next waiter, whose `Waker` gets returned.
+    fn remove_waiter(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry<T>>,
+    ) -> Option<Waker> {
+        match wait_node.state {
+            PollState::Waiting => {
+                // Remove the WaitQueueEntry from the linked list
+                // Safety: Due to the state, we know that the node must be part
+                // of the waiter list
+                if !unsafe { self.waiters.remove(wait_node) } {
+                    // Panic if the address isn't found. This can only happen if the contract was
+                    // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                    panic!("Future could not be removed from wait queue");
+                }
+                wait_node.state = PollState::Done;
+                None
+            }
+            PollState::Granted => {
+                // The task is no longer interested in what it was granted
+                wait_node.state = PollState::Done;
+                match wait_node.object.take() {
+                    Some(object) => self.put_back(object),
+                    None => self.release_slot(),
+                }
+            }
+            PollState::New | PollState::Done => None,
+        }
+    }
+}
+
+/// An RAII guard returned by the `get` and `try_get` methods of a pool.
+/// When this structure is dropped (falls out of scope), the object will be
+/// returned to the pool.
+pub struct GenericPoolGuard<'a, MutexType: RawMutex, T, F> {
+    /// The pool which is associated with this Guard
+    pool: &'a GenericPool<MutexType, T, F>,
+    /// The object which had been checked out
+    object: ManuallyDrop<T>,
+}
+
+impl<MutexType: RawMutex, T: core::fmt::Debug, F> core::fmt::Debug
+    for GenericPoolGuard<'_, MutexType, T, F>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericPoolGuard")
+            .field("object", &*self.object)
+            .finish()
+    }
+}
+
+impl<MutexType: RawMutex, T, F> Drop for GenericPoolGuard<'_, MutexType, T, F> {
+    fn drop(&mut self) {
+        // Safety: The object is never accessed again
+        let object = unsafe { ManuallyDrop::take(&mut self.object) };
+        let waker = { self.pool.state.lock().put_back(object) };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<MutexType: RawMutex, T, F> Deref
+    for GenericPoolGuard<'_, MutexType, T, F>
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.object
+    }
+}
+
+impl<MutexType: RawMutex, T, F> DerefMut
+    for GenericPoolGuard<'_, MutexType, T, F>
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.object
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F> GenericPoolGuard<'a, MutexType, T, F> {
+    /// Removes the object from the pool and returns it.
+    ///
+    /// This should be used for objects which are broken and must not be
+    /// reused. The pool is allowed to create a new object instead of the
+    /// detached one.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `GenericPoolGuard::detach(...)`. A method would interfere with methods
+    /// of the same name on the pooled object.
+    pub fn detach(this: Self) -> T {
+        let mut this = ManuallyDrop::new(this);
+        // Safety: The guard is not dropped, and the object is therefore
+        // never accessed again
+        let object = unsafe { ManuallyDrop::take(&mut this.object) };
+        this.pool.release_slot();
+        object
+    }
+}
+
+/// A future which resolves when an object could be checked out from the pool.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericPoolGetFuture<'a, MutexType: RawMutex, T, F> {
+    /// The pool from which an object should get checked out
+    pool: Option<&'a GenericPool<MutexType, T, F>>,
+    /// Node for waiting at the pool
+    wait_node: ListNode<WaitQueueEntry<T>>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// pool is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: Send, F: Sync> Send
+    for GenericPoolGetFuture<'a, MutexType, T, F>
+{
+}
+
+impl<'a, MutexType: RawMutex, T, F> core::fmt::Debug
+    for GenericPoolGetFuture<'a, MutexType, T, F>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericPoolGetFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F> Future
+    for GenericPoolGetFuture<'a, MutexType, T, F>
+where
+    F: Fn() -> T,
+{
+    type Output = GenericPoolGuard<'a, MutexType, T, F>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside GenericPoolGetFuture is stable,
+        // and we don't move any fields inside the future until it gets dropped.
+        let mut_self: &mut GenericPoolGetFuture<MutexType, T, F> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let pool = mut_self
+            .pool
+            .expect("polled GenericPoolGetFuture after completion");
+
+        let poll_res = unsafe {
+            let mut pool_state = pool.state.lock();
+            pool_state.try_get(&mut mut_self.wait_node, cx)
+        };
+
+        match poll_res {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(checkout) => {
+                mut_self.pool = None;
+                Poll::Ready(pool.checkout(checkout))
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F> FusedFuture
+    for GenericPoolGetFuture<'a, MutexType, T, F>
+where
+    F: Fn() -> T,
+{
+    fn is_terminated(&self) -> bool {
+        self.pool.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T, F> Drop
+    for GenericPoolGetFuture<'a, MutexType, T, F>
+{
+    fn drop(&mut self) {
+        // If this GenericPoolGetFuture has been polled and it was added to the
+        // wait queue at the pool, it must be removed before dropping.
+        // Otherwise the pool would access invalid memory.
+        let waker = if let Some(pool) = self.pool {
+            let mut pool_state = pool.state.lock();
+            pool_state.remove_waiter(&mut self.wait_node)
+        } else {
+            None
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A futures-aware pool of reusable objects.
+///
+/// Objects are created lazily through a factory function once they are
+/// requested and no idle object is available, until the maximum size of the
+/// pool is reached. Afterwards tasks have to wait until an object is returned
+/// to the pool. Waiting tasks are served in FIFO order.
+///
+/// `F` is the type of the factory function. The pool can only be shared
+/// between threads if the factory is `Send` and `Sync`.
+pub struct GenericPool<MutexType: RawMutex, T, F> {
+    /// The function which is used for creating new objects
+    factory: F,
+    state: LockApiMutex<MutexType, PoolState<T>>,
+}
+
+// It is safe to send pools between threads, as long as they are not used and
+// thereby borrowed
+unsafe impl<T: Send, MutexType: RawMutex + Send, F: Send> Send
+    for GenericPool<MutexType, T, F>
+{
+}
+// The pool is thread-safe as long as the utilized mutex is thread-safe
+unsafe impl<T: Send, MutexType: RawMutex + Sync, F: Sync> Sync
+    for GenericPool<MutexType, T, F>
+{
+}
+
+impl<MutexType: RawMutex, T, F> core::fmt::Debug
+    for GenericPool<MutexType, T, F>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("Pool")
+            .field("max_size", &state.max_size)
+            .field("size", &state.size)
+            .field("idle", &state.idle.len())
+            .finish()
+    }
+}
+
+impl<MutexType: RawMutex, T, F> GenericPool<MutexType, T, F>
+where
+    F: Fn() -> T,
+{
+    /// Creates a new pool which contains at most `max_size` objects.
+    ///
+    /// Objects are created on demand through `factory`. The factory is
+    /// called by the task which requests the object, without holding any
+    /// lock on the pool.
+    ///
+    /// Panics if `max_size` is zero.
+    pub fn new(max_size: usize, factory: F) -> GenericPool<MutexType, T, F> {
+        assert!(max_size > 0, "A pool must contain at least one object");
+        GenericPool::<MutexType, T, F> {
+            factory,
+            state: LockApiMutex::new(PoolState::new(max_size)),
+        }
+    }
+
+    /// Checks out an object from the pool asynchronously.
+    ///
+    /// This method returns a future that will resolve once an object is
+    /// available. The object is returned to the pool once the returned guard
+    /// gets dropped.
+    pub fn get(&self) -> GenericPoolGetFuture<'_, MutexType, T, F> {
+        GenericPoolGetFuture::<MutexType, T, F> {
+            pool: Some(self),
+            wait_node: ListNode::new(WaitQueueEntry::new()),
+        }
+    }
+
+    /// Tries to check out an object from the pool.
+    ///
+    /// If an idle object is available or a new object may get created, a
+    /// [`GenericPoolGuard`] will be returned, which allows to access the
+    /// object.
+    ///
+    /// Otherwise `None` will be returned. This is also the case if other
+    /// tasks are already waiting for an object.
+    pub fn try_get(&self) -> Option<GenericPoolGuard<'_, MutexType, T, F>> {
+        let checkout = self.state.lock().try_checkout();
+        checkout.map(|checkout| self.checkout(checkout))
+    }
+
+    /// Returns the maximum amount of objects in the pool.
+    pub fn max_size(&self) -> usize {
+        self.state.lock().max_size
+    }
+
+    /// Returns the amount of objects which currently belong to the pool.
+    ///
+    /// This includes checked out objects as well as idle objects.
+    pub fn size(&self) -> usize {
+        self.state.lock().size
+    }
+
+    /// Returns the amount of objects which are currently not checked out.
+    pub fn idle(&self) -> usize {
+        self.state.lock().idle.len()
+    }
+
+    /// Creates the guard for a checked out object
+    fn checkout(
+        &self,
+        checkout: Checkout<T>,
+    ) -> GenericPoolGuard<'_, MutexType, T, F> {
+        let object = match checkout {
+            Checkout::Object(object) => object,
+            Checkout::Create => self.create(),
+        };
+        GenericPoolGuard {
+            pool: self,
+            object: ManuallyDrop::new(object),
+        }
+    }
+
+    /// Creates a new object in a slot which had been reserved before
+    fn create(&self) -> T {
+        /// Releases the reserved slot if the factory panics
+        struct Reservation<'a, MutexType: RawMutex, T, F>(
+            &'a GenericPool<MutexType, T, F>,
+        );
+
+        impl<MutexType: RawMutex, T, F> Drop for Reservation<'_, MutexType, T, F> {
+            fn drop(&mut self) {
+                self.0.release_slot();
+            }
+        }
+
+        let reservation = Reservation(self);
+        let object = (self.factory)();
+        core::mem::forget(reservation);
+        object
+    }
+}
+
+impl<MutexType: RawMutex, T, F> GenericPool<MutexType, T, F> {
+    /// Releases the slot of an object which no longer belongs to the pool
+    fn release_slot(&self) {
+        let waker = { self.state.lock().release_slot() };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericPool`] which is not thread-safe.
+pub type LocalPool<T, F> = GenericPool<NoopLock, T, F>;
+/// A [`GenericPoolGuard`] for [`LocalPool`].
+pub type LocalPoolGuard<'a, T, F> = GenericPoolGuard<'a, NoopLock, T, F>;
+/// A [`GenericPoolGetFuture`] for [`LocalPool`].
+pub type LocalPoolGetFuture<'a, T, F> =
+    GenericPoolGetFuture<'a, NoopLock, T, F>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericPool`] backed by [`parking_lot`].
+    pub type Pool<T, F> = GenericPool<parking_lot::RawMutex, T, F>;
+    /// A [`GenericPoolGuard`] for [`Pool`].
+    pub type PoolGuard<'a, T, F> =
+        GenericPoolGuard<'a, parking_lot::RawMutex, T, F>;
+    /// A [`GenericPoolGetFuture`] for [`Pool`].
+    pub type PoolGetFuture<'a, T, F> =
+        GenericPoolGetFuture<'a, parking_lot::RawMutex, T, F>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
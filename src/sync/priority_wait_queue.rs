@@ -0,0 +1,212 @@
+//! Wait queue building blocks which allow futures to wait for a
+//! synchronization primitive either in FIFO order or ordered by priority.
+
+use crate::{
+    intrusive_double_linked_list::ListNode,
+    intrusive_pairing_heap::{HeapNode, PairingHeap},
+};
+use core::ops::{Deref, DerefMut};
+
+/// Wraps a wait queue entry with the information which is required to order
+/// it inside a [`PriorityWaitQueue`].
+pub(crate) struct PriorityEntry<T> {
+    /// The priority of the waiter. Higher values are served first.
+    priority: usize,
+    /// Insertion order of the waiter, which breaks ties between waiters of
+    /// the same priority.
+    sequence: u64,
+    /// The wrapped wait queue entry
+    entry: T,
+}
+
+impl<T> PriorityEntry<T> {
+    /// Creates a new PriorityEntry
+    pub const fn new(priority: usize, entry: T) -> PriorityEntry<T> {
+        PriorityEntry {
+            priority,
+            sequence: 0,
+            entry,
+        }
+    }
+}
+
+impl<T> Deref for PriorityEntry<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.entry
+    }
+}
+
+impl<T> DerefMut for PriorityEntry<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.entry
+    }
+}
+
+impl<T> PartialEq for PriorityEntry<T> {
+    fn eq(&self, other: &PriorityEntry<T>) -> bool {
+        self.priority == other.priority && self.sequence == other.sequence
+    }
+}
+
+impl<T> Eq for PriorityEntry<T> {}
+
+impl<T> PartialOrd for PriorityEntry<T> {
+    fn partial_cmp(
+        &self,
+        other: &PriorityEntry<T>,
+    ) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for PriorityEntry<T> {
+    fn cmp(&self, other: &PriorityEntry<T>) -> core::cmp::Ordering {
+        // The heap returns the smallest entry first. Therefore entries with a
+        // higher priority must compare as smaller, and among the entries with
+        // the same priority the oldest one.
+        other
+            .priority
+            .cmp(&self.priority)
+            .then(self.sequence.cmp(&other.sequence))
+    }
+}
+
+/// A node which allows a future to wait either in the FIFO ordered wait queue
+/// of a synchronization primitive, or in its [`PriorityWaitQueue`].
+pub(crate) enum WaitNode<T> {
+    /// The waiter is ordered by the time it started to wait
+    Fifo(ListNode<T>),
+    /// The waiter is ordered by its priority
+    Priority(HeapNode<PriorityEntry<T>>),
+}
+
+impl<T> WaitNode<T> {
+    /// Creates a node for waiting in FIFO order
+    pub const fn fifo(entry: T) -> WaitNode<T> {
+        WaitNode::Fifo(ListNode::new(entry))
+    }
+
+    /// Creates a node for waiting with the given priority
+    pub const fn priority(priority: usize, entry: T) -> WaitNode<T> {
+        WaitNode::Priority(HeapNode::new(PriorityEntry::new(priority, entry)))
+    }
+}
+
+impl<T> Deref for WaitNode<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            WaitNode::Fifo(node) => node,
+            WaitNode::Priority(node) => &node.entry,
+        }
+    }
+}
+
+impl<T> DerefMut for WaitNode<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            WaitNode::Fifo(node) => node,
+            WaitNode::Priority(node) => &mut node.entry,
+        }
+    }
+}
+
+/// An intrusive wait queue which returns the waiter with the highest
+/// priority first. Waiters with the same priority are returned in the order
+/// in which they had been added.
+pub(crate) struct PriorityWaitQueue<T> {
+    heap: PairingHeap<PriorityEntry<T>>,
+    /// The sequence number which gets assigned to the next waiter
+    next_sequence: u64,
+}
+
+impl<T> PriorityWaitQueue<T> {
+    /// Creates an empty queue
+    pub const fn new() -> PriorityWaitQueue<T> {
+        PriorityWaitQueue {
+            heap: PairingHeap::new(),
+            next_sequence: 0,
+        }
+    }
+
+    /// Returns whether no waiter is stored in the queue
+    pub fn is_empty(&self) -> bool {
+        self.heap.peek_min().is_none()
+    }
+
+    /// Adds a waiter to the queue.
+    ///
+    /// Safety: This function is only safe as long as `node` is guaranteed to
+    /// get removed from the queue before it gets moved or dropped.
+    pub unsafe fn insert(&mut self, node: &mut HeapNode<PriorityEntry<T>>) {
+        node.sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        self.heap.insert(node);
+    }
+
+    /// Removes the given waiter from the queue.
+    ///
+    /// Safety: The node must be a member of this queue.
+    pub unsafe fn remove(&mut self, node: &mut HeapNode<PriorityEntry<T>>) {
+        self.heap.remove(node);
+    }
+
+    /// Returns a reference to the waiter with the highest priority without
+    /// removing it.
+    pub fn peek_first(&mut self) -> Option<&mut T> {
+        // Safety: All nodes are guaranteed to be valid as long as they are
+        // part of the queue
+        self.heap
+            .peek_min()
+            .map(|mut node| unsafe { &mut node.as_mut().entry })
+    }
+
+    /// Removes the waiter with the highest priority from the queue and
+    /// returns a reference to it.
+    pub fn remove_first(&mut self) -> Option<&mut T> {
+        // Safety: All nodes are guaranteed to be valid as long as they are
+        // part of the queue
+        self.heap.peek_min().map(|mut node| unsafe {
+            let node = node.as_mut();
+            self.heap.remove(node);
+            &mut node.entry
+        })
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{PriorityEntry, PriorityWaitQueue};
+    use crate::intrusive_pairing_heap::HeapNode;
+
+    #[test]
+    fn returns_highest_priority_first_and_fifo_for_ties() {
+        let mut queue = PriorityWaitQueue::<u32>::new();
+        assert!(queue.is_empty());
+
+        let mut nodes = [
+            HeapNode::new(PriorityEntry::new(1, 0)),
+            HeapNode::new(PriorityEntry::new(3, 1)),
+            HeapNode::new(PriorityEntry::new(1, 2)),
+            HeapNode::new(PriorityEntry::new(3, 3)),
+            HeapNode::new(PriorityEntry::new(0, 4)),
+        ];
+        for node in nodes.iter_mut() {
+            unsafe { queue.insert(node) };
+        }
+        assert!(!queue.is_empty());
+        assert_eq!(Some(&mut 1), queue.peek_first());
+
+        unsafe { queue.remove(&mut nodes[3]) };
+
+        let mut order = Vec::new();
+        while let Some(entry) = queue.remove_first() {
+            order.push(*entry);
+        }
+        assert_eq!(vec![1, 0, 2, 4], order);
+        assert!(queue.is_empty());
+    }
+}
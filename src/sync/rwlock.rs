@@ -0,0 +1,1256 @@
+//! An asynchronously awaitable reader-writer lock for synchronization between
+//! concurrently executing futures.
+
+use crate::{
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use core::{
+    cell::UnsafeCell,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+};
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex as LockApiMutex, RawMutex};
+
+/// Tracks how the future had interacted with the lock
+#[derive(PartialEq)]
+enum PollState {
+    /// The task has never interacted with the lock.
+    New,
+    /// The task was added to the wait queue at the lock.
+    Waiting,
+    /// The task had previously waited on the lock, but was notified
+    /// that the lock was released in the meantime.
+    Notified,
+    /// The task had been polled to completion.
+    Done,
+}
+
+/// The kind of access which a waiter wants to obtain
+#[derive(Clone, Copy, PartialEq)]
+enum LockKind {
+    /// Shared read access
+    Read,
+    /// Shared read access which can later be upgraded to write access
+    UpgradableRead,
+    /// Exclusive write access
+    Write,
+}
+
+/// Tracks the waiting state of the lock futures.
+/// Access to this struct is synchronized through the mutex in the RwLock.
+struct WaitQueueEntry {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: PollState,
+    /// The kind of access the task waits for
+    kind: LockKind,
+}
+
+impl WaitQueueEntry {
+    /// Creates a new WaitQueueEntry
+    fn new(kind: LockKind) -> WaitQueueEntry {
+        WaitQueueEntry {
+            task: None,
+            state: PollState::New,
+            kind,
+        }
+    }
+}
+
+/// Describes by whom the `RwLock` is currently held
+#[derive(Clone, Copy)]
+struct LockHolders {
+    /// The amount of active readers. This does not include an upgradable reader.
+    readers: usize,
+    /// Whether an upgradable reader holds the lock
+    has_upgradable_reader: bool,
+    /// Whether a writer holds the lock
+    is_write_locked: bool,
+}
+
+impl LockHolders {
+    /// Returns whether the lock could be obtained for the given kind of access
+    /// if there was no wait queue.
+    fn is_available(&self, kind: LockKind) -> bool {
+        match kind {
+            LockKind::Read => !self.is_write_locked,
+            LockKind::UpgradableRead => {
+                !self.is_write_locked && !self.has_upgradable_reader
+            }
+            LockKind::Write => {
+                !self.is_write_locked
+                    && !self.has_upgradable_reader
+                    && self.readers == 0
+            }
+        }
+    }
+
+    /// Marks the lock as acquired for the given kind of access.
+    fn acquire(&mut self, kind: LockKind) {
+        match kind {
+            LockKind::Read => self.readers += 1,
+            LockKind::UpgradableRead => self.has_upgradable_reader = true,
+            LockKind::Write => self.is_write_locked = true,
+        }
+    }
+}
+
+/// Internal state of the `RwLock`
+struct RwLockState {
+    is_fair: bool,
+    holders: LockHolders,
+    waiters: LinkedList<WaitQueueEntry>,
+    /// Holds the upgradable reader if it waits for being upgraded to a writer.
+    /// Since there can only be a single upgradable reader, this list never
+    /// contains more than one entry.
+    upgrade_waiters: LinkedList<WaitQueueEntry>,
+}
+
+impl RwLockState {
+    const fn new(is_fair: bool) -> Self {
+        RwLockState {
+            is_fair,
+            holders: LockHolders {
+                readers: 0,
+                has_upgradable_reader: false,
+                is_write_locked: false,
+            },
+            waiters: LinkedList::new(),
+            upgrade_waiters: LinkedList::new(),
+        }
+    }
+
+    /// Returns whether the lock could be obtained for the given kind of access
+    /// if there was no wait queue.
+    fn is_available(&self, kind: LockKind) -> bool {
+        // New readers are not admitted while an upgrade is pending. Otherwise
+        // a constant stream of readers could prevent the upgrade forever.
+        self.holders.is_available(kind)
+            && (kind != LockKind::Read || self.upgrade_waiters.is_empty())
+    }
+
+    /// Marks the lock as acquired for the given kind of access.
+    fn acquire(&mut self, kind: LockKind) {
+        self.holders.acquire(kind);
+    }
+
+    /// Wakes up the waiters which are able to obtain the lock.
+    ///
+    /// A pending upgrade always takes precedence over other waiters.
+    ///
+    /// For a fair lock only the oldest waiter gets notified. If that waiter
+    /// obtains the lock, it will notify the next waiter. This allows a group of
+    /// readers to acquire the lock one after the other.
+    ///
+    /// For an unfair lock all waiters which can obtain the lock get notified
+    /// and removed from the wait queue.
+    fn wakeup_waiters(&mut self) {
+        if let Some(upgrader) = self.upgrade_waiters.peek_last() {
+            if self.holders.readers == 0
+                && upgrader.state != PollState::Notified
+            {
+                upgrader.state = PollState::Notified;
+                if let Some(handle) = upgrader.task.take() {
+                    handle.wake();
+                }
+            }
+            // No other waiter can make progress before the upgrade completed
+            return;
+        }
+
+        // Simulate the lock state in order to find out how many waiters can
+        // get woken up.
+        let mut holders = self.holders;
+
+        loop {
+            let last_waiter = match self.waiters.peek_last() {
+                None => return,
+                Some(last_waiter) => last_waiter,
+            };
+
+            // In a fair lock, the notified waiter stays in the queue until it
+            // obtained the lock. It will wake up the next waiter.
+            if last_waiter.state == PollState::Notified {
+                return;
+            }
+
+            if !holders.is_available(last_waiter.kind) {
+                return;
+            }
+            holders.acquire(last_waiter.kind);
+
+            // Notify the waiter that it can try to lock the RwLock again.
+            // The notification gets tracked inside the waiter.
+            // If the waiter aborts it's wait (drops the future), another task
+            // must be woken.
+            last_waiter.state = PollState::Notified;
+            if let Some(handle) = last_waiter.task.take() {
+                handle.wake();
+            }
+
+            if self.is_fair {
+                return;
+            }
+            // In the case of an unfair lock, the waiters are directly removed
+            // from the wait queue when woken.
+            self.waiters.remove_last();
+        }
+    }
+
+    /// Releases a read lock
+    fn unlock_read(&mut self) {
+        debug_assert!(self.holders.readers > 0);
+        self.holders.readers -= 1;
+        if self.holders.readers == 0 {
+            self.wakeup_waiters();
+        }
+    }
+
+    /// Releases an upgradable read lock
+    fn unlock_upgradable_read(&mut self) {
+        debug_assert!(self.holders.has_upgradable_reader);
+        self.holders.has_upgradable_reader = false;
+        self.wakeup_waiters();
+    }
+
+    /// Releases the write lock
+    fn unlock_write(&mut self) {
+        debug_assert!(self.holders.is_write_locked);
+        self.holders.is_write_locked = false;
+        self.wakeup_waiters();
+    }
+
+    /// Converts the write lock into the given kind of read lock.
+    fn downgrade_write(&mut self, kind: LockKind) {
+        debug_assert!(self.holders.is_write_locked);
+        self.holders.is_write_locked = false;
+        self.acquire(kind);
+        // Readers which had been waiting for the writer might be able to
+        // obtain the lock now.
+        self.wakeup_waiters();
+    }
+
+    /// Converts the upgradable read lock into a normal read lock.
+    fn downgrade_upgradable_read(&mut self) {
+        debug_assert!(self.holders.has_upgradable_reader);
+        self.holders.has_upgradable_reader = false;
+        self.holders.readers += 1;
+        self.wakeup_waiters();
+    }
+
+    /// Tries to upgrade the upgradable read lock to a write lock synchronously.
+    ///
+    /// Returns true if the upgrade succeeded.
+    fn try_upgrade_sync(&mut self) -> bool {
+        debug_assert!(self.holders.has_upgradable_reader);
+        if self.holders.readers == 0 {
+            self.holders.has_upgradable_reader = false;
+            self.holders.is_write_locked = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Tries to upgrade the upgradable read lock to a write lock from a
+    /// WaitQueueEntry.
+    ///
+    /// If the upgrade is not possible, the WaitQueueEntry gets added to the
+    /// upgrade wait queue, and will be signalled once all readers are gone.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn try_upgrade(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        match wait_node.state {
+            PollState::New => {
+                if self.try_upgrade_sync() {
+                    wait_node.state = PollState::Done;
+                    Poll::Ready(())
+                } else {
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = PollState::Waiting;
+                    self.upgrade_waiters.add_front(wait_node);
+                    Poll::Pending
+                }
+            }
+            PollState::Waiting | PollState::Notified => {
+                // The entry stays in the upgrade queue until the upgrade
+                // succeeded, independent of whether the lock is fair.
+                if self.try_upgrade_sync() {
+                    if !self.upgrade_waiters.remove(wait_node) {
+                        panic!("Future could not be removed from wait queue");
+                    }
+                    wait_node.state = PollState::Done;
+                    Poll::Ready(())
+                } else {
+                    wait_node.state = PollState::Waiting;
+                    update_waker_ref(&mut wait_node.task, cx);
+                    Poll::Pending
+                }
+            }
+            PollState::Done => {
+                // The future had been polled to completion before
+                panic!("polled RwLock upgrade after completion");
+            }
+        }
+    }
+
+    /// Aborts an upgrade attempt.
+    ///
+    /// Since the upgradable read guard was consumed by the upgrade attempt,
+    /// this also releases the upgradable read lock.
+    fn cancel_upgrade(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        match wait_node.state {
+            PollState::Waiting | PollState::Notified => {
+                // Safety: Due to the state, we know that the node must be part
+                // of the upgrade waiter list
+                if !unsafe { self.upgrade_waiters.remove(wait_node) } {
+                    panic!("Future could not be removed from wait queue");
+                }
+            }
+            PollState::New | PollState::Done => {}
+        }
+        wait_node.state = PollState::Done;
+        self.unlock_upgradable_read();
+    }
+
+    /// Tries to lock the RwLock synchronously.
+    ///
+    /// Returns true if the lock obtained and false otherwise.
+    fn try_lock_sync(&mut self, kind: LockKind) -> bool {
+        // The lock can only be obtained synchronously if
+        // - it is available for the requested kind of access
+        // - the RwLock is either not fair, or there are no waiters
+        if self.is_available(kind) && (!self.is_fair || self.waiters.is_empty())
+        {
+            self.acquire(kind);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Tries to acquire the RwLock from a WaitQueueEntry.
+    ///
+    /// If it isn't available, the WaitQueueEntry gets added to the wait
+    /// queue at the RwLock, and will be signalled once ready.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn try_lock(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        match wait_node.state {
+            PollState::New => {
+                // The fast path - the RwLock is available.
+                // If the lock is fair, noone must be in the wait list before us.
+                if self.try_lock_sync(wait_node.kind) {
+                    wait_node.state = PollState::Done;
+                    Poll::Ready(())
+                } else {
+                    // Add the task to the wait queue
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = PollState::Waiting;
+                    self.waiters.add_front(wait_node);
+                    Poll::Pending
+                }
+            }
+            PollState::Waiting => {
+                // The future is already in the queue.
+                if !self.is_fair && self.is_available(wait_node.kind) {
+                    // For throughput improvement purposes, grab the lock
+                    // immediately if it's available.
+                    self.acquire(wait_node.kind);
+                    wait_node.state = PollState::Done;
+                    // Since this waiter has been registered before, it must
+                    // get removed from the waiter list.
+                    // Safety: Due to the state, we know that the node must be part
+                    // of the waiter list
+                    self.force_remove_waiter(wait_node);
+                    Poll::Ready(())
+                } else {
+                    // The task needs to wait until it gets notified.
+                    // However the caller might have passed a different `Waker`.
+                    // In this case we need to update it.
+                    update_waker_ref(&mut wait_node.task, cx);
+                    Poll::Pending
+                }
+            }
+            PollState::Notified => {
+                // We had been woken by the RwLock, since it is available again.
+                // Just try to lock again. If the lock isn't available,
+                // we need to add it to the wait queue again.
+                if self.is_available(wait_node.kind) {
+                    if self.is_fair {
+                        // In a fair RwLock, the WaitQueueEntry is kept in the
+                        // linked list and must be removed here
+                        // Safety: Due to the state, we know that the node must be part
+                        // of the waiter list
+                        self.force_remove_waiter(wait_node);
+                    }
+                    self.acquire(wait_node.kind);
+                    wait_node.state = PollState::Done;
+                    if self.is_fair {
+                        // There might be other readers which are ready to run,
+                        // but had been waiting behind this waiter.
+                        self.wakeup_waiters();
+                    }
+                    Poll::Ready(())
+                } else if self.is_fair {
+                    // A pending upgrade might have taken precedence over the
+                    // notified waiter. The waiter keeps its position in the
+                    // queue and will get notified again.
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = PollState::Waiting;
+                    Poll::Pending
+                } else {
+                    // Add to queue
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = PollState::Waiting;
+                    self.waiters.add_front(wait_node);
+                    Poll::Pending
+                }
+            }
+            PollState::Done => {
+                // The future had been polled to completion before
+                panic!("polled RwLock after completion");
+            }
+        }
+    }
+
+    /// Tries to remove a waiter from the wait queue, and panics if the
+    /// waiter is no longer valid.
+    unsafe fn force_remove_waiter(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+    ) {
+        if !self.waiters.remove(wait_node) {
+            // Panic if the address isn't found. This can only happen if the contract was
+            // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+            panic!("Future could not be removed from wait queue");
+        }
+    }
+
+    /// Removes the waiter from the list.
+    ///
+    /// This function is only safe as long as the reference that is passed here
+    /// equals the reference/address under which the waiter was added.
+    /// The waiter must not have been moved in between.
+    fn remove_waiter(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        // The future only needs to get removed if it had been added to
+        // the wait queue of the RwLock. This has happened in the PollState::Waiting case.
+        // If the current waiter was notified, another waiter must get notified now.
+        match wait_node.state {
+            PollState::Notified => {
+                if self.is_fair {
+                    // In a fair RwLock, the WaitQueueEntry is kept in the
+                    // linked list and must be removed here
+                    // Safety: Due to the state, we know that the node must be part
+                    // of the waiter list
+                    unsafe { self.force_remove_waiter(wait_node) };
+                }
+                wait_node.state = PollState::Done;
+                // Since the task was notified but did not lock the RwLock,
+                // other tasks get the chance to run.
+                self.wakeup_waiters();
+            }
+            PollState::Waiting => {
+                // Remove the WaitQueueEntry from the linked list
+                // Safety: Due to the state, we know that the node must be part
+                // of the waiter list
+                unsafe { self.force_remove_waiter(wait_node) };
+                wait_node.state = PollState::Done;
+                if self.is_fair {
+                    // The waiter might have blocked the waiters behind it,
+                    // which might be able to obtain the lock now.
+                    self.wakeup_waiters();
+                }
+            }
+            PollState::New | PollState::Done => {}
+        }
+    }
+}
+
+/// An RAII guard returned by the `read` and `try_read` methods.
+/// When this structure is dropped (falls out of scope), the shared read access
+/// will be released.
+pub struct GenericRwLockReadGuard<'a, MutexType: RawMutex, T: 'a> {
+    /// The RwLock which is associated with this Guard
+    rwlock: &'a GenericRwLock<MutexType, T>,
+}
+
+impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericRwLockReadGuard<'_, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericRwLockReadGuard").finish()
+    }
+}
+
+impl<MutexType: RawMutex, T> Drop for GenericRwLockReadGuard<'_, MutexType, T> {
+    fn drop(&mut self) {
+        // Release the read lock
+        self.rwlock.state.lock().unlock_read();
+    }
+}
+
+impl<MutexType: RawMutex, T> Deref
+    for GenericRwLockReadGuard<'_, MutexType, T>
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.rwlock.value.get() }
+    }
+}
+
+// Safety: GenericRwLockReadGuard may only be used across threads if the
+// underlying type is Sync.
+unsafe impl<MutexType: RawMutex, T: Sync> Sync
+    for GenericRwLockReadGuard<'_, MutexType, T>
+{
+}
+
+/// An RAII guard returned by the `write` and `try_write` methods.
+/// When this structure is dropped (falls out of scope), the exclusive write
+/// access will be released.
+pub struct GenericRwLockWriteGuard<'a, MutexType: RawMutex, T: 'a> {
+    /// The RwLock which is associated with this Guard
+    rwlock: &'a GenericRwLock<MutexType, T>,
+}
+
+impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericRwLockWriteGuard<'_, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericRwLockWriteGuard").finish()
+    }
+}
+
+impl<MutexType: RawMutex, T> Drop
+    for GenericRwLockWriteGuard<'_, MutexType, T>
+{
+    fn drop(&mut self) {
+        // Release the write lock
+        self.rwlock.state.lock().unlock_write();
+    }
+}
+
+impl<MutexType: RawMutex, T> Deref
+    for GenericRwLockWriteGuard<'_, MutexType, T>
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.rwlock.value.get() }
+    }
+}
+
+impl<MutexType: RawMutex, T> DerefMut
+    for GenericRwLockWriteGuard<'_, MutexType, T>
+{
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.rwlock.value.get() }
+    }
+}
+
+// Safety: GenericRwLockWriteGuard may only be used across threads if the
+// underlying type is Sync.
+unsafe impl<MutexType: RawMutex, T: Sync> Sync
+    for GenericRwLockWriteGuard<'_, MutexType, T>
+{
+}
+
+impl<'a, MutexType: RawMutex, T> GenericRwLockWriteGuard<'a, MutexType, T> {
+    /// Atomically converts the write guard into a read guard.
+    ///
+    /// No other writer can obtain the lock in between. Readers which had been
+    /// waiting for the writer are allowed to obtain the lock afterwards.
+    pub fn downgrade(guard: Self) -> GenericRwLockReadGuard<'a, MutexType, T> {
+        let rwlock = guard.rwlock;
+        core::mem::forget(guard);
+        rwlock.state.lock().downgrade_write(LockKind::Read);
+        GenericRwLockReadGuard { rwlock }
+    }
+
+    /// Atomically converts the write guard into an upgradable read guard.
+    ///
+    /// No other writer can obtain the lock in between.
+    pub fn downgrade_to_upgradable(
+        guard: Self,
+    ) -> GenericRwLockUpgradableReadGuard<'a, MutexType, T> {
+        let rwlock = guard.rwlock;
+        core::mem::forget(guard);
+        rwlock
+            .state
+            .lock()
+            .downgrade_write(LockKind::UpgradableRead);
+        GenericRwLockUpgradableReadGuard { rwlock }
+    }
+}
+
+/// An RAII guard returned by the `upgradable_read` and `try_upgradable_read`
+/// methods.
+///
+/// The guard provides shared read access, and can coexist with other readers.
+/// However only a single upgradable read guard can exist at any point of time.
+/// The guard can be upgraded into a write guard without allowing other
+/// writers to obtain the lock in between.
+/// When this structure is dropped (falls out of scope), the read access will
+/// be released.
+pub struct GenericRwLockUpgradableReadGuard<'a, MutexType: RawMutex, T: 'a> {
+    /// The RwLock which is associated with this Guard
+    rwlock: &'a GenericRwLock<MutexType, T>,
+}
+
+impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericRwLockUpgradableReadGuard<'_, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericRwLockUpgradableReadGuard").finish()
+    }
+}
+
+impl<MutexType: RawMutex, T> Drop
+    for GenericRwLockUpgradableReadGuard<'_, MutexType, T>
+{
+    fn drop(&mut self) {
+        // Release the upgradable read lock
+        self.rwlock.state.lock().unlock_upgradable_read();
+    }
+}
+
+impl<MutexType: RawMutex, T> Deref
+    for GenericRwLockUpgradableReadGuard<'_, MutexType, T>
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.rwlock.value.get() }
+    }
+}
+
+// Safety: GenericRwLockUpgradableReadGuard may only be used across threads if
+// the underlying type is Sync.
+unsafe impl<MutexType: RawMutex, T: Sync> Sync
+    for GenericRwLockUpgradableReadGuard<'_, MutexType, T>
+{
+}
+
+impl<'a, MutexType: RawMutex, T>
+    GenericRwLockUpgradableReadGuard<'a, MutexType, T>
+{
+    /// Upgrades the guard to a write guard asynchronously.
+    ///
+    /// This method returns a future that will resolve once all other readers
+    /// have released the lock. While the upgrade is pending, no new readers
+    /// are allowed to obtain the lock.
+    ///
+    /// If the returned future is dropped before it completes, the upgradable
+    /// read lock is released.
+    pub fn upgrade(
+        guard: Self,
+    ) -> GenericRwLockUpgradeFuture<'a, MutexType, T> {
+        let rwlock = guard.rwlock;
+        core::mem::forget(guard);
+        GenericRwLockUpgradeFuture {
+            rwlock: Some(rwlock),
+            wait_node: ListNode::new(WaitQueueEntry::new(LockKind::Write)),
+        }
+    }
+
+    /// Tries to upgrade the guard to a write guard.
+    ///
+    /// If no other readers hold the lock, the upgrade succeeds and a
+    /// [`GenericRwLockWriteGuard`] is returned. Otherwise the original guard
+    /// gets returned.
+    pub fn try_upgrade(
+        guard: Self,
+    ) -> Result<GenericRwLockWriteGuard<'a, MutexType, T>, Self> {
+        if guard.rwlock.state.lock().try_upgrade_sync() {
+            let rwlock = guard.rwlock;
+            core::mem::forget(guard);
+            Ok(GenericRwLockWriteGuard { rwlock })
+        } else {
+            Err(guard)
+        }
+    }
+
+    /// Atomically converts the upgradable read guard into a normal read guard.
+    ///
+    /// Another upgradable reader or a writer can obtain the lock afterwards.
+    pub fn downgrade(guard: Self) -> GenericRwLockReadGuard<'a, MutexType, T> {
+        let rwlock = guard.rwlock;
+        core::mem::forget(guard);
+        rwlock.state.lock().downgrade_upgradable_read();
+        GenericRwLockReadGuard { rwlock }
+    }
+}
+
+/// A future which resolves when shared read access to the target RwLock has
+/// been obtained.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericRwLockReadFuture<'a, MutexType: RawMutex, T: 'a> {
+    /// The RwLock which should get locked trough this Future
+    rwlock: Option<&'a GenericRwLock<MutexType, T>>,
+    /// Node for waiting at the RwLock
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// RwLock is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: 'a> Send
+    for GenericRwLockReadFuture<'a, MutexType, T>
+{
+}
+
+impl<'a, MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericRwLockReadFuture<'a, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericRwLockReadFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Future
+    for GenericRwLockReadFuture<'a, MutexType, T>
+{
+    type Output = GenericRwLockReadGuard<'a, MutexType, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside GenericRwLockReadFuture is stable,
+        // and we don't move any fields inside the future until it gets dropped.
+        let mut_self: &mut GenericRwLockReadFuture<MutexType, T> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let rwlock = mut_self
+            .rwlock
+            .expect("polled GenericRwLockReadFuture after completion");
+        let mut rwlock_state = rwlock.state.lock();
+
+        let poll_res =
+            unsafe { rwlock_state.try_lock(&mut mut_self.wait_node, cx) };
+
+        match poll_res {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(()) => {
+                // The read lock was acquired
+                mut_self.rwlock = None;
+                Poll::Ready(GenericRwLockReadGuard::<'a, MutexType, T> {
+                    rwlock,
+                })
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> FusedFuture
+    for GenericRwLockReadFuture<'a, MutexType, T>
+{
+    fn is_terminated(&self) -> bool {
+        self.rwlock.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Drop
+    for GenericRwLockReadFuture<'a, MutexType, T>
+{
+    fn drop(&mut self) {
+        // If this GenericRwLockReadFuture has been polled and it was added to the
+        // wait queue at the RwLock, it must be removed before dropping.
+        // Otherwise the RwLock would access invalid memory.
+        if let Some(rwlock) = self.rwlock {
+            let mut rwlock_state = rwlock.state.lock();
+            rwlock_state.remove_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+/// A future which resolves when exclusive write access to the target RwLock
+/// has been obtained.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericRwLockWriteFuture<'a, MutexType: RawMutex, T: 'a> {
+    /// The RwLock which should get locked trough this Future
+    rwlock: Option<&'a GenericRwLock<MutexType, T>>,
+    /// Node for waiting at the RwLock
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// RwLock is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: 'a> Send
+    for GenericRwLockWriteFuture<'a, MutexType, T>
+{
+}
+
+impl<'a, MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericRwLockWriteFuture<'a, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericRwLockWriteFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Future
+    for GenericRwLockWriteFuture<'a, MutexType, T>
+{
+    type Output = GenericRwLockWriteGuard<'a, MutexType, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside GenericRwLockWriteFuture is stable,
+        // and we don't move any fields inside the future until it gets dropped.
+        let mut_self: &mut GenericRwLockWriteFuture<MutexType, T> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let rwlock = mut_self
+            .rwlock
+            .expect("polled GenericRwLockWriteFuture after completion");
+        let mut rwlock_state = rwlock.state.lock();
+
+        let poll_res =
+            unsafe { rwlock_state.try_lock(&mut mut_self.wait_node, cx) };
+
+        match poll_res {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(()) => {
+                // The write lock was acquired
+                mut_self.rwlock = None;
+                Poll::Ready(GenericRwLockWriteGuard::<'a, MutexType, T> {
+                    rwlock,
+                })
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> FusedFuture
+    for GenericRwLockWriteFuture<'a, MutexType, T>
+{
+    fn is_terminated(&self) -> bool {
+        self.rwlock.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Drop
+    for GenericRwLockWriteFuture<'a, MutexType, T>
+{
+    fn drop(&mut self) {
+        // If this GenericRwLockWriteFuture has been polled and it was added to the
+        // wait queue at the RwLock, it must be removed before dropping.
+        // Otherwise the RwLock would access invalid memory.
+        if let Some(rwlock) = self.rwlock {
+            let mut rwlock_state = rwlock.state.lock();
+            rwlock_state.remove_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+/// A future which resolves when upgradable read access to the target RwLock
+/// has been obtained.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericRwLockUpgradableReadFuture<'a, MutexType: RawMutex, T: 'a> {
+    /// The RwLock which should get locked trough this Future
+    rwlock: Option<&'a GenericRwLock<MutexType, T>>,
+    /// Node for waiting at the RwLock
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// RwLock is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: 'a> Send
+    for GenericRwLockUpgradableReadFuture<'a, MutexType, T>
+{
+}
+
+impl<'a, MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericRwLockUpgradableReadFuture<'a, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericRwLockUpgradableReadFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Future
+    for GenericRwLockUpgradableReadFuture<'a, MutexType, T>
+{
+    type Output = GenericRwLockUpgradableReadGuard<'a, MutexType, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside
+        // GenericRwLockUpgradableReadFuture is stable,
+        // and we don't move any fields inside the future until it gets dropped.
+        let mut_self: &mut GenericRwLockUpgradableReadFuture<MutexType, T> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let rwlock = mut_self.rwlock.expect(
+            "polled GenericRwLockUpgradableReadFuture after completion",
+        );
+        let mut rwlock_state = rwlock.state.lock();
+
+        let poll_res =
+            unsafe { rwlock_state.try_lock(&mut mut_self.wait_node, cx) };
+
+        match poll_res {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(()) => {
+                // The upgradable read lock was acquired
+                mut_self.rwlock = None;
+                Poll::Ready(GenericRwLockUpgradableReadGuard::<
+                    'a,
+                    MutexType,
+                    T,
+                > {
+                    rwlock,
+                })
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> FusedFuture
+    for GenericRwLockUpgradableReadFuture<'a, MutexType, T>
+{
+    fn is_terminated(&self) -> bool {
+        self.rwlock.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Drop
+    for GenericRwLockUpgradableReadFuture<'a, MutexType, T>
+{
+    fn drop(&mut self) {
+        // If this GenericRwLockUpgradableReadFuture has been polled and it was
+        // added to the wait queue at the RwLock, it must be removed before dropping.
+        // Otherwise the RwLock would access invalid memory.
+        if let Some(rwlock) = self.rwlock {
+            let mut rwlock_state = rwlock.state.lock();
+            rwlock_state.remove_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+/// A future which resolves when an upgradable read lock has been upgraded to
+/// exclusive write access.
+///
+/// The future holds the upgradable read lock until it completes. If it gets
+/// dropped before completion, the upgradable read lock is released.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericRwLockUpgradeFuture<'a, MutexType: RawMutex, T: 'a> {
+    /// The RwLock which should get upgraded trough this Future
+    rwlock: Option<&'a GenericRwLock<MutexType, T>>,
+    /// Node for waiting at the RwLock
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// RwLock is thread-safe (Sync), which allows to poll/register/unregister from
+// a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync, T: 'a> Send
+    for GenericRwLockUpgradeFuture<'a, MutexType, T>
+{
+}
+
+impl<'a, MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericRwLockUpgradeFuture<'a, MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericRwLockUpgradeFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Future
+    for GenericRwLockUpgradeFuture<'a, MutexType, T>
+{
+    type Output = GenericRwLockWriteGuard<'a, MutexType, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside GenericRwLockUpgradeFuture
+        // is stable, and we don't move any fields inside the future until it
+        // gets dropped.
+        let mut_self: &mut GenericRwLockUpgradeFuture<MutexType, T> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let rwlock = mut_self
+            .rwlock
+            .expect("polled GenericRwLockUpgradeFuture after completion");
+        let mut rwlock_state = rwlock.state.lock();
+
+        let poll_res =
+            unsafe { rwlock_state.try_upgrade(&mut mut_self.wait_node, cx) };
+
+        match poll_res {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(()) => {
+                // The write lock was acquired
+                mut_self.rwlock = None;
+                Poll::Ready(GenericRwLockWriteGuard::<'a, MutexType, T> {
+                    rwlock,
+                })
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> FusedFuture
+    for GenericRwLockUpgradeFuture<'a, MutexType, T>
+{
+    fn is_terminated(&self) -> bool {
+        self.rwlock.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex, T> Drop
+    for GenericRwLockUpgradeFuture<'a, MutexType, T>
+{
+    fn drop(&mut self) {
+        // If the upgrade didn't complete, the future still owns the
+        // upgradable read lock. It must be removed from the wait queue and
+        // release the lock.
+        if let Some(rwlock) = self.rwlock {
+            let mut rwlock_state = rwlock.state.lock();
+            rwlock_state.cancel_upgrade(&mut self.wait_node);
+        }
+    }
+}
+
+/// A futures-aware reader-writer lock.
+///
+/// The lock allows an arbitrary amount of readers or a single writer to
+/// access the protected data at any point of time.
+///
+/// In addition to this a single upgradable reader can hold the lock together
+/// with normal readers. The upgradable reader can later on be upgraded to a
+/// writer without allowing other writers to obtain the lock in between.
+pub struct GenericRwLock<MutexType: RawMutex, T> {
+    value: UnsafeCell<T>,
+    state: LockApiMutex<MutexType, RwLockState>,
+}
+
+// It is safe to send RwLocks between threads, as long as they are not used and
+// thereby borrowed
+unsafe impl<T: Send, MutexType: RawMutex + Send> Send
+    for GenericRwLock<MutexType, T>
+{
+}
+// The RwLock is thread-safe as long as the utilized mutex is thread-safe.
+// Since readers access the value concurrently, it must also be Sync.
+unsafe impl<T: Send + Sync, MutexType: RawMutex + Sync> Sync
+    for GenericRwLock<MutexType, T>
+{
+}
+
+impl<MutexType: RawMutex, T: core::fmt::Debug> core::fmt::Debug
+    for GenericRwLock<MutexType, T>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("RwLock")
+            .field("readers", &state.holders.readers)
+            .field(
+                "has_upgradable_reader",
+                &state.holders.has_upgradable_reader,
+            )
+            .field("is_write_locked", &state.holders.is_write_locked)
+            .finish()
+    }
+}
+
+impl<MutexType: RawMutex, T> GenericRwLock<MutexType, T> {
+    /// Creates a new futures-aware reader-writer lock.
+    ///
+    /// `is_fair` defines whether the `RwLock` should behave be fair regarding
+    /// the order of waiters. A fair `RwLock` will only allow the first waiter
+    /// which tried to lock but failed to lock the `RwLock` once it's available
+    /// again. If that waiter is a reader, the readers which are queued directly
+    /// behind it are allowed to lock the `RwLock` afterwards.
+    /// New lock attempts will not succeed as long as there are waiters queued.
+    ///
+    /// On an unfair `RwLock` a constant stream of readers might prevent a
+    /// writer from ever obtaining the lock.
+    pub const fn new(value: T, is_fair: bool) -> GenericRwLock<MutexType, T> {
+        GenericRwLock::<MutexType, T> {
+            value: UnsafeCell::new(value),
+            state: LockApiMutex::const_new(
+                MutexType::INIT,
+                RwLockState::new(is_fair),
+            ),
+        }
+    }
+
+    /// Acquire shared read access to the RwLock asynchronously.
+    ///
+    /// This method returns a future that will resolve once read access has
+    /// been successfully obtained.
+    pub fn read(&self) -> GenericRwLockReadFuture<'_, MutexType, T> {
+        GenericRwLockReadFuture::<MutexType, T> {
+            rwlock: Some(self),
+            wait_node: ListNode::new(WaitQueueEntry::new(LockKind::Read)),
+        }
+    }
+
+    /// Acquire exclusive write access to the RwLock asynchronously.
+    ///
+    /// This method returns a future that will resolve once write access has
+    /// been successfully obtained.
+    pub fn write(&self) -> GenericRwLockWriteFuture<'_, MutexType, T> {
+        GenericRwLockWriteFuture::<MutexType, T> {
+            rwlock: Some(self),
+            wait_node: ListNode::new(WaitQueueEntry::new(LockKind::Write)),
+        }
+    }
+
+    /// Acquire upgradable read access to the RwLock asynchronously.
+    ///
+    /// This method returns a future that will resolve once upgradable read
+    /// access has been successfully obtained. Upgradable read access is
+    /// compatible with normal readers, but excludes writers and other
+    /// upgradable readers.
+    pub fn upgradable_read(
+        &self,
+    ) -> GenericRwLockUpgradableReadFuture<'_, MutexType, T> {
+        GenericRwLockUpgradableReadFuture::<MutexType, T> {
+            rwlock: Some(self),
+            wait_node: ListNode::new(WaitQueueEntry::new(
+                LockKind::UpgradableRead,
+            )),
+        }
+    }
+
+    /// Tries to acquire shared read access to the RwLock
+    ///
+    /// If acquiring the lock is successful, a [`GenericRwLockReadGuard`]
+    /// will be returned, which allows to access the contained data.
+    ///
+    /// Otherwise `None` will be returned.
+    pub fn try_read(&self) -> Option<GenericRwLockReadGuard<'_, MutexType, T>> {
+        if self.state.lock().try_lock_sync(LockKind::Read) {
+            Some(GenericRwLockReadGuard { rwlock: self })
+        } else {
+            None
+        }
+    }
+
+    /// Tries to acquire exclusive write access to the RwLock
+    ///
+    /// If acquiring the lock is successful, a [`GenericRwLockWriteGuard`]
+    /// will be returned, which allows to modify the contained data.
+    ///
+    /// Otherwise `None` will be returned.
+    pub fn try_write(
+        &self,
+    ) -> Option<GenericRwLockWriteGuard<'_, MutexType, T>> {
+        if self.state.lock().try_lock_sync(LockKind::Write) {
+            Some(GenericRwLockWriteGuard { rwlock: self })
+        } else {
+            None
+        }
+    }
+
+    /// Tries to acquire upgradable read access to the RwLock
+    ///
+    /// If acquiring the lock is successful, a
+    /// [`GenericRwLockUpgradableReadGuard`] will be returned, which allows to
+    /// access the contained data.
+    ///
+    /// Otherwise `None` will be returned.
+    pub fn try_upgradable_read(
+        &self,
+    ) -> Option<GenericRwLockUpgradableReadGuard<'_, MutexType, T>> {
+        if self.state.lock().try_lock_sync(LockKind::UpgradableRead) {
+            Some(GenericRwLockUpgradableReadGuard { rwlock: self })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the amount of readers which currently hold the lock.
+    ///
+    /// An upgradable reader is not included in this count.
+    pub fn readers(&self) -> usize {
+        self.state.lock().holders.readers
+    }
+
+    /// Returns whether an upgradable reader holds the RwLock.
+    pub fn has_upgradable_reader(&self) -> bool {
+        self.state.lock().holders.has_upgradable_reader
+    }
+
+    /// Returns whether the RwLock is locked by a writer.
+    pub fn is_write_locked(&self) -> bool {
+        self.state.lock().holders.is_write_locked
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericRwLock`] which is not thread-safe.
+pub type LocalRwLock<T> = GenericRwLock<NoopLock, T>;
+/// A [`GenericRwLockReadGuard`] for [`LocalRwLock`].
+pub type LocalRwLockReadGuard<'a, T> = GenericRwLockReadGuard<'a, NoopLock, T>;
+/// A [`GenericRwLockWriteGuard`] for [`LocalRwLock`].
+pub type LocalRwLockWriteGuard<'a, T> =
+    GenericRwLockWriteGuard<'a, NoopLock, T>;
+/// A [`GenericRwLockUpgradableReadGuard`] for [`LocalRwLock`].
+pub type LocalRwLockUpgradableReadGuard<'a, T> =
+    GenericRwLockUpgradableReadGuard<'a, NoopLock, T>;
+/// A [`GenericRwLockReadFuture`] for [`LocalRwLock`].
+pub type LocalRwLockReadFuture<'a, T> =
+    GenericRwLockReadFuture<'a, NoopLock, T>;
+/// A [`GenericRwLockWriteFuture`] for [`LocalRwLock`].
+pub type LocalRwLockWriteFuture<'a, T> =
+    GenericRwLockWriteFuture<'a, NoopLock, T>;
+/// A [`GenericRwLockUpgradableReadFuture`] for [`LocalRwLock`].
+pub type LocalRwLockUpgradableReadFuture<'a, T> =
+    GenericRwLockUpgradableReadFuture<'a, NoopLock, T>;
+/// A [`GenericRwLockUpgradeFuture`] for [`LocalRwLock`].
+pub type LocalRwLockUpgradeFuture<'a, T> =
+    GenericRwLockUpgradeFuture<'a, NoopLock, T>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericRwLock`] backed by [`parking_lot`].
+    pub type RwLock<T> = GenericRwLock<parking_lot::RawMutex, T>;
+    /// A [`GenericRwLockReadGuard`] for [`RwLock`].
+    pub type RwLockReadGuard<'a, T> =
+        GenericRwLockReadGuard<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericRwLockWriteGuard`] for [`RwLock`].
+    pub type RwLockWriteGuard<'a, T> =
+        GenericRwLockWriteGuard<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericRwLockUpgradableReadGuard`] for [`RwLock`].
+    pub type RwLockUpgradableReadGuard<'a, T> =
+        GenericRwLockUpgradableReadGuard<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericRwLockReadFuture`] for [`RwLock`].
+    pub type RwLockReadFuture<'a, T> =
+        GenericRwLockReadFuture<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericRwLockWriteFuture`] for [`RwLock`].
+    pub type RwLockWriteFuture<'a, T> =
+        GenericRwLockWriteFuture<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericRwLockUpgradableReadFuture`] for [`RwLock`].
+    pub type RwLockUpgradableReadFuture<'a, T> =
+        GenericRwLockUpgradableReadFuture<'a, parking_lot::RawMutex, T>;
+    /// A [`GenericRwLockUpgradeFuture`] for [`RwLock`].
+    pub type RwLockUpgradeFuture<'a, T> =
+        GenericRwLockUpgradeFuture<'a, parking_lot::RawMutex, T>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
@@ -1,10 +1,9 @@
 //! An asynchronously awaitable semaphore for synchronization between concurrently
 //! executing futures.
 
+use super::priority_wait_queue::{PriorityWaitQueue, WaitNode};
 use crate::{
-    intrusive_double_linked_list::{LinkedList, ListNode},
-    utils::update_waker_ref,
-    NoopLock,
+    intrusive_double_linked_list::LinkedList, utils::update_waker_ref, NoopLock,
 };
 use core::pin::Pin;
 use futures_core::{
@@ -13,6 +12,22 @@ use futures_core::{
 };
 use lock_api::{Mutex as LockApiMutex, RawMutex};
 
+/// The error which is returned when acquiring permits from a semaphore fails.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum AcquireError {
+    /// The semaphore had been closed. No more permits can be acquired.
+    Closed,
+}
+
+impl AcquireError {
+    /// Returns whether the error is the `Closed` variant.
+    pub fn is_closed(self) -> bool {
+        match self {
+            Self::Closed => true,
+        }
+    }
+}
+
 /// Tracks how the future had interacted with the semaphore
 #[derive(PartialEq)]
 enum PollState {
@@ -24,6 +39,9 @@ enum PollState {
     /// that the semaphore was released in the meantime and that the task
     /// thereby could retry.
     Notified,
+    /// The requested permits had been handed over to the task by the
+    /// semaphore, and the task was removed from the wait queue.
+    Granted,
     /// The task had been polled to completion.
     Done,
 }
@@ -52,24 +70,112 @@ impl WaitQueueEntry {
 /// Internal state of the `Semaphore`
 struct SemaphoreState {
     is_fair: bool,
+    is_closed: bool,
     permits: usize,
     waiters: LinkedList<WaitQueueEntry>,
+    /// Waiters which had been registered with a priority. Those are served
+    /// before all waiters in `waiters`.
+    priority_waiters: PriorityWaitQueue<WaitQueueEntry>,
 }
 
 impl SemaphoreState {
-    fn new(is_fair: bool, permits: usize) -> Self {
+    const fn new(is_fair: bool, permits: usize) -> Self {
         SemaphoreState {
             is_fair,
+            is_closed: false,
             permits,
             waiters: LinkedList::new(),
+            priority_waiters: PriorityWaitQueue::new(),
+        }
+    }
+
+    /// Closes the semaphore.
+    ///
+    /// All pending acquire attempts get woken up and removed from the wait
+    /// queue. They will fail once they get polled again.
+    fn close(&mut self) {
+        if self.is_closed {
+            return;
+        }
+        self.is_closed = true;
+
+        // Wakeup all waiters. The waiters will check the closed state
+        // once they get polled.
+        // Safety: The waiters are removed from the list before they
+        // get woken up.
+        self.waiters.reverse_drain(|waiter| {
+            if let Some(handle) = waiter.task.take() {
+                handle.wake();
+            }
+            waiter.state = PollState::New;
+        });
+        while let Some(waiter) = self.priority_waiters.remove_first() {
+            if let Some(handle) = waiter.task.take() {
+                handle.wake();
+            }
+            waiter.state = PollState::New;
         }
     }
 
-    /// Wakes up the last waiter and removes it from the wait queue
+    fn is_closed(&self) -> bool {
+        self.is_closed
+    }
+
+    /// Wakes up as many waiters as the available permits allow, starting with
+    /// the oldest waiter.
+    ///
+    /// Waiters with a priority take precedence over all other waiters. The
+    /// permits are handed over to them directly in order of their priority.
     fn wakeup_waiters(&mut self) {
+        if !self.priority_waiters.is_empty() {
+            // Permits which had been promised to notified waiters can't be
+            // handed over anymore
+            let reserved = self.reserved_permits();
+            while let Some(waiter) = self.priority_waiters.peek_first() {
+                // Waiters with a lower priority are not allowed to overtake
+                // the first waiter
+                if self.permits.saturating_sub(reserved)
+                    < waiter.required_permits
+                {
+                    return;
+                }
+                self.permits -= waiter.required_permits;
+                waiter.state = PollState::Granted;
+                let task = waiter.task.take();
+                self.priority_waiters.remove_first();
+                if let Some(handle) = task {
+                    handle.wake();
+                }
+            }
+        }
+
         // Wake as many tasks as the permits allow
         let mut available = self.permits;
 
+        if self.is_fair {
+            // In a fair Semaphore waiters are notified in FIFO order, until the
+            // oldest remaining waiter can't be satisfied anymore. Skipping it
+            // would allow newer waiters to starve it.
+            // The notified waiters are kept in the wait queue until they
+            // acquired their permits. Since newer tasks can't acquire the
+            // Semaphore as long as there are waiters, the permits which are
+            // accounted for here are guaranteed to still be available then.
+            for waiter in self.waiters.reverse_iter_mut() {
+                if available < waiter.required_permits {
+                    return;
+                }
+                available -= waiter.required_permits;
+
+                if waiter.state != PollState::Notified {
+                    waiter.state = PollState::Notified;
+                    if let Some(ref handle) = waiter.task {
+                        handle.wake_by_ref();
+                    }
+                }
+            }
+            return;
+        }
+
         loop {
             match self.waiters.peek_last() {
                 None => return,
@@ -97,16 +203,7 @@ impl SemaphoreState {
                     // In the case of a non-fair semaphore, the waiters are directly
                     // removed from the semaphores wait queue when woken.
                     // That avoids having to remove the wait element later.
-                    if !self.is_fair {
-                        self.waiters.remove_last();
-                    } else {
-                        // For a fair Semaphore we never wake more than 1 task.
-                        // That one needs to acquire the Semaphore.
-                        // TODO: We actually should be able to wake more, since
-                        // it's guaranteed that both tasks could make progress.
-                        // However the we currently can't peek iterate in reverse order.
-                        return;
-                    }
+                    self.waiters.remove_last();
                 }
             }
         }
@@ -117,17 +214,51 @@ impl SemaphoreState {
     }
 
     /// Releases a certain amount of permits back to the semaphore
+    ///
+    /// Panics if the amount of permits would overflow.
     fn release(&mut self, permits: usize) {
         if permits == 0 {
             return;
         }
-        // TODO: Overflow check
-        self.permits += permits;
+        self.permits = self
+            .permits
+            .checked_add(permits)
+            .expect("Semaphore permit overflow");
 
         // Wakeup the last waiter
         self.wakeup_waiters();
     }
 
+    /// Returns the amount of available permits which had been promised to
+    /// notified waiters.
+    fn reserved_permits(&mut self) -> usize {
+        // Only notified waiters of a fair Semaphore are guaranteed to obtain
+        // their permits. Those waiters are the oldest ones, and are therefore
+        // located at the end of the wait queue.
+        if self.is_fair {
+            self.waiters
+                .reverse_iter_mut()
+                .take_while(|waiter| waiter.state == PollState::Notified)
+                .map(|waiter| waiter.required_permits)
+                .sum()
+        } else {
+            0
+        }
+    }
+
+    /// Removes up to `permits` of the available permits from the semaphore.
+    ///
+    /// Returns the amount of permits which had actually been removed.
+    fn forget_permits(&mut self, permits: usize) -> usize {
+        // Permits which had been promised to notified waiters can't be
+        // removed anymore.
+        let reserved = self.reserved_permits();
+        let forgotten =
+            core::cmp::min(permits, self.permits.saturating_sub(reserved));
+        self.permits -= forgotten;
+        forgotten
+    }
+
     /// Tries to acquire the given amount of permits synchronously.
     ///
     /// Returns true if the permits were obtained and false otherwise.
@@ -135,11 +266,16 @@ impl SemaphoreState {
         // Permits can only be obtained synchronously if there are
         // - enough permits available
         // - the Semaphore is either not fair, or there are no waiters
+        // - there are no waiters with a priority
         // - required_permits == 0
+        // - the Semaphore hasn't been closed
+        if self.is_closed {
+            return false;
+        }
         if (self.permits >= required_permits)
-            && (!self.is_fair
-                || self.waiters.is_empty()
-                || required_permits == 0)
+            && (required_permits == 0
+                || (self.priority_waiters.is_empty()
+                    && (!self.is_fair || self.waiters.is_empty())))
         {
             self.permits -= required_permits;
             true
@@ -155,28 +291,40 @@ impl SemaphoreState {
     /// to be stable until it gets removed from the queue.
     unsafe fn try_acquire(
         &mut self,
-        wait_node: &mut ListNode<WaitQueueEntry>,
+        wait_node: &mut WaitNode<WaitQueueEntry>,
         cx: &mut Context<'_>,
-    ) -> Poll<()> {
+    ) -> Poll<Result<(), AcquireError>> {
+        if self.is_closed
+            && wait_node.state != PollState::Done
+            && wait_node.state != PollState::Granted
+        {
+            // Closing the semaphore removed all waiters from the wait queue.
+            // Notified waiters of an unfair semaphore had already been removed
+            // before. Granted waiters already own their permits.
+            wait_node.state = PollState::Done;
+            return Poll::Ready(Err(AcquireError::Closed));
+        }
+
         match wait_node.state {
             PollState::New => {
                 // The fast path - enough permits are available
                 if self.try_acquire_sync(wait_node.required_permits) {
                     wait_node.state = PollState::Done;
-                    Poll::Ready(())
+                    Poll::Ready(Ok(()))
                 } else {
                     // Add the task to the wait queue
                     wait_node.task = Some(cx.waker().clone());
                     wait_node.state = PollState::Waiting;
-                    self.waiters.add_front(wait_node);
+                    self.add_waiter(wait_node);
                     Poll::Pending
                 }
             }
             PollState::Waiting => {
                 // The SemaphoreAcquireFuture is already in the queue.
-                if self.is_fair {
+                if self.is_fair || !self.priority_waiters.is_empty() {
                     // The task needs to wait until it gets notified in order to
-                    // maintain the ordering.
+                    // maintain the ordering. Waiters with a priority wait until
+                    // the permits get handed over to them.
                     // However the caller might have passed a different `Waker`.
                     // In this case we need to update it.
                     update_waker_ref(&mut wait_node.task, cx);
@@ -192,7 +340,7 @@ impl SemaphoreState {
                         // Safety: Due to the state, we know that the node must be part
                         // of the waiter list
                         self.force_remove_waiter(wait_node);
-                        Poll::Ready(())
+                        Poll::Ready(Ok(()))
                     } else {
                         // The caller might have passed a different `Waker`.
                         // In this case we need to update it.
@@ -206,7 +354,11 @@ impl SemaphoreState {
                 // The semaphore thereby removed us from the waiters list.
                 // Just try to lock again. If the semaphore isn't available,
                 // we need to add it to the wait queue again.
-                if self.permits >= wait_node.required_permits {
+                // Waiters with a priority which started to wait in the
+                // meantime must not be overtaken by a non-fair waiter.
+                if self.permits >= wait_node.required_permits
+                    && (self.is_fair || self.priority_waiters.is_empty())
+                {
                     if self.is_fair {
                         // In a fair Semaphore, the WaitQueueEntry is kept in the
                         // linked list and must be removed here
@@ -221,7 +373,7 @@ impl SemaphoreState {
                         self.wakeup_waiters();
                     }
                     wait_node.state = PollState::Done;
-                    Poll::Ready(())
+                    Poll::Ready(Ok(()))
                 } else {
                     // A fair semaphore should never end up in that branch, since
                     // it's only notified when it's permits are guaranteed to
@@ -233,10 +385,15 @@ impl SemaphoreState {
                     // Add to queue
                     wait_node.task = Some(cx.waker().clone());
                     wait_node.state = PollState::Waiting;
-                    self.waiters.add_front(wait_node);
+                    self.add_waiter(wait_node);
                     Poll::Pending
                 }
             }
+            PollState::Granted => {
+                // The permits had been handed over to us
+                wait_node.state = PollState::Done;
+                Poll::Ready(Ok(()))
+            }
             PollState::Done => {
                 // The future had been polled to completion before
                 panic!("polled Mutex after completion");
@@ -244,16 +401,32 @@ impl SemaphoreState {
         }
     }
 
+    /// Adds a waiter to the wait queue which is determined by its node type.
+    ///
+    /// This function is only safe as long as the `wait_node`s address is
+    /// guaranteed to be stable until it gets removed from the queue.
+    unsafe fn add_waiter(&mut self, wait_node: &mut WaitNode<WaitQueueEntry>) {
+        match wait_node {
+            WaitNode::Fifo(node) => self.waiters.add_front(node),
+            WaitNode::Priority(node) => self.priority_waiters.insert(node),
+        }
+    }
+
     /// Tries to remove a waiter from the wait queue, and panics if the
     /// waiter is no longer valid.
     unsafe fn force_remove_waiter(
         &mut self,
-        wait_node: &mut ListNode<WaitQueueEntry>,
+        wait_node: &mut WaitNode<WaitQueueEntry>,
     ) {
-        if !self.waiters.remove(wait_node) {
-            // Panic if the address isn't found. This can only happen if the contract was
-            // violated, e.g. the WaitQueueEntry got moved after the initial poll.
-            panic!("Future could not be removed from wait queue");
+        match wait_node {
+            WaitNode::Fifo(node) => {
+                if !self.waiters.remove(node) {
+                    // Panic if the address isn't found. This can only happen if the contract was
+                    // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                    panic!("Future could not be removed from wait queue");
+                }
+            }
+            WaitNode::Priority(node) => self.priority_waiters.remove(node),
         }
     }
 
@@ -261,7 +434,7 @@ impl SemaphoreState {
     /// This function is only safe as long as the reference that is passed here
     /// equals the reference/address under which the waiter was added.
     /// The waiter must not have been moved in between.
-    fn remove_waiter(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+    fn remove_waiter(&mut self, wait_node: &mut WaitNode<WaitQueueEntry>) {
         // SemaphoreAcquireFuture only needs to get removed if it had been added to
         // the wait queue of the Semaphore. This has happened in the PollState::Waiting case.
         // If the current waiter was notified, another waiter must get notified now.
@@ -284,6 +457,18 @@ impl SemaphoreState {
                 // of the waiter list
                 unsafe { self.force_remove_waiter(wait_node) };
                 wait_node.state = PollState::Done;
+                if let WaitNode::Priority(_) = wait_node {
+                    // The waiter might have blocked waiters which requested
+                    // less permits
+                    self.wakeup_waiters();
+                }
+            }
+            PollState::Granted => {
+                // The task owns the permits, but is no longer interested in
+                // them.
+                wait_node.state = PollState::Done;
+                let permits = wait_node.required_permits;
+                self.release(permits);
             }
             PollState::New | PollState::Done => {}
         }
@@ -322,6 +507,50 @@ impl<MutexType: RawMutex> GenericSemaphoreReleaser<'_, MutexType> {
         self.permits = 0;
         permits
     }
+
+    /// Returns the amount of permits which will be released back to the
+    /// Semaphore when the SemaphoreReleaser gets dropped.
+    pub fn permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Splits `permits` permits off into a new SemaphoreReleaser.
+    ///
+    /// The permits of both SemaphoreReleasers get released independently.
+    /// Returns `None` if the SemaphoreReleaser doesn't hold enough permits.
+    pub fn split(&mut self, permits: usize) -> Option<Self> {
+        if permits > self.permits {
+            return None;
+        }
+        self.permits -= permits;
+        Some(GenericSemaphoreReleaser {
+            semaphore: self.semaphore,
+            permits,
+        })
+    }
+
+    /// Merges the permits of `other` into this SemaphoreReleaser.
+    ///
+    /// Panics if `other` belongs to a different Semaphore.
+    pub fn merge(&mut self, mut other: Self) {
+        assert!(
+            core::ptr::eq(self.semaphore, other.semaphore),
+            "Merged SemaphoreReleasers must belong to the same Semaphore"
+        );
+        self.permits = self
+            .permits
+            .checked_add(other.disarm())
+            .expect("Semaphore permit overflow");
+    }
+
+    /// Drops the SemaphoreReleaser without releasing its permits back to the
+    /// Semaphore.
+    ///
+    /// In contrast to [`disarm`](GenericSemaphoreReleaser::disarm) the
+    /// permits are gone for good, and should not be released manually.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
 }
 
 impl<MutexType: RawMutex> Drop for GenericSemaphoreReleaser<'_, MutexType> {
@@ -339,7 +568,7 @@ pub struct GenericSemaphoreAcquireFuture<'a, MutexType: RawMutex> {
     /// The Semaphore which should get acquired trough this Future
     semaphore: Option<&'a GenericSemaphore<MutexType>>,
     /// Node for waiting at the semaphore
-    wait_node: ListNode<WaitQueueEntry>,
+    wait_node: WaitNode<WaitQueueEntry>,
     /// Whether the obtained permits should automatically be released back
     /// to the semaphore.
     auto_release: bool,
@@ -364,7 +593,7 @@ impl<'a, MutexType: RawMutex> core::fmt::Debug
 impl<'a, MutexType: RawMutex> Future
     for GenericSemaphoreAcquireFuture<'a, MutexType>
 {
-    type Output = GenericSemaphoreReleaser<'a, MutexType>;
+    type Output = Result<GenericSemaphoreReleaser<'a, MutexType>, AcquireError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         // Safety: The next operations are safe, because Pin promises us that
@@ -383,17 +612,21 @@ impl<'a, MutexType: RawMutex> Future
 
         match poll_res {
             Poll::Pending => Poll::Pending,
-            Poll::Ready(()) => {
+            Poll::Ready(Err(err)) => {
+                mut_self.semaphore = None;
+                Poll::Ready(Err(err))
+            }
+            Poll::Ready(Ok(())) => {
                 // The semaphore was acquired.
                 mut_self.semaphore = None;
                 let to_release = match mut_self.auto_release {
                     true => mut_self.wait_node.required_permits,
                     false => 0,
                 };
-                Poll::Ready(GenericSemaphoreReleaser::<'a, MutexType> {
+                Poll::Ready(Ok(GenericSemaphoreReleaser::<'a, MutexType> {
                     semaphore,
                     permits: to_release,
-                })
+                }))
             }
         }
     }
@@ -467,9 +700,15 @@ impl<MutexType: RawMutex> GenericSemaphore<MutexType> {
     ///
     /// `permits` is the amount of permits that a semaphore should hold when
     /// created.
-    pub fn new(is_fair: bool, permits: usize) -> GenericSemaphore<MutexType> {
+    pub const fn new(
+        is_fair: bool,
+        permits: usize,
+    ) -> GenericSemaphore<MutexType> {
         GenericSemaphore::<MutexType> {
-            state: LockApiMutex::new(SemaphoreState::new(is_fair, permits)),
+            state: LockApiMutex::const_new(
+                MutexType::INIT,
+                SemaphoreState::new(is_fair, permits),
+            ),
         }
     }
 
@@ -479,13 +718,45 @@ impl<MutexType: RawMutex> GenericSemaphore<MutexType> {
     /// permits have been acquired.
     /// The Future will resolve to a [`GenericSemaphoreReleaser`], which will
     /// release all acquired permits automatically when dropped.
+    ///
+    /// If the semaphore gets closed before the permits could be acquired, the
+    /// Future will resolve to [`AcquireError::Closed`].
     pub fn acquire(
         &self,
         nr_permits: usize,
     ) -> GenericSemaphoreAcquireFuture<'_, MutexType> {
         GenericSemaphoreAcquireFuture::<MutexType> {
             semaphore: Some(&self),
-            wait_node: ListNode::new(WaitQueueEntry::new(nr_permits)),
+            wait_node: WaitNode::fifo(WaitQueueEntry::new(nr_permits)),
+            auto_release: true,
+        }
+    }
+
+    /// Acquire a certain amount of permits on a semaphore asynchronously with
+    /// the given priority.
+    ///
+    /// This method returns a future that will resolve once the given amount of
+    /// permits have been acquired. It behaves like
+    /// [`GenericSemaphore::acquire`], except for the ordering of waiters.
+    ///
+    /// Waiters with a priority are served before all waiters which had been
+    /// created through [`GenericSemaphore::acquire`], independent of the
+    /// fairness setting of the semaphore. Among each other they are served in
+    /// order of their `priority`, where higher values take precedence.
+    /// Waiters with equal priorities are served in FIFO order.
+    /// Permits which get released are handed over directly to the waiter
+    /// with the highest priority, as long as they satisfy its request.
+    pub fn acquire_with_priority(
+        &self,
+        nr_permits: usize,
+        priority: usize,
+    ) -> GenericSemaphoreAcquireFuture<'_, MutexType> {
+        GenericSemaphoreAcquireFuture::<MutexType> {
+            semaphore: Some(self),
+            wait_node: WaitNode::priority(
+                priority,
+                WaitQueueEntry::new(nr_permits),
+            ),
             auto_release: true,
         }
     }
@@ -496,7 +767,8 @@ impl<MutexType: RawMutex> GenericSemaphore<MutexType> {
     /// will be returned, which will release all acquired permits automatically
     /// when dropped.
     ///
-    /// Otherwise `None` will be returned.
+    /// Otherwise `None` will be returned. This is also the case if the
+    /// semaphore has been closed.
     pub fn try_acquire(
         &self,
         nr_permits: usize,
@@ -521,6 +793,8 @@ impl<MutexType: RawMutex> GenericSemaphore<MutexType> {
     /// disabled by calling [`GenericSemaphoreReleaser::disarm`],
     /// or when the amount of permits in the Semaphore
     /// should increase from the initial amount.
+    ///
+    /// Panics if the amount of available permits would overflow.
     pub fn release(&self, nr_permits: usize) {
         self.state.lock().release(nr_permits)
     }
@@ -529,6 +803,38 @@ impl<MutexType: RawMutex> GenericSemaphore<MutexType> {
     pub fn permits(&self) -> usize {
         self.state.lock().permits()
     }
+
+    /// Adds the given amount of new permits to the semaphore.
+    ///
+    /// Waiting tasks are woken up if their acquire attempt can succeed now.
+    ///
+    /// Panics if the amount of available permits would overflow.
+    pub fn add_permits(&self, nr_permits: usize) {
+        self.state.lock().release(nr_permits)
+    }
+
+    /// Permanently removes up to `nr_permits` of the available permits from
+    /// the semaphore.
+    ///
+    /// Permits which are held by a [`GenericSemaphoreReleaser`] are not
+    /// affected. Returns the amount of permits which had been removed.
+    pub fn forget_permits(&self, nr_permits: usize) -> usize {
+        self.state.lock().forget_permits(nr_permits)
+    }
+
+    /// Closes the semaphore.
+    ///
+    /// All pending and future attempts to acquire permits from the semaphore
+    /// will fail with [`AcquireError::Closed`]. Permits which had been
+    /// acquired before remain valid and can still be released.
+    pub fn close(&self) {
+        self.state.lock().close()
+    }
+
+    /// Returns whether the semaphore has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().is_closed()
+    }
 }
 
 // Export a non thread-safe version using NoopLock
@@ -600,6 +906,48 @@ mod if_alloc {
             self.permits = 0;
             permits
         }
+
+        /// Returns the amount of permits which will be released back to the
+        /// Semaphore when the SharedSemaphoreReleaser gets dropped.
+        pub fn permits(&self) -> usize {
+            self.permits
+        }
+
+        /// Splits `permits` permits off into a new SharedSemaphoreReleaser.
+        ///
+        /// See [`GenericSemaphoreReleaser::split`] for more information.
+        pub fn split(&mut self, permits: usize) -> Option<Self> {
+            if permits > self.permits {
+                return None;
+            }
+            self.permits -= permits;
+            Some(GenericSharedSemaphoreReleaser {
+                semaphore: self.semaphore.clone(),
+                permits,
+            })
+        }
+
+        /// Merges the permits of `other` into this SharedSemaphoreReleaser.
+        ///
+        /// Panics if `other` belongs to a different Semaphore.
+        pub fn merge(&mut self, mut other: Self) {
+            assert!(
+                Arc::ptr_eq(&self.semaphore.state, &other.semaphore.state),
+                "Merged SemaphoreReleasers must belong to the same Semaphore"
+            );
+            self.permits = self
+                .permits
+                .checked_add(other.disarm())
+                .expect("Semaphore permit overflow");
+        }
+
+        /// Drops the SharedSemaphoreReleaser without releasing its permits
+        /// back to the Semaphore.
+        ///
+        /// See [`GenericSemaphoreReleaser::forget`] for more information.
+        pub fn forget(mut self) {
+            self.permits = 0;
+        }
     }
 
     impl<MutexType: RawMutex> Drop for GenericSharedSemaphoreReleaser<MutexType> {
@@ -617,7 +965,7 @@ mod if_alloc {
         /// The Semaphore which should get acquired trough this Future
         semaphore: Option<GenericSharedSemaphore<MutexType>>,
         /// Node for waiting at the semaphore
-        wait_node: ListNode<WaitQueueEntry>,
+        wait_node: WaitNode<WaitQueueEntry>,
         /// Whether the obtained permits should automatically be released back
         /// to the semaphore.
         auto_release: bool,
@@ -643,7 +991,8 @@ mod if_alloc {
     impl<MutexType: RawMutex> Future
         for GenericSharedSemaphoreAcquireFuture<MutexType>
     {
-        type Output = GenericSharedSemaphoreReleaser<MutexType>;
+        type Output =
+            Result<GenericSharedSemaphoreReleaser<MutexType>, AcquireError>;
 
         fn poll(
             self: Pin<&mut Self>,
@@ -670,15 +1019,18 @@ mod if_alloc {
                     mut_self.semaphore.replace(semaphore);
                     Poll::Pending
                 }
-                Poll::Ready(()) => {
+                Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+                Poll::Ready(Ok(())) => {
                     let to_release = match mut_self.auto_release {
                         true => mut_self.wait_node.required_permits,
                         false => 0,
                     };
-                    Poll::Ready(GenericSharedSemaphoreReleaser::<MutexType> {
-                        semaphore,
-                        permits: to_release,
-                    })
+                    Poll::Ready(Ok(
+                        GenericSharedSemaphoreReleaser::<MutexType> {
+                            semaphore,
+                            permits: to_release,
+                        },
+                    ))
                 }
             }
         }
@@ -770,13 +1122,36 @@ mod if_alloc {
         /// permits have been acquired.
         /// The Future will resolve to a [`GenericSharedSemaphoreReleaser`], which will
         /// release all acquired permits automatically when dropped.
+        ///
+        /// If the semaphore gets closed before the permits could be acquired,
+        /// the Future will resolve to [`AcquireError::Closed`].
         pub fn acquire(
             &self,
             nr_permits: usize,
         ) -> GenericSharedSemaphoreAcquireFuture<MutexType> {
             GenericSharedSemaphoreAcquireFuture::<MutexType> {
                 semaphore: Some(self.clone()),
-                wait_node: ListNode::new(WaitQueueEntry::new(nr_permits)),
+                wait_node: WaitNode::fifo(WaitQueueEntry::new(nr_permits)),
+                auto_release: true,
+            }
+        }
+
+        /// Acquire a certain amount of permits on a semaphore asynchronously
+        /// with the given priority.
+        ///
+        /// The ordering of waiters follows the same rules as for
+        /// [`GenericSemaphore::acquire_with_priority`].
+        pub fn acquire_with_priority(
+            &self,
+            nr_permits: usize,
+            priority: usize,
+        ) -> GenericSharedSemaphoreAcquireFuture<MutexType> {
+            GenericSharedSemaphoreAcquireFuture::<MutexType> {
+                semaphore: Some(self.clone()),
+                wait_node: WaitNode::priority(
+                    priority,
+                    WaitQueueEntry::new(nr_permits),
+                ),
                 auto_release: true,
             }
         }
@@ -787,7 +1162,8 @@ mod if_alloc {
         /// will be returned, which will release all acquired permits automatically
         /// when dropped.
         ///
-        /// Otherwise `None` will be returned.
+        /// Otherwise `None` will be returned. This is also the case if the
+        /// semaphore has been closed.
         pub fn try_acquire(
             &self,
             nr_permits: usize,
@@ -812,6 +1188,8 @@ mod if_alloc {
         /// disabled by calling [`GenericSharedSemaphoreReleaser::disarm`],
         /// or when the amount of permits in the Semaphore
         /// should increase from the initial amount.
+        ///
+        /// Panics if the amount of available permits would overflow.
         pub fn release(&self, nr_permits: usize) {
             self.state.lock().release(nr_permits)
         }
@@ -820,6 +1198,33 @@ mod if_alloc {
         pub fn permits(&self) -> usize {
             self.state.lock().permits()
         }
+
+        /// Adds the given amount of new permits to the semaphore.
+        ///
+        /// See [`GenericSemaphore::add_permits`] for more information.
+        pub fn add_permits(&self, nr_permits: usize) {
+            self.state.lock().release(nr_permits)
+        }
+
+        /// Permanently removes up to `nr_permits` of the available permits
+        /// from the semaphore.
+        ///
+        /// See [`GenericSemaphore::forget_permits`] for more information.
+        pub fn forget_permits(&self, nr_permits: usize) -> usize {
+            self.state.lock().forget_permits(nr_permits)
+        }
+
+        /// Closes the semaphore.
+        ///
+        /// See [`GenericSemaphore::close`] for more information.
+        pub fn close(&self) {
+            self.state.lock().close()
+        }
+
+        /// Returns whether the semaphore has been closed.
+        pub fn is_closed(&self) -> bool {
+            self.state.lock().is_closed()
+        }
     }
 
     // Export parking_lot based shared semaphores in std mode
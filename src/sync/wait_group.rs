@@ -0,0 +1,525 @@
+//! An asynchronously awaitable wait group, which allows to wait until a set
+//! of outstanding operations has finished.
+
+use crate::{
+    intrusive_double_linked_list::{LinkedList, ListNode},
+    utils::update_waker_ref,
+    NoopLock,
+};
+use core::pin::Pin;
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll, Waker},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Tracks how the future had interacted with the wait group
+#[derive(PartialEq)]
+enum PollState {
+    /// The task has never interacted with the wait group.
+    New,
+    /// The task was added to the wait queue at the wait group.
+    Waiting,
+    /// The task has been polled to completion.
+    Done,
+}
+
+/// Tracks the WaitGroupWaitFuture waiting state.
+/// Access to this struct is synchronized through the mutex in the WaitGroup.
+struct WaitQueueEntry {
+    /// The task handle of the waiting task
+    task: Option<Waker>,
+    /// Current polling state
+    state: PollState,
+}
+
+impl WaitQueueEntry {
+    /// Creates a new WaitQueueEntry
+    fn new() -> WaitQueueEntry {
+        WaitQueueEntry {
+            task: None,
+            state: PollState::New,
+        }
+    }
+}
+
+/// Internal state of the `WaitGroup`
+struct WaitGroupState {
+    /// The amount of outstanding operations
+    count: usize,
+    waiters: LinkedList<WaitQueueEntry>,
+}
+
+impl WaitGroupState {
+    const fn new(count: usize) -> WaitGroupState {
+        WaitGroupState {
+            count,
+            waiters: LinkedList::new(),
+        }
+    }
+
+    fn add(&mut self, count: usize) {
+        self.count = self
+            .count
+            .checked_add(count)
+            .expect("WaitGroup counter overflow");
+    }
+
+    fn done(&mut self) {
+        self.count = self
+            .count
+            .checked_sub(1)
+            .expect("WaitGroup::done called more often than add");
+
+        if self.count == 0 {
+            // Wakeup all waiters.
+            // Use a reverse iterator, so that the oldest waiter gets
+            // scheduled first
+            self.waiters.reverse_drain(|waiter| {
+                if let Some(handle) = waiter.task.take() {
+                    handle.wake();
+                }
+                waiter.state = PollState::Done;
+            });
+        }
+    }
+
+    /// Checks if all outstanding operations have finished.
+    /// If not, the WaitQueueEntry gets added to the wait queue at the wait
+    /// group.
+    /// This function is only safe as long as the `wait_node`s address is guaranteed
+    /// to be stable until it gets removed from the queue.
+    unsafe fn try_wait(
+        &mut self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        match wait_node.state {
+            PollState::New => {
+                if self.count == 0 {
+                    // The wait group is already done
+                    wait_node.state = PollState::Done;
+                    Poll::Ready(())
+                } else {
+                    // Added the task to the wait queue
+                    wait_node.task = Some(cx.waker().clone());
+                    wait_node.state = PollState::Waiting;
+                    self.waiters.add_front(wait_node);
+                    Poll::Pending
+                }
+            }
+            PollState::Waiting => {
+                // The WaitGroup is not done yet. However the caller might
+                // have passed a different `Waker`. In this case we need to
+                // update it.
+                update_waker_ref(&mut wait_node.task, cx);
+                Poll::Pending
+            }
+            PollState::Done => {
+                // The counter had reached 0 after we started waiting
+                Poll::Ready(())
+            }
+        }
+    }
+
+    fn remove_waiter(&mut self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        // WaitGroupWaitFuture only needs to get removed if it has been added
+        // to the wait queue of the WaitGroup. This has happened in the
+        // PollState::Waiting case.
+        if let PollState::Waiting = wait_node.state {
+            // Safety: Due to the state, we know that the node must be part
+            // of the waiter list
+            if !unsafe { self.waiters.remove(wait_node) } {
+                // Panic if the address isn't found. This can only happen if the contract was
+                // violated, e.g. the WaitQueueEntry got moved after the initial poll.
+                panic!("Future could not be removed from wait queue");
+            }
+            wait_node.state = PollState::Done;
+        }
+    }
+}
+
+/// A synchronization primitive which allows to wait until a set of
+/// outstanding operations has finished.
+///
+/// The wait group maintains a counter of outstanding operations, which gets
+/// incremented via `add` and decremented via `done`. Tasks obtain a Future
+/// via `wait`, which gets fulfilled once the counter reaches 0.
+pub struct GenericWaitGroup<MutexType: RawMutex> {
+    inner: Mutex<MutexType, WaitGroupState>,
+}
+
+// The WaitGroup is can be sent to other threads as long as it's not borrowed
+unsafe impl<MutexType: RawMutex + Send> Send for GenericWaitGroup<MutexType> {}
+// The WaitGroup is thread-safe as long as the utilized Mutex is thread-safe
+unsafe impl<MutexType: RawMutex + Sync> Sync for GenericWaitGroup<MutexType> {}
+
+impl<MutexType: RawMutex> core::fmt::Debug for GenericWaitGroup<MutexType> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("WaitGroup")
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
+impl<MutexType: RawMutex> GenericWaitGroup<MutexType> {
+    /// Creates a new WaitGroup with the given amount of outstanding
+    /// operations.
+    pub const fn new(count: usize) -> GenericWaitGroup<MutexType> {
+        GenericWaitGroup {
+            inner: Mutex::<MutexType, WaitGroupState>::const_new(
+                MutexType::INIT,
+                WaitGroupState::new(count),
+            ),
+        }
+    }
+
+    /// Adds `count` outstanding operations to the wait group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter would overflow.
+    pub fn add(&self, count: usize) {
+        self.inner.lock().add(count)
+    }
+
+    /// Marks a single outstanding operation as finished.
+    ///
+    /// If this was the last outstanding operation, all tasks which are
+    /// waiting on the wait group get woken up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no operation is outstanding.
+    pub fn done(&self) {
+        self.inner.lock().done()
+    }
+
+    /// Returns the amount of outstanding operations.
+    pub fn count(&self) -> usize {
+        self.inner.lock().count
+    }
+
+    /// Returns a future that gets fulfilled once no operation is outstanding
+    /// anymore.
+    pub fn wait(&self) -> GenericWaitGroupWaitFuture<'_, MutexType> {
+        GenericWaitGroupWaitFuture {
+            wait_group: Some(self),
+            wait_node: ListNode::new(WaitQueueEntry::new()),
+        }
+    }
+
+    unsafe fn try_wait(
+        &self,
+        wait_node: &mut ListNode<WaitQueueEntry>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        self.inner.lock().try_wait(wait_node, cx)
+    }
+
+    fn remove_waiter(&self, wait_node: &mut ListNode<WaitQueueEntry>) {
+        self.inner.lock().remove_waiter(wait_node)
+    }
+}
+
+/// A Future that is resolved once the corresponding WaitGroup has no
+/// outstanding operations anymore.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericWaitGroupWaitFuture<'a, MutexType: RawMutex> {
+    /// The WaitGroup that is associated with this WaitGroupWaitFuture
+    wait_group: Option<&'a GenericWaitGroup<MutexType>>,
+    /// Node for waiting at the wait group
+    wait_node: ListNode<WaitQueueEntry>,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// wait group is thread-safe (Sync), which allows to poll/register/unregister
+// from a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync> Send
+    for GenericWaitGroupWaitFuture<'a, MutexType>
+{
+}
+
+impl<'a, MutexType: RawMutex> core::fmt::Debug
+    for GenericWaitGroupWaitFuture<'a, MutexType>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericWaitGroupWaitFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Future
+    for GenericWaitGroupWaitFuture<'a, MutexType>
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the wait queue entry inside GenericWaitGroupWaitFuture
+        // is stable, and we don't move any fields inside the future until it
+        // gets dropped.
+        let mut_self: &mut GenericWaitGroupWaitFuture<MutexType> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let wait_group = mut_self
+            .wait_group
+            .expect("polled WaitGroupWaitFuture after completion");
+
+        let poll_res =
+            unsafe { wait_group.try_wait(&mut mut_self.wait_node, cx) };
+
+        if poll_res.is_ready() {
+            // The wait group is done
+            mut_self.wait_group = None;
+        }
+
+        poll_res
+    }
+}
+
+impl<'a, MutexType: RawMutex> FusedFuture
+    for GenericWaitGroupWaitFuture<'a, MutexType>
+{
+    fn is_terminated(&self) -> bool {
+        self.wait_group.is_none()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Drop
+    for GenericWaitGroupWaitFuture<'a, MutexType>
+{
+    fn drop(&mut self) {
+        // If this WaitGroupWaitFuture has been polled and it was added to the
+        // wait queue at the wait group, it must be removed before dropping.
+        // Otherwise the wait group would access invalid memory.
+        if let Some(wait_group) = self.wait_group {
+            wait_group.remove_waiter(&mut self.wait_node);
+        }
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericWaitGroup`] which is not thread-safe.
+pub type LocalWaitGroup = GenericWaitGroup<NoopLock>;
+/// A [`GenericWaitGroupWaitFuture`] for [`LocalWaitGroup`].
+pub type LocalWaitGroupWaitFuture<'a> =
+    GenericWaitGroupWaitFuture<'a, NoopLock>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericWaitGroup`] implementation backed by [`parking_lot`].
+    pub type WaitGroup = GenericWaitGroup<parking_lot::RawMutex>;
+    /// A [`GenericWaitGroupWaitFuture`] for [`WaitGroup`].
+    pub type WaitGroupWaitFuture<'a> =
+        GenericWaitGroupWaitFuture<'a, parking_lot::RawMutex>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
+
+#[cfg(feature = "alloc")]
+mod if_alloc {
+    use super::*;
+
+    use alloc::sync::Arc;
+
+    /// A Future that is resolved once the corresponding
+    /// [`GenericSharedWaitGroup`] has no outstanding operations anymore.
+    #[must_use = "futures do nothing unless polled"]
+    pub struct GenericSharedWaitGroupWaitFuture<MutexType: RawMutex> {
+        /// The WaitGroup that is associated with this future
+        wait_group: Option<GenericSharedWaitGroup<MutexType>>,
+        /// Node for waiting at the wait group
+        wait_node: ListNode<WaitQueueEntry>,
+    }
+
+    // Safety: Futures can be sent between threads as long as the underlying
+    // wait group is thread-safe (Sync), which allows to poll/register/unregister
+    // from a different thread.
+    unsafe impl<MutexType: RawMutex + Sync> Send
+        for GenericSharedWaitGroupWaitFuture<MutexType>
+    {
+    }
+
+    impl<MutexType: RawMutex> core::fmt::Debug
+        for GenericSharedWaitGroupWaitFuture<MutexType>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("GenericSharedWaitGroupWaitFuture").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex> Future
+        for GenericSharedWaitGroupWaitFuture<MutexType>
+    {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            // Safety: The next operations are safe, because Pin promises us that
+            // the address of the wait queue entry inside the future is stable,
+            // and we don't move any fields inside the future until it gets dropped.
+            let mut_self: &mut GenericSharedWaitGroupWaitFuture<MutexType> =
+                unsafe { Pin::get_unchecked_mut(self) };
+
+            let wait_group = mut_self
+                .wait_group
+                .take()
+                .expect("polled SharedWaitGroupWaitFuture after completion");
+
+            let poll_res = unsafe {
+                wait_group.inner.try_wait(&mut mut_self.wait_node, cx)
+            };
+
+            if poll_res.is_pending() {
+                mut_self.wait_group.replace(wait_group);
+            }
+
+            poll_res
+        }
+    }
+
+    impl<MutexType: RawMutex> FusedFuture
+        for GenericSharedWaitGroupWaitFuture<MutexType>
+    {
+        fn is_terminated(&self) -> bool {
+            self.wait_group.is_none()
+        }
+    }
+
+    impl<MutexType: RawMutex> Drop for GenericSharedWaitGroupWaitFuture<MutexType> {
+        fn drop(&mut self) {
+            // If this future has been polled and it was added to the
+            // wait queue at the wait group, it must be removed before dropping.
+            // Otherwise the wait group would access invalid memory.
+            if let Some(wait_group) = self.wait_group.take() {
+                wait_group.inner.remove_waiter(&mut self.wait_node);
+            }
+        }
+    }
+
+    /// A guard which represents a single outstanding operation of a
+    /// [`GenericSharedWaitGroup`].
+    ///
+    /// The operation is marked as finished when the guard is dropped.
+    #[must_use = "if unused the operation will immediately be marked as done"]
+    pub struct GenericSharedWaitGroupGuard<MutexType: RawMutex> {
+        wait_group: GenericSharedWaitGroup<MutexType>,
+    }
+
+    impl<MutexType: RawMutex> core::fmt::Debug
+        for GenericSharedWaitGroupGuard<MutexType>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("GenericSharedWaitGroupGuard").finish()
+        }
+    }
+
+    impl<MutexType: RawMutex> Drop for GenericSharedWaitGroupGuard<MutexType> {
+        fn drop(&mut self) {
+            self.wait_group.done();
+        }
+    }
+
+    /// A shared version of [`GenericWaitGroup`].
+    ///
+    /// The wait group can be cloned, which creates another handle to the same
+    /// wait group. The futures and guards which are produced by the wait group
+    /// do not have an associated lifetime, and can therefore be moved into
+    /// independent tasks.
+    pub struct GenericSharedWaitGroup<MutexType: RawMutex> {
+        inner: Arc<GenericWaitGroup<MutexType>>,
+    }
+
+    impl<MutexType: RawMutex> Clone for GenericSharedWaitGroup<MutexType> {
+        fn clone(&self) -> Self {
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+
+    impl<MutexType: RawMutex> core::fmt::Debug
+        for GenericSharedWaitGroup<MutexType>
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("SharedWaitGroup")
+                .field("count", &self.count())
+                .finish()
+        }
+    }
+
+    impl<MutexType: RawMutex> GenericSharedWaitGroup<MutexType> {
+        /// Creates a new shared WaitGroup.
+        ///
+        /// See [`GenericWaitGroup::new`] for more information.
+        pub fn new(count: usize) -> GenericSharedWaitGroup<MutexType> {
+            GenericSharedWaitGroup {
+                inner: Arc::new(GenericWaitGroup::new(count)),
+            }
+        }
+
+        /// Adds `count` outstanding operations to the wait group.
+        ///
+        /// See [`GenericWaitGroup::add`] for more information.
+        pub fn add(&self, count: usize) {
+            self.inner.add(count)
+        }
+
+        /// Marks a single outstanding operation as finished.
+        ///
+        /// See [`GenericWaitGroup::done`] for more information.
+        pub fn done(&self) {
+            self.inner.done()
+        }
+
+        /// Adds a single outstanding operation to the wait group, and returns
+        /// a guard which marks the operation as finished when it is dropped.
+        pub fn add_guard(&self) -> GenericSharedWaitGroupGuard<MutexType> {
+            self.add(1);
+            GenericSharedWaitGroupGuard {
+                wait_group: self.clone(),
+            }
+        }
+
+        /// Returns the amount of outstanding operations.
+        pub fn count(&self) -> usize {
+            self.inner.count()
+        }
+
+        /// Returns a future that gets fulfilled once no operation is
+        /// outstanding anymore.
+        pub fn wait(&self) -> GenericSharedWaitGroupWaitFuture<MutexType> {
+            GenericSharedWaitGroupWaitFuture {
+                wait_group: Some(self.clone()),
+                wait_node: ListNode::new(WaitQueueEntry::new()),
+            }
+        }
+    }
+
+    // Export parking_lot based shared wait groups in std mode
+    #[cfg(feature = "std")]
+    mod if_std {
+        use super::*;
+
+        /// A [`GenericSharedWaitGroup`] backed by [`parking_lot`].
+        pub type SharedWaitGroup =
+            GenericSharedWaitGroup<parking_lot::RawMutex>;
+        /// A [`GenericSharedWaitGroupGuard`] for [`SharedWaitGroup`].
+        pub type SharedWaitGroupGuard =
+            GenericSharedWaitGroupGuard<parking_lot::RawMutex>;
+        /// A [`GenericSharedWaitGroupWaitFuture`] for [`SharedWaitGroup`].
+        pub type SharedWaitGroupWaitFuture =
+            GenericSharedWaitGroupWaitFuture<parking_lot::RawMutex>;
+    }
+
+    #[cfg(feature = "std")]
+    pub use self::if_std::*;
+}
+
+#[cfg(feature = "alloc")]
+pub use self::if_alloc::*;
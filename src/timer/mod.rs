@@ -4,6 +4,9 @@
 //! `Future`s.
 //! The timer can work with a configurable clock source. In order to utilize
 //! the system clock, a global instance `StdClock` can be utilized.
+//!
+//! On top of the timer a token-bucket `RateLimiter` is provided, which allows
+//! to throttle operations to a configured rate.
 
 mod clock;
 pub use self::clock::{Clock, MockClock};
@@ -20,3 +23,13 @@ pub use self::timer::{
 
 #[cfg(feature = "std")]
 pub use self::timer::TimerService;
+
+mod rate_limiter;
+
+pub use self::rate_limiter::{
+    GenericRateLimiter, GenericRateLimiterAcquireFuture, LocalRateLimiter,
+    LocalRateLimiterAcquireFuture,
+};
+
+#[cfg(feature = "std")]
+pub use self::rate_limiter::{RateLimiter, RateLimiterAcquireFuture};
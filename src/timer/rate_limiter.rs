@@ -0,0 +1,307 @@
+//! An asynchronously awaitable token-bucket rate limiter.
+
+use super::timer::{GenericTimerService, LocalTimer, LocalTimerFuture};
+use crate::{
+    sync::{GenericSemaphore, GenericSemaphoreAcquireFuture},
+    NoopLock,
+};
+use core::{pin::Pin, time::Duration};
+use futures_core::{
+    future::{FusedFuture, Future},
+    task::{Context, Poll},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Internal state of the rate limiter which tracks refills
+struct RateLimiterState {
+    /// The timestamp at which permits had been refilled the last time.
+    /// This is `None` until the rate limiter is used for the first time.
+    last_refill: Option<u64>,
+}
+
+/// A token-bucket rate limiter.
+///
+/// The rate limiter holds up to `burst` permits. Every `interval` it gets
+/// refilled with `permits_per_interval` new permits, until the `burst` size
+/// is reached again. Permits which are obtained from the rate limiter are
+/// consumed and will not be returned.
+///
+/// Refills are driven by the [`Clock`](super::Clock) of the utilized
+/// [`GenericTimerService`]. Tasks which wait for permits register a timer
+/// on the timer service in order to get woken up once the next refill is due.
+/// Therefore [`GenericTimerService::check_expirations`] must be called in
+/// regular intervals.
+pub struct GenericRateLimiter<'a, MutexType: RawMutex> {
+    /// The timer which is utilized for waiting on refills
+    timer: &'a GenericTimerService<MutexType>,
+    /// The semaphore which holds the currently available permits
+    semaphore: GenericSemaphore<MutexType>,
+    /// Refill state
+    state: Mutex<MutexType, RateLimiterState>,
+    /// The amount of permits which are added on each refill
+    permits_per_interval: usize,
+    /// The refill interval in milliseconds
+    interval_ms: u64,
+    /// The maximum amount of permits which can be stored
+    burst: usize,
+}
+
+impl<'a, MutexType: RawMutex> core::fmt::Debug
+    for GenericRateLimiter<'a, MutexType>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("permits_per_interval", &self.permits_per_interval)
+            .field("interval_ms", &self.interval_ms)
+            .field("burst", &self.burst)
+            .finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex> GenericRateLimiter<'a, MutexType> {
+    /// Creates a new rate limiter.
+    ///
+    /// The rate limiter adds `permits_per_interval` permits after each
+    /// `interval` has elapsed, and stores at most `burst` permits.
+    /// It starts with `burst` permits being available.
+    ///
+    /// Panics if `interval` is shorter than one millisecond, since this is
+    /// the precision of the utilized timer.
+    pub const fn new(
+        timer: &'a GenericTimerService<MutexType>,
+        permits_per_interval: usize,
+        interval: Duration,
+        burst: usize,
+    ) -> GenericRateLimiter<'a, MutexType> {
+        let interval_ms = interval.as_millis();
+        assert!(
+            interval_ms > 0,
+            "The refill interval must be at least one millisecond"
+        );
+        let interval_ms = if interval_ms > u64::MAX as u128 {
+            u64::MAX
+        } else {
+            interval_ms as u64
+        };
+
+        GenericRateLimiter::<'a, MutexType> {
+            timer,
+            semaphore: GenericSemaphore::new(true, burst),
+            state: Mutex::const_new(
+                MutexType::INIT,
+                RateLimiterState { last_refill: None },
+            ),
+            permits_per_interval,
+            interval_ms,
+            burst,
+        }
+    }
+
+    /// Acquires the given amount of permits from the rate limiter
+    /// asynchronously.
+    ///
+    /// This method returns a future that will resolve once the given amount
+    /// of permits have been obtained. The obtained permits are consumed.
+    ///
+    /// Panics if `nr_permits` is bigger than the `burst` size of the rate
+    /// limiter, since such an attempt could never succeed.
+    pub fn acquire(
+        &self,
+        nr_permits: usize,
+    ) -> GenericRateLimiterAcquireFuture<'_, MutexType> {
+        assert!(
+            nr_permits <= self.burst,
+            "Can not acquire more permits than the burst size"
+        );
+        GenericRateLimiterAcquireFuture::<MutexType> {
+            limiter: Some(self),
+            acquire_future: self.semaphore.acquire(nr_permits),
+            timer_future: None,
+            timer_deadline: 0,
+        }
+    }
+
+    /// Tries to acquire the given amount of permits from the rate limiter.
+    ///
+    /// Returns `true` if the permits could be obtained. The obtained permits
+    /// are consumed.
+    pub fn try_acquire(&self, nr_permits: usize) -> bool {
+        self.refill();
+        match self.semaphore.try_acquire(nr_permits) {
+            Some(mut releaser) => {
+                releaser.disarm();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the amount of permits that are currently available on the
+    /// rate limiter.
+    pub fn permits(&self) -> usize {
+        self.refill();
+        self.semaphore.permits()
+    }
+
+    /// Adds the permits for all refill intervals that have elapsed since
+    /// the last refill.
+    fn refill(&self) {
+        let now = self.timer.now();
+        let mut state = self.state.lock();
+
+        let last_refill = match state.last_refill {
+            Some(last_refill) => last_refill,
+            None => {
+                state.last_refill = Some(now);
+                return;
+            }
+        };
+
+        let intervals = now.saturating_sub(last_refill) / self.interval_ms;
+        if intervals == 0 {
+            return;
+        }
+        // Keep the refills aligned to the interval, so that the time which
+        // elapsed since the last full interval is not lost.
+        state.last_refill = Some(last_refill + intervals * self.interval_ms);
+
+        let intervals = core::cmp::min(intervals, usize::MAX as u64);
+        let new_permits =
+            (intervals as usize).saturating_mul(self.permits_per_interval);
+        let missing = self.burst.saturating_sub(self.semaphore.permits());
+        let to_add = core::cmp::min(new_permits, missing);
+        if to_add != 0 {
+            self.semaphore.add_permits(to_add);
+        }
+    }
+
+    /// Returns the timestamp at which the next refill is due
+    fn next_refill(&self) -> u64 {
+        match self.state.lock().last_refill {
+            Some(last_refill) => last_refill.saturating_add(self.interval_ms),
+            None => self.timer.now(),
+        }
+    }
+}
+
+/// A future which resolves once the requested amount of permits has been
+/// obtained from a rate limiter.
+#[must_use = "futures do nothing unless polled"]
+pub struct GenericRateLimiterAcquireFuture<'a, MutexType: RawMutex> {
+    /// The rate limiter from which permits should get acquired
+    limiter: Option<&'a GenericRateLimiter<'a, MutexType>>,
+    /// The future which waits for permits on the internal semaphore
+    acquire_future: GenericSemaphoreAcquireFuture<'a, MutexType>,
+    /// The future which waits for the next refill
+    timer_future: Option<LocalTimerFuture<'a>>,
+    /// The deadline for which `timer_future` had been started
+    timer_deadline: u64,
+}
+
+// Safety: Futures can be sent between threads as long as the underlying
+// rate limiter and timer are thread-safe (Sync), which allows to
+// poll/register/unregister from a different thread.
+unsafe impl<'a, MutexType: RawMutex + Sync> Send
+    for GenericRateLimiterAcquireFuture<'a, MutexType>
+{
+}
+
+impl<'a, MutexType: RawMutex> core::fmt::Debug
+    for GenericRateLimiterAcquireFuture<'a, MutexType>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("GenericRateLimiterAcquireFuture").finish()
+    }
+}
+
+impl<'a, MutexType: RawMutex> Future
+    for GenericRateLimiterAcquireFuture<'a, MutexType>
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Safety: The next operations are safe, because Pin promises us that
+        // the address of the inner futures is stable, and we don't move any
+        // fields inside the future until it gets dropped.
+        let mut_self: &mut GenericRateLimiterAcquireFuture<MutexType> =
+            unsafe { Pin::get_unchecked_mut(self) };
+
+        let limiter = mut_self
+            .limiter
+            .expect("polled GenericRateLimiterAcquireFuture after completion");
+
+        loop {
+            limiter.refill();
+
+            let acquire_future =
+                unsafe { Pin::new_unchecked(&mut mut_self.acquire_future) };
+            match acquire_future.poll(cx) {
+                Poll::Ready(Ok(mut releaser)) => {
+                    releaser.disarm();
+                    mut_self.limiter = None;
+                    mut_self.timer_future = None;
+                    return Poll::Ready(());
+                }
+                Poll::Ready(Err(_)) => {
+                    unreachable!(
+                        "The semaphore of a RateLimiter is never closed"
+                    )
+                }
+                Poll::Pending => {}
+            }
+
+            // Wait until the next refill is due
+            let deadline = limiter.next_refill();
+            if mut_self.timer_future.is_none()
+                || mut_self.timer_deadline != deadline
+            {
+                mut_self.timer_future =
+                    Some(LocalTimer::deadline(limiter.timer, deadline));
+                mut_self.timer_deadline = deadline;
+            }
+
+            let timer_future = unsafe {
+                Pin::new_unchecked(mut_self.timer_future.as_mut().unwrap())
+            };
+            match timer_future.poll(cx) {
+                Poll::Ready(()) => {
+                    // A refill is due. Retry acquiring the permits.
+                    mut_self.timer_future = None;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<'a, MutexType: RawMutex> FusedFuture
+    for GenericRateLimiterAcquireFuture<'a, MutexType>
+{
+    fn is_terminated(&self) -> bool {
+        self.limiter.is_none()
+    }
+}
+
+// Export a non thread-safe version using NoopLock
+
+/// A [`GenericRateLimiter`] which is not thread-safe.
+pub type LocalRateLimiter<'a> = GenericRateLimiter<'a, NoopLock>;
+/// A [`GenericRateLimiterAcquireFuture`] for [`LocalRateLimiter`].
+pub type LocalRateLimiterAcquireFuture<'a> =
+    GenericRateLimiterAcquireFuture<'a, NoopLock>;
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+
+    // Export a thread-safe version using parking_lot::RawMutex
+
+    /// A [`GenericRateLimiter`] backed by [`parking_lot`].
+    pub type RateLimiter<'a> = GenericRateLimiter<'a, parking_lot::RawMutex>;
+    /// A [`GenericRateLimiterAcquireFuture`] for [`RateLimiter`].
+    pub type RateLimiterAcquireFuture<'a> =
+        GenericRateLimiterAcquireFuture<'a, parking_lot::RawMutex>;
+}
+
+#[cfg(feature = "std")]
+pub use self::if_std::*;
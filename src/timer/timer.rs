@@ -82,7 +82,7 @@ struct TimerState {
 }
 
 impl TimerState {
-    fn new(clock: &'static dyn Clock) -> TimerState {
+    const fn new(clock: &'static dyn Clock) -> TimerState {
         TimerState {
             clock,
             waiters: PairingHeap::new(),
@@ -260,9 +260,11 @@ impl<MutexType: RawMutex> GenericTimerService<MutexType> {
     /// [`StdClock`](super::StdClock) can be utilized.
     /// In order to simulate time for test purposes,
     /// [`MockClock`](super::MockClock) can be utilized.
-    pub fn new(clock: &'static dyn Clock) -> GenericTimerService<MutexType> {
+    pub const fn new(
+        clock: &'static dyn Clock,
+    ) -> GenericTimerService<MutexType> {
         GenericTimerService::<MutexType> {
-            inner: Mutex::new(TimerState::new(clock)),
+            inner: Mutex::const_new(MutexType::INIT, TimerState::new(clock)),
         }
     }
 
@@ -284,9 +286,15 @@ impl<MutexType: RawMutex> GenericTimerService<MutexType> {
         self.inner.lock().check_expirations()
     }
 
+    /// Returns the current timestamp of the [`Clock`] which is utilized by
+    /// the timer
+    pub(super) fn now(&self) -> u64 {
+        self.inner.lock().clock.now()
+    }
+
     /// Returns a deadline based on the current timestamp plus the given Duration
     fn deadline_from_now(&self, duration: Duration) -> u64 {
-        let now = self.inner.lock().clock.now();
+        let now = self.now();
         let duration_ms =
             core::cmp::min(duration.as_millis(), core::u64::MAX as u128) as u64;
         now.saturating_add(duration_ms)
@@ -0,0 +1,284 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::Context;
+use futures_intrusive::sync::LocalAutoResetEvent;
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_event_tests {
+    ($mod_name:ident, $event_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn synchronous() {
+                let event = $event_type::new(false);
+
+                assert!(!event.is_set());
+                event.notify_one();
+                assert!(event.is_set());
+                event.reset();
+                assert!(!event.is_set());
+                event.notify_all();
+                assert!(!event.is_set());
+            }
+
+            #[test]
+            fn stored_notification_is_consumed() {
+                let event = $event_type::new(true);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                assert!(event.is_set());
+
+                let poll1 = event.wait();
+                pin_mut!(poll1);
+                assert!(!poll1.as_mut().is_terminated());
+                assert!(poll1.as_mut().poll(cx).is_ready());
+                assert!(poll1.as_mut().is_terminated());
+                assert!(!event.is_set());
+
+                // The notification has been consumed
+                let poll2 = event.wait();
+                pin_mut!(poll2);
+                assert!(poll2.as_mut().poll(cx).is_pending());
+                assert_eq!(count, 0);
+            }
+
+            #[test]
+            fn only_a_single_notification_is_stored() {
+                let event = $event_type::new(false);
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                event.notify_one();
+                event.notify_one();
+
+                let poll1 = event.wait();
+                pin_mut!(poll1);
+                assert!(poll1.as_mut().poll(cx).is_ready());
+
+                let poll2 = event.wait();
+                pin_mut!(poll2);
+                assert!(poll2.as_mut().poll(cx).is_pending());
+            }
+
+            #[test]
+            fn notify_one_wakes_oldest_waiter() {
+                let event = $event_type::new(false);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let poll1 = event.wait();
+                let poll2 = event.wait();
+                pin_mut!(poll1);
+                pin_mut!(poll2);
+
+                assert!(poll1.as_mut().poll(cx).is_pending());
+                assert!(poll2.as_mut().poll(cx).is_pending());
+
+                event.notify_one();
+                assert_eq!(count, 1);
+                assert!(!event.is_set());
+                assert!(poll2.as_mut().poll(cx).is_pending());
+                assert!(poll1.as_mut().poll(cx).is_ready());
+                assert!(poll1.as_mut().is_terminated());
+
+                event.notify_one();
+                assert_eq!(count, 2);
+                assert!(poll2.as_mut().poll(cx).is_ready());
+                assert!(poll2.as_mut().is_terminated());
+                assert!(!event.is_set());
+            }
+
+            #[test]
+            fn notify_all_wakes_all_waiters() {
+                let event = $event_type::new(false);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let poll1 = event.wait();
+                let poll2 = event.wait();
+                let poll3 = event.wait();
+                pin_mut!(poll1);
+                pin_mut!(poll2);
+                pin_mut!(poll3);
+
+                assert!(poll1.as_mut().poll(cx).is_pending());
+                assert!(poll2.as_mut().poll(cx).is_pending());
+
+                event.notify_all();
+                assert_eq!(count, 2);
+                assert!(!event.is_set());
+
+                assert!(poll1.as_mut().poll(cx).is_ready());
+                assert!(poll2.as_mut().poll(cx).is_ready());
+                // Waiters which start waiting afterwards are not notified
+                assert!(poll3.as_mut().poll(cx).is_pending());
+            }
+
+            #[test]
+            fn cancel_waiter() {
+                let event = $event_type::new(false);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut poll1 = Box::pin(event.wait());
+                let poll2 = event.wait();
+                pin_mut!(poll2);
+
+                assert!(poll1.as_mut().poll(cx).is_pending());
+                assert!(poll2.as_mut().poll(cx).is_pending());
+
+                drop(poll1);
+
+                event.notify_one();
+                assert_eq!(count, 1);
+                assert!(poll2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn notification_is_passed_on_if_notified_waiter_is_cancelled() {
+                let event = $event_type::new(false);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut poll1 = Box::pin(event.wait());
+                let poll2 = event.wait();
+                pin_mut!(poll2);
+
+                assert!(poll1.as_mut().poll(cx).is_pending());
+                assert!(poll2.as_mut().poll(cx).is_pending());
+
+                event.notify_one();
+                assert_eq!(count, 1);
+
+                // The notified waiter doesn't use the notification.
+                // The next waiter must get notified.
+                drop(poll1);
+                assert_eq!(count, 2);
+                assert!(poll2.as_mut().poll(cx).is_ready());
+                assert!(!event.is_set());
+            }
+
+            #[test]
+            fn notification_is_stored_if_last_notified_waiter_is_cancelled() {
+                let event = $event_type::new(false);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut poll1 = Box::pin(event.wait());
+                assert!(poll1.as_mut().poll(cx).is_pending());
+
+                event.notify_one();
+                assert_eq!(count, 1);
+                assert!(!event.is_set());
+
+                drop(poll1);
+                assert!(event.is_set());
+            }
+
+            #[test]
+            fn poll_from_multiple_executors() {
+                let (waker_1, count_1) = new_count_waker();
+                let (waker_2, count_2) = new_count_waker();
+                let event = $event_type::new(false);
+
+                let cx_1 = &mut Context::from_waker(&waker_1);
+                let cx_2 = &mut Context::from_waker(&waker_2);
+
+                let fut = event.wait();
+                pin_mut!(fut);
+
+                assert!(fut.as_mut().poll(cx_1).is_pending());
+                assert!(fut.as_mut().poll(cx_2).is_pending());
+
+                event.notify_one();
+                assert_eq!(count_1, 0);
+                assert_eq!(count_2, 1);
+
+                assert!(fut.as_mut().poll(cx_2).is_ready());
+                assert!(fut.as_mut().is_terminated());
+            }
+        }
+    };
+}
+
+gen_event_tests!(local_auto_reset_event_tests, LocalAutoResetEvent);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::sync::{AutoResetEvent, SharedAutoResetEvent};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    gen_event_tests!(auto_reset_event_tests, AutoResetEvent);
+    gen_event_tests!(shared_auto_reset_event_tests, SharedAutoResetEvent);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn event_futures_are_send() {
+        let event = AutoResetEvent::new(false);
+        is_sync(&event);
+        {
+            let wait_fut = event.wait();
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(event);
+    }
+
+    #[test]
+    fn shared_event_futures_are_send() {
+        let event = SharedAutoResetEvent::new(false);
+        is_sync(&event);
+        {
+            let wait_fut = event.wait();
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(event);
+    }
+
+    #[test]
+    fn multithreaded_smoke() {
+        let event = Arc::new(AutoResetEvent::new(false));
+        let completed = Arc::new(AtomicUsize::new(0));
+
+        let waiters: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|_| {
+                let ev = event.clone();
+                let completed = completed.clone();
+                thread::spawn(move || {
+                    block_on(ev.wait());
+                    completed.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+
+        // Only a single notification can be stored. Therefore wait until it
+        // got consumed before issuing the next one.
+        let mut notifications = 0;
+        while notifications < 4 {
+            if !event.is_set() {
+                event.notify_one();
+                notifications += 1;
+            }
+            thread::yield_now();
+        }
+
+        for waiter in waiters.into_iter() {
+            waiter.join().unwrap();
+        }
+        assert_eq!(4, completed.load(Ordering::SeqCst));
+    }
+}
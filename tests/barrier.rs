@@ -0,0 +1,248 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::sync::LocalBarrier;
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_barrier_tests {
+    ($mod_name:ident, $barrier_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn single_task_is_always_leader() {
+                let barrier = $barrier_type::new(1);
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                for generation in 0..3 {
+                    assert_eq!(generation, barrier.generation());
+                    let fut = barrier.wait();
+                    pin_mut!(fut);
+                    assert!(!fut.as_mut().is_terminated());
+                    match fut.as_mut().poll(cx) {
+                        Poll::Ready(res) => assert!(res.is_leader()),
+                        Poll::Pending => panic!("Expected barrier to complete"),
+                    }
+                    assert!(fut.as_mut().is_terminated());
+                }
+                assert_eq!(3, barrier.generation());
+            }
+
+            #[test]
+            fn zero_tasks_behaves_like_single_task() {
+                let barrier = $barrier_type::new(0);
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut = barrier.wait();
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn completes_once_all_tasks_arrived() {
+                let barrier = $barrier_type::new(3);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut1 = barrier.wait();
+                let fut2 = barrier.wait();
+                let fut3 = barrier.wait();
+                pin_mut!(fut1, fut2, fut3);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert_eq!(1, barrier.arrived());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert_eq!(2, barrier.arrived());
+                assert_eq!(count, 0);
+
+                match fut3.as_mut().poll(cx) {
+                    Poll::Ready(res) => assert!(res.is_leader()),
+                    Poll::Pending => panic!("Expected barrier to complete"),
+                }
+                assert_eq!(count, 2);
+                assert_eq!(0, barrier.arrived());
+                assert_eq!(1, barrier.generation());
+
+                match fut1.as_mut().poll(cx) {
+                    Poll::Ready(res) => assert!(!res.is_leader()),
+                    Poll::Pending => panic!("Expected barrier to complete"),
+                }
+                match fut2.as_mut().poll(cx) {
+                    Poll::Ready(res) => assert!(!res.is_leader()),
+                    Poll::Pending => panic!("Expected barrier to complete"),
+                }
+                assert!(fut1.as_mut().is_terminated());
+                assert!(fut2.as_mut().is_terminated());
+                assert!(fut3.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn barrier_is_reusable() {
+                let barrier = $barrier_type::new(2);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                for generation in 0..3 {
+                    let fut1 = barrier.wait();
+                    let fut2 = barrier.wait();
+                    pin_mut!(fut1, fut2);
+
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    assert!(fut2.as_mut().poll(cx).is_ready());
+                    assert_eq!(count, generation + 1);
+                    assert!(fut1.as_mut().poll(cx).is_ready());
+                }
+                assert_eq!(3, barrier.generation());
+            }
+
+            #[test]
+            fn unpolled_futures_are_not_counted() {
+                let barrier = $barrier_type::new(2);
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut1 = barrier.wait();
+                let fut2 = barrier.wait();
+                drop(fut1);
+                assert_eq!(0, barrier.arrived());
+
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert_eq!(1, barrier.arrived());
+            }
+
+            #[test]
+            fn cancelled_waiter_is_uncounted() {
+                let barrier = $barrier_type::new(2);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 = Box::pin(barrier.wait());
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert_eq!(1, barrier.arrived());
+                drop(fut1);
+                assert_eq!(0, barrier.arrived());
+
+                let fut2 = barrier.wait();
+                let fut3 = barrier.wait();
+                pin_mut!(fut2, fut3);
+                // The cancelled task must not complete the generation
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert_eq!(0, barrier.generation());
+                assert!(fut3.as_mut().poll(cx).is_ready());
+                assert_eq!(count, 1);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+                assert_eq!(1, barrier.generation());
+            }
+
+            #[test]
+            fn dropping_completed_waiter_does_not_affect_next_generation() {
+                let barrier = $barrier_type::new(2);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 = Box::pin(barrier.wait());
+                let fut2 = barrier.wait();
+                pin_mut!(fut2);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_ready());
+                assert_eq!(count, 1);
+
+                // The generation got completed without fut1 being polled
+                // again. Dropping it must not uncount it.
+                let fut3 = barrier.wait();
+                pin_mut!(fut3);
+                assert!(fut3.as_mut().poll(cx).is_pending());
+                drop(fut1);
+                assert_eq!(1, barrier.arrived());
+            }
+
+            #[test]
+            fn poll_from_multiple_executors() {
+                let (waker_1, count_1) = new_count_waker();
+                let (waker_2, count_2) = new_count_waker();
+                let barrier = $barrier_type::new(2);
+
+                let cx_1 = &mut Context::from_waker(&waker_1);
+                let cx_2 = &mut Context::from_waker(&waker_2);
+
+                let fut1 = barrier.wait();
+                let fut2 = barrier.wait();
+                pin_mut!(fut1, fut2);
+
+                assert!(fut1.as_mut().poll(cx_1).is_pending());
+                assert!(fut1.as_mut().poll(cx_2).is_pending());
+
+                assert!(fut2.as_mut().poll(cx_2).is_ready());
+                assert_eq!(count_1, 0);
+                assert_eq!(count_2, 1);
+
+                assert!(fut1.as_mut().poll(cx_2).is_ready());
+                assert!(fut1.as_mut().is_terminated());
+            }
+        }
+    };
+}
+
+gen_barrier_tests!(local_barrier_tests, LocalBarrier);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::sync::Barrier;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    gen_barrier_tests!(barrier_tests, Barrier);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn barrier_futures_are_send() {
+        let barrier = Barrier::new(2);
+        is_sync(&barrier);
+        {
+            let wait_fut = barrier.wait();
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(barrier);
+    }
+
+    #[test]
+    fn multithreaded_smoke() {
+        const TASKS: usize = 4;
+        const GENERATIONS: usize = 10;
+        let barrier = Arc::new(Barrier::new(TASKS));
+        let leaders = Arc::new(AtomicUsize::new(0));
+
+        let threads: Vec<thread::JoinHandle<()>> = (0..TASKS)
+            .map(|_| {
+                let barrier = barrier.clone();
+                let leaders = leaders.clone();
+                thread::spawn(move || {
+                    for _ in 0..GENERATIONS {
+                        if block_on(barrier.wait()).is_leader() {
+                            leaders.fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads.into_iter() {
+            thread.join().unwrap();
+        }
+        assert_eq!(GENERATIONS, leaders.load(Ordering::SeqCst));
+        assert_eq!(GENERATIONS, barrier.generation());
+    }
+}
@@ -0,0 +1,367 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::channel::{
+    BroadcastReceiveError, ChannelSendError, LocalBroadcastChannel,
+    TryBroadcastReceiveError,
+};
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_broadcast_tests {
+    ($mod_name:ident, $channel_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            type ChannelType = $channel_type<i32, [i32; 3]>;
+
+            macro_rules! assert_receive {
+                ($cx:ident, $subscriber:expr, $expected: expr) => {{
+                    let receive_fut = $subscriber.receive();
+                    pin_mut!(receive_fut);
+                    assert!(!receive_fut.as_mut().is_terminated());
+                    match receive_fut.as_mut().poll($cx) {
+                        Poll::Ready(res) => assert_eq!($expected, res),
+                        Poll::Pending => panic!("future is not ready"),
+                    };
+                    assert!(receive_fut.as_mut().is_terminated());
+                }};
+            }
+
+            #[test]
+            fn close_status() {
+                let channel = ChannelType::new();
+                assert!(channel.close().is_newly_closed());
+                assert!(channel.close().is_already_closed());
+            }
+
+            #[test]
+            fn send_on_closed_channel() {
+                let channel = ChannelType::new();
+                assert!(channel.close().is_newly_closed());
+                assert_eq!(Err(ChannelSendError(5)), channel.send(5));
+            }
+
+            #[test]
+            fn subscriber_starts_at_tail() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                assert_eq!(Ok(()), channel.send(1));
+
+                let mut subscriber = channel.subscribe();
+                assert_eq!(
+                    Err(TryBroadcastReceiveError::Empty),
+                    subscriber.try_receive()
+                );
+                assert_eq!(Ok(()), channel.send(2));
+                assert_receive!(cx, subscriber, Ok(2));
+            }
+
+            #[test]
+            fn every_subscriber_receives_every_value() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut subscriber1 = channel.subscribe();
+                let mut subscriber2 = channel.subscribe();
+
+                assert_eq!(Ok(()), channel.send(1));
+                assert_eq!(Ok(()), channel.send(2));
+
+                assert_receive!(cx, subscriber1, Ok(1));
+                assert_receive!(cx, subscriber1, Ok(2));
+                assert_eq!(Ok(1), subscriber2.try_receive());
+                assert_receive!(cx, subscriber2, Ok(2));
+                assert_eq!(
+                    Err(TryBroadcastReceiveError::Empty),
+                    subscriber1.try_receive()
+                );
+                assert_eq!(
+                    Err(TryBroadcastReceiveError::Empty),
+                    subscriber2.try_receive()
+                );
+            }
+
+            #[test]
+            fn receive_waits_for_value() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut subscriber1 = channel.subscribe();
+                let mut subscriber2 = channel.subscribe();
+
+                let fut1 = subscriber1.receive();
+                pin_mut!(fut1);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = subscriber2.receive();
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert_eq!(count, 0);
+
+                assert_eq!(Ok(()), channel.send(7));
+                assert_eq!(count, 2);
+                assert_eq!(Poll::Ready(Ok(7)), fut1.as_mut().poll(cx));
+                assert!(fut1.as_mut().is_terminated());
+                assert_eq!(Poll::Ready(Ok(7)), fut2.as_mut().poll(cx));
+                assert!(fut2.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn lagging_subscriber_gets_notified() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut slow = channel.subscribe();
+                let mut fast = channel.subscribe();
+
+                for value in 1..=5 {
+                    assert_eq!(Ok(()), channel.send(value));
+                    assert_eq!(Ok(value), fast.try_receive());
+                }
+
+                assert_receive!(
+                    cx,
+                    slow,
+                    Err(BroadcastReceiveError::Lagged(2))
+                );
+                assert_receive!(cx, slow, Ok(3));
+                assert_receive!(cx, slow, Ok(4));
+
+                for value in 6..=9 {
+                    assert_eq!(Ok(()), channel.send(value));
+                }
+                assert_eq!(
+                    Err(TryBroadcastReceiveError::Lagged(2)),
+                    slow.try_receive()
+                );
+                assert_eq!(Ok(7), slow.try_receive());
+                assert_eq!(Ok(8), slow.try_receive());
+                assert_eq!(Ok(9), slow.try_receive());
+                assert_eq!(
+                    Err(TryBroadcastReceiveError::Empty),
+                    slow.try_receive()
+                );
+            }
+
+            #[test]
+            fn cloned_subscriber_keeps_position() {
+                let channel = ChannelType::new();
+                let mut subscriber = channel.subscribe();
+                assert_eq!(Ok(()), channel.send(1));
+                assert_eq!(Ok(()), channel.send(2));
+                assert_eq!(Ok(1), subscriber.try_receive());
+
+                let mut cloned = subscriber.clone();
+                assert_eq!(Ok(2), cloned.try_receive());
+                assert_eq!(Ok(2), subscriber.try_receive());
+            }
+
+            #[test]
+            fn close_delivers_retained_values() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut subscriber1 = channel.subscribe();
+                let mut subscriber2 = channel.subscribe();
+                assert_eq!(Ok(()), channel.send(1));
+                assert_eq!(Ok(1), subscriber2.try_receive());
+
+                let fut = subscriber2.receive();
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                assert!(channel.close().is_newly_closed());
+                assert_eq!(count, 1);
+                assert_eq!(
+                    Poll::Ready(Err(BroadcastReceiveError::Closed)),
+                    fut.as_mut().poll(cx)
+                );
+                assert!(fut.as_mut().is_terminated());
+
+                assert_receive!(cx, subscriber1, Ok(1));
+                assert_receive!(
+                    cx,
+                    subscriber1,
+                    Err(BroadcastReceiveError::Closed)
+                );
+                assert_eq!(
+                    Err(TryBroadcastReceiveError::Closed),
+                    subscriber1.try_receive()
+                );
+            }
+
+            #[test]
+            fn cancel_receive() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut subscriber1 = channel.subscribe();
+                let mut subscriber2 = channel.subscribe();
+
+                let mut fut1 = Box::pin(subscriber1.receive());
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = subscriber2.receive();
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                drop(fut1);
+                assert_eq!(Ok(()), channel.send(3));
+                assert_eq!(count, 1);
+                assert_eq!(Poll::Ready(Ok(3)), fut2.as_mut().poll(cx));
+
+                // The cancelled receive didn't consume the value
+                assert_eq!(Ok(3), subscriber1.try_receive());
+            }
+
+            #[test]
+            fn channel_without_capacity_reports_lost_values() {
+                let channel = $channel_type::<i32, [i32; 0]>::new();
+                let mut subscriber = channel.subscribe();
+                assert_eq!(Ok(()), channel.send(1));
+                assert_eq!(Ok(()), channel.send(2));
+                assert_eq!(
+                    Err(TryBroadcastReceiveError::Lagged(2)),
+                    subscriber.try_receive()
+                );
+                assert_eq!(
+                    Err(TryBroadcastReceiveError::Empty),
+                    subscriber.try_receive()
+                );
+            }
+        }
+    };
+}
+
+gen_broadcast_tests!(local_broadcast_channel_tests, LocalBroadcastChannel);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::channel::{
+        shared::broadcast_channel, BroadcastChannel,
+    };
+    use std::thread;
+
+    gen_broadcast_tests!(broadcast_channel_tests, BroadcastChannel);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn channel_futures_are_send() {
+        let channel = BroadcastChannel::<i32, [i32; 3]>::new();
+        is_sync(&channel);
+        let mut subscriber = channel.subscribe();
+        is_send(&subscriber);
+        {
+            let recv_fut = subscriber.receive();
+            is_send(&recv_fut);
+            pin_mut!(recv_fut);
+            is_send(&recv_fut);
+        }
+
+        let (sender, mut receiver) = broadcast_channel::<i32>(3);
+        is_send(&sender);
+        is_sync(&sender);
+        is_send(&receiver);
+        is_sync(&receiver);
+        let fut = async move {
+            let _ = receiver.receive().await;
+        };
+        is_send_value(fut);
+    }
+
+    #[test]
+    fn shared_broadcast_smoke_test() {
+        let waker = &panic_waker();
+        let cx = &mut Context::from_waker(&waker);
+        let (sender, mut receiver1) = broadcast_channel::<i32>(2);
+
+        assert_eq!(Ok(()), sender.send(1));
+        let mut receiver2 = sender.subscribe();
+        let mut receiver3 = receiver1.clone();
+        assert_eq!(Ok(()), sender.send(2));
+        assert_eq!(Ok(()), sender.send(3));
+
+        {
+            let fut = receiver1.receive();
+            pin_mut!(fut);
+            assert_eq!(
+                Poll::Ready(Err(BroadcastReceiveError::Lagged(1))),
+                fut.as_mut().poll(cx)
+            );
+        }
+        assert_eq!(Ok(2), receiver1.try_receive());
+        assert_eq!(Ok(3), receiver1.try_receive());
+        assert_eq!(Ok(2), receiver2.try_receive());
+        assert_eq!(Ok(3), receiver2.try_receive());
+        assert_eq!(
+            Err(TryBroadcastReceiveError::Lagged(1)),
+            receiver3.try_receive()
+        );
+
+        assert!(sender.close().is_newly_closed());
+        assert_eq!(Err(ChannelSendError(4)), sender.send(4));
+        assert_eq!(Ok(2), receiver3.try_receive());
+    }
+
+    #[test]
+    fn shared_channel_is_closed_when_senders_are_dropped() {
+        let (waker, count) = new_count_waker();
+        let cx = &mut Context::from_waker(&waker);
+        let (sender, mut receiver) = broadcast_channel::<i32>(2);
+        let sender2 = sender.clone();
+
+        {
+            let fut = receiver.receive();
+            pin_mut!(fut);
+            assert!(fut.as_mut().poll(cx).is_pending());
+
+            drop(sender);
+            assert_eq!(count, 0);
+            assert_eq!(Ok(()), sender2.send(5));
+            assert_eq!(count, 1);
+            assert_eq!(Poll::Ready(Ok(5)), fut.as_mut().poll(cx));
+        }
+
+        drop(sender2);
+        assert_eq!(
+            Err(TryBroadcastReceiveError::Closed),
+            receiver.try_receive()
+        );
+    }
+
+    #[test]
+    fn values_are_broadcast_between_threads() {
+        let (sender, receiver) = broadcast_channel::<usize>(1000);
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let mut receiver = receiver.clone();
+                thread::spawn(move || {
+                    block_on(async {
+                        let mut sum = 0;
+                        while let Ok(value) = receiver.receive().await {
+                            sum += value;
+                        }
+                        sum
+                    })
+                })
+            })
+            .collect();
+        drop(receiver);
+
+        for value in 0..1000 {
+            assert_eq!(Ok(()), sender.send(value));
+        }
+        drop(sender);
+
+        for thread in threads {
+            assert_eq!((0..1000).sum::<usize>(), thread.join().unwrap());
+        }
+    }
+}
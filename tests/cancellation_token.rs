@@ -0,0 +1,353 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::Context;
+use futures_intrusive::sync::LocalCancellationToken;
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_cancellation_token_tests {
+    ($mod_name:ident, $token_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn synchronous() {
+                let token = $token_type::new();
+
+                assert!(!token.is_cancelled());
+                token.cancel();
+                assert!(token.is_cancelled());
+                token.cancel();
+                assert!(token.is_cancelled());
+            }
+
+            #[test]
+            fn cancelled_future_completes_if_token_is_cancelled() {
+                let token = $token_type::new();
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                token.cancel();
+
+                let fut = token.cancelled();
+                pin_mut!(fut);
+                assert!(!fut.as_mut().is_terminated());
+                assert!(fut.as_mut().poll(cx).is_ready());
+                assert!(fut.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn cancel_wakes_up_waiters() {
+                let token = $token_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut1 = token.cancelled();
+                let fut2 = token.cancelled();
+                pin_mut!(fut1, fut2);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                token.cancel();
+                assert_eq!(count, 2);
+
+                assert!(fut1.as_mut().poll(cx).is_ready());
+                assert!(fut2.as_mut().poll(cx).is_ready());
+                assert!(fut1.as_mut().is_terminated());
+                assert!(fut2.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn cancelling_parent_cancels_children() {
+                let parent = $token_type::new();
+                let child = parent.child_token();
+                let grand_child = child.child_token();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let child_fut = child.cancelled();
+                let grand_child_fut = grand_child.cancelled();
+                pin_mut!(child_fut, grand_child_fut);
+                assert!(child_fut.as_mut().poll(cx).is_pending());
+                assert!(grand_child_fut.as_mut().poll(cx).is_pending());
+
+                parent.cancel();
+                assert!(parent.is_cancelled());
+                assert!(child.is_cancelled());
+                assert!(grand_child.is_cancelled());
+                assert!(count.get() >= 2);
+
+                assert!(child_fut.as_mut().poll(cx).is_ready());
+                assert!(grand_child_fut.as_mut().poll(cx).is_ready());
+
+                // Tokens which are created after the cancellation start in
+                // the cancelled state
+                let late_child = parent.child_token();
+                assert!(late_child.is_cancelled());
+            }
+
+            #[test]
+            fn cancelling_child_does_not_cancel_parent() {
+                let parent = $token_type::new();
+                let child1 = parent.child_token();
+                let child2 = parent.child_token();
+                let grand_child = child1.child_token();
+                let (waker, _) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let parent_fut = parent.cancelled();
+                let child2_fut = child2.cancelled();
+                let grand_child_fut = grand_child.cancelled();
+                pin_mut!(parent_fut, child2_fut, grand_child_fut);
+                assert!(parent_fut.as_mut().poll(cx).is_pending());
+                assert!(child2_fut.as_mut().poll(cx).is_pending());
+                assert!(grand_child_fut.as_mut().poll(cx).is_pending());
+
+                child1.cancel();
+                assert!(child1.is_cancelled());
+                assert!(grand_child.is_cancelled());
+                assert!(!parent.is_cancelled());
+                assert!(!child2.is_cancelled());
+
+                assert!(grand_child_fut.as_mut().poll(cx).is_ready());
+                assert!(parent_fut.as_mut().poll(cx).is_pending());
+                assert!(child2_fut.as_mut().poll(cx).is_pending());
+
+                parent.cancel();
+                assert!(parent_fut.as_mut().poll(cx).is_ready());
+                assert!(child2_fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn waiters_are_woken_after_sibling_cancellation() {
+                let parent = $token_type::new();
+                let child1 = parent.child_token();
+                let child2 = parent.child_token();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let child2_fut = child2.cancelled();
+                pin_mut!(child2_fut);
+                assert!(child2_fut.as_mut().poll(cx).is_pending());
+
+                child1.cancel();
+                // A wakeup after the cancellation of an unrelated token is
+                // allowed, but must not complete the future
+                assert!(child2_fut.as_mut().poll(cx).is_pending());
+                let wakeups = count.get();
+
+                child2.cancel();
+                assert!(count.get() > wakeups);
+                assert!(child2_fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn cancelling_child_only_wakes_its_waiters() {
+                let parent = $token_type::new();
+                let child1 = parent.child_token();
+                let child2 = parent.child_token();
+                let grand_child1 = child1.child_token();
+                let grand_child2 = child2.child_token();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let parent_fut = parent.cancelled();
+                let child2_fut = child2.cancelled();
+                let grand_child1_fut = grand_child1.cancelled();
+                let grand_child2_fut = grand_child2.cancelled();
+                pin_mut!(
+                    parent_fut,
+                    child2_fut,
+                    grand_child1_fut,
+                    grand_child2_fut
+                );
+                assert!(parent_fut.as_mut().poll(cx).is_pending());
+                assert!(child2_fut.as_mut().poll(cx).is_pending());
+                assert!(grand_child1_fut.as_mut().poll(cx).is_pending());
+                assert!(grand_child2_fut.as_mut().poll(cx).is_pending());
+
+                child1.cancel();
+                assert_eq!(count, 1);
+                assert!(grand_child1_fut.as_mut().poll(cx).is_ready());
+
+                grand_child2.cancel();
+                assert_eq!(count, 2);
+                assert!(grand_child2_fut.as_mut().poll(cx).is_ready());
+
+                parent.cancel();
+                assert_eq!(count, 4);
+                assert!(parent_fut.as_mut().poll(cx).is_ready());
+                assert!(child2_fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn child_tokens_can_be_moved_after_waiting() {
+                let parent = $token_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let child = parent.child_token();
+                let grand_child = child.child_token();
+                {
+                    let mut fut = Box::pin(grand_child.cancelled());
+                    assert!(fut.as_mut().poll(cx).is_pending());
+                }
+
+                let moved_grand_child = Box::new(grand_child);
+                parent.cancel();
+                assert_eq!(count, 0);
+                assert!(child.is_cancelled());
+                assert!(moved_grand_child.is_cancelled());
+            }
+
+            #[test]
+            fn child_tokens_can_be_dropped_after_leaking_a_future() {
+                let parent = $token_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let child = Box::new(parent.child_token());
+                let grand_child = Box::new(child.child_token());
+                {
+                    let mut fut = Box::pin(grand_child.cancelled());
+                    assert!(fut.as_mut().poll(cx).is_pending());
+                    core::mem::forget(fut);
+                }
+                drop(grand_child);
+                drop(child);
+
+                parent.cancel();
+                assert_eq!(count, 0);
+                assert!(parent.is_cancelled());
+            }
+
+            #[test]
+            fn cancel_waiter() {
+                let token = $token_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 = Box::pin(token.cancelled());
+                let fut2 = token.cancelled();
+                pin_mut!(fut2);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                drop(fut1);
+
+                token.cancel();
+                assert_eq!(count, 1);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn poll_from_multiple_executors() {
+                let (waker_1, count_1) = new_count_waker();
+                let (waker_2, count_2) = new_count_waker();
+                let token = $token_type::new();
+
+                let cx_1 = &mut Context::from_waker(&waker_1);
+                let cx_2 = &mut Context::from_waker(&waker_2);
+
+                let fut = token.cancelled();
+                pin_mut!(fut);
+
+                assert!(fut.as_mut().poll(cx_1).is_pending());
+                assert!(fut.as_mut().poll(cx_2).is_pending());
+
+                token.cancel();
+                assert_eq!(count_1, 0);
+                assert_eq!(count_2, 1);
+
+                assert!(fut.as_mut().poll(cx_2).is_ready());
+                assert!(fut.as_mut().is_terminated());
+            }
+        }
+    };
+}
+
+gen_cancellation_token_tests!(
+    local_cancellation_token_tests,
+    LocalCancellationToken
+);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::sync::{CancellationToken, SharedCancellationToken};
+    use std::thread;
+
+    gen_cancellation_token_tests!(cancellation_token_tests, CancellationToken);
+    gen_cancellation_token_tests!(
+        shared_cancellation_token_tests,
+        SharedCancellationToken
+    );
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn cancellation_token_futures_are_send() {
+        let token = CancellationToken::new();
+        is_sync(&token);
+        {
+            let child = token.child_token();
+            is_send(&child);
+            let wait_fut = child.cancelled();
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(token);
+    }
+
+    #[test]
+    fn shared_cancellation_token_futures_are_send() {
+        let token = SharedCancellationToken::new();
+        is_sync(&token);
+        {
+            let wait_fut = token.cancelled();
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(token.child_token());
+        is_send_value(token);
+    }
+
+    #[test]
+    fn dropped_shared_child_tokens_are_not_cancelled() {
+        let token = SharedCancellationToken::new();
+        for _ in 0..10 {
+            let _ = token.child_token();
+        }
+        let child = token.child_token();
+        token.cancel();
+        assert!(child.is_cancelled());
+    }
+
+    #[test]
+    fn multithreaded_smoke() {
+        let parent = SharedCancellationToken::new();
+
+        let waiters: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|_| {
+                let child = parent.child_token();
+                thread::spawn(move || {
+                    block_on(child.child_token().cancelled());
+                    assert!(child.is_cancelled());
+                })
+            })
+            .collect();
+
+        parent.cancel();
+
+        for waiter in waiters.into_iter() {
+            waiter.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::sync::{LocalCondvar, LocalMutex};
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_condvar_tests {
+    ($mod_name:ident, $condvar_type:ident, $mutex_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn notify_without_waiters() {
+                let condvar = $condvar_type::new();
+                condvar.notify_one();
+                condvar.notify_all();
+            }
+
+            #[test]
+            fn wait_releases_and_reacquires_the_mutex() {
+                let mutex = $mutex_type::new(5, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let guard = mutex.try_lock().unwrap();
+                let fut = condvar.wait(guard);
+                pin_mut!(fut);
+                // The mutex is only released once the future is polled
+                assert!(mutex.is_locked());
+                assert!(!fut.as_mut().is_terminated());
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert!(!mutex.is_locked());
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                condvar.notify_one();
+                assert_eq!(count, 1);
+
+                match fut.as_mut().poll(cx) {
+                    Poll::Ready(guard) => {
+                        assert!(mutex.is_locked());
+                        assert_eq!(5, *guard);
+                    }
+                    Poll::Pending => panic!("Expected the mutex to be locked"),
+                }
+                assert!(fut.as_mut().is_terminated());
+                assert!(!mutex.is_locked());
+            }
+
+            #[test]
+            fn notified_waiter_waits_for_the_mutex() {
+                let mutex = $mutex_type::new(5, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                let mut guard = mutex.try_lock().unwrap();
+                *guard = 7;
+                condvar.notify_one();
+                assert_eq!(count, 1);
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                drop(guard);
+                assert_eq!(count, 2);
+                match fut.as_mut().poll(cx) {
+                    Poll::Ready(guard) => assert_eq!(7, *guard),
+                    Poll::Pending => panic!("Expected the mutex to be locked"),
+                };
+            }
+
+            #[test]
+            fn notify_one_wakes_oldest_waiter() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut1 = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut1);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                condvar.notify_one();
+                assert_eq!(count, 1);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                let guard = match fut1.as_mut().poll(cx) {
+                    Poll::Ready(guard) => guard,
+                    Poll::Pending => panic!("Expected the mutex to be locked"),
+                };
+                drop(guard);
+
+                condvar.notify_one();
+                assert_eq!(count, 2);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn notify_all_wakes_all_waiters() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut1 = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut1);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                condvar.notify_all();
+                assert_eq!(count, 2);
+
+                let guard = match fut1.as_mut().poll(cx) {
+                    Poll::Ready(guard) => guard,
+                    Poll::Pending => panic!("Expected the mutex to be locked"),
+                };
+                // The second waiter needs to wait for the mutex
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                drop(guard);
+                assert_eq!(count, 3);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn dropping_unpolled_future_releases_the_mutex() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+
+                let fut = condvar.wait(mutex.try_lock().unwrap());
+                assert!(mutex.is_locked());
+                drop(fut);
+                assert!(!mutex.is_locked());
+            }
+
+            #[test]
+            fn cancel_waiting_future() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 =
+                    Box::pin(condvar.wait(mutex.try_lock().unwrap()));
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                drop(fut1);
+                assert!(!mutex.is_locked());
+
+                condvar.notify_one();
+                assert_eq!(count, 1);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn notification_is_passed_on_if_notified_waiter_is_cancelled() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 =
+                    Box::pin(condvar.wait(mutex.try_lock().unwrap()));
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                condvar.notify_one();
+                assert_eq!(count, 1);
+
+                drop(fut1);
+                assert_eq!(count, 2);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn cancel_future_while_reacquiring_the_mutex() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 =
+                    Box::pin(condvar.wait(mutex.try_lock().unwrap()));
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                let guard = mutex.try_lock().unwrap();
+                condvar.notify_one();
+                assert_eq!(count, 1);
+                // fut1 is now waiting for the mutex
+                assert!(fut1.as_mut().poll(cx).is_pending());
+
+                // The notification is passed on to fut2
+                drop(fut1);
+                assert_eq!(count, 2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                drop(guard);
+                assert_eq!(count, 3);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+                assert!(!mutex.is_locked());
+            }
+
+            #[test]
+            fn wait_while_returns_immediately_if_condition_is_not_met() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut =
+                    condvar.wait_while(mutex.try_lock().unwrap(), |v| *v != 0);
+                pin_mut!(fut);
+                assert!(!fut.as_mut().is_terminated());
+                assert!(fut.as_mut().poll(cx).is_ready());
+                assert!(fut.as_mut().is_terminated());
+                assert!(!mutex.is_locked());
+            }
+
+            #[test]
+            fn wait_while_waits_until_condition_is_not_met() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut =
+                    condvar.wait_while(mutex.try_lock().unwrap(), |v| *v < 2);
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert!(!mutex.is_locked());
+
+                *mutex.try_lock().unwrap() = 1;
+                condvar.notify_all();
+                assert_eq!(count, 1);
+                // The condition is still true
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert!(!mutex.is_locked());
+
+                *mutex.try_lock().unwrap() = 2;
+                condvar.notify_one();
+                assert_eq!(count, 2);
+                match fut.as_mut().poll(cx) {
+                    Poll::Ready(guard) => assert_eq!(2, *guard),
+                    Poll::Pending => panic!("Expected the mutex to be locked"),
+                }
+                assert!(fut.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn cancel_wait_while() {
+                let mutex = $mutex_type::new(0, false);
+                let condvar = $condvar_type::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 = Box::pin(
+                    condvar.wait_while(mutex.try_lock().unwrap(), |v| *v == 0),
+                );
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = condvar.wait(mutex.try_lock().unwrap());
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                condvar.notify_one();
+                drop(fut1);
+                assert_eq!(count, 2);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+        }
+    };
+}
+
+gen_condvar_tests!(local_condvar_tests, LocalCondvar, LocalMutex);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::sync::{Condvar, Mutex};
+    use std::sync::Arc;
+    use std::thread;
+
+    gen_condvar_tests!(condvar_tests, Condvar, Mutex);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn condvar_futures_are_send() {
+        let mutex = Mutex::new(0, true);
+        let condvar = Condvar::new();
+        is_sync(&condvar);
+        {
+            let wait_fut = condvar.wait(mutex.try_lock().unwrap());
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        {
+            let wait_fut =
+                condvar.wait_while(mutex.try_lock().unwrap(), |v| *v == 0);
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(condvar);
+    }
+
+    #[test]
+    fn multithreaded_smoke() {
+        let state = Arc::new((Mutex::new(0usize, false), Condvar::new()));
+
+        let producers: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|_| {
+                let state = state.clone();
+                thread::spawn(move || {
+                    block_on(async {
+                        let (mutex, condvar) = &*state;
+                        let mut guard = mutex.lock().await;
+                        *guard += 1;
+                        drop(guard);
+                        condvar.notify_all();
+                    })
+                })
+            })
+            .collect();
+
+        block_on(async {
+            let (mutex, condvar) = &*state;
+            let guard =
+                condvar.wait_while(mutex.lock().await, |v| *v < 4).await;
+            assert_eq!(4, *guard);
+        });
+
+        for producer in producers.into_iter() {
+            producer.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,390 @@
+#![cfg(feature = "deadlock_detection")]
+
+use futures::future::Future;
+use futures::task::Context;
+use futures_intrusive::sync::deadlock_detection::{
+    reset_deadlock_handler, set_deadlock_handler, Deadlock,
+};
+use futures_intrusive::sync::{
+    LocalMutex, LocalMutexGuard, Mutex, MutexGuard, SharedMutex,
+    SharedMutexGuard,
+};
+use futures_test::task::new_count_waker;
+use pin_utils::pin_mut;
+use std::sync::{Mutex as StdMutex, MutexGuard as StdMutexGuard};
+
+/// The deadlock handler is global. Tests which install it must therefore not
+/// run concurrently.
+static SERIALIZE: StdMutex<()> = StdMutex::new(());
+
+static REPORTS: StdMutex<Vec<Deadlock>> = StdMutex::new(Vec::new());
+
+fn serialize() -> StdMutexGuard<'static, ()> {
+    SERIALIZE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn record_reports() {
+    REPORTS.lock().unwrap().clear();
+    set_deadlock_handler(|deadlock| {
+        REPORTS.lock().unwrap().push(deadlock.clone());
+    });
+}
+
+fn take_reports() -> Vec<Deadlock> {
+    core::mem::take(&mut *REPORTS.lock().unwrap())
+}
+
+macro_rules! gen_deadlock_detection_tests {
+    ($mod_name:ident, $mutex_type:ident, $guard_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn two_lock_deadlock_is_reported_as_cycle() {
+                let _serialized = serialize();
+                record_reports();
+                let (waker1, _) = new_count_waker();
+                let cx1 = &mut Context::from_waker(&waker1);
+                let (waker2, _) = new_count_waker();
+                let cx2 = &mut Context::from_waker(&waker2);
+                let mtx1 = $mutex_type::new(1, false);
+                let mtx2 = $mutex_type::new(2, false);
+
+                let lock1 = mtx1.lock();
+                pin_mut!(lock1);
+                let guard1 = lock1.as_mut().poll(cx1);
+                assert!(guard1.is_ready());
+                let lock2 = mtx2.lock();
+                pin_mut!(lock2);
+                let guard2 = lock2.as_mut().poll(cx2);
+                assert!(guard2.is_ready());
+
+                // Task 1 holds mutex 1 and waits for mutex 2
+                let wait1 = mtx2.lock();
+                pin_mut!(wait1);
+                assert!(wait1.as_mut().poll(cx1).is_pending());
+                assert!(take_reports().is_empty());
+
+                // Task 2 holds mutex 2 and waits for mutex 1
+                let wait2 = mtx1.lock();
+                pin_mut!(wait2);
+                assert!(wait2.as_mut().poll(cx2).is_pending());
+                assert_eq!(
+                    vec![Deadlock::Cycle(vec![mtx1.lock_id(), mtx2.lock_id()])],
+                    take_reports()
+                );
+
+                // Polling again doesn't produce another report
+                assert!(wait2.as_mut().poll(cx2).is_pending());
+                assert!(take_reports().is_empty());
+                reset_deadlock_handler();
+            }
+
+            #[test]
+            #[should_panic(expected = "deadlock detected")]
+            fn default_handler_panics() {
+                let _serialized = serialize();
+                reset_deadlock_handler();
+                let (waker1, _) = new_count_waker();
+                let cx1 = &mut Context::from_waker(&waker1);
+                let (waker2, _) = new_count_waker();
+                let cx2 = &mut Context::from_waker(&waker2);
+                let mtx1 = $mutex_type::new(1, false);
+                let mtx2 = $mutex_type::new(2, false);
+
+                let lock1 = mtx1.lock();
+                pin_mut!(lock1);
+                let guard1 = lock1.as_mut().poll(cx1);
+                assert!(guard1.is_ready());
+                let lock2 = mtx2.lock();
+                pin_mut!(lock2);
+                let guard2 = lock2.as_mut().poll(cx2);
+                assert!(guard2.is_ready());
+
+                let wait1 = mtx2.lock();
+                pin_mut!(wait1);
+                assert!(wait1.as_mut().poll(cx1).is_pending());
+                let wait2 = mtx1.lock();
+                pin_mut!(wait2);
+                let _ = wait2.as_mut().poll(cx2);
+            }
+
+            #[test]
+            fn lock_order_inversion_is_reported() {
+                let _serialized = serialize();
+                record_reports();
+                let (waker1, _) = new_count_waker();
+                let cx1 = &mut Context::from_waker(&waker1);
+                let (waker2, _) = new_count_waker();
+                let cx2 = &mut Context::from_waker(&waker2);
+                let mtx1 = $mutex_type::new(1, false);
+                let mtx2 = $mutex_type::new(2, false);
+
+                // Task 1 acquires mutex 2 while holding mutex 1
+                let lock1 = mtx1.lock();
+                pin_mut!(lock1);
+                let guard1 = lock1.as_mut().poll(cx1);
+                assert!(guard1.is_ready());
+                {
+                    let lock2 = mtx2.lock();
+                    pin_mut!(lock2);
+                    let guard2 = lock2.as_mut().poll(cx1);
+                    assert!(guard2.is_ready());
+                }
+                assert!(take_reports().is_empty());
+
+                // Another task acquires the mutexes in opposite order
+                let lock2 = mtx2.lock();
+                pin_mut!(lock2);
+                let guard2 = lock2.as_mut().poll(cx2);
+                assert!(guard2.is_ready());
+                let wait1 = mtx1.lock();
+                pin_mut!(wait1);
+                assert!(wait1.as_mut().poll(cx2).is_pending());
+                assert_eq!(
+                    vec![Deadlock::LockOrderInversion {
+                        first: mtx2.lock_id(),
+                        second: mtx1.lock_id(),
+                    }],
+                    take_reports()
+                );
+                reset_deadlock_handler();
+            }
+
+            #[test]
+            fn inversion_is_reported_after_the_first_lock_had_been_released() {
+                let _serialized = serialize();
+                record_reports();
+                let (waker1, _) = new_count_waker();
+                let cx1 = &mut Context::from_waker(&waker1);
+                let (waker2, _) = new_count_waker();
+                let cx2 = &mut Context::from_waker(&waker2);
+                let mtx1 = $mutex_type::new(1, false);
+                let mtx2 = $mutex_type::new(2, false);
+
+                {
+                    let lock1 = mtx1.lock();
+                    pin_mut!(lock1);
+                    let guard1 = lock1.as_mut().poll(cx1);
+                    assert!(guard1.is_ready());
+                    let lock2 = mtx2.lock();
+                    pin_mut!(lock2);
+                    let guard2 = lock2.as_mut().poll(cx1);
+                    assert!(guard2.is_ready());
+                }
+
+                // Both mutexes had been released before they get acquired
+                // in opposite order
+                let lock2 = mtx2.lock();
+                pin_mut!(lock2);
+                let guard2 = lock2.as_mut().poll(cx2);
+                assert!(guard2.is_ready());
+                let lock1 = mtx1.lock();
+                pin_mut!(lock1);
+                let guard1 = lock1.as_mut().poll(cx2);
+                assert!(guard1.is_ready());
+                assert_eq!(
+                    vec![Deadlock::LockOrderInversion {
+                        first: mtx2.lock_id(),
+                        second: mtx1.lock_id(),
+                    }],
+                    take_reports()
+                );
+                reset_deadlock_handler();
+            }
+
+            #[test]
+            fn order_is_kept_when_another_lock_is_dropped() {
+                let _serialized = serialize();
+                record_reports();
+                let (waker1, _) = new_count_waker();
+                let cx1 = &mut Context::from_waker(&waker1);
+                let (waker2, _) = new_count_waker();
+                let cx2 = &mut Context::from_waker(&waker2);
+                let mtx1 = $mutex_type::new(1, false);
+                let mtx2 = $mutex_type::new(2, false);
+                let mtx3 = $mutex_type::new(3, false);
+
+                {
+                    let lock1 = mtx1.lock();
+                    pin_mut!(lock1);
+                    let guard1 = lock1.as_mut().poll(cx1);
+                    assert!(guard1.is_ready());
+                    let lock2 = mtx2.lock();
+                    pin_mut!(lock2);
+                    let guard2 = lock2.as_mut().poll(cx1);
+                    assert!(guard2.is_ready());
+                    let lock3 = mtx3.lock();
+                    pin_mut!(lock3);
+                    let guard3 = lock3.as_mut().poll(cx1);
+                    assert!(guard3.is_ready());
+                }
+                drop(mtx2);
+
+                // The order of mutex 1 and 3 is still known
+                let lock3 = mtx3.lock();
+                pin_mut!(lock3);
+                let guard3 = lock3.as_mut().poll(cx2);
+                assert!(guard3.is_ready());
+                let lock1 = mtx1.lock();
+                pin_mut!(lock1);
+                let guard1 = lock1.as_mut().poll(cx2);
+                assert!(guard1.is_ready());
+                assert_eq!(
+                    vec![Deadlock::LockOrderInversion {
+                        first: mtx3.lock_id(),
+                        second: mtx1.lock_id(),
+                    }],
+                    take_reports()
+                );
+                reset_deadlock_handler();
+            }
+
+            #[test]
+            fn lock_id_is_kept_when_the_mutex_gets_moved() {
+                let mtx1 = $mutex_type::new(1, false);
+                let id = mtx1.lock_id();
+                let moved = Box::new(mtx1);
+                assert_eq!(id, moved.lock_id());
+                let mtx2 = $mutex_type::new(2, false);
+                assert_ne!(id, mtx2.lock_id());
+            }
+
+            #[test]
+            fn only_the_acquiring_future_stops_waiting() {
+                let _serialized = serialize();
+                record_reports();
+                let (waker1, _) = new_count_waker();
+                let cx1 = &mut Context::from_waker(&waker1);
+                let (waker2, _) = new_count_waker();
+                let cx2 = &mut Context::from_waker(&waker2);
+                let mtx1 = $mutex_type::new(1, false);
+                let mtx2 = $mutex_type::new(2, false);
+
+                // Two futures of task 1 wait for the same mutex
+                let guard2 = mtx2.try_lock().unwrap();
+                let wait1 = mtx2.lock();
+                pin_mut!(wait1);
+                assert!(wait1.as_mut().poll(cx1).is_pending());
+                let wait2 = mtx2.lock();
+                pin_mut!(wait2);
+                assert!(wait2.as_mut().poll(cx1).is_pending());
+
+                // Only the acquiring future stops waiting
+                drop(guard2);
+                let guard2 = match wait1.as_mut().poll(cx1) {
+                    core::task::Poll::Ready(guard) => guard,
+                    _ => panic!("Expect mutex to get locked"),
+                };
+
+                // Task 2 holds mutex 1 and waits for mutex 2, which is held
+                // by task 1. Task 1 still waits for mutex 2 through the other
+                // future, but no other mutex.
+                let lock1 = mtx1.lock();
+                pin_mut!(lock1);
+                assert!(lock1.as_mut().poll(cx2).is_ready());
+                let wait3 = mtx2.lock();
+                pin_mut!(wait3);
+                assert!(wait3.as_mut().poll(cx2).is_pending());
+                assert!(take_reports().is_empty());
+                drop(guard2);
+                reset_deadlock_handler();
+            }
+
+            #[test]
+            fn bumping_without_waiters_releases_the_previous_holder() {
+                let _serialized = serialize();
+                record_reports();
+                let (waker1, _) = new_count_waker();
+                let cx1 = &mut Context::from_waker(&waker1);
+                let (waker2, _) = new_count_waker();
+                let cx2 = &mut Context::from_waker(&waker2);
+                let (waker3, _) = new_count_waker();
+                let cx3 = &mut Context::from_waker(&waker3);
+                let mtx1 = $mutex_type::new(1, false);
+                let mtx2 = $mutex_type::new(2, false);
+
+                // Task 1 bumps mutex 1 while no other task waits for it, and
+                // releases it afterwards
+                {
+                    let lock1 = mtx1.lock();
+                    pin_mut!(lock1);
+                    let guard1 = match lock1.as_mut().poll(cx1) {
+                        core::task::Poll::Ready(guard) => guard,
+                        _ => panic!("Expect mutex to get locked"),
+                    };
+                    let bump_fut = $guard_type::bump(guard1);
+                    pin_mut!(bump_fut);
+                    assert!(bump_fut.as_mut().poll(cx1).is_ready());
+                }
+
+                // Task 3 holds mutex 1. Task 2 holds mutex 2 and waits for
+                // mutex 1. Task 1 doesn't hold any mutex while it waits for
+                // mutex 2.
+                let lock1 = mtx1.lock();
+                pin_mut!(lock1);
+                let guard1 = lock1.as_mut().poll(cx3);
+                assert!(guard1.is_ready());
+                let lock2 = mtx2.lock();
+                pin_mut!(lock2);
+                let guard2 = lock2.as_mut().poll(cx2);
+                assert!(guard2.is_ready());
+                let wait1 = mtx1.lock();
+                pin_mut!(wait1);
+                assert!(wait1.as_mut().poll(cx2).is_pending());
+                let wait2 = mtx2.lock();
+                pin_mut!(wait2);
+                assert!(wait2.as_mut().poll(cx1).is_pending());
+                assert!(take_reports().is_empty());
+                reset_deadlock_handler();
+            }
+
+            #[test]
+            fn consistent_lock_order_is_not_reported() {
+                let _serialized = serialize();
+                record_reports();
+                let (waker1, _) = new_count_waker();
+                let cx1 = &mut Context::from_waker(&waker1);
+                let (waker2, _) = new_count_waker();
+                let cx2 = &mut Context::from_waker(&waker2);
+                let mtx1 = $mutex_type::new(1, false);
+                let mtx2 = $mutex_type::new(2, false);
+
+                let lock1 = mtx1.lock();
+                pin_mut!(lock1);
+                let guard1 = match lock1.as_mut().poll(cx1) {
+                    core::task::Poll::Ready(guard) => guard,
+                    _ => panic!("Expect mutex to get locked"),
+                };
+
+                // Task 2 waits for the mutexes in the same order
+                let wait1 = mtx1.lock();
+                pin_mut!(wait1);
+                assert!(wait1.as_mut().poll(cx2).is_pending());
+
+                // Another future of the same task waits for a held mutex
+                let wait_same_task = mtx1.lock();
+                pin_mut!(wait_same_task);
+                assert!(wait_same_task.as_mut().poll(cx1).is_pending());
+
+                let lock2 = mtx2.lock();
+                pin_mut!(lock2);
+                let guard2 = lock2.as_mut().poll(cx1);
+                assert!(guard2.is_ready());
+
+                drop(guard1);
+                assert!(wait1.as_mut().poll(cx2).is_ready());
+                assert!(take_reports().is_empty());
+                reset_deadlock_handler();
+            }
+        }
+    };
+}
+
+gen_deadlock_detection_tests!(local_mutex_tests, LocalMutex, LocalMutexGuard);
+gen_deadlock_detection_tests!(mutex_tests, Mutex, MutexGuard);
+gen_deadlock_detection_tests!(
+    shared_mutex_tests,
+    SharedMutex,
+    SharedMutexGuard
+);
@@ -0,0 +1,242 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::sync::LocalLockMap;
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_lock_map_tests {
+    ($mod_name:ident, $map_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn uncontended_lock() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let map = $map_type::<u32>::new();
+                assert!(map.is_empty());
+                assert!(!map.is_locked(&1));
+
+                let fut = map.lock(1);
+                pin_mut!(fut);
+                let guard = match fut.as_mut().poll(cx) {
+                    Poll::Ready(guard) => guard,
+                    Poll::Pending => panic!("Expected the lock to be acquired"),
+                };
+                assert!(fut.as_mut().is_terminated());
+                assert_eq!(1, *guard.key());
+                assert!(map.is_locked(&1));
+                assert_eq!(1, map.len());
+
+                drop(guard);
+                assert!(!map.is_locked(&1));
+                assert!(map.is_empty());
+            }
+
+            #[test]
+            fn different_keys_do_not_exclude_each_other() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let map = $map_type::<u32>::new();
+
+                let guard1 = map.try_lock(1).unwrap();
+                let fut = map.lock(2);
+                pin_mut!(fut);
+                let guard2 = fut.as_mut().poll(cx);
+                assert!(guard2.is_ready());
+                assert_eq!(2, map.len());
+
+                drop(guard1);
+                assert_eq!(1, map.len());
+                assert!(!map.is_locked(&1));
+                assert!(map.is_locked(&2));
+            }
+
+            #[test]
+            fn try_lock() {
+                let map = $map_type::<u32>::new();
+                let guard = map.try_lock(5).unwrap();
+                assert!(map.try_lock(5).is_none());
+                assert!(map.try_lock(6).is_some());
+                drop(guard);
+                assert!(map.try_lock(5).is_some());
+                assert!(map.is_empty());
+            }
+
+            #[test]
+            fn same_key_waits_for_unlock() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let map = $map_type::<u32>::new();
+
+                let guard = map.try_lock(1).unwrap();
+                let fut1 = map.lock(1);
+                pin_mut!(fut1);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = map.lock(1);
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert!(map.try_lock(1).is_none());
+                assert_eq!(count, 0);
+
+                drop(guard);
+                assert_eq!(count, 1);
+                // Waiters are served in FIFO order
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                let guard1 = match fut1.as_mut().poll(cx) {
+                    Poll::Ready(guard) => guard,
+                    Poll::Pending => panic!("Expected the lock to be acquired"),
+                };
+
+                drop(guard1);
+                assert_eq!(count, 2);
+                let guard2 = fut2.as_mut().poll(cx);
+                assert!(guard2.is_ready());
+                assert_eq!(1, map.len());
+                drop(guard2);
+                assert!(map.is_empty());
+            }
+
+            #[test]
+            fn entry_is_removed_after_last_waiter_is_cancelled() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let map = $map_type::<u32>::new();
+
+                let guard = map.try_lock(1).unwrap();
+                let mut fut = Box::pin(map.lock(1));
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                drop(guard);
+                assert_eq!(count, 1);
+                // The entry is kept alive by the notified waiter
+                assert_eq!(1, map.len());
+                assert!(!map.is_locked(&1));
+
+                drop(fut);
+                assert!(map.is_empty());
+            }
+
+            #[test]
+            fn cancelled_waiter_passes_on_notification() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let map = $map_type::<u32>::new();
+
+                let guard = map.try_lock(1).unwrap();
+                let mut fut1 = Box::pin(map.lock(1));
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = map.lock(1);
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                drop(guard);
+                assert_eq!(count, 1);
+                drop(fut1);
+                assert_eq!(count, 2);
+                let guard2 = fut2.as_mut().poll(cx);
+                assert!(guard2.is_ready());
+                drop(guard2);
+                assert!(map.is_empty());
+            }
+
+            #[test]
+            fn unpolled_future_does_not_create_entry() {
+                let map = $map_type::<u32>::new();
+                let fut = map.lock(1);
+                assert!(map.is_empty());
+                drop(fut);
+                assert!(map.is_empty());
+            }
+
+            #[test]
+            fn owned_keys() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let map = $map_type::<String>::new();
+
+                let fut = map.lock("customer".to_string());
+                pin_mut!(fut);
+                let guard = match fut.as_mut().poll(cx) {
+                    Poll::Ready(guard) => guard,
+                    Poll::Pending => panic!("Expected the lock to be acquired"),
+                };
+                assert_eq!("customer", guard.key());
+                assert!(map.is_locked(&"customer".to_string()));
+                assert!(map.try_lock("customer".to_string()).is_none());
+            }
+        }
+    };
+}
+
+gen_lock_map_tests!(local_lock_map_tests, LocalLockMap);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::sync::LockMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    gen_lock_map_tests!(lock_map_tests, LockMap);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn lock_map_futures_are_send() {
+        let map = LockMap::<u32>::new();
+        is_sync(&map);
+        {
+            let fut = map.lock(1);
+            is_send(&fut);
+            pin_mut!(fut);
+            is_send(&fut);
+        }
+
+        let fut = async move {
+            let guard = map.lock(1).await;
+            is_send(&guard);
+            is_sync(&guard);
+        };
+        is_send_value(fut);
+    }
+
+    #[test]
+    fn operations_on_same_key_are_serialized() {
+        let map = Arc::new(LockMap::<u32>::new());
+        let active = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let map = map.clone();
+                let active = active.clone();
+                thread::spawn(move || {
+                    let key = i % 2;
+                    for _ in 0..100 {
+                        block_on(async {
+                            let _guard = map.lock(key).await;
+                            let counter = &active[key as usize];
+                            assert_eq!(
+                                0,
+                                counter.fetch_add(1, Ordering::SeqCst)
+                            );
+                            thread::yield_now();
+                            counter.fetch_sub(1, Ordering::SeqCst);
+                        });
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert!(map.is_empty());
+    }
+}
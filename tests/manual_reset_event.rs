@@ -160,12 +160,13 @@ gen_event_tests!(local_manual_reset_event_tests, LocalManualResetEvent);
 mod if_std {
     use super::*;
     use futures::executor::block_on;
-    use futures_intrusive::sync::ManualResetEvent;
+    use futures_intrusive::sync::{ManualResetEvent, SharedManualResetEvent};
     use std::sync::Arc;
     use std::thread;
     use std::time;
 
     gen_event_tests!(manual_reset_event_tests, ManualResetEvent);
+    gen_event_tests!(shared_manual_reset_event_tests, SharedManualResetEvent);
 
     fn is_send<T: Send>(_: &T) {}
 
@@ -186,6 +187,19 @@ mod if_std {
         is_send_value(event);
     }
 
+    #[test]
+    fn shared_event_futures_are_send() {
+        let event = SharedManualResetEvent::new(false);
+        is_sync(&event);
+        {
+            let wait_fut = event.wait();
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(event);
+    }
+
     #[test]
     fn multithreaded_smoke() {
         let event = Arc::new(ManualResetEvent::new(false));
@@ -212,4 +226,14 @@ mod if_std {
             assert!(diff > time::Duration::from_millis(50));
         }
     }
+
+    static STATIC_EVENT: ManualResetEvent = ManualResetEvent::new(false);
+
+    #[test]
+    fn static_event() {
+        let waiter = thread::spawn(|| block_on(STATIC_EVENT.wait()));
+        STATIC_EVENT.set();
+        waiter.join().unwrap();
+        assert!(STATIC_EVENT.is_set());
+    }
 }
@@ -4,7 +4,8 @@ use futures::{
     stream::{FusedStream, Stream},
 };
 use futures_intrusive::channel::{
-    ChannelSendError, LocalChannel, LocalUnbufferedChannel,
+    ChannelClosedError, ChannelSendError, LocalChannel, LocalUnbufferedChannel,
+    TrySendError,
 };
 use futures_test::task::{new_count_waker, panic_waker};
 use pin_utils::pin_mut;
@@ -151,6 +152,8 @@ macro_rules! gen_mpmc_tests {
     ($mod_name:ident, $channel_type:ident, $unbuffered_channel_type:ident) => {
         mod $mod_name {
             use super::*;
+            #[cfg(feature = "sink")]
+            use futures::sink::Sink as _;
 
             type ChannelType = $channel_type<i32, [i32; 3]>;
             type UnbufferedChannelType = $unbuffered_channel_type<i32>;
@@ -1054,6 +1057,539 @@ macro_rules! gen_mpmc_tests {
                     assert_eq!(count, 31);
                 }
             }
+
+            #[test]
+            fn try_send_many_try_receive_many_smoke_test() {
+                let channel = ChannelType::new();
+
+                let mut values = [Some(1), Some(2), Some(3), Some(4), Some(5)];
+                assert_eq!(Ok(3), channel.try_send_many(&mut values));
+                assert_eq!([None, None, None, Some(4), Some(5)], values);
+                assert!(channel
+                    .try_send_many(&mut values)
+                    .unwrap_err()
+                    .is_full());
+                assert_eq!([None, None, None, Some(4), Some(5)], values);
+
+                let mut received = [None; 2];
+                assert_eq!(Ok(2), channel.try_receive_many(&mut received));
+                assert_eq!([Some(1), Some(2)], received);
+                assert_eq!(Ok(2), channel.try_send_many(&mut values));
+                assert_eq!([None; 5], values);
+                // Sending only empty slots succeeds
+                assert_eq!(Ok(0), channel.try_send_many(&mut values));
+
+                let mut received = [Some(0); 5];
+                assert_eq!(Ok(3), channel.try_receive_many(&mut received));
+                assert_eq!([Some(3), Some(4), Some(5), None, None], received);
+                assert!(channel
+                    .try_receive_many(&mut received)
+                    .unwrap_err()
+                    .is_empty());
+                assert_eq!([None; 5], received);
+
+                assert!(channel.close().is_newly_closed());
+                let mut values = [Some(6), Some(7)];
+                assert!(channel
+                    .try_send_many(&mut values)
+                    .unwrap_err()
+                    .is_closed());
+                assert_eq!([Some(6), Some(7)], values);
+                assert!(channel
+                    .try_receive_many(&mut received)
+                    .unwrap_err()
+                    .is_closed());
+            }
+
+            #[test]
+            fn try_send_many_notifies_a_single_receiver() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+
+                let receive_fut1 = channel.receive();
+                pin_mut!(receive_fut1);
+                assert!(receive_fut1.as_mut().poll(cx).is_pending());
+                let receive_fut2 = channel.receive();
+                pin_mut!(receive_fut2);
+                assert!(receive_fut2.as_mut().poll(cx).is_pending());
+                let receive_fut3 = channel.receive();
+                pin_mut!(receive_fut3);
+                assert!(receive_fut3.as_mut().poll(cx).is_pending());
+                assert_eq!(count, 0);
+
+                assert_eq!(
+                    Ok(2),
+                    channel.try_send_many(&mut [Some(1), Some(2)])
+                );
+                assert_eq!(count, 1);
+
+                // Each receiver which leaves values inside the channel notifies
+                // the next receiver
+                assert_receive_done(cx, &mut receive_fut1, Some(1));
+                assert_eq!(count, 2);
+                assert_receive_done(cx, &mut receive_fut2, Some(2));
+                assert_eq!(count, 2);
+                assert!(receive_fut3.as_mut().poll(cx).is_pending());
+            }
+
+            #[test]
+            fn receive_many_notifies_next_receiver() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut received = [None];
+
+                {
+                    let receive_fut1 = channel.receive_many(&mut received);
+                    pin_mut!(receive_fut1);
+                    assert!(receive_fut1.as_mut().poll(cx).is_pending());
+                    let receive_fut2 = channel.receive();
+                    pin_mut!(receive_fut2);
+                    assert!(receive_fut2.as_mut().poll(cx).is_pending());
+
+                    assert_eq!(
+                        Ok(3),
+                        channel.try_send_many(&mut [Some(1), Some(2), Some(3)])
+                    );
+                    assert_eq!(count, 1);
+                    assert_eq!(Poll::Ready(1), receive_fut1.as_mut().poll(cx));
+                    assert_eq!(count, 2);
+                    assert_receive_done(cx, &mut receive_fut2, Some(2));
+                }
+                assert_eq!([Some(1)], received);
+            }
+
+            #[test]
+            fn send_all_waits_for_capacity() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut values = [Some(1), Some(2), Some(3), Some(4), Some(5)];
+
+                {
+                    let send_fut = channel.send_all(&mut values);
+                    pin_mut!(send_fut);
+                    assert!(!send_fut.as_mut().is_terminated());
+                    assert!(send_fut.as_mut().poll(cx).is_pending());
+                    assert!(send_fut.as_mut().poll(cx).is_pending());
+                    assert_eq!(count, 0);
+
+                    // Receiving copies the waiting value into the channel
+                    let mut received = [None; 2];
+                    assert_eq!(Ok(2), channel.try_receive_many(&mut received));
+                    assert_eq!([Some(1), Some(2)], received);
+                    assert_eq!(count, 1);
+                    assert_eq!(Poll::Ready(Ok(())), send_fut.as_mut().poll(cx));
+                    assert!(send_fut.as_mut().is_terminated());
+                }
+                assert_eq!([None; 5], values);
+
+                let mut received = [None; 5];
+                assert_eq!(Ok(3), channel.try_receive_many(&mut received));
+                assert_eq!([Some(3), Some(4), Some(5), None, None], received);
+            }
+
+            #[test]
+            fn completed_senders_pass_space_on() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                assert_eq!(
+                    Ok(3),
+                    channel.try_send_many(&mut [Some(1), Some(2), Some(3)])
+                );
+
+                let send_fut1 = channel.send(4);
+                pin_mut!(send_fut1);
+                assert!(send_fut1.as_mut().poll(cx).is_pending());
+                let mut send_fut2 = Box::pin(channel.send(5));
+                assert!(send_fut2.as_mut().poll(cx).is_pending());
+                let send_fut3 = channel.send(6);
+                pin_mut!(send_fut3);
+                assert!(send_fut3.as_mut().poll(cx).is_pending());
+
+                // Only the oldest sender gets completed
+                let mut received = [None; 3];
+                assert_eq!(Ok(3), channel.try_receive_many(&mut received));
+                assert_eq!([Some(1), Some(2), Some(3)], received);
+                assert_eq!(count, 1);
+
+                // Completed senders pass the remaining space on, even if they
+                // get dropped before observing the completion
+                assert_send_done(cx, &mut send_fut1, Ok(()));
+                assert_eq!(count, 2);
+                drop(send_fut2);
+                assert_eq!(count, 3);
+                assert_send_done(cx, &mut send_fut3, Ok(()));
+                assert_eq!(count, 3);
+
+                assert_eq!(Ok(3), channel.try_receive_many(&mut received));
+                assert_eq!([Some(4), Some(5), Some(6)], received);
+            }
+
+            #[test]
+            fn close_unblocks_send_all() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut values = [Some(1), Some(2), Some(3), Some(4), Some(5)];
+
+                {
+                    let send_fut = channel.send_all(&mut values);
+                    pin_mut!(send_fut);
+                    assert!(send_fut.as_mut().poll(cx).is_pending());
+
+                    assert!(channel.close().is_newly_closed());
+                    assert_eq!(count, 1);
+                    assert_eq!(
+                        Poll::Ready(Err(ChannelClosedError)),
+                        send_fut.as_mut().poll(cx)
+                    );
+                }
+
+                // The values which had not been sent remain in the slice
+                assert_eq!([None, None, None, Some(4), Some(5)], values);
+
+                let mut received = [None; 5];
+                assert_eq!(Ok(3), channel.try_receive_many(&mut received));
+                assert_eq!([Some(1), Some(2), Some(3), None, None], received);
+            }
+
+            #[test]
+            fn dropping_send_all_keeps_unsent_values() {
+                let (waker, _) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut values = [Some(1), Some(2), Some(3), Some(4), Some(5)];
+
+                {
+                    let send_fut = channel.send_all(&mut values);
+                    pin_mut!(send_fut);
+                    assert!(send_fut.as_mut().poll(cx).is_pending());
+                }
+
+                assert_eq!([None, None, None, Some(4), Some(5)], values);
+            }
+
+            #[test]
+            fn receive_many_waits_for_values() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut received = [None; 2];
+
+                {
+                    let receive_fut = channel.receive_many(&mut received);
+                    pin_mut!(receive_fut);
+                    assert!(!receive_fut.as_mut().is_terminated());
+                    assert!(receive_fut.as_mut().poll(cx).is_pending());
+                    assert_eq!(count, 0);
+
+                    assert_eq!(
+                        Ok(3),
+                        channel.try_send_many(&mut [Some(1), Some(2), Some(3)])
+                    );
+                    assert_eq!(count, 1);
+                    assert_eq!(Poll::Ready(2), receive_fut.as_mut().poll(cx));
+                    assert!(receive_fut.as_mut().is_terminated());
+                }
+                assert_eq!([Some(1), Some(2)], received);
+
+                {
+                    let receive_fut = channel.receive_many(&mut received);
+                    pin_mut!(receive_fut);
+                    assert_eq!(Poll::Ready(1), receive_fut.as_mut().poll(cx));
+                }
+                assert_eq!([Some(3), None], received);
+            }
+
+            #[test]
+            fn close_unblocks_receive_many() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut received = [None; 2];
+
+                let receive_fut = channel.receive_many(&mut received);
+                pin_mut!(receive_fut);
+                assert!(receive_fut.as_mut().poll(cx).is_pending());
+
+                assert!(channel.close().is_newly_closed());
+                assert_eq!(count, 1);
+                assert_eq!(Poll::Ready(0), receive_fut.as_mut().poll(cx));
+                assert!(receive_fut.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn cancel_receive_many_notifies_other_receiver() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let mut received = [None; 2];
+
+                let mut receive_fut1 =
+                    Box::pin(channel.receive_many(&mut received));
+                assert!(receive_fut1.as_mut().poll(cx).is_pending());
+                let receive_fut2 = channel.receive();
+                pin_mut!(receive_fut2);
+                assert!(receive_fut2.as_mut().poll(cx).is_pending());
+
+                channel.try_send(5).unwrap();
+                assert_eq!(count, 1);
+
+                // Dropping the notified receiver passes the notification on
+                drop(receive_fut1);
+                assert_eq!(count, 2);
+                assert_receive_done(cx, &mut receive_fut2, Some(5));
+                assert_eq!([None; 2], received);
+            }
+
+            #[test]
+            fn unbuffered_send_all_receive_many() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = UnbufferedChannelType::new();
+                let mut values = [Some(1), Some(2), Some(3)];
+
+                {
+                    let send_fut = channel.send_all(&mut values);
+                    pin_mut!(send_fut);
+                    let mut received = [None; 3];
+
+                    for value in 1..=3 {
+                        assert!(send_fut.as_mut().poll(cx).is_pending());
+                        // Only the value of the waiting sender can be received
+                        assert_eq!(
+                            Ok(1),
+                            channel.try_receive_many(&mut received)
+                        );
+                        assert_eq!([Some(value), None, None], received);
+                        assert_eq!(count, value as usize);
+                    }
+                    assert_eq!(Poll::Ready(Ok(())), send_fut.as_mut().poll(cx));
+                }
+                assert_eq!([None; 3], values);
+            }
+
+            #[cfg(feature = "sink")]
+            #[test]
+            fn buffered_sink_smoke_test() {
+                let channel = ChannelType::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let sink = channel.sink();
+                pin_mut!(sink);
+
+                // Fill the buffer of the channel
+                for i in 0..3 {
+                    assert_eq!(
+                        Poll::Ready(Ok(())),
+                        sink.as_mut().poll_ready(cx)
+                    );
+                    sink.as_mut().start_send(i).unwrap();
+                    assert_eq!(
+                        Poll::Ready(Ok(())),
+                        sink.as_mut().poll_flush(cx)
+                    );
+                }
+
+                // The sink waits until space is available
+                assert!(sink.as_mut().poll_ready(cx).is_pending());
+                assert!(sink.as_mut().poll_ready(cx).is_pending());
+                assert_eq!(count, 0);
+
+                assert_eq!(Ok(0), channel.try_receive());
+                assert_eq!(count, 1);
+                assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_ready(cx));
+                sink.as_mut().start_send(3).unwrap();
+                assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_flush(cx));
+
+                // Closing the sink closes the channel
+                assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_close(cx));
+                assert!(channel.close().is_already_closed());
+                for i in 1..4 {
+                    assert_eq!(Ok(i), channel.try_receive());
+                }
+                assert_eq!(
+                    Err(futures_intrusive::channel::TryReceiveError::Closed),
+                    channel.try_receive()
+                );
+            }
+
+            #[cfg(feature = "sink")]
+            #[test]
+            fn sink_reserves_space_when_ready() {
+                let channel = ChannelType::new();
+                let (waker, _) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                assert_eq!(Ok(()), channel.try_send(0));
+                assert_eq!(Ok(()), channel.try_send(1));
+
+                let sink = channel.sink();
+                pin_mut!(sink);
+                assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_ready(cx));
+                // Polling again doesn't reserve another slot
+                assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_ready(cx));
+
+                // The last slot is reserved for the sink
+                assert_eq!(Err(TrySendError::Full(2)), channel.try_send(2));
+                let send_fut = channel.send(2);
+                pin_mut!(send_fut);
+                assert!(send_fut.as_mut().poll(cx).is_pending());
+
+                sink.as_mut().start_send(3).unwrap();
+                assert_eq!(Ok(0), channel.try_receive());
+                assert_send_done(cx, &mut send_fut, Ok(()));
+                for i in &[1, 3, 2] {
+                    assert_eq!(Ok(*i), channel.try_receive());
+                }
+            }
+
+            #[cfg(feature = "sink")]
+            #[test]
+            fn dropping_sink_releases_reserved_space() {
+                let channel = ChannelType::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                for i in 0..3 {
+                    assert_eq!(Ok(()), channel.try_send(i));
+                }
+
+                let send_fut = channel.send(3);
+                pin_mut!(send_fut);
+                {
+                    let sink = channel.sink();
+                    pin_mut!(sink);
+                    assert!(sink.as_mut().poll_ready(cx).is_pending());
+                    assert!(send_fut.as_mut().poll(cx).is_pending());
+
+                    // The space is reserved for the sink, which waits longer
+                    assert_eq!(Ok(0), channel.try_receive());
+                    assert_eq!(count, 1);
+                    assert!(send_fut.as_mut().poll(cx).is_pending());
+                }
+
+                // Dropping the sink passed the space on to the future
+                assert_eq!(count, 2);
+                assert_send_done(cx, &mut send_fut, Ok(()));
+
+                for i in 1..4 {
+                    assert_eq!(Ok(i), channel.try_receive());
+                }
+
+                // A sink which is dropped after it became ready releases its
+                // space too
+                {
+                    let sink = channel.sink();
+                    pin_mut!(sink);
+                    for _ in 0..2 {
+                        assert_eq!(
+                            Poll::Ready(Ok(())),
+                            sink.as_mut().poll_ready(cx)
+                        );
+                        sink.as_mut().start_send(5).unwrap();
+                    }
+                    assert_eq!(
+                        Poll::Ready(Ok(())),
+                        sink.as_mut().poll_ready(cx)
+                    );
+                    assert_eq!(Err(TrySendError::Full(6)), channel.try_send(6));
+                }
+                assert_eq!(Ok(()), channel.try_send(6));
+                for i in &[5, 5, 6] {
+                    assert_eq!(Ok(*i), channel.try_receive());
+                }
+            }
+
+            #[cfg(feature = "sink")]
+            #[test]
+            fn unbuffered_sink_smoke_test() {
+                let channel = UnbufferedChannelType::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let sink = channel.sink();
+                pin_mut!(sink);
+
+                for i in 0..3 {
+                    assert_eq!(
+                        Poll::Ready(Ok(())),
+                        sink.as_mut().poll_ready(cx)
+                    );
+                    sink.as_mut().start_send(i).unwrap();
+                    assert!(sink.as_mut().poll_flush(cx).is_pending());
+                    assert!(sink.as_mut().poll_ready(cx).is_pending());
+
+                    let receive_fut = channel.receive();
+                    pin_mut!(receive_fut);
+                    assert_receive_done(cx, &mut receive_fut, Some(i));
+                    assert_eq!(count, i as usize + 1);
+                    assert_eq!(
+                        Poll::Ready(Ok(())),
+                        sink.as_mut().poll_ready(cx)
+                    );
+                }
+            }
+
+            #[cfg(feature = "sink")]
+            #[test]
+            fn sink_fails_if_channel_is_closed() {
+                let channel = UnbufferedChannelType::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let sink = channel.sink();
+                pin_mut!(sink);
+
+                assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_ready(cx));
+                sink.as_mut().start_send(5).unwrap();
+                assert!(sink.as_mut().poll_flush(cx).is_pending());
+
+                assert!(channel.close().is_newly_closed());
+                assert_eq!(count, 1);
+                assert_eq!(
+                    Poll::Ready(Err(ChannelClosedError)),
+                    sink.as_mut().poll_flush(cx)
+                );
+                assert_eq!(
+                    Poll::Ready(Err(ChannelClosedError)),
+                    sink.as_mut().poll_ready(cx)
+                );
+
+                // A buffered sink which waits for space fails too
+                let channel = ChannelType::new();
+                for i in 0..3 {
+                    assert_eq!(Ok(()), channel.try_send(i));
+                }
+                let sink = channel.sink();
+                pin_mut!(sink);
+                assert!(sink.as_mut().poll_ready(cx).is_pending());
+                assert!(channel.close().is_newly_closed());
+                assert_eq!(count, 2);
+                assert_eq!(
+                    Poll::Ready(Err(ChannelClosedError)),
+                    sink.as_mut().poll_ready(cx)
+                );
+
+                // As well as a sink which had reserved space before
+                let channel = ChannelType::new();
+                let sink = channel.sink();
+                pin_mut!(sink);
+                assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_ready(cx));
+                assert!(channel.close().is_newly_closed());
+                assert_eq!(
+                    Err(ChannelClosedError),
+                    sink.as_mut().start_send(6)
+                );
+                assert_eq!(
+                    Err(futures_intrusive::channel::TryReceiveError::Closed),
+                    channel.try_receive()
+                );
+            }
         }
     };
 }
@@ -1115,6 +1651,27 @@ mod if_std {
             pin_mut!(send_fut);
             is_send(&send_fut);
         }
+        {
+            let mut values = [Some(1), Some(2), Some(3)];
+            let send_all_fut = channel.send_all(&mut values);
+            is_send(&send_all_fut);
+            pin_mut!(send_all_fut);
+            is_send(&send_all_fut);
+        }
+        {
+            let mut values = [None; 3];
+            let recv_many_fut = channel.receive_many(&mut values);
+            is_send(&recv_many_fut);
+            pin_mut!(recv_many_fut);
+            is_send(&recv_many_fut);
+        }
+        #[cfg(feature = "sink")]
+        {
+            let sink = channel.sink();
+            is_send(&sink);
+            pin_mut!(sink);
+            is_send(&sink);
+        }
         is_send_value(channel);
     }
 
@@ -1133,6 +1690,13 @@ mod if_std {
         is_send(&send_fut);
         pin_mut!(send_fut);
         is_send(&send_fut);
+        #[cfg(feature = "sink")]
+        {
+            let sink = sender.clone().into_sink();
+            is_send(&sink);
+            pin_mut!(sink);
+            is_send(&sink);
+        }
     }
 
     // Check if SharedChannel can be used in traits
@@ -1470,6 +2034,62 @@ mod if_std {
         }
     }
 
+    #[cfg(feature = "sink")]
+    #[test]
+    fn shared_sink_forwards_stream() {
+        use futures::executor::block_on;
+        use futures::stream::{self, StreamExt};
+
+        let (sender, receiver) = channel::<i32>(3);
+
+        let producer = async move {
+            stream::iter(0..10)
+                .map(Ok)
+                .forward(sender.into_sink())
+                .await
+        };
+        let consumer = receiver.into_stream().collect::<Vec<_>>();
+
+        let (sent, received) =
+            block_on(async { futures::join!(producer, consumer) });
+        assert_eq!(Ok(()), sent);
+        assert_eq!((0..10).collect::<Vec<_>>(), received);
+    }
+
+    #[cfg(feature = "sink")]
+    #[test]
+    fn shared_sink_smoke_test() {
+        use futures::sink::Sink as _;
+
+        let (sender, receiver) = channel::<i32>(1);
+        let (waker, count) = new_count_waker();
+        let cx = &mut Context::from_waker(&waker);
+
+        let sink = sender.into_sink();
+        pin_mut!(sink);
+
+        assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_ready(cx));
+        sink.as_mut().start_send(1).unwrap();
+        assert!(sink.as_mut().poll_ready(cx).is_pending());
+
+        assert_receive!(cx, &receiver, Some(1));
+        assert_eq!(count, 1);
+        assert_eq!(Poll::Ready(Ok(())), sink.as_mut().poll_ready(cx));
+        sink.as_mut().start_send(2).unwrap();
+        assert!(sink.as_mut().poll_close(cx).is_ready());
+        assert_receive!(cx, &receiver, Some(2));
+        assert_receive!(cx, &receiver, None);
+
+        let (sender, receiver) = channel::<i32>(1);
+        let sink = sender.into_sink();
+        pin_mut!(sink);
+        drop(receiver);
+        assert_eq!(
+            Poll::Ready(Err(ChannelClosedError)),
+            sink.as_mut().poll_ready(cx)
+        );
+    }
+
     #[test]
     fn cancel_send_mid_wait() {
         let (sender, receiver) = channel::<i32>(3);
@@ -1583,4 +2203,14 @@ mod if_std {
 
         assert_eq!(count, 4);
     }
+
+    static STATIC_CHANNEL: Channel<i32, [i32; 3]> = Channel::new();
+
+    #[test]
+    fn static_channel() {
+        STATIC_CHANNEL.try_send(1).unwrap();
+        STATIC_CHANNEL.try_send(2).unwrap();
+        assert_eq!(Ok(1), STATIC_CHANNEL.try_receive());
+        assert_eq!(Ok(2), STATIC_CHANNEL.try_receive());
+    }
 }
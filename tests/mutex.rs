@@ -1,11 +1,18 @@
 use futures::future::{FusedFuture, Future};
 use futures::task::{Context, Poll};
-use futures_intrusive::sync::LocalMutex;
+use futures_intrusive::sync::{
+    LocalMappedMutexGuard, LocalMutex, LocalMutexGuard,
+};
 use futures_test::task::{new_count_waker, panic_waker};
 use pin_utils::pin_mut;
 
 macro_rules! gen_mutex_tests {
-    ($mod_name:ident, $mutex_type:ident) => {
+    (
+        $mod_name:ident,
+        $mutex_type:ident,
+        $guard_type:ident,
+        $mapped_type:ident
+    ) => {
         mod $mod_name {
             use super::*;
 
@@ -430,19 +437,439 @@ macro_rules! gen_mutex_tests {
                     assert!(fut.as_mut().is_terminated());
                 }
             }
+
+            #[test]
+            fn map_guard() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let mtx = $mutex_type::new((5, 7), *is_fair);
+
+                    let guard = mtx.try_lock().unwrap();
+                    let mut mapped = $guard_type::map(guard, |v| &mut v.1);
+                    assert_eq!(7, *mapped);
+                    *mapped = 9;
+                    assert!(mtx.is_locked());
+
+                    let fut = mtx.lock();
+                    pin_mut!(fut);
+                    assert!(fut.as_mut().poll(cx).is_pending());
+
+                    // Dropping the mapped guard releases the mutex
+                    drop(mapped);
+                    assert_eq!(count, 1);
+                    match fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect mutex to get locked"),
+                        Poll::Ready(guard) => assert_eq!((5, 9), *guard),
+                    };
+                    assert!(!mtx.is_locked());
+                }
+            }
+
+            #[test]
+            fn map_mapped_guard() {
+                let mtx = $mutex_type::new((5, [1, 2, 3]), false);
+
+                let guard = mtx.try_lock().unwrap();
+                let mapped = $guard_type::map(guard, |v| &mut v.1[..]);
+                assert_eq!(&[1, 2, 3], &*mapped);
+                let mut mapped = $mapped_type::map(mapped, |v| &mut v[1]);
+                *mapped = 5;
+                drop(mapped);
+                assert!(!mtx.is_locked());
+
+                let guard = mtx.try_lock().unwrap();
+                let mapped = $guard_type::map(guard, |v| &mut v.1);
+                let mapped = match $mapped_type::try_map(mapped, |v| v.get_mut(3))
+                {
+                    Ok(_) => panic!("Expected the mapping to fail"),
+                    Err(mapped) => mapped,
+                };
+                assert!(mtx.is_locked());
+                assert_eq!([1, 5, 3], *mapped);
+                drop(mapped);
+                assert!(!mtx.is_locked());
+            }
+
+            #[test]
+            fn try_map_guard() {
+                let mtx = $mutex_type::new(Some(5), false);
+
+                let guard = mtx.try_lock().unwrap();
+                let mapped = $guard_type::try_map(guard, |v| v.as_mut());
+                let mut mapped = match mapped {
+                    Ok(mapped) => mapped,
+                    Err(_) => panic!("Expected the guard to get mapped"),
+                };
+                *mapped += 1;
+                assert!(mtx.is_locked());
+                drop(mapped);
+                assert!(!mtx.is_locked());
+
+                *mtx.try_lock().unwrap() = None;
+                let guard = mtx.try_lock().unwrap();
+                let guard = match $guard_type::try_map(guard, |v| v.as_mut()) {
+                    Ok(_) => panic!("Expected the mapping to fail"),
+                    Err(guard) => guard,
+                };
+                // The original guard still holds the lock
+                assert!(mtx.is_locked());
+                assert_eq!(None, *guard);
+                drop(guard);
+                assert!(!mtx.is_locked());
+            }
+
+            #[test]
+            fn unlock_fair_hands_mutex_to_oldest_waiter() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let mtx = $mutex_type::new(5, *is_fair);
+
+                    let guard = mtx.try_lock().unwrap();
+                    let fut1 = mtx.lock();
+                    pin_mut!(fut1);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let fut2 = mtx.lock();
+                    pin_mut!(fut2);
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+
+                    $guard_type::unlock_fair(guard);
+                    assert_eq!(count, 1);
+                    // The mutex is never available to other tasks
+                    assert!(mtx.is_locked());
+                    assert!(mtx.try_lock().is_none());
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+
+                    match fut1.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect mutex to get locked"),
+                        Poll::Ready(guard) => assert_eq!(5, *guard),
+                    };
+                    assert_eq!(count, 2);
+                    assert!(fut2.as_mut().poll(cx).is_ready());
+                    assert!(!mtx.is_locked());
+                }
+            }
+
+            #[test]
+            fn unlock_fair_without_waiters_unlocks() {
+                let mtx = $mutex_type::new(5, false);
+                let guard = mtx.try_lock().unwrap();
+                $guard_type::unlock_fair(guard);
+                assert!(!mtx.is_locked());
+            }
+
+            #[test]
+            fn cancel_future_after_mutex_was_handed_over() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let mtx = $mutex_type::new(5, *is_fair);
+
+                    let guard = mtx.try_lock().unwrap();
+                    let mut fut1 = Box::pin(mtx.lock());
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let fut2 = mtx.lock();
+                    pin_mut!(fut2);
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+
+                    $guard_type::unlock_fair(guard);
+                    assert_eq!(count, 1);
+
+                    // The mutex is released again and passed on
+                    drop(fut1);
+                    assert_eq!(count, 2);
+                    assert!(fut2.as_mut().poll(cx).is_ready());
+                    assert!(!mtx.is_locked());
+                }
+            }
+
+            #[test]
+            fn bump_without_waiters_keeps_the_mutex() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let mtx = $mutex_type::new(5, false);
+
+                let guard = mtx.try_lock().unwrap();
+                let fut = $guard_type::bump(guard);
+                pin_mut!(fut);
+                assert!(mtx.is_locked());
+                assert!(mtx.try_lock().is_none());
+                match fut.as_mut().poll(cx) {
+                    Poll::Pending => panic!("Expect mutex to get locked"),
+                    Poll::Ready(guard) => assert_eq!(5, *guard),
+                };
+                assert!(fut.as_mut().is_terminated());
+                assert!(!mtx.is_locked());
+
+                // Dropping the future releases the mutex
+                let guard = mtx.try_lock().unwrap();
+                drop($guard_type::bump(guard));
+                assert!(!mtx.is_locked());
+            }
+
+            #[test]
+            fn bump_yields_to_waiter() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let mtx = $mutex_type::new(5, *is_fair);
+
+                    let guard = mtx.try_lock().unwrap();
+                    let fut1 = mtx.lock();
+                    pin_mut!(fut1);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+
+                    let bump_fut = $guard_type::bump(guard);
+                    pin_mut!(bump_fut);
+                    assert_eq!(count, 1);
+                    assert!(bump_fut.as_mut().poll(cx).is_pending());
+
+                    let mut guard = match fut1.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect mutex to get locked"),
+                        Poll::Ready(guard) => guard,
+                    };
+                    *guard = 7;
+                    drop(guard);
+                    assert_eq!(count, 2);
+
+                    match bump_fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect mutex to get locked"),
+                        Poll::Ready(guard) => assert_eq!(7, *guard),
+                    };
+                    assert!(!mtx.is_locked());
+                }
+            }
+
+            #[test]
+            fn priority_waiters_are_served_first() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let mtx = $mutex_type::new(0, *is_fair);
+
+                    let guard = mtx.try_lock().unwrap();
+                    let fut1 = mtx.lock();
+                    pin_mut!(fut1);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let fut2 = mtx.lock_with_priority(1);
+                    pin_mut!(fut2);
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+                    let fut3 = mtx.lock_with_priority(5);
+                    pin_mut!(fut3);
+                    assert!(fut3.as_mut().poll(cx).is_pending());
+                    let fut4 = mtx.lock_with_priority(5);
+                    pin_mut!(fut4);
+                    assert!(fut4.as_mut().poll(cx).is_pending());
+
+                    // The mutex is handed over to the highest priority waiter
+                    drop(guard);
+                    assert_eq!(count, 1);
+                    assert!(mtx.is_locked());
+                    assert!(mtx.try_lock().is_none());
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    assert!(fut4.as_mut().poll(cx).is_pending());
+                    let guard = match fut3.as_mut().poll(cx) {
+                        Poll::Ready(guard) => guard,
+                        _ => panic!("Expect mutex to get locked"),
+                    };
+
+                    // Equal priorities are served in FIFO order
+                    drop(guard);
+                    assert_eq!(count, 2);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+                    let guard = match fut4.as_mut().poll(cx) {
+                        Poll::Ready(guard) => guard,
+                        _ => panic!("Expect mutex to get locked"),
+                    };
+
+                    drop(guard);
+                    assert_eq!(count, 3);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let guard = match fut2.as_mut().poll(cx) {
+                        Poll::Ready(guard) => guard,
+                        _ => panic!("Expect mutex to get locked"),
+                    };
+
+                    // Waiters without a priority are served last
+                    drop(guard);
+                    assert_eq!(count, 4);
+                    assert!(fut1.as_mut().poll(cx).is_ready());
+                    assert!(!mtx.is_locked());
+                }
+            }
+
+            #[test]
+            fn uncontended_lock_with_priority() {
+                for is_fair in &[true, false] {
+                    let waker = &panic_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let mtx = $mutex_type::new(5, *is_fair);
+
+                    let fut = mtx.lock_with_priority(3);
+                    pin_mut!(fut);
+                    match fut.as_mut().poll(cx) {
+                        Poll::Ready(guard) => assert_eq!(5, *guard),
+                        _ => panic!("Expect mutex to get locked"),
+                    };
+                    assert!(fut.as_mut().is_terminated());
+                    assert!(!mtx.is_locked());
+                }
+            }
+
+            #[test]
+            fn cancel_priority_waiters() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let mtx = $mutex_type::new(5, *is_fair);
+
+                    let guard = mtx.try_lock().unwrap();
+                    let mut fut1 = Box::pin(mtx.lock_with_priority(2));
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let mut fut2 = Box::pin(mtx.lock_with_priority(1));
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+                    let fut3 = mtx.lock();
+                    pin_mut!(fut3);
+                    assert!(fut3.as_mut().poll(cx).is_pending());
+
+                    // Cancelling a waiting task removes it from the queue
+                    drop(fut1);
+                    assert_eq!(count, 0);
+                    drop(guard);
+                    assert_eq!(count, 1);
+
+                    // Cancelling the task which owns the mutex passes it on
+                    drop(fut2);
+                    assert_eq!(count, 2);
+                    assert!(fut3.as_mut().poll(cx).is_ready());
+                    assert!(!mtx.is_locked());
+                }
+            }
+
+            #[test]
+            fn unlocked_releases_the_mutex_while_running() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let mtx = $mutex_type::new(5, *is_fair);
+
+                    let guard = mtx.try_lock().unwrap();
+                    let fut = $guard_type::unlocked(guard, || {
+                        futures::future::ready(3)
+                    });
+                    pin_mut!(fut);
+                    assert!(!mtx.is_locked());
+                    assert!(!fut.as_mut().is_terminated());
+
+                    // Another task locks the mutex in the meantime
+                    let mut guard = mtx.try_lock().unwrap();
+                    *guard = 7;
+                    assert!(fut.as_mut().poll(cx).is_pending());
+                    drop(guard);
+                    assert_eq!(count, 1);
+
+                    match fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect mutex to get locked"),
+                        Poll::Ready((guard, output)) => {
+                            assert!(mtx.is_locked());
+                            assert_eq!(7, *guard);
+                            assert_eq!(3, output);
+                        }
+                    };
+                    assert!(fut.as_mut().is_terminated());
+                    assert!(!mtx.is_locked());
+                }
+            }
+
+            #[test]
+            fn unlocked_reacquires_behind_waiting_tasks() {
+                let (waker, _) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let mtx = $mutex_type::new(5, true);
+
+                let guard = mtx.try_lock().unwrap();
+                let fut = $guard_type::unlocked(guard, || {
+                    futures::future::ready(())
+                });
+                pin_mut!(fut);
+
+                // Another task locks the mutex and a third one starts to wait
+                // before the mutex is requested again
+                let guard2 = mtx.try_lock().unwrap();
+                let fut3 = mtx.lock();
+                pin_mut!(fut3);
+                assert!(fut3.as_mut().poll(cx).is_pending());
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                drop(guard2);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                let guard3 = match fut3.as_mut().poll(cx) {
+                    Poll::Pending => panic!("Expect mutex to get locked"),
+                    Poll::Ready(guard) => guard,
+                };
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                drop(guard3);
+                assert!(fut.as_mut().poll(cx).is_ready());
+                assert!(!mtx.is_locked());
+            }
+
+            #[test]
+            fn unlocked_waits_for_inner_future() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let mtx = $mutex_type::new(5, false);
+                let other = $mutex_type::new((), false);
+
+                let other_guard = other.try_lock().unwrap();
+                let guard = mtx.try_lock().unwrap();
+                let fut = $guard_type::unlocked(guard, || other.lock());
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert!(!mtx.is_locked());
+
+                drop(other_guard);
+                assert_eq!(count, 1);
+                match fut.as_mut().poll(cx) {
+                    Poll::Pending => panic!("Expect mutex to get locked"),
+                    Poll::Ready((guard, other_guard)) => {
+                        assert_eq!(5, *guard);
+                        assert!(other.is_locked());
+                        drop(other_guard);
+                    }
+                };
+                assert!(!mtx.is_locked());
+                assert!(!other.is_locked());
+            }
         }
     };
 }
 
-gen_mutex_tests!(local_mutex_tests, LocalMutex);
+gen_mutex_tests!(
+    local_mutex_tests,
+    LocalMutex,
+    LocalMutexGuard,
+    LocalMappedMutexGuard
+);
 
 #[cfg(feature = "std")]
 mod if_std {
     use super::*;
     use futures::FutureExt;
-    use futures_intrusive::sync::Mutex;
+    use futures_intrusive::sync::{
+        MappedMutexGuard, Mutex, MutexGuard, SharedMappedMutexGuard,
+        SharedMutex, SharedMutexGuard,
+    };
 
-    gen_mutex_tests!(mutex_tests, Mutex);
+    gen_mutex_tests!(mutex_tests, Mutex, MutexGuard, MappedMutexGuard);
+    gen_mutex_tests!(
+        shared_mutex_tests,
+        SharedMutex,
+        SharedMutexGuard,
+        SharedMappedMutexGuard
+    );
 
     fn is_send<T: Send>(_: &T) {}
 
@@ -469,8 +896,86 @@ mod if_std {
                 Poll::Pending => panic!("Expected to be ready"),
             };
             is_send(&guard);
-            is_send_value(guard);
+            let mapped = MutexGuard::map(guard, |v| v);
+            is_send(&mapped);
+            is_sync(&mapped);
+            is_send_value(mapped);
+        }
+        {
+            let unlocked_fut =
+                MutexGuard::unlocked(mutex.try_lock().unwrap(), || {
+                    futures::future::ready(())
+                });
+            is_send(&unlocked_fut);
+            pin_mut!(unlocked_fut);
+            is_send(&unlocked_fut);
         }
         is_send_value(mutex);
     }
+
+    #[test]
+    fn shared_mutex_futures_are_send() {
+        let mutex = SharedMutex::new(true, true);
+        is_sync(&mutex);
+        {
+            let lock_fut = mutex.lock();
+            is_send(&lock_fut);
+            pin_mut!(lock_fut);
+            is_send(&lock_fut);
+
+            let waker = &panic_waker();
+            let cx = &mut Context::from_waker(&waker);
+            pin_mut!(lock_fut);
+            let res = lock_fut.poll_unpin(cx);
+            let guard = match res {
+                Poll::Ready(v) => v,
+                Poll::Pending => panic!("Expected to be ready"),
+            };
+            is_send(&guard);
+            let mapped = SharedMutexGuard::map(guard, |v| v);
+            is_send(&mapped);
+            is_sync(&mapped);
+            is_send_value(mapped);
+        }
+        {
+            let unlocked_fut =
+                SharedMutexGuard::unlocked(mutex.try_lock().unwrap(), || {
+                    futures::future::ready(())
+                });
+            is_send(&unlocked_fut);
+            pin_mut!(unlocked_fut);
+            is_send(&unlocked_fut);
+        }
+        is_send_value(mutex);
+    }
+
+    #[test]
+    fn shared_mutex_guard_can_be_held_in_spawned_task() {
+        let mutex = SharedMutex::new(0, true);
+        let mut guard = futures::executor::block_on(mutex.lock());
+
+        let mutex_clone = mutex.clone();
+        let handle = std::thread::spawn(move || {
+            futures::executor::block_on(async move {
+                let mut guard = mutex_clone.lock().await;
+                *guard += 1;
+            })
+        });
+
+        *guard += 1;
+        drop(guard);
+        handle.join().unwrap();
+
+        assert_eq!(2, *mutex.try_lock().unwrap());
+        assert!(!mutex.is_locked());
+    }
+
+    static STATIC_MUTEX: Mutex<u32> = Mutex::new(0, true);
+
+    #[test]
+    fn static_mutex() {
+        *STATIC_MUTEX.try_lock().unwrap() += 5;
+        assert!(!STATIC_MUTEX.is_locked());
+        assert_eq!(5, *STATIC_MUTEX.try_lock().unwrap());
+    }
 }
@@ -0,0 +1,259 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::sync::{LocalManualResetEvent, LocalOnceCell};
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_once_cell_tests {
+    ($mod_name:ident, $cell_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn set_and_get() {
+                let cell = $cell_type::<u32>::new();
+                assert_eq!(None, cell.get());
+                assert_eq!(Ok(()), cell.set(5));
+                assert_eq!(Some(&5), cell.get());
+                assert_eq!(Err(6), cell.set(6));
+                assert_eq!(Some(&5), cell.get());
+            }
+
+            #[test]
+            fn get_or_init_returns_existing_value() {
+                let cell = $cell_type::<u32>::new();
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                cell.set(5).unwrap();
+
+                let fut =
+                    cell.get_or_init(|| -> futures::future::Ready<u32> {
+                        panic!("initializer must not run")
+                    });
+                pin_mut!(fut);
+                assert!(!fut.as_mut().is_terminated());
+                match fut.as_mut().poll(cx) {
+                    Poll::Ready(value) => assert_eq!(5, *value),
+                    Poll::Pending => {
+                        panic!("Expected the cell to be initialized")
+                    }
+                }
+                assert!(fut.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn get_or_init_runs_initializer() {
+                let cell = $cell_type::<u32>::new();
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut = cell.get_or_init(|| futures::future::ready(7));
+                pin_mut!(fut);
+                match fut.as_mut().poll(cx) {
+                    Poll::Ready(value) => assert_eq!(7, *value),
+                    Poll::Pending => {
+                        panic!("Expected the cell to be initialized")
+                    }
+                }
+                assert!(fut.as_mut().is_terminated());
+                assert_eq!(Some(&7), cell.get());
+            }
+
+            #[test]
+            fn only_one_initializer_runs() {
+                let cell = $cell_type::<u32>::new();
+                let event = LocalManualResetEvent::new(false);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut1 = cell.get_or_init(|| async {
+                    event.wait().await;
+                    1
+                });
+                let fut2 = cell.get_or_init(|| async {
+                    panic!("second initializer must not run");
+                });
+                pin_mut!(fut1, fut2);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert_eq!(None, cell.get());
+                // The cell can't be set while an initializer is running
+                assert_eq!(Err(3), cell.set(3));
+
+                event.set();
+                assert_eq!(count, 1);
+                match fut1.as_mut().poll(cx) {
+                    Poll::Ready(value) => assert_eq!(1, *value),
+                    Poll::Pending => {
+                        panic!("Expected the cell to be initialized")
+                    }
+                }
+                assert_eq!(count, 2);
+                match fut2.as_mut().poll(cx) {
+                    Poll::Ready(value) => assert_eq!(1, *value),
+                    Poll::Pending => {
+                        panic!("Expected the cell to be initialized")
+                    }
+                }
+            }
+
+            #[test]
+            fn waiter_takes_over_if_initializer_is_cancelled() {
+                let cell = $cell_type::<u32>::new();
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 =
+                    Box::pin(cell.get_or_init(|| futures::future::pending()));
+                let fut2 = cell.get_or_init(|| futures::future::ready(2));
+                pin_mut!(fut2);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                drop(fut1);
+                assert_eq!(count, 1);
+                assert_eq!(None, cell.get());
+
+                match fut2.as_mut().poll(cx) {
+                    Poll::Ready(value) => assert_eq!(2, *value),
+                    Poll::Pending => {
+                        panic!("Expected the cell to be initialized")
+                    }
+                }
+                assert_eq!(Some(&2), cell.get());
+            }
+
+            #[test]
+            fn waiter_takes_over_if_initializer_fails() {
+                let cell = $cell_type::<u32>::new();
+                let event = LocalManualResetEvent::new(false);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut1 = cell.get_or_try_init(|| async {
+                    event.wait().await;
+                    Err("failed")
+                });
+                let fut2 =
+                    cell.get_or_try_init(|| async { Ok::<u32, &str>(2) });
+                pin_mut!(fut1, fut2);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                event.set();
+                match fut1.as_mut().poll(cx) {
+                    Poll::Ready(res) => assert_eq!(Err("failed"), res),
+                    Poll::Pending => panic!("Expected the initializer to fail"),
+                }
+                assert!(fut1.as_mut().is_terminated());
+                assert_eq!(count, 2);
+                assert_eq!(None, cell.get());
+
+                match fut2.as_mut().poll(cx) {
+                    Poll::Ready(res) => assert_eq!(Ok(&2), res),
+                    Poll::Pending => {
+                        panic!("Expected the cell to be initialized")
+                    }
+                }
+                assert_eq!(Some(&2), cell.get());
+            }
+
+            #[test]
+            fn cancel_waiter() {
+                let cell = $cell_type::<u32>::new();
+                let event = LocalManualResetEvent::new(false);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut1 = cell.get_or_init(|| async {
+                    event.wait().await;
+                    1
+                });
+                let mut fut2 = Box::pin(cell.get_or_init(|| async { 2 }));
+                pin_mut!(fut1);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                drop(fut2);
+
+                event.set();
+                assert_eq!(count, 1);
+                assert!(fut1.as_mut().poll(cx).is_ready());
+                // No other waiter must have been woken up
+                assert_eq!(count, 1);
+            }
+        }
+    };
+}
+
+gen_once_cell_tests!(local_once_cell_tests, LocalOnceCell);
+
+#[test]
+fn local_once_cell_can_be_created_in_const_context() {
+    const CELL: LocalOnceCell<u32> = LocalOnceCell::new();
+    let cell = CELL;
+    assert_eq!(None, cell.get());
+}
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::sync::OnceCell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    gen_once_cell_tests!(once_cell_tests, OnceCell);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn once_cell_futures_are_send() {
+        let cell = OnceCell::<u32>::new();
+        is_sync(&cell);
+        {
+            let fut = cell.get_or_init(|| async { 1 });
+            is_send(&fut);
+            pin_mut!(fut);
+            is_send(&fut);
+
+            let fut = cell.get_or_try_init(|| async { Ok::<u32, ()>(1) });
+            is_send(&fut);
+            pin_mut!(fut);
+            is_send(&fut);
+        }
+        is_send_value(cell);
+    }
+
+    static STATIC_CELL: OnceCell<u32> = OnceCell::new();
+    static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn multithreaded_static_cell() {
+        let threads: Vec<thread::JoinHandle<u32>> = (0..4)
+            .map(|i| {
+                thread::spawn(move || {
+                    *block_on(STATIC_CELL.get_or_init(|| async move {
+                        INIT_COUNT.fetch_add(1, Ordering::SeqCst);
+                        thread::yield_now();
+                        i
+                    }))
+                })
+            })
+            .collect();
+
+        let values: Vec<u32> =
+            threads.into_iter().map(|t| t.join().unwrap()).collect();
+        assert_eq!(1, INIT_COUNT.load(Ordering::SeqCst));
+        let value = *STATIC_CELL.get().unwrap();
+        assert!(values.iter().all(|v| *v == value));
+    }
+}
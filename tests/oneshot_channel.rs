@@ -330,4 +330,12 @@ mod if_std {
 
         assert_eq!(Err(ChannelSendError(5)), sender.send(5));
     }
+
+    static STATIC_CHANNEL: OneshotChannel<i32> = OneshotChannel::new();
+
+    #[test]
+    fn static_channel() {
+        STATIC_CHANNEL.send(5).unwrap();
+        assert_eq!(Err(ChannelSendError(7)), STATIC_CHANNEL.send(7));
+    }
 }
@@ -0,0 +1,341 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::sync::{LocalPool, LocalPoolGuard};
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+macro_rules! gen_pool_tests {
+    ($mod_name:ident, $pool_type:ident, $guard_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            /// Creates a pool whose objects are numbered in creation order
+            fn numbered_pool(
+                max_size: usize,
+            ) -> $pool_type<usize, impl Fn() -> usize> {
+                let created = AtomicUsize::new(0);
+                $pool_type::new(max_size, move || {
+                    created.fetch_add(1, Ordering::SeqCst)
+                })
+            }
+
+            #[test]
+            fn creates_objects_lazily() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let pool = numbered_pool(2);
+                assert_eq!(2, pool.max_size());
+                assert_eq!(0, pool.size());
+                assert_eq!(0, pool.idle());
+
+                let fut = pool.get();
+                pin_mut!(fut);
+                let guard = match fut.as_mut().poll(cx) {
+                    Poll::Ready(guard) => guard,
+                    Poll::Pending => panic!("Expected an object"),
+                };
+                assert!(fut.as_mut().is_terminated());
+                assert_eq!(0, *guard);
+                assert_eq!(1, pool.size());
+                assert_eq!(0, pool.idle());
+
+                drop(guard);
+                assert_eq!(1, pool.size());
+                assert_eq!(1, pool.idle());
+            }
+
+            #[test]
+            fn reuses_returned_objects() {
+                let pool = numbered_pool(2);
+
+                let guard1 = pool.try_get().unwrap();
+                let guard2 = pool.try_get().unwrap();
+                assert_eq!(0, *guard1);
+                assert_eq!(1, *guard2);
+                assert!(pool.try_get().is_none());
+                drop(guard2);
+
+                let guard3 = pool.try_get().unwrap();
+                assert_eq!(1, *guard3);
+                assert_eq!(2, pool.size());
+            }
+
+            #[test]
+            fn objects_can_be_modified() {
+                let pool = $pool_type::new(1, Vec::<u32>::new);
+                {
+                    let mut guard = pool.try_get().unwrap();
+                    guard.push(5);
+                }
+                assert_eq!(vec![5], *pool.try_get().unwrap());
+            }
+
+            #[test]
+            fn get_waits_for_returned_object() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let pool = numbered_pool(1);
+
+                let guard = pool.try_get().unwrap();
+                let fut = pool.get();
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert!(!fut.as_mut().is_terminated());
+                assert_eq!(count, 0);
+
+                drop(guard);
+                assert_eq!(count, 1);
+                // The object is handed over directly to the waiter
+                assert_eq!(0, pool.idle());
+                assert!(pool.try_get().is_none());
+
+                match fut.as_mut().poll(cx) {
+                    Poll::Ready(guard) => assert_eq!(0, *guard),
+                    Poll::Pending => panic!("Expected an object"),
+                }
+                assert!(fut.as_mut().is_terminated());
+                assert_eq!(1, pool.idle());
+                assert_eq!(1, pool.size());
+            }
+
+            #[test]
+            fn waiters_are_served_in_fifo_order() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let pool = numbered_pool(2);
+
+                let guard1 = pool.try_get().unwrap();
+                let guard2 = pool.try_get().unwrap();
+
+                let fut1 = pool.get();
+                pin_mut!(fut1);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = pool.get();
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                drop(guard2);
+                assert_eq!(count, 1);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                let guard3 = match fut1.as_mut().poll(cx) {
+                    Poll::Ready(guard) => guard,
+                    Poll::Pending => panic!("Expected an object"),
+                };
+                assert_eq!(1, *guard3);
+
+                drop(guard1);
+                assert_eq!(count, 2);
+                match fut2.as_mut().poll(cx) {
+                    Poll::Ready(guard) => assert_eq!(0, *guard),
+                    Poll::Pending => panic!("Expected an object"),
+                };
+            }
+
+            #[test]
+            fn try_get_does_not_overtake_waiters() {
+                let (waker, _count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let pool = numbered_pool(1);
+
+                let guard = pool.try_get().unwrap();
+                let fut = pool.get();
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                drop(guard);
+                assert!(pool.try_get().is_none());
+                assert!(fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn detached_objects_are_replaced() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let pool = numbered_pool(1);
+
+                let guard = pool.try_get().unwrap();
+                assert_eq!(0, $guard_type::detach(guard));
+                assert_eq!(0, pool.size());
+
+                let guard = pool.try_get().unwrap();
+                assert_eq!(1, *guard);
+
+                let fut = pool.get();
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                // The waiter creates a new object
+                assert_eq!(1, $guard_type::detach(guard));
+                assert_eq!(count, 1);
+                assert_eq!(1, pool.size());
+                match fut.as_mut().poll(cx) {
+                    Poll::Ready(guard) => assert_eq!(2, *guard),
+                    Poll::Pending => panic!("Expected an object"),
+                }
+                assert_eq!(1, pool.size());
+                assert_eq!(1, pool.idle());
+            }
+
+            #[test]
+            fn cancel_waiting_get() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let pool = numbered_pool(1);
+
+                let guard = pool.try_get().unwrap();
+                let mut fut1 = Box::pin(pool.get());
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = pool.get();
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                drop(fut1);
+                assert_eq!(count, 0);
+                drop(guard);
+                assert_eq!(count, 1);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn cancel_granted_get_passes_object_on() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let pool = numbered_pool(1);
+
+                let guard = pool.try_get().unwrap();
+                let mut fut1 = Box::pin(pool.get());
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = pool.get();
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                drop(guard);
+                assert_eq!(count, 1);
+                drop(fut1);
+                assert_eq!(count, 2);
+                match fut2.as_mut().poll(cx) {
+                    Poll::Ready(guard) => assert_eq!(0, *guard),
+                    Poll::Pending => panic!("Expected an object"),
+                }
+
+                // A granted slot is passed on as well
+                let guard = pool.try_get().unwrap();
+                let mut fut1 = Box::pin(pool.get());
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = pool.get();
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                $guard_type::detach(guard);
+                drop(fut1);
+                assert_eq!(1, pool.size());
+                match fut2.as_mut().poll(cx) {
+                    Poll::Ready(guard) => assert_eq!(1, *guard),
+                    Poll::Pending => panic!("Expected an object"),
+                };
+            }
+
+            #[test]
+            fn panicking_factory_releases_slot() {
+                let pool = $pool_type::new(1, || -> u32 {
+                    panic!("Creating the object failed")
+                });
+
+                let res = std::panic::catch_unwind(
+                    std::panic::AssertUnwindSafe(|| pool.try_get().is_some()),
+                );
+                assert!(res.is_err());
+                assert_eq!(0, pool.size());
+            }
+
+            #[test]
+            #[should_panic]
+            fn zero_max_size_should_panic() {
+                let _ = numbered_pool(0);
+            }
+        }
+    };
+}
+
+gen_pool_tests!(local_pool_tests, LocalPool, LocalPoolGuard);
+
+#[test]
+fn local_pool_accepts_factories_which_are_not_thread_safe() {
+    let waker = &panic_waker();
+    let cx = &mut Context::from_waker(&waker);
+    let created = std::rc::Rc::new(std::cell::Cell::new(0));
+    let counter = created.clone();
+    let pool = LocalPool::new(1, move || counter.set(counter.get() + 1));
+
+    let fut = pool.get();
+    pin_mut!(fut);
+    assert!(fut.as_mut().poll(cx).is_ready());
+    assert_eq!(1, created.get());
+}
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::sync::{Pool, PoolGuard};
+    use std::thread;
+
+    gen_pool_tests!(pool_tests, Pool, PoolGuard);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn pool_futures_are_send() {
+        let pool = Pool::new(1, || 5u32);
+        is_sync(&pool);
+        {
+            let fut = pool.get();
+            is_send(&fut);
+            pin_mut!(fut);
+            is_send(&fut);
+        }
+
+        let fut = async move {
+            let guard = pool.get().await;
+            is_send(&guard);
+            is_sync(&guard);
+        };
+        is_send_value(fut);
+    }
+
+    #[test]
+    fn objects_are_shared_between_threads() {
+        let pool = Arc::new(Pool::new(2, Vec::<usize>::new));
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        block_on(async {
+                            pool.get().await.push(i);
+                        });
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert!(pool.size() <= 2);
+        assert_eq!(pool.size(), pool.idle());
+        let mut total = 0;
+        for _ in 0..pool.size() {
+            let guard = pool.try_get().unwrap();
+            total += PoolGuard::detach(guard).len();
+        }
+        assert_eq!(400, total);
+        assert_eq!(0, pool.size());
+    }
+}
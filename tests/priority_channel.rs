@@ -0,0 +1,226 @@
+use core::cmp::Ordering;
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::buffer::{ArrayPriorityBuf, PriorityOrder};
+use futures_intrusive::channel::{
+    ChannelSendError, GenericChannel, LocalPriorityChannel, TryReceiveError,
+};
+use futures_intrusive::NoopLock;
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_priority_channel_tests {
+    ($mod_name:ident, $channel_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            type ChannelType = $channel_type<i32, [i32; 3]>;
+
+            #[test]
+            fn receives_biggest_value_first() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+
+                assert_eq!(Ok(()), channel.try_send(2));
+                assert_eq!(Ok(()), channel.try_send(5));
+                assert_eq!(Ok(()), channel.try_send(1));
+                assert!(channel.try_send(3).unwrap_err().is_full());
+
+                let fut = channel.receive();
+                pin_mut!(fut);
+                assert_eq!(Poll::Ready(Some(5)), fut.as_mut().poll(cx));
+                assert!(fut.as_mut().is_terminated());
+
+                assert_eq!(Ok(()), channel.try_send(3));
+                assert_eq!(Ok(3), channel.try_receive());
+                assert_eq!(Ok(2), channel.try_receive());
+                assert_eq!(Ok(1), channel.try_receive());
+                assert_eq!(Err(TryReceiveError::Empty), channel.try_receive());
+            }
+
+            #[test]
+            fn receive_waits_for_value() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+
+                let fut = channel.receive();
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert_eq!(count, 0);
+
+                assert_eq!(Ok(()), channel.try_send(4));
+                assert_eq!(count, 1);
+                assert_eq!(Poll::Ready(Some(4)), fut.as_mut().poll(cx));
+            }
+
+            #[test]
+            fn send_waits_for_capacity() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                for value in 1..=3 {
+                    assert_eq!(Ok(()), channel.try_send(value));
+                }
+
+                let fut = channel.send(9);
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert_eq!(count, 0);
+
+                // The waiting value is not part of the buffer yet
+                assert_eq!(Ok(3), channel.try_receive());
+                assert_eq!(count, 1);
+                assert_eq!(Poll::Ready(Ok(())), fut.as_mut().poll(cx));
+                assert!(fut.as_mut().is_terminated());
+
+                assert_eq!(Ok(9), channel.try_receive());
+                assert_eq!(Ok(2), channel.try_receive());
+                assert_eq!(Ok(1), channel.try_receive());
+            }
+
+            #[test]
+            fn closed_channel_drains_in_priority_order() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                assert_eq!(Ok(()), channel.try_send(1));
+                assert_eq!(Ok(()), channel.try_send(7));
+                assert!(channel.close().is_newly_closed());
+
+                let fut = channel.send(8);
+                pin_mut!(fut);
+                assert_eq!(
+                    Poll::Ready(Err(ChannelSendError(8))),
+                    fut.as_mut().poll(cx)
+                );
+
+                assert_eq!(Ok(7), channel.try_receive());
+                assert_eq!(Ok(1), channel.try_receive());
+                assert_eq!(Err(TryReceiveError::Closed), channel.try_receive());
+            }
+        }
+    };
+}
+
+gen_priority_channel_tests!(local_priority_channel_tests, LocalPriorityChannel);
+
+#[derive(Debug, PartialEq)]
+struct Job {
+    name: &'static str,
+    priority: u8,
+}
+
+/// Runs jobs with a lower priority value first
+struct UrgentFirst;
+
+impl PriorityOrder<Job> for UrgentFirst {
+    fn compare(a: &Job, b: &Job) -> Ordering {
+        b.priority.cmp(&a.priority)
+    }
+}
+
+#[test]
+fn custom_priority_order() {
+    let channel = GenericChannel::<
+        NoopLock,
+        Job,
+        ArrayPriorityBuf<Job, [Job; 4], UrgentFirst>,
+    >::new();
+
+    for (name, priority) in [("cleanup", 3), ("alert", 0), ("report", 2)] {
+        assert_eq!(Ok(()), channel.try_send(Job { name, priority }));
+    }
+
+    let names: Vec<_> = (0..3)
+        .map(|_| channel.try_receive().unwrap().name)
+        .collect();
+    assert_eq!(vec!["alert", "report", "cleanup"], names);
+}
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::channel::{
+        shared::priority_channel, PriorityChannel,
+    };
+    use std::thread;
+
+    gen_priority_channel_tests!(priority_channel_tests, PriorityChannel);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn channel_futures_are_send() {
+        let channel = PriorityChannel::<i32, [i32; 3]>::new();
+        is_sync(&channel);
+        {
+            let recv_fut = channel.receive();
+            is_send(&recv_fut);
+            pin_mut!(recv_fut);
+            is_send(&recv_fut);
+            let send_fut = channel.send(3);
+            is_send(&send_fut);
+            pin_mut!(send_fut);
+            is_send(&send_fut);
+        }
+
+        let (sender, receiver) = priority_channel::<i32>(3);
+        is_send(&sender);
+        is_sync(&sender);
+        is_send(&receiver);
+        is_sync(&receiver);
+    }
+
+    #[test]
+    fn shared_priority_channel_smoke_test() {
+        let (sender, receiver) = priority_channel::<u32>(4);
+        block_on(async {
+            for value in [4, 8, 1, 6] {
+                sender.send(value).await.unwrap();
+            }
+        });
+        assert!(sender.try_send(2).unwrap_err().is_full());
+
+        let values: Vec<_> = (0..4)
+            .map(|_| block_on(receiver.receive()).unwrap())
+            .collect();
+        assert_eq!(vec![8, 6, 4, 1], values);
+    }
+
+    #[test]
+    fn values_are_exchanged_between_threads() {
+        let (sender, receiver) = priority_channel::<usize>(8);
+        let senders: Vec<_> = (0..4)
+            .map(|i| {
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    block_on(async {
+                        for value in 0..100 {
+                            sender.send(i * 100 + value).await.unwrap();
+                        }
+                    })
+                })
+            })
+            .collect();
+        drop(sender);
+
+        let mut values = block_on(async {
+            let mut values = Vec::new();
+            while let Some(value) = receiver.receive().await {
+                values.push(value);
+            }
+            values
+        });
+        for thread in senders {
+            thread.join().unwrap();
+        }
+
+        values.sort_unstable();
+        assert_eq!((0..400).collect::<Vec<_>>(), values);
+    }
+}
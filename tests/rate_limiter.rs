@@ -0,0 +1,271 @@
+use core::time::Duration;
+use futures::future::{FusedFuture, Future};
+use futures::task::Context;
+use futures_intrusive::timer::{
+    LocalRateLimiter, LocalTimerService, MockClock,
+};
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_rate_limiter_tests {
+    ($mod_name:ident, $limiter_type:ident, $timer_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn starts_with_burst_permits() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                TEST_CLOCK.set_time(100);
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let limiter = $limiter_type::new(
+                    &timer,
+                    1,
+                    Duration::from_millis(100),
+                    3,
+                );
+
+                assert_eq!(3, limiter.permits());
+                assert!(limiter.try_acquire(2));
+                assert_eq!(1, limiter.permits());
+                assert!(!limiter.try_acquire(2));
+                assert!(limiter.try_acquire(1));
+                assert!(!limiter.try_acquire(1));
+                assert_eq!(0, limiter.permits());
+            }
+
+            #[test]
+            fn refills_permits_per_interval() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                TEST_CLOCK.set_time(1000);
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let limiter = $limiter_type::new(
+                    &timer,
+                    2,
+                    Duration::from_millis(100),
+                    5,
+                );
+
+                assert!(limiter.try_acquire(5));
+                TEST_CLOCK.set_time(1099);
+                assert_eq!(0, limiter.permits());
+                TEST_CLOCK.set_time(1100);
+                assert_eq!(2, limiter.permits());
+                // Time which elapsed after the last refill is not lost
+                TEST_CLOCK.set_time(1250);
+                assert_eq!(4, limiter.permits());
+                TEST_CLOCK.set_time(1300);
+                assert_eq!(5, limiter.permits());
+            }
+
+            #[test]
+            fn permits_do_not_exceed_burst() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                TEST_CLOCK.set_time(0);
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let limiter = $limiter_type::new(
+                    &timer,
+                    10,
+                    Duration::from_millis(10),
+                    4,
+                );
+
+                assert!(limiter.try_acquire(1));
+                TEST_CLOCK.set_time(10_000);
+                assert_eq!(4, limiter.permits());
+                assert!(!limiter.try_acquire(5));
+            }
+
+            #[test]
+            fn acquire_waits_for_refill() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                TEST_CLOCK.set_time(200);
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let limiter =
+                    $limiter_type::new(&timer, 1, Duration::from_millis(50), 2);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                {
+                    let fut = limiter.acquire(2);
+                    pin_mut!(fut);
+                    assert!(fut.as_mut().poll(cx).is_ready());
+                    assert!(fut.as_mut().is_terminated());
+                }
+
+                let fut = limiter.acquire(2);
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert_eq!(Some(250), timer.next_expiration());
+
+                TEST_CLOCK.set_time(250);
+                timer.check_expirations();
+                assert_eq!(count, 1);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert_eq!(Some(300), timer.next_expiration());
+
+                TEST_CLOCK.set_time(300);
+                timer.check_expirations();
+                assert_eq!(count, 2);
+                assert!(fut.as_mut().poll(cx).is_ready());
+                assert!(fut.as_mut().is_terminated());
+                assert_eq!(0, limiter.permits());
+                assert_eq!(None, timer.next_expiration());
+            }
+
+            #[test]
+            fn acquire_is_immediately_ready_after_elapsed_time() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                TEST_CLOCK.set_time(0);
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let limiter =
+                    $limiter_type::new(&timer, 1, Duration::from_millis(10), 1);
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                assert!(limiter.try_acquire(1));
+                TEST_CLOCK.set_time(10);
+                let fut = limiter.acquire(1);
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn waiters_are_served_in_order() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                TEST_CLOCK.set_time(0);
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let limiter =
+                    $limiter_type::new(&timer, 1, Duration::from_millis(10), 3);
+                let (waker, _count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                assert!(limiter.try_acquire(3));
+                let fut1 = limiter.acquire(3);
+                pin_mut!(fut1);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = limiter.acquire(1);
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                // The small request must not overtake the big one
+                TEST_CLOCK.set_time(10);
+                timer.check_expirations();
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert!(fut1.as_mut().poll(cx).is_pending());
+
+                TEST_CLOCK.set_time(30);
+                timer.check_expirations();
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                assert!(fut1.as_mut().poll(cx).is_ready());
+
+                TEST_CLOCK.set_time(40);
+                timer.check_expirations();
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn cancel_waiting_acquire() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                TEST_CLOCK.set_time(0);
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let limiter =
+                    $limiter_type::new(&timer, 1, Duration::from_millis(10), 2);
+                let (waker, _count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                assert!(limiter.try_acquire(2));
+                let mut fut1 = Box::pin(limiter.acquire(2));
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = limiter.acquire(1);
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                drop(fut1);
+                assert_eq!(Some(10), timer.next_expiration());
+                TEST_CLOCK.set_time(10);
+                timer.check_expirations();
+                assert!(fut2.as_mut().poll(cx).is_ready());
+                assert_eq!(None, timer.next_expiration());
+            }
+
+            #[test]
+            #[should_panic]
+            fn acquire_more_than_burst_should_panic() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let limiter =
+                    $limiter_type::new(&timer, 1, Duration::from_millis(10), 2);
+                let _ = limiter.acquire(3);
+            }
+
+            #[test]
+            #[should_panic]
+            fn zero_interval_should_panic() {
+                static TEST_CLOCK: MockClock = MockClock::new();
+                let timer = $timer_type::new(&TEST_CLOCK);
+                let _ = $limiter_type::new(
+                    &timer,
+                    1,
+                    Duration::from_micros(500),
+                    2,
+                );
+            }
+        }
+    };
+}
+
+gen_rate_limiter_tests!(
+    local_rate_limiter_tests,
+    LocalRateLimiter,
+    LocalTimerService
+);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::timer::{RateLimiter, TimerService};
+
+    gen_rate_limiter_tests!(rate_limiter_tests, RateLimiter, TimerService);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn rate_limiter_futures_are_send() {
+        static TEST_CLOCK: MockClock = MockClock::new();
+        let timer = TimerService::new(&TEST_CLOCK);
+        let limiter = RateLimiter::new(&timer, 1, Duration::from_millis(10), 1);
+        is_sync(&limiter);
+        {
+            let fut = limiter.acquire(1);
+            is_send(&fut);
+            pin_mut!(fut);
+            is_send(&fut);
+        }
+
+        let fut = async move {
+            limiter.acquire(1).await;
+        };
+        is_send_value(fut);
+    }
+
+    #[test]
+    fn static_rate_limiter() {
+        static TEST_CLOCK: MockClock = MockClock::new();
+        static TIMER: TimerService = TimerService::new(&TEST_CLOCK);
+        static LIMITER: RateLimiter =
+            RateLimiter::new(&TIMER, 1, Duration::from_millis(10), 2);
+
+        TEST_CLOCK.set_time(0);
+        block_on(async {
+            LIMITER.acquire(2).await;
+        });
+        assert!(!LIMITER.try_acquire(1));
+        TEST_CLOCK.set_time(20);
+        assert!(LIMITER.try_acquire(2));
+    }
+}
@@ -0,0 +1,604 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::sync::{
+    GenericRwLockUpgradableReadGuard, GenericRwLockWriteGuard, LocalRwLock,
+};
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_rwlock_tests {
+    ($mod_name:ident, $rwlock_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn uncontended_read_and_write() {
+                for is_fair in &[true, false] {
+                    let waker = &panic_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+                    assert_eq!(0, rwlock.readers());
+                    assert_eq!(false, rwlock.is_write_locked());
+
+                    {
+                        let write_fut = rwlock.write();
+                        pin_mut!(write_fut);
+                        match write_fut.as_mut().poll(cx) {
+                            Poll::Pending => {
+                                panic!("Expect lock to get locked")
+                            }
+                            Poll::Ready(mut guard) => {
+                                assert_eq!(true, rwlock.is_write_locked());
+                                assert_eq!(5, *guard);
+                                *guard += 7;
+                            }
+                        };
+                        assert!(write_fut.as_mut().is_terminated());
+                    }
+
+                    assert_eq!(false, rwlock.is_write_locked());
+
+                    {
+                        let read_fut = rwlock.read();
+                        pin_mut!(read_fut);
+                        match read_fut.as_mut().poll(cx) {
+                            Poll::Pending => {
+                                panic!("Expect lock to get locked")
+                            }
+                            Poll::Ready(guard) => {
+                                assert_eq!(1, rwlock.readers());
+                                assert_eq!(12, *guard);
+                            }
+                        };
+                        assert!(read_fut.as_mut().is_terminated());
+                    }
+
+                    assert_eq!(0, rwlock.readers());
+                }
+            }
+
+            #[test]
+            #[should_panic]
+            fn poll_after_completion_should_panic() {
+                for is_fair in &[true, false] {
+                    let waker = &panic_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let read_fut = rwlock.read();
+                    pin_mut!(read_fut);
+                    let guard = match read_fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect lock to get locked"),
+                        Poll::Ready(guard) => guard,
+                    };
+                    assert_eq!(5, *guard);
+                    assert!(read_fut.as_mut().is_terminated());
+
+                    let _ = read_fut.poll(cx);
+                }
+            }
+
+            #[test]
+            fn multiple_readers() {
+                for is_fair in &[true, false] {
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_read().unwrap();
+                    let guard2 = rwlock.try_read().unwrap();
+                    assert_eq!(2, rwlock.readers());
+                    assert_eq!(5, *guard1);
+                    assert_eq!(5, *guard2);
+                    assert!(rwlock.try_write().is_none());
+
+                    drop(guard1);
+                    assert!(rwlock.try_write().is_none());
+                    drop(guard2);
+                    assert_eq!(0, rwlock.readers());
+
+                    let guard3 = rwlock.try_write().unwrap();
+                    assert!(rwlock.try_read().is_none());
+                    assert!(rwlock.try_write().is_none());
+                    drop(guard3);
+                }
+            }
+
+            #[test]
+            fn writer_waits_for_readers() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_read().unwrap();
+                    let guard2 = rwlock.try_read().unwrap();
+
+                    let write_fut = rwlock.write();
+                    pin_mut!(write_fut);
+                    assert!(write_fut.as_mut().poll(cx).is_pending());
+                    assert!(!write_fut.as_mut().is_terminated());
+
+                    drop(guard1);
+                    assert_eq!(count, 0);
+                    drop(guard2);
+                    assert_eq!(count, 1);
+
+                    let mut guard = match write_fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect lock to get locked"),
+                        Poll::Ready(guard) => guard,
+                    };
+                    *guard = 27;
+                    assert!(write_fut.as_mut().is_terminated());
+                    assert_eq!(true, rwlock.is_write_locked());
+                }
+            }
+
+            #[test]
+            fn readers_wait_for_writer() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let mut guard = rwlock.try_write().unwrap();
+                    *guard = 27;
+
+                    let read_fut1 = rwlock.read();
+                    pin_mut!(read_fut1);
+                    let read_fut2 = rwlock.read();
+                    pin_mut!(read_fut2);
+                    assert!(read_fut1.as_mut().poll(cx).is_pending());
+                    assert!(read_fut2.as_mut().poll(cx).is_pending());
+                    assert_eq!(count, 0);
+
+                    drop(guard);
+
+                    let guard1 = match read_fut1.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect lock to get locked"),
+                        Poll::Ready(guard) => guard,
+                    };
+                    // The second reader is woken up either directly when the
+                    // writer unlocks, or after the first reader obtained the
+                    // lock.
+                    assert_eq!(count, 2);
+                    let guard2 = match read_fut2.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect lock to get locked"),
+                        Poll::Ready(guard) => guard,
+                    };
+                    assert_eq!(27, *guard1);
+                    assert_eq!(27, *guard2);
+                    assert_eq!(2, rwlock.readers());
+                }
+            }
+
+            #[test]
+            fn readers_queued_behind_writer_in_fair_mode() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let rwlock = $rwlock_type::new(5, true);
+
+                let guard1 = rwlock.try_read().unwrap();
+
+                let write_fut = rwlock.write();
+                pin_mut!(write_fut);
+                assert!(write_fut.as_mut().poll(cx).is_pending());
+
+                // New readers must queue up behind the writer
+                assert!(rwlock.try_read().is_none());
+                let read_fut = rwlock.read();
+                pin_mut!(read_fut);
+                assert!(read_fut.as_mut().poll(cx).is_pending());
+
+                drop(guard1);
+                assert_eq!(count, 1);
+                assert!(read_fut.as_mut().poll(cx).is_pending());
+
+                let guard2 = match write_fut.as_mut().poll(cx) {
+                    Poll::Pending => panic!("Expect lock to get locked"),
+                    Poll::Ready(guard) => guard,
+                };
+                assert_eq!(count, 1);
+
+                drop(guard2);
+                assert_eq!(count, 2);
+                match read_fut.as_mut().poll(cx) {
+                    Poll::Pending => panic!("Expect lock to get locked"),
+                    Poll::Ready(guard) => guard,
+                };
+            }
+
+            #[test]
+            fn readers_can_bypass_writer_in_unfair_mode() {
+                let (waker, _count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let rwlock = $rwlock_type::new(5, false);
+
+                let guard1 = rwlock.try_read().unwrap();
+
+                let write_fut = rwlock.write();
+                pin_mut!(write_fut);
+                assert!(write_fut.as_mut().poll(cx).is_pending());
+
+                let guard2 = rwlock.try_read().unwrap();
+                assert_eq!(2, rwlock.readers());
+
+                drop(guard1);
+                drop(guard2);
+                assert!(write_fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn cancel_wait_for_rwlock() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_write().unwrap();
+
+                    let mut write_fut2 = Box::pin(rwlock.write());
+                    let mut write_fut3 = Box::pin(rwlock.write());
+
+                    assert!(write_fut2.as_mut().poll(cx).is_pending());
+                    assert!(write_fut3.as_mut().poll(cx).is_pending());
+
+                    // Before the lock gets available, cancel one lock attempt
+                    drop(write_fut2);
+
+                    drop(guard1);
+                    assert_eq!(count, 1);
+
+                    match write_fut3.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect lock to get locked"),
+                        Poll::Ready(guard) => guard,
+                    };
+                }
+            }
+
+            #[test]
+            fn unlock_next_when_notification_is_not_used() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_write().unwrap();
+
+                    let mut write_fut2 = Box::pin(rwlock.write());
+                    let mut read_fut3 = Box::pin(rwlock.read());
+
+                    assert!(write_fut2.as_mut().poll(cx).is_pending());
+                    assert!(read_fut3.as_mut().poll(cx).is_pending());
+                    assert_eq!(count, 0);
+
+                    // Unlock - the writer should have been notified
+                    drop(guard1);
+                    assert_eq!(count, 1);
+
+                    // We don't use the notification. Expect the next waiting
+                    // task to be woken up
+                    drop(write_fut2);
+                    assert_eq!(count, 2);
+
+                    match read_fut3.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect lock to get locked"),
+                        Poll::Ready(guard) => guard,
+                    };
+                }
+            }
+
+            #[test]
+            fn upgradable_reader_coexists_with_readers() {
+                for is_fair in &[true, false] {
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_upgradable_read().unwrap();
+                    assert_eq!(true, rwlock.has_upgradable_reader());
+                    let guard2 = rwlock.try_read().unwrap();
+                    assert_eq!(5, *guard1);
+                    assert_eq!(5, *guard2);
+
+                    // Only a single upgradable reader is allowed
+                    assert!(rwlock.try_upgradable_read().is_none());
+                    assert!(rwlock.try_write().is_none());
+
+                    drop(guard1);
+                    assert_eq!(false, rwlock.has_upgradable_reader());
+                    let guard3 = rwlock.try_upgradable_read().unwrap();
+                    drop(guard2);
+                    drop(guard3);
+                    assert!(rwlock.try_write().is_some());
+                }
+            }
+
+            #[test]
+            fn try_upgrade() {
+                for is_fair in &[true, false] {
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_upgradable_read().unwrap();
+                    let guard2 = rwlock.try_read().unwrap();
+
+                    let guard1 =
+                        match GenericRwLockUpgradableReadGuard::try_upgrade(
+                            guard1,
+                        ) {
+                            Ok(_) => panic!("Expect upgrade to fail"),
+                            Err(guard) => guard,
+                        };
+
+                    drop(guard2);
+                    let mut guard =
+                        match GenericRwLockUpgradableReadGuard::try_upgrade(
+                            guard1,
+                        ) {
+                            Ok(guard) => guard,
+                            Err(_) => panic!("Expect upgrade to succeed"),
+                        };
+                    *guard = 27;
+                    assert_eq!(true, rwlock.is_write_locked());
+                    assert_eq!(false, rwlock.has_upgradable_reader());
+                    assert!(rwlock.try_read().is_none());
+                }
+            }
+
+            #[test]
+            fn upgrade_waits_for_readers_and_blocks_new_readers() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_upgradable_read().unwrap();
+                    let guard2 = rwlock.try_read().unwrap();
+
+                    let upgrade_fut =
+                        GenericRwLockUpgradableReadGuard::upgrade(guard1);
+                    pin_mut!(upgrade_fut);
+                    assert!(upgrade_fut.as_mut().poll(cx).is_pending());
+                    assert!(!upgrade_fut.as_mut().is_terminated());
+
+                    // New readers are not admitted while the upgrade is pending
+                    assert!(rwlock.try_read().is_none());
+                    let read_fut = rwlock.read();
+                    pin_mut!(read_fut);
+                    assert!(read_fut.as_mut().poll(cx).is_pending());
+
+                    drop(guard2);
+                    assert_eq!(count, 1);
+
+                    let mut guard = match upgrade_fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect upgrade to succeed"),
+                        Poll::Ready(guard) => guard,
+                    };
+                    assert!(upgrade_fut.as_mut().is_terminated());
+                    *guard = 27;
+                    assert_eq!(true, rwlock.is_write_locked());
+                    assert_eq!(false, rwlock.has_upgradable_reader());
+
+                    drop(guard);
+                    assert_eq!(count, 2);
+                    match read_fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect lock to get locked"),
+                        Poll::Ready(guard) => assert_eq!(27, *guard),
+                    };
+                }
+            }
+
+            #[test]
+            fn cancel_upgrade_releases_upgradable_lock() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_upgradable_read().unwrap();
+                    let guard2 = rwlock.try_read().unwrap();
+
+                    let mut upgrade_fut = Box::pin(
+                        GenericRwLockUpgradableReadGuard::upgrade(guard1),
+                    );
+                    assert!(upgrade_fut.as_mut().poll(cx).is_pending());
+
+                    let read_fut = rwlock.read();
+                    pin_mut!(read_fut);
+                    assert!(read_fut.as_mut().poll(cx).is_pending());
+
+                    drop(upgrade_fut);
+                    assert_eq!(false, rwlock.has_upgradable_reader());
+                    assert_eq!(count, 1);
+                    assert!(read_fut.as_mut().poll(cx).is_ready());
+
+                    drop(guard2);
+                    assert!(rwlock.try_upgradable_read().is_some());
+                }
+            }
+
+            #[test]
+            fn downgrade_write_to_read() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let mut guard = rwlock.try_write().unwrap();
+                    *guard = 27;
+
+                    let write_fut = rwlock.write();
+                    pin_mut!(write_fut);
+                    assert!(write_fut.as_mut().poll(cx).is_pending());
+
+                    let read_guard = GenericRwLockWriteGuard::downgrade(guard);
+                    assert_eq!(27, *read_guard);
+                    assert_eq!(false, rwlock.is_write_locked());
+                    assert_eq!(1, rwlock.readers());
+
+                    // The waiting writer must not get the lock in between
+                    assert_eq!(count, 0);
+                    assert!(write_fut.as_mut().poll(cx).is_pending());
+
+                    drop(read_guard);
+                    assert_eq!(count, 1);
+                    assert!(write_fut.as_mut().poll(cx).is_ready());
+                }
+            }
+
+            #[test]
+            fn downgrade_write_wakes_up_readers() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard = rwlock.try_write().unwrap();
+
+                    let read_fut = rwlock.read();
+                    pin_mut!(read_fut);
+                    assert!(read_fut.as_mut().poll(cx).is_pending());
+
+                    let _read_guard = GenericRwLockWriteGuard::downgrade(guard);
+                    assert_eq!(count, 1);
+                    let _guard = match read_fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect lock to get locked"),
+                        Poll::Ready(guard) => guard,
+                    };
+                    assert_eq!(2, rwlock.readers());
+                }
+            }
+
+            #[test]
+            fn downgrade_write_to_upgradable_and_upgrade_again() {
+                for is_fair in &[true, false] {
+                    let waker = &panic_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard = rwlock.try_write().unwrap();
+                    let guard =
+                        GenericRwLockWriteGuard::downgrade_to_upgradable(guard);
+                    assert_eq!(true, rwlock.has_upgradable_reader());
+                    assert_eq!(false, rwlock.is_write_locked());
+                    assert!(rwlock.try_upgradable_read().is_none());
+
+                    let upgrade_fut =
+                        GenericRwLockUpgradableReadGuard::upgrade(guard);
+                    pin_mut!(upgrade_fut);
+                    let guard = match upgrade_fut.as_mut().poll(cx) {
+                        Poll::Pending => panic!("Expect upgrade to succeed"),
+                        Poll::Ready(guard) => guard,
+                    };
+                    assert_eq!(true, rwlock.is_write_locked());
+
+                    let guard =
+                        GenericRwLockWriteGuard::downgrade_to_upgradable(guard);
+                    let _guard =
+                        GenericRwLockUpgradableReadGuard::downgrade(guard);
+                    assert_eq!(false, rwlock.has_upgradable_reader());
+                    assert_eq!(1, rwlock.readers());
+                }
+            }
+
+            #[test]
+            fn upgradable_readers_wait_for_each_other() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_upgradable_read().unwrap();
+
+                    let upgradable_fut = rwlock.upgradable_read();
+                    pin_mut!(upgradable_fut);
+                    assert!(upgradable_fut.as_mut().poll(cx).is_pending());
+
+                    drop(guard1);
+                    assert_eq!(count, 1);
+                    assert!(upgradable_fut.as_mut().poll(cx).is_ready());
+                    assert!(upgradable_fut.as_mut().is_terminated());
+                }
+            }
+
+            #[test]
+            fn poll_from_multiple_executors() {
+                for is_fair in &[true, false] {
+                    let (waker_1, count_1) = new_count_waker();
+                    let (waker_2, count_2) = new_count_waker();
+                    let rwlock = $rwlock_type::new(5, *is_fair);
+
+                    let guard1 = rwlock.try_read().unwrap();
+
+                    let cx_1 = &mut Context::from_waker(&waker_1);
+                    let cx_2 = &mut Context::from_waker(&waker_2);
+
+                    let fut = rwlock.write();
+                    pin_mut!(fut);
+
+                    assert!(fut.as_mut().poll(cx_1).is_pending());
+                    assert!(fut.as_mut().poll(cx_2).is_pending());
+
+                    drop(guard1);
+                    assert_eq!(count_1, 0);
+                    assert_eq!(count_2, 1);
+
+                    assert!(fut.as_mut().poll(cx_2).is_ready());
+                    assert!(fut.as_mut().is_terminated());
+                }
+            }
+        }
+    };
+}
+
+gen_rwlock_tests!(local_rwlock_tests, LocalRwLock);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::FutureExt;
+    use futures_intrusive::sync::RwLock;
+
+    gen_rwlock_tests!(rwlock_tests, RwLock);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn rwlock_futures_are_send() {
+        let rwlock = RwLock::new(true, true);
+        is_sync(&rwlock);
+        {
+            let read_fut = rwlock.read();
+            is_send(&read_fut);
+            pin_mut!(read_fut);
+            is_send(&read_fut);
+
+            let waker = &panic_waker();
+            let cx = &mut Context::from_waker(&waker);
+            let res = read_fut.poll_unpin(cx);
+            let guard = match res {
+                Poll::Ready(v) => v,
+                Poll::Pending => panic!("Expected to be ready"),
+            };
+            is_send(&guard);
+            is_send_value(guard);
+        }
+        {
+            let write_fut = rwlock.write();
+            is_send(&write_fut);
+            pin_mut!(write_fut);
+            is_send(&write_fut);
+
+            let waker = &panic_waker();
+            let cx = &mut Context::from_waker(&waker);
+            let res = write_fut.poll_unpin(cx);
+            let guard = match res {
+                Poll::Ready(v) => v,
+                Poll::Pending => panic!("Expected to be ready"),
+            };
+            is_send(&guard);
+            is_send_value(guard);
+        }
+        is_send_value(rwlock);
+    }
+}
@@ -0,0 +1,352 @@
+use core::pin::Pin;
+use futures::future::{FusedFuture, Future};
+use futures::task::{Context, Poll};
+use futures_intrusive::channel::{LocalChannel, TryReceiveError};
+use futures_intrusive::select::{select, SelectSource};
+use futures_intrusive::sync::LocalManualResetEvent;
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_select_tests {
+    ($mod_name:ident, $channel_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            type ChannelType = $channel_type<i32, [i32; 3]>;
+
+            #[test]
+            fn resolves_with_first_ready_source() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel1 = ChannelType::new();
+                let channel2 = ChannelType::new();
+                assert_eq!(Ok(()), channel2.try_send(5));
+
+                let sources = (channel1.stream(), channel2.stream());
+                pin_mut!(sources);
+                let fut = select(sources.as_mut());
+                pin_mut!(fut);
+                assert!(!fut.as_mut().is_terminated());
+                assert_eq!(Poll::Ready((1, Some(5))), fut.as_mut().poll(cx));
+                assert!(fut.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn prefers_sources_with_lower_index() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel1 = ChannelType::new();
+                let channel2 = ChannelType::new();
+                assert_eq!(Ok(()), channel1.try_send(1));
+                assert_eq!(Ok(()), channel2.try_send(2));
+
+                let sources = (channel1.stream(), channel2.stream());
+                pin_mut!(sources);
+                {
+                    let fut = select(sources.as_mut());
+                    pin_mut!(fut);
+                    assert_eq!(
+                        Poll::Ready((0, Some(1))),
+                        fut.as_mut().poll(cx)
+                    );
+                }
+                {
+                    let fut = select(sources.as_mut());
+                    pin_mut!(fut);
+                    assert_eq!(
+                        Poll::Ready((1, Some(2))),
+                        fut.as_mut().poll(cx)
+                    );
+                }
+            }
+
+            #[test]
+            fn values_of_other_sources_are_retained() {
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel1 = ChannelType::new();
+                let channel2 = ChannelType::new();
+                assert_eq!(Ok(()), channel1.try_send(1));
+                assert_eq!(Ok(()), channel2.try_send(2));
+
+                {
+                    let sources = (channel1.stream(), channel2.stream());
+                    pin_mut!(sources);
+                    let fut = select(sources.as_mut());
+                    pin_mut!(fut);
+                    assert_eq!(
+                        Poll::Ready((0, Some(1))),
+                        fut.as_mut().poll(cx)
+                    );
+                }
+
+                assert_eq!(Err(TryReceiveError::Empty), channel1.try_receive());
+                assert_eq!(Ok(2), channel2.try_receive());
+            }
+
+            #[test]
+            fn waits_until_a_source_is_ready() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel1 = ChannelType::new();
+                let channel2 = ChannelType::new();
+                let channel3 = ChannelType::new();
+
+                let sources =
+                    (channel1.stream(), channel2.stream(), channel3.stream());
+                pin_mut!(sources);
+                let fut = select(sources.as_mut());
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert_eq!(count, 0);
+
+                assert_eq!(Ok(()), channel3.try_send(7));
+                assert_eq!(count, 1);
+                assert_eq!(Poll::Ready((2, Some(7))), fut.as_mut().poll(cx));
+            }
+
+            #[test]
+            fn sources_stay_registered_between_selects() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel1 = ChannelType::new();
+                let channel2 = ChannelType::new();
+
+                let sources = (channel1.stream(), channel2.stream());
+                pin_mut!(sources);
+                {
+                    let fut = select(sources.as_mut());
+                    pin_mut!(fut);
+                    assert!(fut.as_mut().poll(cx).is_pending());
+                }
+
+                // The sources are still registered, even though the select
+                // future has been dropped.
+                assert_eq!(Ok(()), channel2.try_send(3));
+                assert_eq!(count, 1);
+
+                let fut = select(sources.as_mut());
+                pin_mut!(fut);
+                assert_eq!(Poll::Ready((1, Some(3))), fut.as_mut().poll(cx));
+            }
+
+            #[test]
+            fn closed_channel_yields_none() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel1 = ChannelType::new();
+                let channel2 = ChannelType::new();
+
+                let sources = (channel1.stream(), channel2.stream());
+                pin_mut!(sources);
+                let fut = select(sources.as_mut());
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                assert!(channel2.close().is_newly_closed());
+                assert_eq!(count, 1);
+                assert_eq!(Poll::Ready((1, None)), fut.as_mut().poll(cx));
+            }
+
+            #[test]
+            fn selects_from_slice_of_sources() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channels = [
+                    ChannelType::new(),
+                    ChannelType::new(),
+                    ChannelType::new(),
+                    ChannelType::new(),
+                ];
+
+                let sources = [
+                    channels[0].stream(),
+                    channels[1].stream(),
+                    channels[2].stream(),
+                    channels[3].stream(),
+                ];
+                pin_mut!(sources);
+                let mut sources: Pin<&mut [_]> = sources;
+                {
+                    let fut = select(sources.as_mut());
+                    pin_mut!(fut);
+                    assert!(fut.as_mut().poll(cx).is_pending());
+                }
+
+                assert_eq!(Ok(()), channels[2].try_send(9));
+                assert_eq!(count, 1);
+                let fut = select(sources.as_mut());
+                pin_mut!(fut);
+                assert_eq!(Poll::Ready((2, Some(9))), fut.as_mut().poll(cx));
+            }
+
+            #[test]
+            fn selects_events_and_channels() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let channel = ChannelType::new();
+                let event = LocalManualResetEvent::new(false);
+
+                let sources = (
+                    event.wait().map_output(|()| -1),
+                    channel.stream().map_output(|value| value.unwrap_or(0)),
+                );
+                pin_mut!(sources);
+                {
+                    let fut = select(sources.as_mut());
+                    pin_mut!(fut);
+                    assert!(fut.as_mut().poll(cx).is_pending());
+                }
+
+                assert_eq!(Ok(()), channel.try_send(4));
+                assert_eq!(count, 1);
+                {
+                    let fut = select(sources.as_mut());
+                    pin_mut!(fut);
+                    assert_eq!(Poll::Ready((1, 4)), fut.as_mut().poll(cx));
+                    assert_eq!(count, 1);
+                }
+
+                event.set();
+                assert_eq!(count, 2);
+                {
+                    let fut = select(sources.as_mut());
+                    pin_mut!(fut);
+                    assert_eq!(Poll::Ready((0, -1)), fut.as_mut().poll(cx));
+                }
+
+                // The event source has produced its value and doesn't get
+                // chosen again.
+                let fut = select(sources.as_mut());
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+                assert_eq!(Ok(()), channel.try_send(5));
+                assert_eq!(Poll::Ready((1, 5)), fut.as_mut().poll(cx));
+            }
+        }
+    };
+}
+
+gen_select_tests!(local_select_tests, LocalChannel);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::channel::{shared::channel, Channel};
+    use futures_intrusive::sync::ManualResetEvent;
+    use std::sync::Arc;
+    use std::thread;
+
+    gen_select_tests!(select_tests, Channel);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    #[test]
+    fn select_futures_are_send() {
+        let channel = Channel::<i32, [i32; 3]>::new();
+        let event = ManualResetEvent::new(false);
+        let sources = (
+            channel.stream().map_output(|value| value.is_some()),
+            event.wait().map_output(|()| false),
+        );
+        pin_mut!(sources);
+        is_send(&sources);
+        let fut = select(sources.as_mut());
+        is_send(&fut);
+    }
+
+    #[test]
+    fn selects_from_shared_streams() {
+        let (waker, count) = new_count_waker();
+        let cx = &mut Context::from_waker(&waker);
+        let (sender1, receiver1) = channel::<i32>(2);
+        let (sender2, receiver2) = channel::<i32>(2);
+
+        let sources = (receiver1.into_stream(), receiver2.into_stream());
+        pin_mut!(sources);
+        {
+            let fut = select(sources.as_mut());
+            pin_mut!(fut);
+            assert!(fut.as_mut().poll(cx).is_pending());
+        }
+
+        assert_eq!(Ok(()), sender2.try_send(6));
+        assert_eq!(Ok(()), sender1.try_send(5));
+        assert_eq!(count, 2);
+        {
+            let fut = select(sources.as_mut());
+            pin_mut!(fut);
+            assert_eq!(Poll::Ready((0, Some(5))), fut.as_mut().poll(cx));
+        }
+
+        {
+            let fut = select(sources.as_mut());
+            pin_mut!(fut);
+            assert_eq!(Poll::Ready((1, Some(6))), fut.as_mut().poll(cx));
+        }
+
+        drop(sender1);
+        let fut = select(sources.as_mut());
+        pin_mut!(fut);
+        assert_eq!(Poll::Ready((0, None)), fut.as_mut().poll(cx));
+    }
+
+    #[test]
+    fn values_are_selected_between_threads() {
+        let channel1 = Arc::new(Channel::<usize, [usize; 4]>::new());
+        let channel2 = Arc::new(Channel::<usize, [usize; 4]>::new());
+        let shutdown = Arc::new(ManualResetEvent::new(false));
+
+        let senders: Vec<_> = [channel1.clone(), channel2.clone()]
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, channel)| {
+                thread::spawn(move || {
+                    block_on(async {
+                        for value in 0..100 {
+                            channel.send(i * 100 + value).await.unwrap();
+                        }
+                    })
+                })
+            })
+            .collect();
+
+        let shutdown_setter = shutdown.clone();
+        let waiter = thread::spawn(move || {
+            for thread in senders {
+                thread.join().unwrap();
+            }
+            shutdown_setter.set();
+        });
+
+        let values = block_on(async {
+            let sources = (
+                shutdown.wait().map_output(|()| None),
+                channel1.stream(),
+                channel2.stream(),
+            );
+            pin_mut!(sources);
+            let mut values = Vec::new();
+            while let (_, Some(value)) = select(sources.as_mut()).await {
+                values.push(value);
+            }
+            values
+        });
+        waiter.join().unwrap();
+
+        // Values which were sent before the shutdown might still be stored
+        // inside the channels.
+        let mut values = values;
+        while let Ok(value) = channel1.try_receive() {
+            values.push(value);
+        }
+        while let Ok(value) = channel2.try_receive() {
+            values.push(value);
+        }
+        values.sort_unstable();
+        assert_eq!((0..200).collect::<Vec<_>>(), values);
+    }
+}
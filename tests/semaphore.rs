@@ -1,6 +1,6 @@
 use futures::future::{FusedFuture, Future};
 use futures::task::{Context, Poll};
-use futures_intrusive::sync::LocalSemaphore;
+use futures_intrusive::sync::{AcquireError, LocalSemaphore};
 use futures_test::task::{new_count_waker, panic_waker};
 use pin_utils::pin_mut;
 
@@ -21,10 +21,10 @@ macro_rules! gen_semaphore_tests {
                         let sem_fut = sem.acquire(1);
                         pin_mut!(sem_fut);
                         match sem_fut.as_mut().poll(cx) {
-                            Poll::Pending => panic!("Expect semaphore to get acquired"),
-                            Poll::Ready(_guard) => {
+                            Poll::Ready(Ok(_guard)) => {
                                 assert_eq!(1, sem.permits());
                             },
+                            _ => panic!("Expect semaphore to get acquired"),
                         };
                         assert!(sem_fut.as_mut().is_terminated());
                         assert_eq!(2, sem.permits());
@@ -36,10 +36,10 @@ macro_rules! gen_semaphore_tests {
                         let sem_fut = sem.acquire(2);
                         pin_mut!(sem_fut);
                         match sem_fut.as_mut().poll(cx) {
-                            Poll::Pending => panic!("Expect semaphore to get acquired"),
-                            Poll::Ready(_guard) => {
+                            Poll::Ready(Ok(_guard)) => {
                                 assert_eq!(0, sem.permits());
                             },
+                            _ => panic!("Expect semaphore to get acquired"),
                         };
                         assert!(sem_fut.as_mut().is_terminated());
                     }
@@ -60,11 +60,11 @@ macro_rules! gen_semaphore_tests {
                         let sem_fut = sem.acquire(1);
                         pin_mut!(sem_fut);
                         match sem_fut.as_mut().poll(cx) {
-                            Poll::Pending => panic!("Expect semaphore to get acquired"),
-                            Poll::Ready(mut guard) => {
+                            Poll::Ready(Ok(mut guard)) => {
                                 assert_eq!(1, sem.permits());
                                 guard.disarm();
                             },
+                            _ => panic!("Expect semaphore to get acquired"),
                         };
                         assert!(sem_fut.as_mut().is_terminated());
                         assert_eq!(1, sem.permits());
@@ -76,11 +76,11 @@ macro_rules! gen_semaphore_tests {
                         let sem_fut = sem.acquire(1);
                         pin_mut!(sem_fut);
                         match sem_fut.as_mut().poll(cx) {
-                            Poll::Pending => panic!("Expect semaphore to get acquired"),
-                            Poll::Ready(mut guard) => {
+                            Poll::Ready(Ok(mut guard)) => {
                                 assert_eq!(0, sem.permits());
                                 guard.disarm();
                             },
+                            _ => panic!("Expect semaphore to get acquired"),
                         };
                         assert!(sem_fut.as_mut().is_terminated());
                     }
@@ -103,8 +103,8 @@ macro_rules! gen_semaphore_tests {
                     let sem_fut = sem.acquire(2);
                     pin_mut!(sem_fut);
                     match sem_fut.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired"),
-                        Poll::Ready(guard) => guard,
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired"),
                     };
                     assert!(sem_fut.as_mut().is_terminated());
 
@@ -124,8 +124,8 @@ macro_rules! gen_semaphore_tests {
 
                     // Acquire the semaphore
                     let guard1 = match sem_fut1.poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired 1"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired 1"),
                     };
 
                     // The next acquire attempts must fail
@@ -148,17 +148,13 @@ macro_rules! gen_semaphore_tests {
                     assert_eq!(0, sem.permits());
                     drop(guard1);
                     assert_eq!(3, sem.permits());
-                    // At least one task should be awoken.
-                    if *is_fair {
-                        assert_eq!(count, 1);
-                    }
-                    else {
-                        assert_eq!(count, 2);
-                    }
+                    // Both tasks for which enough permits are available
+                    // should be awoken.
+                    assert_eq!(count, 2);
 
                     let guard2 = match sem_fut2.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired 2"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired 2"),
                     };
                     assert!(sem_fut2.as_mut().is_terminated());
                     assert_eq!(2, sem.permits());
@@ -166,8 +162,8 @@ macro_rules! gen_semaphore_tests {
                     assert_eq!(count, 2);
 
                     let guard3 = match sem_fut3.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired 3"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired 3"),
                     };
                     assert!(sem_fut3.as_mut().is_terminated());
                     assert_eq!(0, sem.permits());
@@ -190,8 +186,8 @@ macro_rules! gen_semaphore_tests {
                     assert_eq!(count, 3);
 
                     let guard4 = match sem_fut4.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired 4"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired 4"),
                     };
                     assert!(sem_fut4.as_mut().is_terminated());
 
@@ -213,8 +209,8 @@ macro_rules! gen_semaphore_tests {
 
                     // Acquire the semaphore
                     let guard1 = match sem_fut1.poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired 1"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired 1"),
                     };
 
                     // Some failing acquire attempts
@@ -264,8 +260,8 @@ macro_rules! gen_semaphore_tests {
                     let sem_fut2 = sem.acquire(0);
                     pin_mut!(sem_fut2);
                     let guard2 = match sem_fut2.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired 2"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired 2"),
                     };
 
                     drop(guard2);
@@ -294,8 +290,8 @@ macro_rules! gen_semaphore_tests {
                     let sem_fut3 = sem.acquire(0);
                     pin_mut!(sem_fut3);
                     let guard3 = match sem_fut3.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired 3"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired 3"),
                     };
 
                     drop(guard3);
@@ -304,8 +300,8 @@ macro_rules! gen_semaphore_tests {
                     assert_eq!(3, sem.permits());
 
                     let guard2 = match sem_fut2.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired 2"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired 2"),
                     };
                     assert_eq!(2, sem.permits());
                     drop(guard2);
@@ -339,8 +335,8 @@ macro_rules! gen_semaphore_tests {
 
                     // Unlock - semaphore should be available again
                     match sem_fut3.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired"),
                     };
                 }
             }
@@ -355,8 +351,8 @@ macro_rules! gen_semaphore_tests {
                     let guard1 = sem.try_acquire(2).unwrap();
 
                     // The second and third acquire attempt must fail
-                    let mut sem_fut2 = Box::pin(sem.acquire(1));
-                    let mut sem_fut3 = Box::pin(sem.acquire(1));
+                    let mut sem_fut2 = Box::pin(sem.acquire(2));
+                    let mut sem_fut3 = Box::pin(sem.acquire(2));
 
                     assert!(sem_fut2.as_mut().poll(cx).is_pending());
                     assert!(!sem_fut2.as_mut().is_terminated());
@@ -366,20 +362,15 @@ macro_rules! gen_semaphore_tests {
 
                     // Release - semaphore should be available again. fut2 should have been notified
                     drop(guard1);
-                    if *is_fair {
-                        assert_eq!(count, 1);
-                    }
-                    else {
-                        assert_eq!(count, 2);
-                    }
+                    assert_eq!(count, 1);
 
                     // We don't use the notification. Expect the next waiting task to be woken up
                     drop(sem_fut2);
                     assert_eq!(count, 2);
 
                     match sem_fut3.as_mut().poll(cx) {
-                        Poll::Pending => panic!("Expect semaphore to get acquired"),
-                        Poll::Ready(guard) => guard
+                        Poll::Ready(Ok(guard)) => guard,
+                        _ => panic!("Expect semaphore to get acquired"),
                     };
                 }
             }
@@ -405,8 +396,8 @@ macro_rules! gen_semaphore_tests {
 
                 // Acquire fut3 in between. This should succeed
                 let guard3 = match sem_fut3.as_mut().poll(cx) {
-                    Poll::Pending => panic!("Expect semaphore to get acquired"),
-                    Poll::Ready(guard) => guard
+                    Poll::Ready(Ok(guard)) => guard,
+                    _ => panic!("Expect semaphore to get acquired"),
                 };
                 // Now fut2 can't use it's notification and is still pending
                 assert!(sem_fut2.as_mut().poll(cx).is_pending());
@@ -416,8 +407,8 @@ macro_rules! gen_semaphore_tests {
                 drop(guard3);
                 assert_eq!(count, 2);
                 match sem_fut2.as_mut().poll(cx) {
-                    Poll::Pending => panic!("Expect semaphore to get acquired"),
-                    Poll::Ready(_guard) => {},
+                    Poll::Ready(Ok(_guard)) => {},
+                    _ => panic!("Expect semaphore to get acquired"),
                 };
             }
 
@@ -443,8 +434,8 @@ macro_rules! gen_semaphore_tests {
 
                 // Acquire fut3 in between. This should succeed
                 let guard3 = match sem_fut3.as_mut().poll(cx) {
-                    Poll::Pending => panic!("Expect semaphore to get acquired"),
-                    Poll::Ready(guard) => guard
+                    Poll::Ready(Ok(guard)) => guard,
+                    _ => panic!("Expect semaphore to get acquired"),
                 };
                 // Now fut2 can't use it's notification and is still pending
                 assert!(sem_fut2.as_mut().poll(cx).is_pending());
@@ -454,8 +445,8 @@ macro_rules! gen_semaphore_tests {
                 drop(guard3);
                 assert_eq!(count, 2);
                 match sem_fut2.as_mut().poll(cx) {
-                    Poll::Pending => panic!("Expect semaphore to get acquired"),
-                    Poll::Ready(_guard) => {},
+                    Poll::Ready(Ok(_guard)) => {},
+                    _ => panic!("Expect semaphore to get acquired"),
                 };
             }
 
@@ -483,15 +474,15 @@ macro_rules! gen_semaphore_tests {
 
                 // fut2 should be be able to get acquired
                 match sem_fut2.as_mut().poll(cx) {
-                    Poll::Pending => panic!("Expect semaphore to get acquired"),
-                    Poll::Ready(_guard) => {},
+                    Poll::Ready(Ok(_guard)) => {},
+                    _ => panic!("Expect semaphore to get acquired"),
                 };
 
                 // Now fut3 should have been signaled and should be able to get acquired
                 assert_eq!(count, 2);
                 match sem_fut3.as_mut().poll(cx) {
-                    Poll::Pending => panic!("Expect semaphore to get acquired"),
-                    Poll::Ready(_guard) => {},
+                    Poll::Ready(Ok(_guard)) => {},
+                    _ => panic!("Expect semaphore to get acquired"),
                 };
             }
 
@@ -520,16 +511,16 @@ macro_rules! gen_semaphore_tests {
 
                 // fut2 should be acquired
                 match sem_fut2.as_mut().poll(cx) {
-                    Poll::Pending => panic!("Expect semaphore to get acquired"),
-                    Poll::Ready(_guard) => {},
+                    Poll::Ready(Ok(_guard)) => {},
+                    _ => panic!("Expect semaphore to get acquired"),
                 };
 
                 // Now fut3 should be able to get acquired
                 assert_eq!(count, 2);
 
                 match sem_fut3.as_mut().poll(cx) {
-                    Poll::Pending => panic!("Expect semaphore to get acquired"),
-                    Poll::Ready(_guard) => {},
+                    Poll::Ready(Ok(_guard)) => {},
+                    _ => panic!("Expect semaphore to get acquired"),
                 };
             }
 
@@ -559,6 +550,498 @@ macro_rules! gen_semaphore_tests {
                     assert!(fut.as_mut().is_terminated());
                 }
             }
+
+            #[test]
+            fn fair_semaphore_wakes_all_waiters_whose_permits_fit() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let sem = $semaphore_type::new(true, 0);
+
+                let mut futs: Vec<_> = [1, 2, 1, 3, 1]
+                    .iter()
+                    .map(|permits| Box::pin(sem.acquire(*permits)))
+                    .collect();
+                for fut in futs.iter_mut() {
+                    assert!(fut.as_mut().poll(cx).is_pending());
+                }
+
+                // The first 3 waiters fit into the released permits. The
+                // 4th one doesn't, and the 5th one must not overtake it.
+                sem.release(5);
+                assert_eq!(count, 3);
+
+                // The notified waiters can acquire in any order
+                let mut guards = Vec::new();
+                for idx in &[2, 0, 1] {
+                    match futs[*idx].as_mut().poll(cx) {
+                        Poll::Ready(Ok(guard)) => guards.push(guard),
+                        _ => panic!("Expect semaphore to get acquired"),
+                    };
+                }
+                assert_eq!(1, sem.permits());
+                assert_eq!(count, 3);
+                assert!(futs[3].as_mut().poll(cx).is_pending());
+                assert!(futs[4].as_mut().poll(cx).is_pending());
+
+                // Releasing the permits allows the remaining waiters to
+                // continue
+                drop(guards);
+                assert_eq!(5, sem.permits());
+                assert_eq!(count, 5);
+                assert!(futs[4].as_mut().poll(cx).is_ready());
+                assert!(futs[3].as_mut().poll(cx).is_ready());
+                assert_eq!(5, sem.permits());
+            }
+
+            #[test]
+            fn fair_semaphore_does_not_starve_large_waiters() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let sem = $semaphore_type::new(true, 0);
+
+                let large_fut = sem.acquire(2);
+                pin_mut!(large_fut);
+                assert!(large_fut.as_mut().poll(cx).is_pending());
+                let small_fut = sem.acquire(1);
+                pin_mut!(small_fut);
+                assert!(small_fut.as_mut().poll(cx).is_pending());
+
+                // The permit would suffice for the small waiter, but the older
+                // large waiter needs to get served first
+                sem.release(1);
+                assert_eq!(count, 0);
+                assert!(sem.try_acquire(1).is_none());
+                assert!(small_fut.as_mut().poll(cx).is_pending());
+
+                sem.release(1);
+                assert_eq!(count, 1);
+                let guard = match large_fut.as_mut().poll(cx) {
+                    Poll::Ready(Ok(guard)) => guard,
+                    _ => panic!("Expect semaphore to get acquired"),
+                };
+                assert!(small_fut.as_mut().poll(cx).is_pending());
+
+                drop(guard);
+                assert_eq!(count, 2);
+                assert!(small_fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn split_and_merge_releasers() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 5);
+
+                    let mut releaser = sem.try_acquire(5).unwrap();
+                    assert_eq!(5, releaser.permits());
+                    assert!(releaser.split(6).is_none());
+                    let part1 = releaser.split(2).unwrap();
+                    let part2 = releaser.split(1).unwrap();
+                    assert_eq!(2, releaser.permits());
+                    assert_eq!(2, part1.permits());
+                    assert_eq!(1, part2.permits());
+                    assert_eq!(0, sem.permits());
+
+                    let fut = sem.acquire(2);
+                    pin_mut!(fut);
+                    assert!(fut.as_mut().poll(cx).is_pending());
+
+                    // Parts of the permits can be released independently
+                    drop(part2);
+                    assert_eq!(1, sem.permits());
+                    assert_eq!(count, 0);
+                    drop(part1);
+                    assert_eq!(3, sem.permits());
+                    assert_eq!(count, 1);
+                    let acquired = match fut.as_mut().poll(cx) {
+                        Poll::Ready(Ok(releaser)) => releaser,
+                        _ => panic!("Expect semaphore to get acquired"),
+                    };
+
+                    releaser.merge(acquired);
+                    assert_eq!(4, releaser.permits());
+                    assert_eq!(1, sem.permits());
+                    drop(releaser);
+                    assert_eq!(5, sem.permits());
+                }
+            }
+
+            #[test]
+            #[should_panic]
+            fn merge_releasers_of_different_semaphores_should_panic() {
+                let sem1 = $semaphore_type::new(false, 1);
+                let sem2 = $semaphore_type::new(false, 1);
+                let mut releaser = sem1.try_acquire(1).unwrap();
+                releaser.merge(sem2.try_acquire(1).unwrap());
+            }
+
+            #[test]
+            fn forget_releaser() {
+                let sem = $semaphore_type::new(false, 5);
+                let mut releaser = sem.try_acquire(3).unwrap();
+                let part = releaser.split(1).unwrap();
+                releaser.forget();
+                assert_eq!(2, sem.permits());
+                drop(part);
+                assert_eq!(3, sem.permits());
+            }
+
+            #[test]
+            fn add_permits() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 0);
+
+                    let fut = sem.acquire(2);
+                    pin_mut!(fut);
+                    assert!(fut.as_mut().poll(cx).is_pending());
+                    sem.add_permits(1);
+                    assert_eq!(count, 0);
+                    sem.add_permits(2);
+                    assert_eq!(count, 1);
+                    assert!(fut.as_mut().poll(cx).is_ready());
+                    assert_eq!(3, sem.permits());
+                }
+            }
+
+            #[test]
+            #[should_panic]
+            fn add_permits_overflow_should_panic() {
+                let sem = $semaphore_type::new(false, 1);
+                sem.add_permits(core::usize::MAX);
+            }
+
+            #[test]
+            #[should_panic]
+            fn release_overflow_should_panic() {
+                let sem = $semaphore_type::new(false, core::usize::MAX);
+                let mut releaser = sem.try_acquire(1).unwrap();
+                releaser.disarm();
+                sem.release(2);
+            }
+
+            #[test]
+            fn forget_permits() {
+                for is_fair in &[true, false] {
+                    let sem = $semaphore_type::new(*is_fair, 5);
+                    let releaser = sem.try_acquire(2).unwrap();
+                    assert_eq!(2, sem.forget_permits(2));
+                    assert_eq!(1, sem.permits());
+                    assert_eq!(1, sem.forget_permits(3));
+                    assert_eq!(0, sem.permits());
+                    assert_eq!(0, sem.forget_permits(1));
+
+                    // Acquired permits are not affected
+                    drop(releaser);
+                    assert_eq!(2, sem.permits());
+                }
+            }
+
+            #[test]
+            fn forget_permits_keeps_permits_of_notified_fair_waiters() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let sem = $semaphore_type::new(true, 0);
+
+                let fut1 = sem.acquire(2);
+                pin_mut!(fut1);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                let fut2 = sem.acquire(3);
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                sem.add_permits(4);
+                assert_eq!(count, 1);
+                // The permits of the notified waiter are reserved
+                assert_eq!(2, sem.forget_permits(4));
+                assert_eq!(2, sem.permits());
+                assert!(fut1.as_mut().poll(cx).is_ready());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+            }
+
+            #[test]
+            fn priority_waiters_are_served_first() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 0);
+
+                    let fut1 = sem.acquire(1);
+                    pin_mut!(fut1);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let fut2 = sem.acquire_with_priority(2, 1);
+                    pin_mut!(fut2);
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+                    let fut3 = sem.acquire_with_priority(1, 5);
+                    pin_mut!(fut3);
+                    assert!(fut3.as_mut().poll(cx).is_pending());
+                    let fut4 = sem.acquire_with_priority(3, 5);
+                    pin_mut!(fut4);
+                    assert!(fut4.as_mut().poll(cx).is_pending());
+
+                    // The permits are handed over to the highest priority
+                    sem.release(1);
+                    assert_eq!(count, 1);
+                    assert_eq!(0, sem.permits());
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let releaser3 = match fut3.as_mut().poll(cx) {
+                        Poll::Ready(Ok(releaser)) => releaser,
+                        _ => panic!("Expect semaphore to get acquired"),
+                    };
+                    assert_eq!(1, releaser3.permits());
+                    releaser3.forget();
+
+                    // Waiters with a lower priority can't overtake
+                    sem.release(2);
+                    assert_eq!(count, 1);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+                    assert!(sem.try_acquire(1).is_none());
+
+                    sem.release(1);
+                    assert_eq!(count, 2);
+                    assert_eq!(0, sem.permits());
+                    let releaser4 = match fut4.as_mut().poll(cx) {
+                        Poll::Ready(Ok(releaser)) => releaser,
+                        _ => panic!("Expect semaphore to get acquired"),
+                    };
+
+                    // Returning the permits serves the remaining waiters
+                    drop(releaser4);
+                    assert_eq!(count, 4);
+                    assert!(fut2.as_mut().poll(cx).is_ready());
+                    assert!(fut1.as_mut().poll(cx).is_ready());
+                    assert_eq!(3, sem.permits());
+                }
+            }
+
+            #[test]
+            fn uncontended_acquire_with_priority() {
+                for is_fair in &[true, false] {
+                    let waker = &panic_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 3);
+
+                    let fut = sem.acquire_with_priority(2, 7);
+                    pin_mut!(fut);
+                    match fut.as_mut().poll(cx) {
+                        Poll::Ready(Ok(releaser)) => {
+                            assert_eq!(2, releaser.permits());
+                            assert_eq!(1, sem.permits());
+                        }
+                        _ => panic!("Expect semaphore to get acquired"),
+                    };
+                    assert!(fut.as_mut().is_terminated());
+                    assert_eq!(3, sem.permits());
+                }
+            }
+
+            #[test]
+            fn cancel_priority_waiter_after_permits_were_granted() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 0);
+
+                    let mut fut1 = Box::pin(sem.acquire_with_priority(2, 1));
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let fut2 = sem.acquire(2);
+                    pin_mut!(fut2);
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+
+                    sem.release(2);
+                    assert_eq!(count, 1);
+                    assert_eq!(0, sem.permits());
+
+                    // The granted permits are passed on
+                    drop(fut1);
+                    assert_eq!(count, 2);
+                    assert!(fut2.as_mut().poll(cx).is_ready());
+                    assert_eq!(2, sem.permits());
+                }
+            }
+
+            #[test]
+            fn cancel_blocking_priority_waiter() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 1);
+
+                    let mut fut1 = Box::pin(sem.acquire_with_priority(3, 1));
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let fut2 = sem.acquire_with_priority(1, 0);
+                    pin_mut!(fut2);
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+                    let fut3 = sem.acquire(1);
+                    pin_mut!(fut3);
+                    assert!(fut3.as_mut().poll(cx).is_pending());
+
+                    // Removing the first waiter unblocks the next one
+                    drop(fut1);
+                    assert_eq!(count, 1);
+                    assert!(fut3.as_mut().poll(cx).is_pending());
+                    let releaser = match fut2.as_mut().poll(cx) {
+                        Poll::Ready(Ok(releaser)) => releaser,
+                        _ => panic!("Expect semaphore to get acquired"),
+                    };
+                    drop(releaser);
+                    assert_eq!(count, 2);
+                    assert!(fut3.as_mut().poll(cx).is_ready());
+                }
+            }
+
+            #[test]
+            fn priority_waiters_do_not_take_permits_of_notified_fair_waiters() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let sem = $semaphore_type::new(true, 0);
+
+                let fut1 = sem.acquire(2);
+                pin_mut!(fut1);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                sem.release(2);
+                assert_eq!(count, 1);
+
+                let fut2 = sem.acquire_with_priority(2, 1);
+                pin_mut!(fut2);
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                sem.release(1);
+                assert_eq!(count, 1);
+                assert!(fut1.as_mut().poll(cx).is_ready());
+                assert_eq!(count, 2);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn close_fails_pending_priority_acquires() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 0);
+
+                    let fut1 = sem.acquire_with_priority(1, 2);
+                    pin_mut!(fut1);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let fut2 = sem.acquire_with_priority(2, 1);
+                    pin_mut!(fut2);
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+
+                    sem.release(1);
+                    assert_eq!(count, 1);
+                    sem.close();
+                    assert_eq!(count, 2);
+
+                    // Permits which had been granted before closing are kept
+                    match fut1.as_mut().poll(cx) {
+                        Poll::Ready(Ok(releaser)) => drop(releaser),
+                        _ => panic!("Expect semaphore to get acquired"),
+                    };
+                    match fut2.as_mut().poll(cx) {
+                        Poll::Ready(Err(err)) => assert!(err.is_closed()),
+                        _ => panic!("Expect acquire to fail"),
+                    };
+                    assert_eq!(1, sem.permits());
+                }
+            }
+
+            #[test]
+            fn close_fails_pending_acquires() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 2);
+
+                    let guard = sem.try_acquire(2).unwrap();
+                    let fut1 = sem.acquire(1);
+                    pin_mut!(fut1);
+                    assert!(fut1.as_mut().poll(cx).is_pending());
+                    let fut2 = sem.acquire(2);
+                    pin_mut!(fut2);
+                    assert!(fut2.as_mut().poll(cx).is_pending());
+
+                    assert!(!sem.is_closed());
+                    sem.close();
+                    assert!(sem.is_closed());
+                    assert_eq!(count, 2);
+
+                    match fut1.as_mut().poll(cx) {
+                        Poll::Ready(Err(err)) => assert!(err.is_closed()),
+                        _ => panic!("Expect acquire to fail"),
+                    };
+                    assert!(fut1.as_mut().is_terminated());
+                    match fut2.as_mut().poll(cx) {
+                        Poll::Ready(Err(AcquireError::Closed)) => {}
+                        _ => panic!("Expect acquire to fail"),
+                    };
+                    assert!(fut2.as_mut().is_terminated());
+
+                    // Acquired permits can still be released
+                    drop(guard);
+                    assert_eq!(2, sem.permits());
+                    assert_eq!(count, 2);
+                }
+            }
+
+            #[test]
+            fn acquire_after_close_fails() {
+                for is_fair in &[true, false] {
+                    let waker = &panic_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 2);
+                    sem.close();
+
+                    assert!(sem.try_acquire(1).is_none());
+                    assert!(sem.try_acquire(0).is_none());
+                    let fut = sem.acquire(1);
+                    pin_mut!(fut);
+                    match fut.as_mut().poll(cx) {
+                        Poll::Ready(Err(AcquireError::Closed)) => {}
+                        _ => panic!("Expect acquire to fail"),
+                    };
+                    assert!(fut.as_mut().is_terminated());
+                }
+            }
+
+            #[test]
+            fn close_fails_notified_acquire() {
+                for is_fair in &[true, false] {
+                    let (waker, count) = new_count_waker();
+                    let cx = &mut Context::from_waker(&waker);
+                    let sem = $semaphore_type::new(*is_fair, 1);
+
+                    let guard = sem.try_acquire(1).unwrap();
+                    let fut = sem.acquire(1);
+                    pin_mut!(fut);
+                    assert!(fut.as_mut().poll(cx).is_pending());
+
+                    drop(guard);
+                    assert_eq!(count, 1);
+                    sem.close();
+
+                    match fut.as_mut().poll(cx) {
+                        Poll::Ready(Err(AcquireError::Closed)) => {}
+                        _ => panic!("Expect acquire to fail"),
+                    };
+                    assert_eq!(1, sem.permits());
+                }
+            }
+
+            #[test]
+            fn cancel_acquire_after_close() {
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+                let sem = $semaphore_type::new(true, 0);
+
+                let mut fut = Box::pin(sem.acquire(1));
+                assert!(fut.as_mut().poll(cx).is_pending());
+                sem.close();
+                assert_eq!(count, 1);
+                drop(fut);
+                assert!(sem.is_closed());
+            }
         }
     }
 }
@@ -568,8 +1051,12 @@ gen_semaphore_tests!(local_semaphore_tests, LocalSemaphore);
 #[cfg(feature = "std")]
 mod if_std {
     use super::*;
+    use futures::executor::block_on;
     use futures::FutureExt;
     use futures_intrusive::sync::{Semaphore, SharedSemaphore};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
 
     gen_semaphore_tests!(semaphore_tests, Semaphore);
     gen_semaphore_tests!(shared_semaphore_tests, SharedSemaphore);
@@ -595,7 +1082,7 @@ mod if_std {
             pin_mut!(wait_fut);
             let res = wait_fut.poll_unpin(cx);
             let releaser = match res {
-                Poll::Ready(v) => v,
+                Poll::Ready(v) => v.unwrap(),
                 Poll::Pending => panic!("Expected to be ready"),
             };
             is_send(&releaser);
@@ -603,4 +1090,45 @@ mod if_std {
         }
         is_send_value(sem);
     }
+
+    static STATIC_SEMAPHORE: Semaphore = Semaphore::new(false, 2);
+
+    #[test]
+    fn static_semaphore() {
+        let releaser = STATIC_SEMAPHORE.try_acquire(2).unwrap();
+        assert_eq!(0, STATIC_SEMAPHORE.permits());
+        drop(releaser);
+        assert_eq!(2, STATIC_SEMAPHORE.permits());
+    }
+
+    #[test]
+    fn multithreaded_fair_semaphore_with_mixed_permits() {
+        const PERMITS: usize = 3;
+        let sem = Arc::new(Semaphore::new(true, PERMITS));
+        let in_use = Arc::new(AtomicUsize::new(0));
+
+        let threads: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|thread_idx| {
+                let sem = sem.clone();
+                let in_use = in_use.clone();
+                thread::spawn(move || {
+                    block_on(async {
+                        for i in 0..200 {
+                            let permits = 1 + (thread_idx + i) % PERMITS;
+                            let _releaser = sem.acquire(permits).await.unwrap();
+                            let total =
+                                in_use.fetch_add(permits, Ordering::SeqCst);
+                            assert!(total + permits <= PERMITS);
+                            in_use.fetch_sub(permits, Ordering::SeqCst);
+                        }
+                    })
+                })
+            })
+            .collect();
+
+        for thread in threads.into_iter() {
+            thread.join().unwrap();
+        }
+        assert_eq!(PERMITS, sem.permits());
+    }
 }
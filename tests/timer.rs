@@ -263,4 +263,22 @@ mod if_std {
         }
         is_send_value(timer);
     }
+
+    static STATIC_CLOCK: MockClock = MockClock::new();
+    static STATIC_TIMER: TimerService = TimerService::new(&STATIC_CLOCK);
+
+    #[test]
+    fn static_timer() {
+        STATIC_CLOCK.set_time(200);
+        let (waker, count) = new_count_waker();
+        let cx = &mut Context::from_waker(&waker);
+        let fut = STATIC_TIMER.deadline(300);
+        pin_mut!(fut);
+        assert!(fut.as_mut().poll(cx).is_pending());
+        assert_eq!(Some(300), STATIC_TIMER.next_expiration());
+        STATIC_CLOCK.set_time(300);
+        STATIC_TIMER.check_expirations();
+        assert_eq!(count, 1);
+        assert!(fut.as_mut().poll(cx).is_ready());
+    }
 }
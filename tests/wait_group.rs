@@ -0,0 +1,229 @@
+use futures::future::{FusedFuture, Future};
+use futures::task::Context;
+use futures_intrusive::sync::LocalWaitGroup;
+use futures_test::task::{new_count_waker, panic_waker};
+use pin_utils::pin_mut;
+
+macro_rules! gen_wait_group_tests {
+    ($mod_name:ident, $wait_group_type:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn synchronous() {
+                let wg = $wait_group_type::new(0);
+
+                assert_eq!(0, wg.count());
+                wg.add(2);
+                assert_eq!(2, wg.count());
+                wg.done();
+                assert_eq!(1, wg.count());
+                wg.done();
+                assert_eq!(0, wg.count());
+            }
+
+            #[test]
+            #[should_panic]
+            fn done_without_outstanding_operation_panics() {
+                let wg = $wait_group_type::new(0);
+                wg.done();
+            }
+
+            #[test]
+            fn wait_completes_immediately_if_nothing_is_outstanding() {
+                let wg = $wait_group_type::new(0);
+                let waker = &panic_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut = wg.wait();
+                pin_mut!(fut);
+                assert!(!fut.as_mut().is_terminated());
+                assert!(fut.as_mut().poll(cx).is_ready());
+                assert!(fut.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn wait_completes_once_all_operations_are_done() {
+                let wg = $wait_group_type::new(1);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                wg.add(1);
+
+                let fut1 = wg.wait();
+                let fut2 = wg.wait();
+                pin_mut!(fut1, fut2);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+
+                wg.done();
+                assert_eq!(count, 0);
+                assert!(fut1.as_mut().poll(cx).is_pending());
+
+                wg.done();
+                assert_eq!(count, 2);
+                assert!(fut1.as_mut().poll(cx).is_ready());
+                assert!(fut2.as_mut().poll(cx).is_ready());
+                assert!(fut1.as_mut().is_terminated());
+                assert!(fut2.as_mut().is_terminated());
+            }
+
+            #[test]
+            fn notified_waiter_completes_even_if_operations_got_added() {
+                let wg = $wait_group_type::new(1);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let fut = wg.wait();
+                pin_mut!(fut);
+                assert!(fut.as_mut().poll(cx).is_pending());
+
+                wg.done();
+                assert_eq!(count, 1);
+                wg.add(1);
+                assert!(fut.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn cancel_waiter() {
+                let wg = $wait_group_type::new(1);
+                let (waker, count) = new_count_waker();
+                let cx = &mut Context::from_waker(&waker);
+
+                let mut fut1 = Box::pin(wg.wait());
+                let fut2 = wg.wait();
+                pin_mut!(fut2);
+
+                assert!(fut1.as_mut().poll(cx).is_pending());
+                assert!(fut2.as_mut().poll(cx).is_pending());
+                drop(fut1);
+
+                wg.done();
+                assert_eq!(count, 1);
+                assert!(fut2.as_mut().poll(cx).is_ready());
+            }
+
+            #[test]
+            fn poll_from_multiple_executors() {
+                let (waker_1, count_1) = new_count_waker();
+                let (waker_2, count_2) = new_count_waker();
+                let wg = $wait_group_type::new(1);
+
+                let cx_1 = &mut Context::from_waker(&waker_1);
+                let cx_2 = &mut Context::from_waker(&waker_2);
+
+                let fut = wg.wait();
+                pin_mut!(fut);
+
+                assert!(fut.as_mut().poll(cx_1).is_pending());
+                assert!(fut.as_mut().poll(cx_2).is_pending());
+
+                wg.done();
+                assert_eq!(count_1, 0);
+                assert_eq!(count_2, 1);
+
+                assert!(fut.as_mut().poll(cx_2).is_ready());
+                assert!(fut.as_mut().is_terminated());
+            }
+        }
+    };
+}
+
+gen_wait_group_tests!(local_wait_group_tests, LocalWaitGroup);
+
+#[cfg(feature = "std")]
+mod if_std {
+    use super::*;
+    use futures::executor::block_on;
+    use futures_intrusive::sync::{SharedWaitGroup, WaitGroup};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    gen_wait_group_tests!(wait_group_tests, WaitGroup);
+    gen_wait_group_tests!(shared_wait_group_tests, SharedWaitGroup);
+
+    fn is_send<T: Send>(_: &T) {}
+
+    fn is_send_value<T: Send>(_: T) {}
+
+    fn is_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn wait_group_futures_are_send() {
+        let wg = WaitGroup::new(1);
+        is_sync(&wg);
+        {
+            let wait_fut = wg.wait();
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(wg);
+    }
+
+    #[test]
+    fn shared_wait_group_futures_are_send() {
+        let wg = SharedWaitGroup::new(0);
+        is_sync(&wg);
+        {
+            let guard = wg.add_guard();
+            is_send(&guard);
+            let wait_fut = wg.wait();
+            is_send(&wait_fut);
+            pin_mut!(wait_fut);
+            is_send(&wait_fut);
+        }
+        is_send_value(wg);
+    }
+
+    #[test]
+    fn shared_guard_marks_operation_as_done() {
+        let wg = SharedWaitGroup::new(0);
+        let (waker, count) = new_count_waker();
+        let cx = &mut Context::from_waker(&waker);
+
+        let guard1 = wg.add_guard();
+        let guard2 = wg.add_guard();
+        assert_eq!(2, wg.count());
+
+        let fut = wg.wait();
+        pin_mut!(fut);
+        assert!(fut.as_mut().poll(cx).is_pending());
+
+        drop(guard1);
+        assert_eq!(1, wg.count());
+        assert_eq!(count, 0);
+
+        drop(guard2);
+        assert_eq!(0, wg.count());
+        assert_eq!(count, 1);
+        assert!(fut.as_mut().poll(cx).is_ready());
+    }
+
+    #[test]
+    fn multithreaded_smoke() {
+        let wg = SharedWaitGroup::new(0);
+        let completed = Arc::new(AtomicUsize::new(0));
+
+        let threads: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|_| {
+                let guard = wg.add_guard();
+                let completed = completed.clone();
+                thread::spawn(move || {
+                    thread::yield_now();
+                    completed.fetch_add(1, Ordering::SeqCst);
+                    drop(guard);
+                })
+            })
+            .collect();
+
+        block_on(wg.wait());
+        assert_eq!(4, completed.load(Ordering::SeqCst));
+
+        for thread in threads.into_iter() {
+            thread.join().unwrap();
+        }
+    }
+}